@@ -0,0 +1,66 @@
+//! Graph layout on a large synthetic history
+//!
+//! Run with `cargo bench --bench graph_layout`.
+
+use chrono::Local;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use git2::Oid;
+use keifu::git::{build_graph, BranchInfo, CommitInfo};
+
+const COMMITS: usize = 50_000;
+
+fn oid(n: usize) -> Oid {
+    let mut bytes = [0u8; 20];
+    bytes[..8].copy_from_slice(&(n as u64 + 1).to_be_bytes());
+    Oid::from_bytes(&bytes).unwrap()
+}
+
+/// Newest-first history: a main line with a short topic branch merged every 10 commits
+fn synthetic_history(count: usize) -> Vec<CommitInfo> {
+    let now = Local::now();
+    (0..count)
+        .map(|i| {
+            let mut parents = Vec::new();
+            if i + 1 < count {
+                parents.push(oid(i + 1));
+            }
+            // Merge commits pull in a side commit three rows further down
+            if i % 10 == 0 && i + 3 < count {
+                parents.push(oid(i + 3));
+            }
+            CommitInfo {
+                oid: oid(i),
+                short_id: format!("{:07}", i),
+                author_name: "bench".to_string(),
+                author_email: "bench@example.com".to_string(),
+                timestamp: now,
+                author_time: now.timestamp(),
+                message: format!("commit {}", i),
+                full_message: format!("commit {}", i),
+                parent_oids: parents.into_iter().collect(),
+            }
+        })
+        .collect()
+}
+
+fn bench_build_graph(c: &mut Criterion) {
+    let commits = synthetic_history(COMMITS);
+    let branches = vec![BranchInfo {
+        name: "main".to_string(),
+        is_head: true,
+        is_remote: false,
+        upstream: None,
+        tip_oid: oid(0),
+        tip_time: 0,
+    }];
+
+    let mut group = c.benchmark_group("build_graph");
+    group.sample_size(10);
+    group.bench_function("50k_commits", |b| {
+        b.iter(|| build_graph(black_box(&commits), black_box(&branches), None, None))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_build_graph);
+criterion_main!(benches);
@@ -14,31 +14,101 @@ pub enum Action {
     PrevBranch,
     BranchLeft,
     BranchRight,
+    NextBookmark,
+    PrevBookmark,
+    JumpToParent,
+    JumpToChild,
+    JumpToStartRev,
+    JumpToProvenance,
+
+    // Bookmarks
+    ToggleBookmark,
+    ClearBookmarks,
+
+    // Bisect
+    BisectGood,
+    BisectBad,
+    BisectReset,
 
     // Git operations
     Checkout,
     CopyHash,
     CreateBranch,
+    RenameBranch,
     DeleteBranch,
     Fetch,
+    /// Fetch, then fast-forward the HEAD branch to its upstream
+    Pull,
+    CancelFetch,
     Merge,
     Rebase,
+    RepeatLastOperation,
+    CherryPick,
+    ExportPatches,
+    Squash,
+    ShowCommitMenu,
+    CommitFixup,
+    CommitSquash,
+    /// Stash the working tree, untracked files included
+    Stash,
+    StashPop,
+    DropStash,
 
     // UI
+    CopyReleaseUrl,
+    DismissUpdate,
+    CopyView,
     ToggleHelp,
+    ShowRawCommit,
+    OpenDiff,
+    DiffScrollDown,
+    DiffScrollUp,
+    ShowRepoInfo,
+    ShowTags,
+    ShowStashes,
+    ToggleVisual,
+    ShowSeriesStats,
+    CycleTagSort,
+    ToggleHideMerges,
+    ToggleCompactGraph,
+    ToggleLaneColors,
+    FilterDates,
+    ClearDateRange,
+    ToggleWalkScope,
+    ToggleFollowHead,
+    ToggleRawDiff,
+    ToggleFileAnnotations,
     Search,
+    CommandPalette,
     Refresh,
     Quit,
+    /// Go back one level: close the active popup or dialog, or quit from the graph (`q`)
+    Back,
 
     // Dialogs
     Confirm,
     Cancel,
     InputChar(char),
     InputBackspace,
+    OverwriteUntracked,
+    StashAndRetry,
+    /// Check or uncheck the selected entry of a checklist
+    ToggleChecked,
+    /// Abandon a merge that stopped with conflicts
+    AbortMerge,
+    WaitAndQuit,
+    AbortAndQuit,
 
     // Search dropdown
     SearchSelectUp,
     SearchSelectDown,
     SearchSelectUpQuiet,   // Tab navigation (no graph jump)
     SearchSelectDownQuiet, // Tab navigation (no graph jump)
+    CycleBranchSort,
+    CycleSearchCase,
+    ToggleSubjectLayout,
+    MarkBranchDiff,
+
+    // Command palette
+    CompleteCommand,
 }
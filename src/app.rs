@@ -1,30 +1,76 @@
 //! Application state management
 
-use std::sync::mpsc::{self, Receiver};
-use std::thread;
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use arboard::Clipboard;
 use ratatui::widgets::ListState;
 
-use git2::Oid;
+use git2::{BranchType, Oid, RepositoryState};
 
 use crate::{
     action::Action,
-    config::Config,
+    bookmarks::Bookmarks,
+    command::{self, Command},
+    config::{Config, QKey},
+    editor::CommitFile,
+    error::{short_oid, Error as GitError},
     git::{
-        build_graph,
-        graph::GraphLayout,
+        bisect::{BisectSession, BisectStep},
+        branch::{branch_section, sort_branch_labels, sort_branches},
+        build_graph_with_options,
+        commit::{file_at_commit, raw_commit_object},
+        commit_patch,
+        filter::{CommitFilter, DateRange},
+        graph::{GraphDensity, GraphLayout, LayoutOptions},
+        last_touch::LastTouch,
         operations::{
-            checkout_branch, checkout_commit, checkout_remote_branch, create_branch, delete_branch,
-            fetch_origin, merge_branch, rebase_branch,
+            abort_merge, check_squash, checkout_branch, checkout_commit, checkout_remote_branch,
+            cherry_pick, commit_autosquash, conflicted_paths, create_branch, delete_branch,
+            ensure_index_unlocked, fast_forward_to_upstream, fetch_origin, head_upstream,
+            merge_branch, rebase_branch, remove_untracked_files, rename_branch, retarget_branches,
+            squash_commits, stale_branches, AutosquashKind, BranchRetarget, MergeOutcome,
+            PullOutcome,
         },
-        BranchInfo, CommitDiffInfo, CommitInfo, GitRepository, WorkingTreeStatus,
+        repo_info::{RepoInfo, RepoStats},
+        series::{export_patches, series_stats, squash_message},
+        signature::SignatureInfo,
+        stash::{stash_apply, stash_drop, stash_list, stash_pop, stash_save, StashInfo},
+        tag::{sort_tags, TagDistance, TagInfo, TagSort},
+        BranchComparison, BranchGrouping, BranchInfo, BranchSort, CommitDiffInfo, CommitInfo,
+        GitRepository, RepoLocation, RepoSnapshot, WalkScope, WorkingTreeStatus,
     },
-    search::{fuzzy_search_branches, FuzzySearchResult},
+    graph::{colors::ColorScheme, glyphs::GraphGlyphs, text::rows_to_text},
+    gutter::{Gutter, GutterMark},
+    keybindings::KeySequencer,
+    provenance::Provenance,
+    search::{fuzzy_search_branches_with, FuzzySearchResult, SearchCase, SubjectLayout},
+    update::{self, UpdateNotice},
+    view_settings::{ViewSettings, ViewStore},
+    worker,
 };
 
+/// Commits loaded at startup and added each time scrolling nears the bottom
+/// (default of `ui.commit_page_size`)
+pub const COMMIT_PAGE_SIZE: usize = 500;
+/// Rows from the bottom at which the next page is loaded
+const LOAD_MORE_THRESHOLD: usize = 10;
+/// Minimum time between page loads, so rapid scrolling loads one page at a time
+const LOAD_MORE_DEBOUNCE: Duration = Duration::from_millis(300);
+/// Pages (`ui.commit_page_size`) the history window may grow to to show a merge base
+const MERGE_BASE_WINDOW_PAGES: usize = 4;
+/// Confirm note when the merge base stays outside the loaded history
+const MERGE_BASE_NOT_LOADED: &str =
+    "merge base is below the loaded history, so the graph does not show it";
+/// Pause before reading the repository again after a transient failure
+const TRANSIENT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
 /// Filter branch names to exclude remote branches that have matching local branches
 /// Returns branches in order: local branches first, then remote-only branches
 fn filter_remote_duplicates(branch_names: &[String]) -> Vec<&str> {
@@ -62,31 +108,256 @@ pub enum AppMode {
     Confirm {
         message: String,
         action: ConfirmAction,
+        /// State the dialog was opened for
+        expected: ExpectedState,
     },
     Error {
         message: String,
     },
+    /// Raw commit object popup
+    RawCommit {
+        oid: Oid,
+        content: String,
+        scroll: u16,
+    },
+    /// Unified diff of a commit in the detail panel, in place of the file list
+    DiffView {
+        oid: Oid,
+        patch: String,
+        scroll_offset: usize,
+    },
+    /// Repository info popup
+    RepoInfo {
+        info: Box<RepoInfo>,
+        scroll: u16,
+    },
+    /// Files that differ between two branch tips
+    BranchDiff {
+        comparison: Box<BranchComparison>,
+        scroll: u16,
+    },
+    /// Span of rows from `anchor` to the cursor, for bulk actions
+    Visual {
+        anchor: usize,
+    },
+    /// Tag browser
+    Tags {
+        tags: Vec<TagInfo>,
+        selected: usize,
+        sort: TagSort,
+    },
+    /// Stash list
+    StashList {
+        stashes: Vec<StashInfo>,
+        selected: usize,
+    },
+    /// Checkout blocked by files that it would overwrite
+    CheckoutBlocked {
+        op: RepeatableOp,
+        paths: Vec<PathBuf>,
+        /// Some of the files have uncommitted changes (only stashing is offered)
+        local_changes: bool,
+        scroll: u16,
+    },
+    /// A merge or cherry-pick stopped with conflicts in these files, left for the user
+    /// to resolve or abort
+    Conflict {
+        op: ConflictOp,
+        files: Vec<String>,
+        scroll: u16,
+    },
+    /// Branches a rebase left on the original commits, to move onto the rewrites
+    RetargetBranches {
+        moves: Vec<BranchRetarget>,
+        /// Which of `moves` to apply (all at first)
+        checked: Vec<bool>,
+        selected: usize,
+    },
+    /// Commit submenu for the selected commit (fixup!/squash! from the staged changes)
+    CommitMenu {
+        target: Oid,
+    },
+    /// Choosing which of the selected commit's loaded children to jump to
+    PickChild {
+        /// Newest first, as in the detail panel
+        children: Vec<Oid>,
+        selected: usize,
+    },
+    /// Quit requested while an operation that changes the repository is running
+    QuitBlocked {
+        /// e.g. "A fetch from origin"
+        operation: &'static str,
+        /// Quit as soon as the operation finishes (or stops after being aborted)
+        waiting: bool,
+    },
+}
+
+impl AppMode {
+    /// Views that nothing is pending in, which help may cover and return to
+    pub fn allows_help_overlay(&self) -> bool {
+        matches!(
+            self,
+            AppMode::Normal
+                | AppMode::RawCommit { .. }
+                | AppMode::DiffView { .. }
+                | AppMode::RepoInfo { .. }
+                | AppMode::BranchDiff { .. }
+                | AppMode::Visual { .. }
+                | AppMode::Tags { .. }
+                | AppMode::StashList { .. }
+                | AppMode::Input {
+                    action: InputAction::Search,
+                    ..
+                }
+        )
+    }
 }
 
 /// Input action kinds
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InputAction {
     CreateBranch,
+    /// Rename the given local branch (edited in place on its graph row)
+    RenameBranch(String),
     Search,
+    /// Author date range filter ("since..until")
+    DateRange,
+    /// Squash these commits (oldest first, ending at HEAD); the input is the new subject
+    Squash(Vec<Oid>),
+    /// Command palette line (see `command`)
+    Command,
 }
 
 /// Confirmation action kinds
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConfirmAction {
+    BisectReset,
+    ClearBookmarks,
     DeleteBranch(String),
     Merge(String),
     Rebase(String),
+    /// Commits to cherry-pick, oldest first
+    CherryPick(Vec<Oid>),
+    DropStash(StashInfo),
+    /// Checkout retried after the index was locked
+    Checkout(Box<RepeatableOp>),
+    /// Quit asked for with `q` (keys.q = "confirm-quit")
+    Quit,
+    /// Abort a merge or cherry-pick that stopped with conflicts (resets to HEAD)
+    AbortConflict(ConflictOp),
+}
+
+impl ConfirmAction {
+    /// Whether the operation writes the index or working tree (and so needs the index lock)
+    fn touches_index(&self) -> bool {
+        !matches!(
+            self,
+            ConfirmAction::ClearBookmarks
+                | ConfirmAction::DeleteBranch(_)
+                | ConfirmAction::DropStash(_)
+                | ConfirmAction::Quit
+        )
+    }
+}
+
+/// Operation that stopped with conflicts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictOp {
+    Merge,
+    CherryPick,
+}
+
+impl ConflictOp {
+    /// e.g. "Cherry-pick"
+    pub fn title(self) -> &'static str {
+        match self {
+            ConflictOp::Merge => "Merge",
+            ConflictOp::CherryPick => "Cherry-pick",
+        }
+    }
+
+    /// e.g. "cherry-pick"
+    pub fn label(self) -> &'static str {
+        match self {
+            ConflictOp::Merge => "merge",
+            ConflictOp::CherryPick => "cherry-pick",
+        }
+    }
+}
+
+/// Repository state an operation was offered for (as loaded), re-checked right before it runs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpectedState {
+    /// HEAD commit, when the operation builds on it
+    head: Option<Oid>,
+    /// Branch the operation acts on and its tip
+    branch: Option<(String, Oid)>,
+}
+
+/// Git operation that can be run again with `.`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepeatableOp {
+    Fetch,
+    CheckoutBranch(String),
+    CheckoutCommit(Oid),
+    /// Re-prompts for a branch at the selected commit, prefilled with this name
+    CreateBranch(String),
+    /// Destructive operations are always confirmed again before repeating
+    Confirmed(ConfirmAction),
+}
+
+impl RepeatableOp {
+    /// Branch name or short commit id the operation targets (empty for fetch)
+    pub fn target_label(&self) -> String {
+        match self {
+            RepeatableOp::CheckoutBranch(name) | RepeatableOp::CreateBranch(name) => name.clone(),
+            RepeatableOp::CheckoutCommit(oid) => oid.to_string()[..7].to_string(),
+            RepeatableOp::Fetch | RepeatableOp::Confirmed(_) => String::new(),
+        }
+    }
+}
+
+/// Origin of a background fetch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FetchKind {
+    /// `f` or `.`: failures open the error dialog
+    Manual,
+    /// `F`: like a manual fetch, then fast-forwards the HEAD branch to its upstream
+    Pull,
+    /// Periodic auto-fetch (or a cancelled fetch): failures are ignored
+    Background,
+    /// `fetch_on_start`: failures leave a dim warning (e.g. when offline)
+    Startup,
 }
 
 /// Result of async diff computation
 struct DiffResult {
     oid: Oid,
     diff: Option<CommitDiffInfo>,
+    /// Computed only when not cached yet
+    tag_distance: Option<Option<TagDistance>>,
+    /// Computed only when not cached yet
+    signature: Option<Option<SignatureInfo>>,
+}
+
+/// Previous change of each requested path, from the annotation worker
+type LastTouches = Vec<(PathBuf, Option<LastTouch>)>;
+
+/// Receiver for a background job (Err carries a panic message)
+type WorkerReceiver<T> = Receiver<Result<T, String>>;
+
+/// Error text shown in the file pane when a diff worker panics
+fn diff_worker_error(panic_message: &str) -> String {
+    format!("internal error while computing diff: {}", panic_message)
+}
+
+/// "1 commit" / "3 commits"
+fn commits(n: usize) -> String {
+    if n == 1 {
+        "1 commit".to_string()
+    } else {
+        format!("{} commits", n)
+    }
 }
 
 /// Search state for branch search feature
@@ -94,12 +365,20 @@ struct DiffResult {
 struct SearchState {
     /// Fuzzy search results (sorted by score)
     fuzzy_matches: Vec<FuzzySearchResult>,
+    /// Commits ahead of the main branch of each result (empty when not shown)
+    counts: Vec<Option<usize>>,
+    /// Tip commit subject of each result
+    subjects: Vec<String>,
+    /// Section header of each result in the full branch list (empty when not shown)
+    sections: Vec<String>,
     /// Selected index in the dropdown (None if no results)
     dropdown_selection: Option<usize>,
     /// Position before search started (for cancel restoration)
     original_position: Option<usize>,
     /// Original node selection before search started
     original_node: Option<usize>,
+    /// Branch marked as the start of a branch diff
+    diff_mark: Option<String>,
 }
 
 impl SearchState {
@@ -126,10 +405,12 @@ impl SearchState {
         });
     }
 
-    /// Get the currently selected result
+    /// Get the currently selected result, or the top one when nothing is selected
     fn selected_result(&self) -> Option<&FuzzySearchResult> {
-        self.dropdown_selection
-            .and_then(|idx| self.fuzzy_matches.get(idx))
+        match self.dropdown_selection {
+            Some(idx) => self.fuzzy_matches.get(idx),
+            None => self.fuzzy_matches.first(),
+        }
     }
 
     /// Clamp dropdown selection to valid range after results update
@@ -149,18 +430,72 @@ impl SearchState {
 
 /// Application state
 pub struct App {
+    /// Active mode (the top of the mode stack)
     pub mode: AppMode,
+    /// Modes interrupted by the active one, restored in turn as it closes
+    mode_stack: Vec<AppMode>,
     pub repo: GitRepository,
     pub repo_path: String,
+    /// Git dir and working tree, for reopening the repository on worker threads
+    pub repo_location: RepoLocation,
     pub head_name: Option<String>,
+    /// Branch HEAD points to while it has no commits yet
+    pub unborn_branch: Option<String>,
+    /// Commit HEAD points to (from the latest snapshot)
+    pub head_oid: Option<Oid>,
+    /// Where the HEAD branch and its upstream diverged (both ahead and behind)
+    pub upstream_fork_point: Option<Oid>,
+    /// Upstream tip of the selected branch label, marked until the next key
+    upstream_flash: Option<Oid>,
 
     // Data
     pub commits: Vec<CommitInfo>,
     pub branches: Vec<BranchInfo>,
+    /// Tag names per commit
+    pub commit_tags: HashMap<Oid, Vec<String>>,
+    /// Latest reachable tag per commit (None: no prior tag), filled by the diff worker
+    tag_distances: HashMap<Oid, Option<TagDistance>>,
+    /// Signer per commit (None: unsigned), filled by the diff worker
+    signatures: HashMap<Oid, Option<SignatureInfo>>,
+    /// Commits ahead of the main branch per branch tip, computed when the branch list needs them
+    ahead_counts: HashMap<Oid, usize>,
+    /// Main branch tip `ahead_counts` was computed against
+    ahead_base: Option<Oid>,
+    /// Subject per branch tip, filled when the branch list shows them (cleared on refresh)
+    tip_subjects: HashMap<Oid, String>,
     pub graph_layout: GraphLayout,
 
     // UI state
     pub graph_list_state: ListState,
+    /// Graph rows visible in the last frame (set while drawing)
+    pub graph_view_height: usize,
+    /// Multi-key sequences typed so far (fed by the main loop)
+    pub key_sequencer: KeySequencer,
+
+    // View options
+    /// Hide merge commits (`git log --no-merges`)
+    hide_merges: bool,
+    /// Show only commits authored in this range
+    date_range: Option<DateRange>,
+    /// All branches, or only the current branch's first-parent history
+    walk_scope: WalkScope,
+    /// Sort order of the branch list in the search dropdown
+    branch_sort: BranchSort,
+    /// Case matching of the branch search (kept for the session)
+    search_case: SearchCase,
+    subject_layout: SubjectLayout,
+    /// Completions for the command palette input
+    command_completions: Vec<String>,
+    /// Compare working tree bytes as-is (no CRLF / text attribute conversion)
+    raw_workdir_diff: bool,
+    /// Move the selection to HEAD after every refresh
+    follow_head: bool,
+    /// Connector rows in the graph (starts at the configured density)
+    graph_density: GraphDensity,
+    /// How the graph's lanes are colored
+    lane_colors: ColorScheme,
+    /// Commit given with `--rev` (kept loaded and marked so it can be returned to)
+    start_rev: Option<Oid>,
 
     // Branch selection state
     /// List of (node_index, branch_name) for all branches
@@ -171,115 +506,490 @@ pub struct App {
     // Search state
     search_state: SearchState,
 
+    /// Bookmarked commits (persisted per repository)
+    pub bookmarks: Bookmarks,
+    /// View options remembered for the repository
+    view_store: ViewStore,
+    /// Originals of commits cherry-picked or rebased in this session
+    pub provenance: Provenance,
+
+    // Bisect
+    bisect: Option<BisectSession>,
+    /// First bad commit found by the bisect session (highlighted in the graph)
+    pub bisect_first_bad: Option<Oid>,
+
+    /// Per-commit marks for the graph gutter (rebuilt after every action and refresh)
+    pub gutter: Gutter,
+
+    /// Most recent git operation (run again with `.`)
+    pub last_operation: Option<RepeatableOp>,
+
     // Diff cache (async load)
     diff_cache: Option<CommitDiffInfo>,
     diff_cache_oid: Option<Oid>,
     diff_loading_oid: Option<Oid>,
-    diff_receiver: Option<Receiver<DiffResult>>,
+    diff_receiver: Option<WorkerReceiver<DiffResult>>,
+    /// Worker failure for `diff_cache_oid`
+    diff_error: Option<String>,
+
+    // Last-touch annotations of the changed files (toggled with A)
+    file_annotations: bool,
+    /// Previous change per (commit, path); None when none was found
+    last_touches: HashMap<(Oid, PathBuf), Option<LastTouch>>,
+    /// Commit and paths the worker is annotating
+    last_touch_pending: Option<(Oid, Vec<PathBuf>)>,
+    last_touch_receiver: Option<WorkerReceiver<LastTouches>>,
 
     // Uncommitted diff cache
     uncommitted_diff_cache: Option<CommitDiffInfo>,
     uncommitted_diff_loading: bool,
-    uncommitted_diff_receiver: Option<Receiver<Option<CommitDiffInfo>>>,
+    uncommitted_diff_receiver: Option<WorkerReceiver<Option<CommitDiffInfo>>>,
+    uncommitted_diff_error: Option<String>,
     /// Cache key: working tree status at the time of caching (for invalidation)
     uncommitted_cache_key: Option<WorkingTreeStatus>,
 
     // Flags
     pub should_quit: bool,
     pub exit_message: Option<String>,
+    /// File to open in the editor once the main loop has suspended the TUI
+    editor_file: Option<CommitFile>,
 
     // Status message with auto-clear
     message: Option<String>,
     message_time: Option<std::time::Instant>,
+    /// The message is a non-blocking warning (shown dimmed)
+    message_warning: bool,
 
     // Async fetch
-    fetch_receiver: Option<Receiver<Result<(), String>>>,
-    /// Whether to suppress error dialogs for fetch failures (for auto-fetch)
-    fetch_silent: bool,
+    fetch_receiver: Option<WorkerReceiver<Result<(), String>>>,
+    /// Who started the running fetch (decides how failures are reported)
+    fetch_kind: FetchKind,
+    /// Set to stop the running fetch
+    fetch_cancel: Arc<AtomicBool>,
+
+    // Update check (opt-in)
+    update_receiver: Option<WorkerReceiver<Option<UpdateNotice>>>,
+    /// Commit count and size for the repository info popup (computed on open)
+    pub repo_stats: Option<RepoStats>,
+    repo_stats_receiver: Option<WorkerReceiver<RepoStats>>,
+    /// Newer release found by the update check (cleared when dismissed)
+    pub update_notice: Option<UpdateNotice>,
+
+    // Configuration
+    pub config: Config,
 
     // Auto-refresh state
-    config: Config,
     last_refresh_time: Instant,
     last_fetch_time: Instant,
+
+    /// Commits to load (grows by a page when scrolling nears the bottom)
+    commit_limit: usize,
+    /// When the last extra page was loaded
+    last_page_load: Option<Instant>,
+    /// Minimum time between page loads
+    load_more_debounce: Duration,
 }
 
 impl App {
     /// Create a new application
     pub fn new() -> Result<Self> {
-        let config = Config::load();
-        let now = Instant::now();
+        Self::with_repo(GitRepository::discover()?, Config::load())
+    }
 
-        let repo = GitRepository::discover()?;
+    /// Create an application for an already opened repository
+    pub fn with_repo(repo: GitRepository, config: Config) -> Result<Self> {
+        let now = Instant::now();
         let repo_path = repo.path.clone();
-        let head_name = repo.head_name();
+        let repo_location = RepoLocation::of(&repo.repo);
 
-        let commits = repo.get_commits(500)?;
-        let branches = repo.get_branches()?;
+        let view_store = ViewStore::load(repo.repo.path());
+        let view = view_store.settings().clone();
+        let page_size = config.ui.commit_page_size;
+        let snapshot = repo.snapshot_scoped(page_size, view.walk_scope)?;
+        let bookmarks = Bookmarks::load(repo.repo.path());
         let uncommitted_count = repo
             .get_working_tree_status()
             .ok()
             .flatten()
             .map(|s| s.file_count);
-        let head_commit_oid = repo.head_oid();
-        let graph_layout = build_graph(&commits, &branches, uncommitted_count, head_commit_oid);
+        let head_commit_oid = snapshot.head_oid;
 
         let mut graph_list_state = ListState::default();
         graph_list_state.select(Some(0));
 
-        // Build branch positions
-        let branch_positions = Self::build_branch_positions(&graph_layout);
-
-        // Determine initial branch selection
-        // If uncommitted node exists (at index 0), don't select any branch
-        // Otherwise, select the first branch if exists
-        let has_uncommitted_node = graph_layout
-            .nodes
-            .first()
-            .is_some_and(|node| node.is_uncommitted);
-        let selected_branch_position = if has_uncommitted_node || branch_positions.is_empty() {
-            None
-        } else {
-            Some(0)
-        };
-
-        Ok(Self {
+        let mut app = Self {
             mode: AppMode::Normal,
+            mode_stack: Vec::new(),
             repo,
             repo_path,
-            head_name,
-            commits,
-            branches,
-            graph_layout,
+            repo_location,
+            head_name: snapshot.head_name,
+            unborn_branch: snapshot.unborn_branch,
+            head_oid: snapshot.head_oid,
+            upstream_fork_point: snapshot.upstream_fork_point,
+            upstream_flash: None,
+            commit_tags: snapshot.tags,
+            tag_distances: HashMap::new(),
+            signatures: HashMap::new(),
+            ahead_counts: HashMap::new(),
+            tip_subjects: HashMap::new(),
+            ahead_base: None,
+            commits: snapshot.commits,
+            branches: snapshot.branches,
+            graph_layout: GraphLayout::default(),
             graph_list_state,
-            branch_positions,
-            selected_branch_position,
+            graph_view_height: 0,
+            key_sequencer: KeySequencer::new(Duration::from_millis(
+                config.keys.sequence_timeout_ms,
+            )),
+            hide_merges: view.hide_merges,
+            date_range: None,
+            walk_scope: view.walk_scope,
+            branch_sort: view.branch_sort.unwrap_or(config.ui.branch_sort),
+            search_case: view.search_case,
+            subject_layout: view.subject_layout,
+            command_completions: Vec::new(),
+            raw_workdir_diff: view.raw_workdir_diff,
+            follow_head: view.follow_head,
+            graph_density: view.graph_density.unwrap_or(config.ui.graph_density),
+            lane_colors: view.lane_colors,
+            start_rev: None,
+            branch_positions: Vec::new(),
+            selected_branch_position: None,
             search_state: SearchState::default(),
+            bookmarks,
+            view_store,
+            provenance: Provenance::default(),
+            bisect: None,
+            bisect_first_bad: None,
+            gutter: Gutter::default(),
+            last_operation: None,
             diff_cache: None,
             diff_cache_oid: None,
             diff_loading_oid: None,
             diff_receiver: None,
+            diff_error: None,
+            file_annotations: false,
+            last_touches: HashMap::new(),
+            last_touch_pending: None,
+            last_touch_receiver: None,
             uncommitted_diff_cache: None,
             uncommitted_diff_loading: false,
             uncommitted_diff_receiver: None,
+            uncommitted_diff_error: None,
             uncommitted_cache_key: None,
             should_quit: false,
             exit_message: None,
+            editor_file: None,
             message: None,
             message_time: None,
+            message_warning: false,
             fetch_receiver: None,
-            fetch_silent: false,
+            fetch_kind: FetchKind::Manual,
+            fetch_cancel: Arc::new(AtomicBool::new(false)),
+            update_receiver: None,
+            repo_stats: None,
+            repo_stats_receiver: None,
+            update_notice: None,
             config,
             last_refresh_time: now,
+            commit_limit: page_size,
+            last_page_load: None,
+            load_more_debounce: LOAD_MORE_DEBOUNCE,
             last_fetch_time: now,
+        };
+
+        app.graph_layout = app.layout_graph(uncommitted_count, head_commit_oid);
+
+        // Build branch positions
+        app.branch_positions = Self::build_branch_positions(&app.graph_layout);
+
+        // Determine initial branch selection
+        // If uncommitted node exists (at index 0), don't select any branch
+        // Otherwise, select the first branch if exists
+        let has_uncommitted_node = app
+            .graph_layout
+            .nodes
+            .first()
+            .is_some_and(|node| node.is_uncommitted);
+        if !has_uncommitted_node && !app.branch_positions.is_empty() {
+            app.selected_branch_position = Some(0);
+        }
+
+        app.rebuild_gutter();
+
+        if app.config.update.check {
+            app.update_receiver = Some(update::spawn_check());
+        }
+
+        let unknown = &app.config.ui.status_segments.unknown;
+        if !unknown.is_empty() {
+            let message = format!(
+                "Unknown status bar segments ignored: {}",
+                unknown.join(", ")
+            );
+            app.set_warning(message);
+        }
+        let invalid = app.view_store.invalid_keys();
+        if !invalid.is_empty() {
+            let message = format!("Invalid view settings ignored: {}", invalid.join(", "));
+            app.set_warning(message);
+        }
+        app.reopen_conflict_view()?;
+
+        Ok(app)
+    }
+
+    /// Open the conflict view for a merge or cherry-pick that is still stopped on
+    /// conflicts (left by an earlier session, or after the view was closed)
+    fn reopen_conflict_view(&mut self) -> Result<()> {
+        let op = match self.repo.repo.state() {
+            RepositoryState::Merge => ConflictOp::Merge,
+            RepositoryState::CherryPick => ConflictOp::CherryPick,
+            _ => return Ok(()),
+        };
+        let files = conflicted_paths(&self.repo.repo)?;
+        if !files.is_empty() && matches!(self.mode, AppMode::Normal) {
+            self.mode = AppMode::Conflict {
+                op,
+                files,
+                scroll: 0,
+            };
+        }
+        Ok(())
+    }
+
+    /// Replace the loaded data with a snapshot and rebuild the graph
+    fn apply_snapshot(&mut self, snapshot: RepoSnapshot, uncommitted_count: Option<usize>) {
+        self.commits = snapshot.commits;
+        self.branches = snapshot.branches;
+        self.head_name = snapshot.head_name;
+        self.unborn_branch = snapshot.unborn_branch;
+        self.head_oid = snapshot.head_oid;
+        self.upstream_fork_point = snapshot.upstream_fork_point;
+        // History is immutable, so distances only change with the tags
+        if self.commit_tags != snapshot.tags {
+            self.tag_distances.clear();
+        }
+        self.commit_tags = snapshot.tags;
+        self.tip_subjects.clear();
+        self.graph_layout = self.layout_graph(uncommitted_count, self.head_oid);
+    }
+
+    /// Build the graph layout from the loaded data, applying view options
+    fn layout_graph(
+        &self,
+        uncommitted_count: Option<usize>,
+        head_commit_oid: Option<Oid>,
+    ) -> GraphLayout {
+        let density = self.graph_density;
+        let colors = self.lane_colors;
+        // Braille glyphs draw connectors in the upper half of the fork point's row
+        let half_rows = self.config.ui.graph_glyphs == GraphGlyphs::Braille;
+        let mut layout = if let Some(filter) = self.view_filter() {
+            let options = LayoutOptions {
+                density,
+                colors,
+                hidden: filter.hidden_links(&self.commits),
+                half_rows,
+            };
+            build_graph_with_options(
+                &filter.apply(&self.commits),
+                &filter.apply_branches(&self.branches),
+                uncommitted_count,
+                head_commit_oid.and_then(|oid| filter.resolve(oid)),
+                &options,
+            )
+        } else {
+            let options = LayoutOptions {
+                density,
+                colors,
+                half_rows,
+                ..Default::default()
+            };
+            build_graph_with_options(
+                &self.commits,
+                &self.branches,
+                uncommitted_count,
+                head_commit_oid,
+                &options,
+            )
+        };
+        self.sort_labels(&mut layout);
+        layout
+    }
+
+    /// Order each row's branch labels for display (`ui.label_order`)
+    fn sort_labels(&self, layout: &mut GraphLayout) {
+        let remotes: HashSet<&str> = self
+            .branches
+            .iter()
+            .filter(|b| b.is_remote)
+            .map(|b| b.name.as_str())
+            .collect();
+        for node in &mut layout.nodes {
+            sort_branch_labels(
+                &mut node.branch_names,
+                self.head_name.as_deref(),
+                |name| remotes.contains(name),
+                self.config.ui.label_order,
+            );
+        }
+    }
+
+    /// Rebuild the graph from the loaded data after a view option change
+    /// Keeps the selected commit (or the commit shown in its place) selected
+    fn relayout(&mut self) {
+        let selected_oid = self
+            .selected_commit_node()
+            .and_then(|n| n.commit.as_ref())
+            .map(|c| c.oid);
+        let uncommitted_count = self.uncommitted_count();
+
+        self.graph_layout = self.layout_graph(uncommitted_count, self.head_oid);
+        self.branch_positions = Self::build_branch_positions(&self.graph_layout);
+        self.search_state = SearchState::default();
+
+        let selected_oid = match self.view_filter() {
+            Some(filter) => selected_oid.and_then(|oid| filter.resolve(oid)),
+            None => selected_oid,
+        };
+        let node_idx = selected_oid
+            .and_then(|oid| {
+                self.graph_layout
+                    .nodes
+                    .iter()
+                    .position(|n| n.commit.as_ref().map(|c| c.oid) == Some(oid))
+            })
+            .unwrap_or(0);
+        self.graph_list_state.select(Some(node_idx));
+        self.sync_branch_selection_to_node(node_idx);
+    }
+
+    /// Filter for the active view options (None when every loaded commit is shown)
+    fn view_filter(&self) -> Option<CommitFilter> {
+        let hide_merges = self.hide_merges;
+        match self.date_range {
+            None if hide_merges => Some(CommitFilter::merges(&self.commits)),
+            None => None,
+            Some(range) => {
+                let name = if hide_merges { "merge/date" } else { "date" };
+                Some(CommitFilter::new(&self.commits, name, |c| {
+                    (hide_merges && c.parent_oids.len() >= 2) || !range.contains(c.author_time)
+                }))
+            }
+        }
+    }
+
+    /// Show only commits authored in the given range (an unbounded range clears the filter)
+    fn set_date_range(&mut self, range: DateRange) {
+        self.date_range = Some(range).filter(|r| !r.is_unbounded());
+        self.relayout();
+    }
+
+    /// Toggle hiding of merge commits
+    fn toggle_hide_merges(&mut self) {
+        self.hide_merges = !self.hide_merges;
+        self.relayout();
+    }
+
+    /// Toggle compact density (one row per commit); off returns to the configured density
+    fn toggle_compact_graph(&mut self) {
+        self.graph_density = match (self.graph_density, self.config.ui.graph_density) {
+            (GraphDensity::Compact, GraphDensity::Compact) => GraphDensity::Normal,
+            (GraphDensity::Compact, configured) => configured,
+            _ => GraphDensity::Compact,
+        };
+        self.relayout();
+        self.set_message(if self.graph_density == GraphDensity::Compact {
+            "Compact graph: one row per commit"
+        } else {
+            "Connector rows shown"
+        });
+    }
+
+    /// Switch the lane colors between the balanced scheme and the plain rotation
+    fn toggle_lane_colors(&mut self) {
+        self.lane_colors = match self.lane_colors {
+            ColorScheme::Balanced => ColorScheme::Rotation,
+            ColorScheme::Rotation => ColorScheme::Balanced,
+        };
+        self.relayout();
+        self.set_message(match self.lane_colors {
+            ColorScheme::Balanced => "Lane colors: balanced",
+            ColorScheme::Rotation => "Lane colors: one per lane position",
+        });
+    }
+
+    /// Toggle between all branches and the current branch's first-parent history
+    /// Keeps the selected commit selected when it is still loaded
+    fn toggle_walk_scope(&mut self) -> Result<()> {
+        let selected_oid = self
+            .selected_commit_node()
+            .and_then(|n| n.commit.as_ref())
+            .map(|c| c.oid);
+
+        self.walk_scope = match self.walk_scope {
+            WalkScope::AllBranches => WalkScope::CurrentBranch,
+            WalkScope::CurrentBranch => WalkScope::AllBranches,
+        };
+        self.refresh(true)?;
+
+        if let Some(oid) = selected_oid {
+            self.select_commit(oid);
+        }
+        Ok(())
+    }
+
+    /// HEAD as shown in the status bar ("(unborn) main" before the first commit)
+    pub fn head_label(&self) -> Option<String> {
+        self.head_name.clone().or_else(|| {
+            self.unborn_branch
+                .as_ref()
+                .map(|name| format!("(unborn) {}", name))
         })
     }
 
+    /// Short indicators for active view options (shown in the status bar)
+    pub fn view_indicators(&self) -> Vec<String> {
+        let mut indicators = Vec::new();
+        if self.hide_merges {
+            indicators.push("[no-merges]".to_string());
+        }
+        if let Some(range) = &self.date_range {
+            indicators.push(format!("[dates {} (X clears)]", range.label()));
+        }
+        if self.walk_scope == WalkScope::CurrentBranch {
+            indicators.push("[this-branch]".to_string());
+        }
+        if self.raw_workdir_diff {
+            indicators.push("[raw-diff]".to_string());
+        }
+        if self.follow_head {
+            indicators.push("[follow]".to_string());
+        }
+        match self.graph_layout.offscreen_branches.len() {
+            0 => {}
+            1 => indicators.push("[1 branch off-screen]".to_string()),
+            n => indicators.push(format!("[{} branches off-screen]", n)),
+        }
+        if let Some(session) = &self.bisect {
+            indicators.push(match (self.bisect_first_bad, session.remaining()) {
+                (Some(_), _) => "[bisect: found]".to_string(),
+                (None, Some(n)) => format!("[bisect: {} left]", n),
+                (None, None) => "[bisect]".to_string(),
+            });
+        }
+        indicators
+    }
+
     /// Clear all diff caches
     fn clear_all_diff_caches(&mut self) {
         self.diff_cache = None;
         self.diff_cache_oid = None;
         self.diff_loading_oid = None;
         self.diff_receiver = None;
+        self.diff_error = None;
         self.clear_uncommitted_diff_cache();
     }
 
@@ -288,12 +998,30 @@ impl App {
         self.uncommitted_diff_cache = None;
         self.uncommitted_diff_loading = false;
         self.uncommitted_diff_receiver = None;
+        self.uncommitted_diff_error = None;
         self.uncommitted_cache_key = None;
     }
 
+    /// Read the repository for a refresh, once more after a short pause if another git
+    /// process held a lock or changed a ref mid-read
+    fn refresh_snapshot(&self) -> Result<RepoSnapshot, GitError> {
+        let read = || {
+            self.repo
+                .snapshot_pinned(self.commit_limit, self.walk_scope, self.start_rev)
+        };
+        match read() {
+            Err(e) if e.is_transient() => {
+                std::thread::sleep(TRANSIENT_RETRY_DELAY);
+                read()
+            }
+            result => result,
+        }
+    }
+
     /// Refresh repository data
     /// If `force` is true, always clears diff cache (for manual refresh)
     /// If `force` is false, keeps cache when the same content is selected (for auto-refresh)
+    /// Nothing is applied on failure, so the view keeps its previous data
     pub fn refresh(&mut self, force: bool) -> Result<()> {
         // Save the current selection state for restoration
         let was_uncommitted_selected = self
@@ -311,16 +1039,7 @@ impl App {
         let working_tree_status = self.repo.get_working_tree_status().ok().flatten();
         let uncommitted_count = working_tree_status.as_ref().map(|s| s.file_count);
 
-        self.commits = self.repo.get_commits(500)?;
-        self.branches = self.repo.get_branches()?;
-        let head_commit_oid = self.repo.head_oid();
-        self.graph_layout = build_graph(
-            &self.commits,
-            &self.branches,
-            uncommitted_count,
-            head_commit_oid,
-        );
-        self.head_name = self.repo.head_name();
+        self.apply_snapshot(self.refresh_snapshot()?, uncommitted_count);
 
         // Rebuild branch positions
         self.branch_positions = Self::build_branch_positions(&self.graph_layout);
@@ -350,6 +1069,10 @@ impl App {
             }
         }
 
+        if self.follow_head {
+            self.select_head();
+        }
+
         // Handle diff cache based on force flag
         if force {
             self.clear_all_diff_caches();
@@ -368,6 +1091,7 @@ impl App {
                 self.diff_cache_oid = None;
                 self.diff_loading_oid = None;
                 self.diff_receiver = None;
+                self.diff_error = None;
             }
 
             // Keep uncommitted diff cache only if:
@@ -381,6 +1105,7 @@ impl App {
 
         // Clear search state on refresh to avoid stale indices
         self.search_state = SearchState::default();
+        self.rebuild_gutter();
 
         // Clamp the selection
         let max_commit = self.graph_layout.nodes.len().saturating_sub(1);
@@ -393,13 +1118,353 @@ impl App {
         Ok(())
     }
 
+    /// Re-read the branches after an operation that only added, removed or renamed refs
+    /// The loaded commits stay the same, so labels are re-attached to the existing rows
+    /// instead of walking history again. Falls back to `refresh` when a deleted branch
+    /// was the only way to reach its tip
+    fn refresh_refs(&mut self) -> Result<()> {
+        let branches = self.repo.get_branches()?;
+        if self.walk_scope == WalkScope::AllBranches {
+            let orphaned = self.branches.iter().any(|old| {
+                !branches.iter().any(|b| b.tip_oid == old.tip_oid)
+                    && self.graph_layout.children_of(old.tip_oid).is_empty()
+                    && self.start_rev != Some(old.tip_oid)
+            });
+            if orphaned {
+                return self.refresh(true);
+            }
+        }
+
+        let selected = self.graph_list_state.selected();
+        let prev_branch_name = self.selected_branch_name().map(str::to_string);
+        self.branches = branches;
+        self.head_name = self.repo.head_name();
+
+        let mut layout = std::mem::take(&mut self.graph_layout);
+        match self.view_filter() {
+            Some(filter) => layout.attach_branches(&filter.apply_branches(&self.branches)),
+            None => layout.attach_branches(&self.branches),
+        }
+        self.sort_labels(&mut layout);
+        self.graph_layout = layout;
+        self.branch_positions = Self::build_branch_positions(&self.graph_layout);
+
+        // Keep the row, and the label on it when it is still there
+        self.selected_branch_position = prev_branch_name.and_then(|name| {
+            self.branch_positions
+                .iter()
+                .position(|(idx, n)| Some(*idx) == selected && *n == name)
+        });
+        if self.selected_branch_position.is_none() {
+            if let Some(row) = selected {
+                self.sync_branch_selection_to_node(row);
+            }
+        }
+        if self.follow_head {
+            self.select_head();
+        }
+        self.search_state = SearchState::default();
+        self.rebuild_gutter();
+        Ok(())
+    }
+
     /// Update fuzzy search results for the given query
+    /// An empty query lists every branch in the current sort order
     fn update_fuzzy_search(&mut self, query: &str) {
-        self.search_state.fuzzy_matches = fuzzy_search_branches(query, &self.branch_positions);
+        if query.is_empty() {
+            self.show_branch_list(self.search_state.original_position);
+        } else {
+            self.search_state.fuzzy_matches =
+                fuzzy_search_branches_with(query, &self.branch_positions, self.search_case);
+            self.search_state.sections.clear();
+            self.search_state.clamp_selection();
+            self.update_search_counts();
+            self.update_search_subjects();
+        }
+    }
+
+    /// Tip commit of a listed branch
+    fn branch_tip(&self, name: &str) -> Option<Oid> {
+        self.branches
+            .iter()
+            .find(|b| b.name == name)
+            .map(|b| b.tip_oid)
+    }
+
+    /// Count the commits ahead of the main branch for tips not counted yet
+    /// Counts are kept per tip until the main branch moves
+    fn update_ahead_counts(&mut self, tips: &[Oid]) {
+        let base = self.repo.main_oid();
+        if base != self.ahead_base {
+            self.ahead_counts.clear();
+            self.ahead_base = base;
+        }
+        let missing: Vec<Oid> = tips
+            .iter()
+            .copied()
+            .filter(|tip| !self.ahead_counts.contains_key(tip))
+            .collect();
+        if !missing.is_empty() {
+            self.ahead_counts.extend(self.repo.ahead_of_main(&missing));
+        }
+    }
+
+    /// Tip of each search result's branch
+    fn search_result_tips(&self) -> Vec<Option<Oid>> {
+        self.search_state
+            .fuzzy_matches
+            .iter()
+            .map(|r| {
+                let (_, name) = self.branch_positions.get(r.branch_idx)?;
+                self.branch_tip(name)
+            })
+            .collect()
+    }
+
+    /// Fill in the tip commit subjects shown with the results
+    /// Subjects come from the loaded commits, or the repository for tips outside them,
+    /// and are kept per tip until the next refresh
+    fn update_search_subjects(&mut self) {
+        let tips = self.search_result_tips();
+        let missing: HashSet<Oid> = tips
+            .iter()
+            .flatten()
+            .filter(|tip| !self.tip_subjects.contains_key(tip))
+            .copied()
+            .collect();
+        if !missing.is_empty() {
+            for commit in self.commits.iter().filter(|c| missing.contains(&c.oid)) {
+                self.tip_subjects.insert(commit.oid, commit.message.clone());
+            }
+            for tip in missing {
+                if !self.tip_subjects.contains_key(&tip) {
+                    let subject = self
+                        .repo
+                        .repo
+                        .find_commit(tip)
+                        .ok()
+                        .and_then(|c| c.summary().map(str::to_string))
+                        .unwrap_or_default();
+                    self.tip_subjects.insert(tip, subject);
+                }
+            }
+        }
+        self.search_state.subjects = tips
+            .into_iter()
+            .map(|tip| {
+                tip.and_then(|tip| self.tip_subjects.get(&tip).cloned())
+                    .unwrap_or_default()
+            })
+            .collect();
+    }
+
+    /// Fill in the commit counts shown next to the results (`ui.branch_counts`)
+    fn update_search_counts(&mut self) {
+        self.search_state.counts.clear();
+        if !self.config.ui.branch_counts {
+            return;
+        }
+        let tips = self.search_result_tips();
+        self.update_ahead_counts(&tips.iter().flatten().copied().collect::<Vec<_>>());
+        self.search_state.counts = tips
+            .into_iter()
+            .map(|tip| tip.and_then(|tip| self.ahead_counts.get(&tip).copied()))
+            .collect();
+    }
+
+    /// List all branches in the dropdown, keeping `selected` (a branch position index) selected
+    fn show_branch_list(&mut self, selected: Option<usize>) {
+        if self.branch_sort == BranchSort::Ahead {
+            let tips: Vec<Oid> = self
+                .branch_positions
+                .iter()
+                .filter_map(|(_, name)| self.branch_tip(name))
+                .collect();
+            self.update_ahead_counts(&tips);
+        }
+
+        let by_name: HashMap<&str, &BranchInfo> =
+            self.branches.iter().map(|b| (b.name.as_str(), b)).collect();
+        let mut listed: Vec<&BranchInfo> = self
+            .branch_positions
+            .iter()
+            .filter_map(|(_, name)| by_name.get(name.as_str()).copied())
+            .collect();
+
+        let grouping = self.config.ui.branch_grouping;
+        sort_branches(&mut listed, self.branch_sort, grouping, &self.ahead_counts);
+
+        let mut sections = Vec::new();
+        let results: Vec<FuzzySearchResult> = listed
+            .iter()
+            .filter_map(|b| {
+                let branch_idx = self
+                    .branch_positions
+                    .iter()
+                    .position(|(_, name)| name == &b.name)?;
+                sections.push(branch_section(b).to_string());
+                Some(FuzzySearchResult {
+                    branch_idx,
+                    score: 0,
+                    matched_indices: Vec::new(),
+                })
+            })
+            .collect();
+        // A single section needs no header
+        if grouping != BranchGrouping::Sections || sections.iter().all(|s| *s == sections[0]) {
+            sections.clear();
+        }
+        self.search_state.sections = sections;
+
+        self.search_state.dropdown_selection =
+            selected.and_then(|idx| results.iter().position(|r| r.branch_idx == idx));
+        self.search_state.fuzzy_matches = results;
+        self.update_search_counts();
+        self.update_search_subjects();
         self.search_state.clamp_selection();
     }
 
+    /// Section header of each search result (empty when the list has no headers)
+    pub fn search_sections(&self) -> &[String] {
+        &self.search_state.sections
+    }
+
+    /// Commits ahead of the main branch of each search result (empty when not shown)
+    pub fn search_counts(&self) -> &[Option<usize>] {
+        &self.search_state.counts
+    }
+
+    /// Tip commit subject of each search result
+    pub fn search_subjects(&self) -> &[String] {
+        &self.search_state.subjects
+    }
+
+    /// Where the branch list shows the tip commit subjects
+    pub fn subject_layout(&self) -> SubjectLayout {
+        self.subject_layout
+    }
+
+    /// Sort order of the branch list
+    pub fn branch_sort(&self) -> BranchSort {
+        self.branch_sort
+    }
+
+    /// Case matching of the branch search
+    pub fn search_case(&self) -> SearchCase {
+        self.search_case
+    }
+
+    /// Completions for the command palette input, best first
+    pub fn command_completions(&self) -> &[String] {
+        &self.command_completions
+    }
+
+    /// Complete the palette input against branch and tag names
+    /// (and the selected commit's changed files for `open`)
+    fn update_command_completions(&mut self, input: &str) {
+        let refs: Vec<String> = self
+            .branches
+            .iter()
+            .map(|b| b.name.clone())
+            .chain(self.commit_tags.values().flatten().cloned())
+            .collect();
+        let selected = self
+            .selected_commit_node()
+            .and_then(|n| n.commit.as_ref())
+            .map(|c| c.oid);
+        let files: Vec<String> = self
+            .diff_cache
+            .as_ref()
+            .filter(|_| selected.is_some() && self.diff_cache_oid == selected)
+            .map(|diff| {
+                diff.files
+                    .iter()
+                    .map(|f| f.path.to_string_lossy().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.command_completions = command::completions(input, &refs, &files);
+    }
+
+    /// Take the file waiting to be opened in the editor (the main loop runs it)
+    pub fn take_editor_file(&mut self) -> Option<CommitFile> {
+        self.editor_file.take()
+    }
+
+    /// Run a command palette line
+    /// Invalid commands and unknown refs fail before the palette closes, so it comes back
+    fn run_command(&mut self, input: &str) -> Result<()> {
+        match Command::parse(input)? {
+            Command::Goto(spec) => {
+                let oid = self.repo.resolve_commit(&spec)?;
+                if self.graph_layout.row_of(oid).is_none() {
+                    anyhow::bail!("{} is not in the loaded commits (see limit)", spec);
+                }
+                self.reset_mode();
+                if self.branch_positions.iter().any(|(_, name)| *name == spec) {
+                    self.select_branch_by_name(&spec);
+                } else {
+                    self.select_commit(oid);
+                }
+            }
+            Command::Checkout(spec) => {
+                let op = if self.branch_tip(&spec).is_some() {
+                    RepeatableOp::CheckoutBranch(spec)
+                } else {
+                    RepeatableOp::CheckoutCommit(self.repo.resolve_commit(&spec)?)
+                };
+                self.reset_mode();
+                self.run_checkout(op)?;
+            }
+            Command::Branch(name) => {
+                let Some(oid) = self
+                    .selected_commit_node()
+                    .and_then(|n| n.commit.as_ref())
+                    .map(|c| c.oid)
+                else {
+                    anyhow::bail!("Select a commit to branch from");
+                };
+                create_branch(&self.repo.repo, &name, oid)?;
+                self.last_operation = Some(RepeatableOp::CreateBranch(name));
+                self.reset_mode();
+                self.refresh_refs()?;
+            }
+            Command::Open(path) => {
+                let Some(commit) = self.selected_commit_node().and_then(|n| n.commit.as_ref())
+                else {
+                    anyhow::bail!("Select a commit to open the file at");
+                };
+                let (oid, short_id) = (commit.oid, commit.short_id.clone());
+                let path = PathBuf::from(path);
+                let content = file_at_commit(&self.repo.repo, oid, &path)?;
+                self.editor_file = Some(CommitFile::create(&path, &short_id, &content)?);
+                self.reset_mode();
+            }
+            Command::ResetView => {
+                self.reset_mode();
+                self.reset_view_settings()?;
+            }
+            Command::Limit(limit) => {
+                self.reset_mode();
+                self.commit_limit = limit;
+                self.refresh(false)?;
+                let last = self.graph_layout.nodes.len().saturating_sub(1);
+                if self
+                    .graph_list_state
+                    .selected()
+                    .is_some_and(|row| row > last)
+                {
+                    self.graph_list_state.select(Some(last));
+                    self.sync_branch_selection_to_node(last);
+                }
+                self.set_message(format!("Loaded {}", commits(self.commits.len())));
+            }
+        }
+        Ok(())
+    }
+
     /// Jump to the currently selected search result
+    /// Selects the searched label too, so checkout targets it when the commit has several
     fn jump_to_search_result(&mut self) {
         let Some(result) = self.search_state.selected_result() else {
             return;
@@ -437,11 +1502,77 @@ impl App {
         self.search_state.dropdown_selection
     }
 
-    /// Jump to the currently checked out branch (HEAD)
-    fn jump_to_head(&mut self) {
+    /// Branch marked as the start of a branch diff
+    pub fn search_diff_mark(&self) -> Option<&str> {
+        self.search_state.diff_mark.as_deref()
+    }
+
+    /// Mark the selected branch, or diff the marked branch against it
+    /// Selecting the marked branch again clears the mark
+    fn mark_branch_diff(&mut self) -> Result<()> {
+        let Some((_, name)) = self
+            .search_state
+            .selected_result()
+            .and_then(|result| self.branch_positions.get(result.branch_idx))
+        else {
+            return Ok(());
+        };
+        let name = name.clone();
+
+        match self.search_state.diff_mark.take() {
+            None => {
+                self.set_message(format!(
+                    "Diff from {}: press Ctrl+d on another branch",
+                    name
+                ));
+                self.search_state.diff_mark = Some(name);
+            }
+            Some(from) if from == name => self.set_message("Diff mark cleared"),
+            Some(from) => {
+                let (Some(from_oid), Some(to_oid)) =
+                    (self.branch_tip(&from), self.branch_tip(&name))
+                else {
+                    return Ok(());
+                };
+                let comparison =
+                    BranchComparison::new(&self.repo.repo, (&from, from_oid), (&name, to_oid))?;
+                self.open_mode(AppMode::BranchDiff {
+                    comparison: Box::new(comparison),
+                    scroll: 0,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Select a branch (and its commit) by name
+    fn select_branch_by_name(&mut self, name: &str) {
+        let Some(pos) = self.branch_positions.iter().position(|(_, n)| n == name) else {
+            return;
+        };
+        self.selected_branch_position = Some(pos);
+        self.graph_list_state
+            .select(Some(self.branch_positions[pos].0));
+    }
+
+    /// Name being typed while a branch label is edited in place
+    pub fn editing_branch_label(&self) -> Option<&str> {
+        match &self.mode {
+            AppMode::Input {
+                input,
+                action: InputAction::RenameBranch(_),
+                ..
+            } => Some(input),
+            _ => None,
+        }
+    }
+
+    /// Jump to the currently checked out branch (HEAD)
+    /// Returns false when HEAD is detached (no branch label to select)
+    fn jump_to_head(&mut self) -> bool {
         // Find the HEAD branch name
         let Some(head_name) = &self.head_name else {
-            return;
+            return false;
         };
 
         // Find the branch position index that matches HEAD
@@ -451,11 +1582,57 @@ impl App {
             .enumerate()
             .find(|(_, (_, name))| name == head_name)
         else {
-            return;
+            return false;
         };
 
         self.selected_branch_position = Some(branch_pos_idx);
         self.graph_list_state.select(Some(*node_idx));
+        true
+    }
+
+    /// Load history down to a revision and select it (`--rev`)
+    pub fn start_at_rev(&mut self, spec: &str) -> Result<()> {
+        let oid = self.repo.resolve_commit(spec)?;
+        self.start_rev = Some(oid);
+        self.refresh(true)?;
+        self.select_commit(oid);
+        Ok(())
+    }
+
+    /// Jump between a commit rewritten in this session and its original
+    fn jump_to_provenance(&mut self) {
+        let Some(oid) = self
+            .selected_commit_node()
+            .and_then(|n| n.commit.as_ref())
+            .map(|c| c.oid)
+        else {
+            return;
+        };
+        match self.provenance.counterpart(oid) {
+            Some(other) if self.graph_layout.row_of(other).is_some() => self.select_commit(other),
+            Some(other) => self.set_message(format!(
+                "{} is not in the loaded commits",
+                &other.to_string()[..7]
+            )),
+            None => self.set_message("Not cherry-picked or rebased in this session"),
+        }
+    }
+
+    /// Select the HEAD branch, or the HEAD commit when detached
+    /// (a detached HEAD is named "HEAD", which no branch label matches)
+    /// Returns false, leaving the selection alone, when HEAD is not shown
+    fn select_head(&mut self) -> bool {
+        if self.jump_to_head() {
+            return true;
+        }
+        match self.head_oid.and_then(|oid| self.graph_layout.row_of(oid)) {
+            Some(row) => {
+                self.graph_list_state.select(Some(row));
+                self.sync_branch_selection_to_node(row);
+                true
+            }
+            None => false,
+        }
     }
 
     /// Check if async fetch has completed and process the result
@@ -463,15 +1640,37 @@ impl App {
         let Some(rx) = &self.fetch_receiver else {
             return;
         };
-        let Ok(fetch_result) = rx.try_recv() else {
-            return;
+        let fetch_result = match rx.try_recv() {
+            Ok(result) => result.and_then(|r| r),
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("Fetch worker stopped unexpectedly".to_string()),
         };
 
-        let silent = self.fetch_silent;
+        let kind = self.fetch_kind;
         self.fetch_receiver = None;
-        self.fetch_silent = false;
+
+        match self.mode {
+            // Quit now that nothing is running, reporting the outcome on exit
+            AppMode::QuitBlocked { waiting: true, .. } => {
+                self.should_quit = true;
+                self.exit_message = Some(match fetch_result {
+                    Ok(()) => "Fetched from origin".to_string(),
+                    Err(e) => e,
+                });
+                return;
+            }
+            // The operation the dialog asked about is over
+            AppMode::QuitBlocked { waiting: false, .. } => self.close_mode(),
+            _ => {}
+        }
 
         match fetch_result {
+            Ok(()) if kind == FetchKind::Pull => {
+                self.reset_timers();
+                if let Err(e) = self.finish_pull() {
+                    self.show_error(e.to_string());
+                }
+            }
             Ok(()) => {
                 self.reset_timers();
                 match self.refresh(true) {
@@ -479,8 +1678,67 @@ impl App {
                     Err(e) => self.show_error(format!("Refresh failed: {e}")),
                 }
             }
-            Err(e) if !silent => self.show_error(e),
-            Err(_) => {} // Silent mode: suppress error dialog for auto-fetch
+            Err(e) => match kind {
+                FetchKind::Manual | FetchKind::Pull => self.show_error(e),
+                FetchKind::Startup => self.set_warning(format!("Showing local state: {e}")),
+                FetchKind::Background => {}
+            },
+        }
+    }
+
+    /// Check if the background update check has completed
+    pub fn update_update_check(&mut self) {
+        let Some(rx) = &self.update_receiver else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(notice) => {
+                // Failures (including panics) are silent for the update check
+                self.update_notice = notice.ok().flatten();
+                self.update_receiver = None;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => self.update_receiver = None,
+        }
+    }
+
+    /// Check if the repository stats worker has finished
+    pub fn update_repo_stats(&mut self) {
+        let Some(rx) = &self.repo_stats_receiver else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(stats) => {
+                // A panicked worker leaves every value unavailable
+                self.repo_stats = Some(stats.unwrap_or_default());
+                self.repo_stats_receiver = None;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => {
+                self.repo_stats = Some(RepoStats::default());
+                self.repo_stats_receiver = None;
+            }
+        }
+    }
+
+    /// Copy the release URL of the available update and dismiss the notice
+    fn copy_release_url(&mut self) -> Result<()> {
+        let Some(notice) = self.update_notice.take() else {
+            return Ok(());
+        };
+        let mut clipboard = Clipboard::new()?;
+        clipboard.set_text(notice.url)?;
+        self.set_message(format!("Copied release URL for v{}", notice.version));
+        Ok(())
+    }
+
+    /// Stop the running fetch (the result arrives through `update_fetch_status`)
+    fn cancel_fetch(&mut self) {
+        if self.is_fetching() {
+            self.fetch_cancel.store(true, Ordering::Relaxed);
+            // Cancelling is a user decision, not an error worth a dialog
+            self.fetch_kind = FetchKind::Background;
+            self.set_message("Fetch cancelled");
         }
     }
 
@@ -502,7 +1760,7 @@ impl App {
         if refresh_config.auto_fetch
             && now.duration_since(self.last_fetch_time).as_secs() >= refresh_config.fetch_interval
         {
-            self.start_fetch(false, true); // silent=true for auto-fetch
+            self.start_fetch(FetchKind::Background);
             return;
         }
 
@@ -516,22 +1774,96 @@ impl App {
         }
     }
 
+    /// Start the opt-in startup fetch (call after the first frame is drawn)
+    pub fn start_startup_fetch(&mut self) {
+        if self.config.refresh.fetch_on_start && !self.is_fetching() {
+            self.start_fetch(FetchKind::Startup);
+        }
+    }
+
+    /// Whether the running fetch is the startup fetch (shown as a quiet indicator)
+    pub fn is_startup_fetch(&self) -> bool {
+        self.is_fetching() && self.fetch_kind == FetchKind::Startup
+    }
+
+    /// Fetch on the user's request, joining a fetch that is already running
+    fn fetch_manually(&mut self) {
+        if !self.is_fetching() {
+            self.start_fetch(FetchKind::Manual);
+        } else if self.fetch_kind != FetchKind::Manual && !self.fetch_cancel.load(Ordering::Relaxed)
+        {
+            // Report the running fetch like a manual one instead of fetching twice
+            self.fetch_kind = FetchKind::Manual;
+            self.set_message("Fetching from origin...");
+        }
+    }
+
+    /// Fetch, then bring the HEAD branch up to its upstream (see `finish_pull`)
+    /// A running fetch is joined instead of fetching twice
+    fn pull(&mut self) -> Result<()> {
+        // Fail before fetching when there is nothing to pull into
+        head_upstream(&self.repo.repo)?;
+        if !self.is_fetching() {
+            self.start_fetch(FetchKind::Pull);
+        } else if !self.fetch_cancel.load(Ordering::Relaxed) {
+            self.fetch_kind = FetchKind::Pull;
+            self.set_message("Pulling from origin...");
+        }
+        Ok(())
+    }
+
+    /// Fast-forward the HEAD branch after the pull's fetch
+    /// A diverged branch is never merged silently: a dialog offers the merge
+    fn finish_pull(&mut self) -> Result<()> {
+        ensure_index_unlocked(&self.repo.repo)?;
+        let outcome = fast_forward_to_upstream(&self.repo.repo)?;
+        self.refresh(true)?;
+        let (head, upstream) = head_upstream(&self.repo.repo)?;
+        match outcome {
+            PullOutcome::UpToDate => self.set_message("Already up to date"),
+            PullOutcome::FastForwarded => {
+                self.set_message(format!("Fast-forwarded '{}' to {}", head, upstream))
+            }
+            PullOutcome::Diverged => {
+                let mut message = format!(
+                    "'{}' and {} have diverged. Merge {} (y) or abort (n)?",
+                    head, upstream, upstream
+                );
+                let tip = self.branches.iter().find(|b| b.name == upstream);
+                if let Some(tip) = tip.map(|b| b.tip_oid) {
+                    if !self.divergence_preview(tip, true)?.1 {
+                        message = format!("{}\n{}", message, MERGE_BASE_NOT_LOADED);
+                    }
+                }
+                let action = ConfirmAction::Merge(upstream.clone());
+                let expected = self.expected_state(&action);
+                self.open_mode(AppMode::Confirm {
+                    message,
+                    action,
+                    expected,
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Start fetch in background
-    /// If `show_message` is true, displays "Fetching from origin..."
-    /// If `silent` is true, errors will not show a dialog (for auto-fetch)
-    fn start_fetch(&mut self, show_message: bool, silent: bool) {
-        let (tx, rx) = mpsc::channel();
-        let repo_path = self.repo_path.clone();
-
-        thread::spawn(move || {
-            let result = fetch_origin(&repo_path).map_err(|e| e.to_string());
-            let _ = tx.send(result);
+    /// Manual fetches display "Fetching from origin..."
+    fn start_fetch(&mut self, kind: FetchKind) {
+        let git_dir = self.repo_location.git_dir.to_string_lossy().to_string();
+        let timeout = Duration::from_secs(self.config.refresh.fetch_timeout);
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.fetch_cancel = Arc::clone(&cancel);
+        let rx = worker::spawn(move || {
+            fetch_origin(&git_dir, timeout, &cancel).map_err(|e| e.to_string())
         });
 
         self.fetch_receiver = Some(rx);
-        self.fetch_silent = silent;
-        if show_message {
-            self.set_message("Fetching from origin...");
+        self.fetch_kind = kind;
+        match kind {
+            FetchKind::Manual => self.set_message("Fetching from origin..."),
+            FetchKind::Pull => self.set_message("Pulling from origin..."),
+            FetchKind::Background | FetchKind::Startup => {}
         }
     }
 
@@ -546,6 +1878,18 @@ impl App {
     pub fn set_message(&mut self, msg: impl Into<String>) {
         self.message = Some(msg.into());
         self.message_time = Some(std::time::Instant::now());
+        self.message_warning = false;
+    }
+
+    /// Set a status message for a failure that needs no attention (shown dimmed)
+    pub fn set_warning(&mut self, msg: impl Into<String>) {
+        self.set_message(msg);
+        self.message_warning = true;
+    }
+
+    /// Whether the current message is a warning
+    pub fn message_is_warning(&self) -> bool {
+        self.message_warning
     }
 
     /// Get current message if not expired (5 seconds timeout)
@@ -574,11 +1918,35 @@ impl App {
 
     /// Update diff info for the selected commit (async)
     pub fn update_diff_cache(&mut self) {
+        self.update_last_touches();
+
         // Pull in completed results for commit diff
         if let Some(ref receiver) = self.diff_receiver {
-            if let Ok(result) = receiver.try_recv() {
-                self.diff_cache = result.diff;
-                self.diff_cache_oid = Some(result.oid);
+            let received = match receiver.try_recv() {
+                Ok(result) => Some(result),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => Some(Err("worker stopped".to_string())),
+            };
+            if let Some(result) = received {
+                match result {
+                    Ok(result) => {
+                        if let Some(distance) = result.tag_distance {
+                            self.tag_distances.insert(result.oid, distance);
+                        }
+                        if let Some(signature) = result.signature {
+                            self.signatures.insert(result.oid, signature);
+                        }
+                        self.diff_cache = result.diff;
+                        self.diff_cache_oid = Some(result.oid);
+                        self.diff_error = None;
+                    }
+                    Err(message) => {
+                        // Remember the failure for this commit so it is not retried in a loop
+                        self.diff_cache = None;
+                        self.diff_cache_oid = self.diff_loading_oid;
+                        self.diff_error = Some(diff_worker_error(&message));
+                    }
+                }
                 self.diff_loading_oid = None;
                 self.diff_receiver = None;
             }
@@ -586,8 +1954,22 @@ impl App {
 
         // Pull in completed results for uncommitted diff
         if let Some(ref receiver) = self.uncommitted_diff_receiver {
-            if let Ok(diff) = receiver.try_recv() {
-                self.uncommitted_diff_cache = diff;
+            let received = match receiver.try_recv() {
+                Ok(result) => Some(result),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => Some(Err("worker stopped".to_string())),
+            };
+            if let Some(result) = received {
+                match result {
+                    Ok(diff) => {
+                        self.uncommitted_diff_cache = diff;
+                        self.uncommitted_diff_error = None;
+                    }
+                    Err(message) => {
+                        self.uncommitted_diff_cache = None;
+                        self.uncommitted_diff_error = Some(diff_worker_error(&message));
+                    }
+                }
                 self.uncommitted_diff_loading = false;
                 self.uncommitted_diff_receiver = None;
             }
@@ -605,28 +1987,28 @@ impl App {
 
         // Handle uncommitted node
         if node.is_uncommitted {
-            // Do nothing if cache exists or already loading
-            if self.uncommitted_diff_cache.is_some() || self.uncommitted_diff_loading {
+            // Do nothing if cache exists, already loading, or the last attempt failed
+            if self.uncommitted_diff_cache.is_some()
+                || self.uncommitted_diff_loading
+                || self.uncommitted_diff_error.is_some()
+            {
                 return;
             }
 
             // Compute uncommitted diff in the background
-            let (tx, rx) = mpsc::channel();
-            let repo_path = self.repo_path.clone();
+            let location = self.repo_location.clone();
+            let raw = self.raw_workdir_diff;
 
             // Save current working tree status as cache key before starting computation
             self.uncommitted_cache_key = self.repo.get_working_tree_status().ok().flatten();
 
             self.uncommitted_diff_loading = true;
-            self.uncommitted_diff_receiver = Some(rx);
-
-            thread::spawn(move || {
-                let diff = git2::Repository::open(&repo_path)
+            self.uncommitted_diff_receiver = Some(worker::spawn(move || {
+                location
+                    .open()
                     .ok()
-                    .and_then(|repo| CommitDiffInfo::from_working_tree(&repo).ok());
-
-                let _ = tx.send(diff);
-            });
+                    .and_then(|repo| CommitDiffInfo::from_working_tree(&repo, raw).ok())
+            }));
             return;
         }
 
@@ -648,19 +2030,120 @@ impl App {
         }
 
         // Compute diff in the background
-        let (tx, rx) = mpsc::channel();
-        let repo_path = self.repo_path.clone();
+        let location = self.repo_location.clone();
+        let need_tag_distance = !self.tag_distances.contains_key(&oid);
+        let need_signature = !self.signatures.contains_key(&oid);
 
         self.diff_loading_oid = Some(oid);
-        self.diff_receiver = Some(rx);
+        self.diff_error = None;
+        self.diff_receiver = Some(worker::spawn(move || {
+            let repo = location.open().ok();
+            let diff = repo
+                .as_ref()
+                .and_then(|repo| CommitDiffInfo::from_commit(repo, oid).ok());
+            let tag_distance = repo
+                .as_ref()
+                .filter(|_| need_tag_distance)
+                .and_then(|repo| TagDistance::of(repo, oid).ok());
+            let signature = repo
+                .as_ref()
+                .filter(|_| need_signature)
+                .and_then(|repo| SignatureInfo::of(repo, oid).ok());
+
+            DiffResult {
+                oid,
+                diff,
+                tag_distance,
+                signature,
+            }
+        }));
+    }
 
-        thread::spawn(move || {
-            let diff = git2::Repository::open(&repo_path)
-                .ok()
-                .and_then(|repo| CommitDiffInfo::from_commit(&repo, oid).ok());
+    /// Collect finished annotations and annotate the selected commit's files when enabled
+    fn update_last_touches(&mut self) {
+        if let Some(ref receiver) = self.last_touch_receiver {
+            let received = match receiver.try_recv() {
+                Ok(result) => Some(result),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => Some(Err("worker stopped".to_string())),
+            };
+            if let Some(result) = received {
+                if let Some((oid, paths)) = self.last_touch_pending.take() {
+                    // A failed worker leaves the rows unannotated rather than retrying forever
+                    let touches =
+                        result.unwrap_or_else(|_| paths.into_iter().map(|p| (p, None)).collect());
+                    for (path, touch) in touches {
+                        self.last_touches.insert((oid, path), touch);
+                    }
+                }
+                self.last_touch_receiver = None;
+            }
+        }
 
-            let _ = tx.send(DiffResult { oid, diff });
-        });
+        if !self.file_annotations || self.last_touch_receiver.is_some() {
+            return;
+        }
+        let (Some(oid), Some(diff)) = (self.diff_cache_oid, &self.diff_cache) else {
+            return;
+        };
+        let selected = self
+            .selected_commit_node()
+            .and_then(|node| node.commit.as_ref())
+            .map(|c| c.oid);
+        if selected != Some(oid) {
+            return;
+        }
+        let paths: Vec<PathBuf> = diff
+            .files
+            .iter()
+            .map(|f| f.path.clone())
+            .filter(|path| !self.last_touches.contains_key(&(oid, path.clone())))
+            .collect();
+        if paths.is_empty() {
+            return;
+        }
+
+        let location = self.repo_location.clone();
+        let requested = paths.clone();
+        self.last_touch_pending = Some((oid, paths));
+        self.last_touch_receiver = Some(worker::spawn(move || {
+            let repo = location.open().ok();
+            requested
+                .into_iter()
+                .map(|path| {
+                    let touch = repo
+                        .as_ref()
+                        .and_then(|repo| LastTouch::before(repo, oid, &path).ok())
+                        .flatten();
+                    (path, touch)
+                })
+                .collect()
+        }));
+    }
+
+    /// Whether the changed files are annotated with their previous change
+    pub fn file_annotations(&self) -> bool {
+        self.file_annotations
+    }
+
+    /// Previous change to a file of the displayed commit
+    /// None while pending, Some(None) when there is no earlier change to show
+    pub fn last_touch(&self, path: &Path) -> Option<Option<&LastTouch>> {
+        let oid = self.diff_cache_oid?;
+        self.last_touches
+            .get(&(oid, path.to_path_buf()))
+            .map(Option::as_ref)
+    }
+
+    /// Latest tag reachable from a commit, once the diff worker has computed it
+    /// (Some(None) when there is no prior tag)
+    pub fn tag_distance(&self, oid: Oid) -> Option<Option<&TagDistance>> {
+        self.tag_distances.get(&oid).map(Option::as_ref)
+    }
+
+    /// Signer of a commit, once the diff worker has read it (Some(None) when unsigned)
+    pub fn signature(&self, oid: Oid) -> Option<Option<&SignatureInfo>> {
+        self.signatures.get(&oid).map(Option::as_ref)
     }
 
     /// Get cached diff info for the currently selected node
@@ -677,6 +2160,25 @@ impl App {
         }
     }
 
+    /// Worker error for the currently selected node's diff (e.g. the worker panicked)
+    pub fn diff_error(&self) -> Option<&str> {
+        let node = self
+            .graph_list_state
+            .selected()
+            .and_then(|idx| self.graph_layout.nodes.get(idx))?;
+
+        if node.is_uncommitted {
+            self.uncommitted_diff_error.as_deref()
+        } else {
+            let oid = node.commit.as_ref()?.oid;
+            if self.diff_cache_oid == Some(oid) {
+                self.diff_error.as_deref()
+            } else {
+                None
+            }
+        }
+    }
+
     /// Whether diff is currently loading for the selected node
     pub fn is_diff_loading(&self) -> bool {
         let node = self
@@ -692,16 +2194,163 @@ impl App {
 
     /// Handle an action
     pub fn handle_action(&mut self, action: Action) -> Result<()> {
-        match &self.mode {
-            AppMode::Normal => self.handle_normal_action(action)?,
-            AppMode::Help => self.handle_help_action(action),
-            AppMode::Input { .. } => self.handle_input_action(action)?,
-            AppMode::Confirm { .. } => self.handle_confirm_action(action)?,
-            AppMode::Error { .. } => self.handle_error_action(action),
+        self.upstream_flash = None;
+        // Help can overlay any read-only view and returns to it when closed
+        if action == Action::ToggleHelp && self.mode.allows_help_overlay() {
+            self.open_mode(AppMode::Help);
+            return Ok(());
+        }
+        // Back closes whatever is open; only the graph has nothing left to close
+        let action = match action {
+            Action::Back if matches!(self.mode, AppMode::Normal) => match self.config.keys.q {
+                QKey::Back => Action::Quit,
+                QKey::ConfirmQuit => {
+                    self.request_confirm("Quit keifu?".to_string(), ConfirmAction::Quit)?;
+                    return Ok(());
+                }
+            },
+            Action::Back => Action::Cancel,
+            action => action,
+        };
+        let result = match &self.mode {
+            AppMode::Normal => self.handle_normal_action(action),
+            AppMode::Help => {
+                self.handle_help_action(action);
+                Ok(())
+            }
+            AppMode::Input { .. } => self.handle_input_action(action),
+            AppMode::Confirm { .. } => self.handle_confirm_action(action),
+            AppMode::Error { .. } => {
+                self.handle_error_action(action);
+                Ok(())
+            }
+            AppMode::RawCommit { .. } | AppMode::RepoInfo { .. } | AppMode::BranchDiff { .. } => {
+                self.handle_scroll_popup_action(action);
+                Ok(())
+            }
+            AppMode::DiffView { .. } => {
+                self.handle_diff_view_action(action);
+                Ok(())
+            }
+            AppMode::Visual { .. } => self.handle_visual_action(action),
+            AppMode::Tags { .. } => self.handle_tags_action(action),
+            AppMode::StashList { .. } => self.handle_stash_list_action(action),
+            AppMode::CheckoutBlocked { .. } => self.handle_checkout_blocked_action(action),
+            AppMode::RetargetBranches { .. } => self.handle_retarget_action(action),
+            AppMode::Conflict { .. } => self.handle_conflict_action(action),
+            AppMode::CommitMenu { .. } => self.handle_commit_menu_action(action),
+            AppMode::PickChild { .. } => self.handle_pick_child_action(action),
+            AppMode::QuitBlocked { .. } => {
+                self.handle_quit_blocked_action(action);
+                Ok(())
+            }
+        };
+        self.rebuild_gutter();
+        self.view_store.update(self.view_settings(), Instant::now());
+        result
+    }
+
+    /// View options to remember (sort and density only when they differ from the config)
+    fn view_settings(&self) -> ViewSettings {
+        ViewSettings {
+            hide_merges: self.hide_merges,
+            walk_scope: self.walk_scope,
+            branch_sort: (self.branch_sort != self.config.ui.branch_sort)
+                .then_some(self.branch_sort),
+            search_case: self.search_case,
+            subject_layout: self.subject_layout,
+            raw_workdir_diff: self.raw_workdir_diff,
+            follow_head: self.follow_head,
+            graph_density: (self.graph_density != self.config.ui.graph_density)
+                .then_some(self.graph_density),
+            lane_colors: self.lane_colors,
+        }
+    }
+
+    /// Save changed view options once they have settled (call from the main loop)
+    pub fn save_view_settings_if_due(&mut self) {
+        if let Err(e) = self.view_store.save_if_due(Instant::now()) {
+            self.set_warning(format!("{}", e));
         }
+    }
+
+    /// Save changed view options now (call on exit)
+    pub fn flush_view_settings(&mut self) -> Result<()> {
+        self.view_store.flush()
+    }
+
+    /// Go back to the default view options and forget the saved ones
+    fn reset_view_settings(&mut self) -> Result<()> {
+        self.view_store.reset()?;
+        let walk_scope_changed = self.walk_scope != WalkScope::default();
+        self.hide_merges = false;
+        self.walk_scope = WalkScope::default();
+        self.branch_sort = self.config.ui.branch_sort;
+        self.search_case = SearchCase::default();
+        self.subject_layout = SubjectLayout::default();
+        if self.raw_workdir_diff {
+            self.raw_workdir_diff = false;
+            self.clear_uncommitted_diff_cache();
+        }
+        self.follow_head = false;
+        self.graph_density = self.config.ui.graph_density;
+        self.lane_colors = ColorScheme::default();
+        if walk_scope_changed {
+            self.refresh(true)?;
+        } else {
+            self.relayout();
+        }
+        self.set_message("View settings reset to defaults");
         Ok(())
     }
 
+    /// Collect gutter marks from bisect, bookmarks and search
+    fn rebuild_gutter(&mut self) {
+        self.gutter.clear();
+
+        if let Some(oid) = self.bisect_first_bad {
+            self.gutter.add(oid, GutterMark::BisectFirstBad);
+        }
+        if let Some(session) = &self.bisect {
+            if let Some(oid) = session.bad() {
+                self.gutter.add(oid, GutterMark::BisectBad);
+            }
+            for oid in session.good() {
+                self.gutter.add(*oid, GutterMark::BisectGood);
+            }
+        }
+        if let Some(oid) = self.upstream_fork_point {
+            self.gutter.add(oid, GutterMark::UpstreamFork);
+        }
+        if let Some(oid) = self.upstream_flash {
+            self.gutter.add(oid, GutterMark::Upstream);
+        }
+        if let Some(oid) = self.start_rev {
+            self.gutter.add(oid, GutterMark::StartRev);
+        }
+        for node in &self.graph_layout.nodes {
+            if let Some(commit) = &node.commit {
+                if self.bookmarks.contains(commit.oid) {
+                    self.gutter.add(commit.oid, GutterMark::Bookmark);
+                }
+            }
+        }
+        for oid in self.provenance.rewritten() {
+            self.gutter.add(oid, GutterMark::Rewritten);
+        }
+        for result in &self.search_state.fuzzy_matches {
+            let oid = self
+                .branch_positions
+                .get(result.branch_idx)
+                .and_then(|(node_idx, _)| self.graph_layout.nodes.get(*node_idx))
+                .and_then(|n| n.commit.as_ref())
+                .map(|c| c.oid);
+            if let Some(oid) = oid {
+                self.gutter.add(oid, GutterMark::SearchMatch);
+            }
+        }
+    }
+
     fn do_copy_hash(&mut self) -> Result<()> {
         if let Some(node) = self.selected_commit_node() {
             if let Some(commit) = &node.commit {
@@ -715,27 +2364,72 @@ impl App {
         Ok(())
     }
 
+    /// Copy the graph rows on screen as plain text
+    fn copy_view(&mut self) -> Result<()> {
+        let nodes = &self.graph_layout.nodes;
+        let start = self.graph_list_state.offset().min(nodes.len());
+        let end = (start + self.graph_view_height).min(nodes.len());
+        let text = rows_to_text(&nodes[start..end], self.head_name.as_deref());
+        let mut clipboard = Clipboard::new()?;
+        clipboard.set_text(text)?;
+        self.set_message(format!("Copied {} lines", end - start));
+        Ok(())
+    }
+
     /// Show an error
+    /// Errors go on top of whatever was active, which comes back once the error is closed
     pub fn show_error(&mut self, message: String) {
-        self.mode = AppMode::Error { message };
+        self.open_mode(AppMode::Error { message });
+    }
+
+    /// Open a mode over the active one, which is restored by `close_mode`
+    fn open_mode(&mut self, mode: AppMode) {
+        let interrupted = std::mem::replace(&mut self.mode, mode);
+        if !matches!(interrupted, AppMode::Normal) {
+            self.mode_stack.push(interrupted);
+        }
+    }
+
+    /// Close the active mode, returning to the one it interrupted
+    fn close_mode(&mut self) {
+        self.mode = self.mode_stack.pop().unwrap_or(AppMode::Normal);
+    }
+
+    /// Back to normal mode once an operation completed (the interrupted modes are stale)
+    fn reset_mode(&mut self) {
+        self.mode = AppMode::Normal;
+        self.mode_stack.clear();
+    }
+
+    /// Modes beneath the active one, bottom first
+    pub fn mode_stack(&self) -> &[AppMode] {
+        &self.mode_stack
     }
 
     fn handle_normal_action(&mut self, action: Action) -> Result<()> {
         match action {
-            Action::Quit => {
-                self.should_quit = true;
-            }
+            Action::Quit => match self.running_mutation() {
+                Some(operation) => {
+                    self.mode = AppMode::QuitBlocked {
+                        operation,
+                        waiting: false,
+                    }
+                }
+                None => self.should_quit = true,
+            },
             Action::MoveUp => {
                 self.move_selection(-1);
             }
             Action::MoveDown => {
                 self.move_selection(1);
+                self.load_more_near_bottom()?;
             }
             Action::PageUp => {
                 self.move_selection(-10);
             }
             Action::PageDown => {
                 self.move_selection(10);
+                self.load_more_near_bottom()?;
             }
             Action::GoToTop => {
                 self.select_first();
@@ -744,31 +2438,224 @@ impl App {
                 self.select_last();
             }
             Action::JumpToHead => {
-                self.jump_to_head();
+                let shown = self.select_head();
+                if !shown {
+                    self.set_message("HEAD not in view");
+                }
             }
             Action::NextBranch => {
                 self.move_to_next_branch();
+                self.show_upstream_position();
             }
             Action::PrevBranch => {
                 self.move_to_prev_branch();
+                self.show_upstream_position();
             }
             Action::BranchLeft => {
                 self.move_branch_left();
+                self.show_upstream_position();
             }
             Action::BranchRight => {
                 self.move_branch_right();
+                self.show_upstream_position();
+            }
+            Action::NextBookmark => {
+                self.move_to_bookmark(true);
+            }
+            Action::PrevBookmark => {
+                self.move_to_bookmark(false);
             }
-            Action::ToggleHelp => {
-                self.mode = AppMode::Help;
+            Action::JumpToParent => {
+                self.jump_to_parent();
+            }
+            Action::JumpToChild => {
+                self.jump_to_child();
+            }
+            Action::ToggleBookmark => {
+                self.toggle_bookmark()?;
+            }
+            Action::ClearBookmarks if !self.bookmarks.is_empty() => {
+                self.request_confirm(
+                    format!("Clear all {} bookmarks?", self.bookmarks.len()),
+                    ConfirmAction::ClearBookmarks,
+                )?;
+            }
+            Action::BisectGood => {
+                self.bisect_mark(true)?;
+            }
+            Action::BisectBad => {
+                self.bisect_mark(false)?;
+            }
+            Action::BisectReset => {
+                if let Some(session) = &self.bisect {
+                    let message = format!(
+                        "End bisect and return to '{}'?",
+                        session.original_head_name()
+                    );
+                    self.request_confirm(message, ConfirmAction::BisectReset)?;
+                }
+            }
+            Action::CopyReleaseUrl => {
+                self.copy_release_url()?;
+            }
+            Action::DismissUpdate => {
+                if let Some(notice) = self.update_notice.take() {
+                    update::dismiss(&notice);
+                    self.set_message(format!("v{} will not be announced again", notice.version));
+                }
+            }
+            Action::ShowRepoInfo => {
+                // The slow values are recomputed on every open
+                self.repo_stats = None;
+                let path = self.repo.repo.path().to_string_lossy().to_string();
+                self.repo_stats_receiver = Some(worker::spawn(move || RepoStats::compute(&path)));
+                self.mode = AppMode::RepoInfo {
+                    info: Box::new(RepoInfo::collect(&self.repo.repo)),
+                    scroll: 0,
+                };
+            }
+            Action::ToggleVisual => {
+                if let Some(anchor) = self.graph_list_state.selected() {
+                    self.mode = AppMode::Visual { anchor };
+                }
+            }
+            Action::ShowTags => {
+                let mut tags = TagInfo::list_all(&self.repo.repo)?;
+                if tags.is_empty() {
+                    self.set_message("No tags");
+                } else {
+                    let sort = TagSort::default();
+                    sort_tags(&mut tags, sort);
+                    self.mode = AppMode::Tags {
+                        tags,
+                        selected: 0,
+                        sort,
+                    };
+                }
+            }
+            Action::ShowStashes => {
+                let stashes = stash_list(&mut self.repo.repo)?;
+                if stashes.is_empty() {
+                    self.set_message("No stashes");
+                } else {
+                    self.mode = AppMode::StashList {
+                        stashes,
+                        selected: 0,
+                    };
+                }
+            }
+            Action::Stash => {
+                ensure_index_unlocked(&self.repo.repo)?;
+                stash_save(&mut self.repo.repo, None)?;
+                self.refresh(true)?;
+                self.set_message("Stashed local changes as stash@{0}");
+            }
+            Action::CherryPick => {
+                let Some(oid) = self
+                    .selected_commit_node()
+                    .and_then(|n| n.commit.as_ref())
+                    .map(|c| c.oid)
+                else {
+                    return Ok(());
+                };
+                if Some(oid) == self.head_oid {
+                    self.set_message("Cannot cherry-pick HEAD onto itself");
+                    return Ok(());
+                }
+                let message = format!("Cherry-pick {} onto HEAD?", short_oid(oid));
+                self.request_confirm(message, ConfirmAction::CherryPick(vec![oid]))?;
+            }
+            Action::ShowCommitMenu => {
+                if let Some(target) = self
+                    .selected_commit_node()
+                    .and_then(|n| n.commit.as_ref())
+                    .map(|c| c.oid)
+                {
+                    self.mode = AppMode::CommitMenu { target };
+                }
+            }
+            Action::ShowRawCommit => {
+                if let Some(oid) = self
+                    .selected_commit_node()
+                    .and_then(|n| n.commit.as_ref())
+                    .map(|c| c.oid)
+                {
+                    let content = raw_commit_object(&self.repo.repo, oid)?;
+                    self.mode = AppMode::RawCommit {
+                        oid,
+                        content,
+                        scroll: 0,
+                    };
+                }
+            }
+            Action::OpenDiff => {
+                if let Some(oid) = self
+                    .selected_commit_node()
+                    .and_then(|n| n.commit.as_ref())
+                    .map(|c| c.oid)
+                {
+                    let patch = commit_patch(&self.repo.repo, oid)?;
+                    self.open_mode(AppMode::DiffView {
+                        oid,
+                        patch,
+                        scroll_offset: 0,
+                    });
+                }
+            }
+            Action::ToggleCompactGraph => {
+                self.toggle_compact_graph();
+            }
+            Action::ToggleLaneColors => {
+                self.toggle_lane_colors();
+            }
+            Action::ToggleHideMerges => {
+                self.toggle_hide_merges();
+            }
+            Action::ToggleRawDiff => {
+                self.raw_workdir_diff = !self.raw_workdir_diff;
+                self.clear_uncommitted_diff_cache();
+            }
+            Action::ToggleFileAnnotations => {
+                self.file_annotations = !self.file_annotations;
+                self.set_message(if self.file_annotations {
+                    "File annotations: on"
+                } else {
+                    "File annotations: off"
+                });
+            }
+            Action::ToggleWalkScope => {
+                self.toggle_walk_scope()?;
+            }
+            Action::JumpToStartRev => match self.start_rev {
+                Some(oid) => self.select_commit(oid),
+                None => self.set_message("No --rev given"),
+            },
+            Action::JumpToProvenance => self.jump_to_provenance(),
+            Action::ToggleFollowHead => {
+                self.follow_head = !self.follow_head;
+                if self.follow_head {
+                    self.select_head();
+                }
             }
             Action::Refresh => {
+                // Back to the first page; scrolling loads the rest again
+                self.commit_limit = self.config.ui.commit_page_size;
                 self.refresh(true)?;
                 self.reset_timers();
+                self.reopen_conflict_view()?;
+            }
+            Action::CancelFetch => {
+                self.cancel_fetch();
             }
             Action::Fetch => {
-                if !self.is_fetching() {
-                    self.start_fetch(true, false); // silent=false for manual fetch
-                }
+                self.fetch_manually();
+                self.last_operation = Some(RepeatableOp::Fetch);
+            }
+            Action::Pull => {
+                self.pull()?;
+            }
+            Action::RepeatLastOperation => {
+                self.repeat_last_operation()?;
             }
             Action::Checkout => {
                 self.do_checkout()?;
@@ -776,6 +2663,9 @@ impl App {
             Action::CopyHash => {
                 self.do_copy_hash()?;
             }
+            Action::CopyView => {
+                self.copy_view()?;
+            }
             Action::CreateBranch => {
                 self.mode = AppMode::Input {
                     title: "New Branch Name".to_string(),
@@ -783,9 +2673,37 @@ impl App {
                     action: InputAction::CreateBranch,
                 };
             }
+            Action::CommandPalette => {
+                self.update_command_completions("");
+                self.mode = AppMode::Input {
+                    title: "Command".to_string(),
+                    input: String::new(),
+                    action: InputAction::Command,
+                };
+            }
+            Action::FilterDates => {
+                self.mode = AppMode::Input {
+                    title: "Author Dates (since..until)".to_string(),
+                    input: self.date_range.map(|r| r.label()).unwrap_or_default(),
+                    action: InputAction::DateRange,
+                };
+            }
+            Action::ClearDateRange if self.date_range.is_some() => {
+                self.set_date_range(DateRange::default());
+            }
+            Action::RenameBranch => {
+                if let Some(branch) = self.renamable_branch() {
+                    self.mode = AppMode::Input {
+                        title: "Rename Branch".to_string(),
+                        input: branch.name.clone(),
+                        action: InputAction::RenameBranch(branch.name.clone()),
+                    };
+                }
+            }
             Action::Search => {
                 // Save position for cancel restoration
                 self.save_search_position();
+                self.update_fuzzy_search("");
                 self.mode = AppMode::Input {
                     title: "Search branches".to_string(),
                     input: String::new(),
@@ -793,50 +2711,466 @@ impl App {
                 };
             }
             Action::DeleteBranch => {
-                if let Some(branch) = self.selected_branch() {
-                    if !branch.is_head && !branch.is_remote {
-                        self.mode = AppMode::Confirm {
-                            message: format!("Delete branch '{}'?", branch.name),
-                            action: ConfirmAction::DeleteBranch(branch.name.clone()),
-                        };
-                    }
+                if let Some(branch) = self.deletable_branch() {
+                    let message = format!("Delete branch '{}'?", branch.name);
+                    let action = ConfirmAction::DeleteBranch(branch.name.clone());
+                    self.request_confirm(message, action)?;
                 }
             }
             Action::Merge => {
-                if let Some(branch) = self.selected_branch() {
-                    if !branch.is_head {
-                        self.mode = AppMode::Confirm {
-                            message: format!("Merge '{}' into current branch?", branch.name),
-                            action: ConfirmAction::Merge(branch.name.clone()),
-                        };
+                if let Some((name, tip)) = self.selected_branch_tip() {
+                    let (incoming, loaded) = self.divergence_preview(tip, true)?;
+                    let mut message = format!(
+                        "Merge '{}' ({}) into current branch?",
+                        name,
+                        commits(incoming)
+                    );
+                    if !loaded {
+                        message = format!("{}\n{}", message, MERGE_BASE_NOT_LOADED);
                     }
+                    self.request_confirm(message, ConfirmAction::Merge(name))?;
                 }
             }
             Action::Rebase => {
-                if let Some(branch) = self.selected_branch() {
-                    if !branch.is_head {
-                        self.mode = AppMode::Confirm {
-                            message: format!("Rebase current branch onto '{}'?", branch.name),
-                            action: ConfirmAction::Rebase(branch.name.clone()),
-                        };
+                if let Some((name, tip)) = self.selected_branch_tip() {
+                    let (replayed, loaded) = self.divergence_preview(tip, false)?;
+                    let mut message = format!(
+                        "Rebase current branch ({}) onto '{}'?",
+                        commits(replayed),
+                        name
+                    );
+                    if !loaded {
+                        message = format!("{}\n{}", message, MERGE_BASE_NOT_LOADED);
                     }
+                    self.request_confirm(message, ConfirmAction::Rebase(name))?;
                 }
             }
             _ => {}
         }
-        Ok(())
+        Ok(())
+    }
+
+    fn handle_help_action(&mut self, action: Action) {
+        if matches!(action, Action::ToggleHelp | Action::Quit | Action::Cancel) {
+            self.close_mode();
+        }
+    }
+
+    /// Cancel a blocked checkout, or retry it after overwriting or stashing the files
+    /// Overwriting is only offered for untracked files; uncommitted changes must be stashed
+    fn handle_checkout_blocked_action(&mut self, action: Action) -> Result<()> {
+        let AppMode::CheckoutBlocked {
+            op,
+            paths,
+            local_changes,
+            scroll,
+        } = &mut self.mode
+        else {
+            return Ok(());
+        };
+        match action {
+            Action::MoveDown => *scroll = scroll.saturating_add(1),
+            Action::MoveUp => *scroll = scroll.saturating_sub(1),
+            Action::OverwriteUntracked if !*local_changes => {
+                let (op, paths) = (op.clone(), std::mem::take(paths));
+                self.reset_mode();
+                remove_untracked_files(&self.repo.repo, &paths)?;
+                self.run_checkout(op)?;
+            }
+            Action::StashAndRetry => {
+                let op = op.clone();
+                self.reset_mode();
+                stash_save(&mut self.repo.repo, Some("keifu: before checkout"))?;
+                self.run_checkout(op)?;
+                self.set_message("Local changes stashed (git stash pop to restore)");
+            }
+            Action::Quit | Action::Cancel => self.close_mode(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Scroll the conflicted files, abort the merge, or leave it to resolve in an editor
+    fn handle_conflict_action(&mut self, action: Action) -> Result<()> {
+        let AppMode::Conflict { op, scroll, .. } = &mut self.mode else {
+            return Ok(());
+        };
+        let op = *op;
+        match action {
+            Action::MoveDown => *scroll = scroll.saturating_add(1),
+            Action::MoveUp => *scroll = scroll.saturating_sub(1),
+            Action::AbortMerge => {
+                let message = format!(
+                    "Abort the {}? Changes to the conflicted files are lost",
+                    op.label()
+                );
+                self.request_confirm(message, ConfirmAction::AbortConflict(op))?;
+            }
+            Action::Cancel => {
+                self.close_mode();
+                self.set_message(format!(
+                    "{} in progress: resolve the conflicts and commit (R lists them again)",
+                    op.title()
+                ));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Pick the branches to move onto their rewritten commits, then move them all at once
+    fn handle_retarget_action(&mut self, action: Action) -> Result<()> {
+        let AppMode::RetargetBranches {
+            moves,
+            checked,
+            selected,
+        } = &mut self.mode
+        else {
+            return Ok(());
+        };
+        match action {
+            Action::MoveDown => *selected = (*selected + 1).min(moves.len().saturating_sub(1)),
+            Action::MoveUp => *selected = selected.saturating_sub(1),
+            Action::ToggleChecked => checked[*selected] = !checked[*selected],
+            Action::Confirm => {
+                let moves: Vec<BranchRetarget> = moves
+                    .iter()
+                    .zip(checked.iter())
+                    .filter(|(_, checked)| **checked)
+                    .map(|(retarget, _)| retarget.clone())
+                    .collect();
+                self.reset_mode();
+                if moves.is_empty() {
+                    self.set_message("No branches moved");
+                    return Ok(());
+                }
+                retarget_branches(&self.repo.repo, &moves)?;
+                self.set_message(match moves.len() {
+                    1 => format!("Moved '{}' onto its rewritten commit", moves[0].name),
+                    n => format!("Moved {} branches onto their rewritten commits", n),
+                });
+                return self.refresh_refs();
+            }
+            Action::Quit | Action::Cancel => self.close_mode(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_pick_child_action(&mut self, action: Action) -> Result<()> {
+        let AppMode::PickChild { children, selected } = &mut self.mode else {
+            return Ok(());
+        };
+        match action {
+            Action::MoveDown => *selected = (*selected + 1).min(children.len() - 1),
+            Action::MoveUp => *selected = selected.saturating_sub(1),
+            Action::Confirm | Action::JumpToChild => {
+                let child = children[*selected];
+                self.close_mode();
+                self.select_commit(child);
+            }
+            Action::Quit | Action::Cancel => self.close_mode(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Commit the staged changes as a fixup!/squash! of the submenu's target
+    fn handle_commit_menu_action(&mut self, action: Action) -> Result<()> {
+        let AppMode::CommitMenu { target } = self.mode else {
+            return Ok(());
+        };
+        let kind = match action {
+            Action::CommitFixup => AutosquashKind::Fixup,
+            Action::CommitSquash => AutosquashKind::Squash,
+            Action::ShowCommitMenu | Action::Quit | Action::Cancel => {
+                self.close_mode();
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+        self.reset_mode();
+        ensure_index_unlocked(&self.repo.repo)?;
+        commit_autosquash(&self.repo.repo, target, kind)?;
+        self.refresh(true)?;
+        self.set_message(format!(
+            "Created {}! commit for {} (fold in with git rebase -i --autosquash)",
+            kind.label(),
+            &target.to_string()[..7]
+        ));
+        Ok(())
+    }
+
+    /// Rows covered by visual mode (None outside it)
+    pub fn visual_range(&self) -> Option<RangeInclusive<usize>> {
+        let AppMode::Visual { anchor } = self.mode else {
+            return None;
+        };
+        let cursor = self.graph_list_state.selected().unwrap_or(anchor);
+        Some(anchor.min(cursor)..=anchor.max(cursor))
+    }
+
+    /// Commits in the visual span, oldest first (connector and uncommitted rows are skipped)
+    fn visual_commits(&self) -> Vec<Oid> {
+        let Some(range) = self.visual_range() else {
+            return Vec::new();
+        };
+        self.graph_layout.nodes[range]
+            .iter()
+            .rev()
+            .filter_map(|node| node.commit.as_ref().map(|c| c.oid))
+            .collect()
+    }
+
+    /// Extend the span with movement keys, or run a bulk action on it
+    fn handle_visual_action(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::MoveUp
+            | Action::MoveDown
+            | Action::PageUp
+            | Action::PageDown
+            | Action::GoToTop
+            | Action::GoToBottom => return self.handle_normal_action(action),
+            Action::CopyHash => {
+                let oids = self.visual_commits();
+                if oids.is_empty() {
+                    return Ok(());
+                }
+                let hashes: Vec<String> = oids.iter().map(|oid| oid.to_string()).collect();
+                let mut clipboard = Clipboard::new()?;
+                clipboard.set_text(hashes.join("\n"))?;
+                self.exit_message = Some(format!("Copied {} hashes", hashes.len()));
+                self.should_quit = true;
+            }
+            Action::CherryPick => {
+                let oids = self.visual_commits();
+                if oids.is_empty() {
+                    return Ok(());
+                }
+                let message = format!("Cherry-pick {} commits onto HEAD?", oids.len());
+                self.request_confirm(message, ConfirmAction::CherryPick(oids))?;
+            }
+            Action::ExportPatches => {
+                let oids = self.visual_commits();
+                let dir = self.repo.repo.workdir().unwrap_or(self.repo.repo.path());
+                let written = export_patches(&self.repo.repo, &oids, &dir.join("patches"))?;
+                self.reset_mode();
+                self.set_message(format!("Wrote {} patches to patches/", written.len()));
+            }
+            Action::ShowSeriesStats => {
+                let stats = series_stats(&self.repo.repo, &self.visual_commits())?;
+                self.set_message(stats.summary());
+            }
+            Action::Squash => {
+                let oids = self.visual_commits();
+                check_squash(&self.repo.repo, &oids)?;
+                let subject = {
+                    let oldest = self.repo.repo.find_commit(oids[0])?;
+                    String::from_utf8_lossy(oldest.summary_bytes().unwrap_or_default()).to_string()
+                };
+                self.open_mode(AppMode::Input {
+                    title: format!("Squash {} Commits (subject)", oids.len()),
+                    input: subject,
+                    action: InputAction::Squash(oids),
+                });
+            }
+            Action::ToggleVisual | Action::Quit | Action::Cancel => self.close_mode(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Cherry-pick commits in order, stopping at the first one that fails
+    /// A conflicting pick is left in progress and opens the conflict view
+    fn cherry_pick_all(&mut self, oids: &[Oid]) -> Result<()> {
+        let mut picked = 0;
+        let result = oids.iter().try_for_each(|oid| {
+            let picked_oid = cherry_pick(&self.repo.repo, *oid)?;
+            self.provenance.record(*oid, picked_oid);
+            picked += 1;
+            Ok::<_, GitError>(())
+        });
+        self.refresh(true)?;
+        match result {
+            Ok(()) => {
+                self.set_message(match oids {
+                    [oid] => format!("Cherry-picked {}", short_oid(*oid)),
+                    _ => format!("Cherry-picked {}", commits(picked)),
+                });
+                Ok(())
+            }
+            Err(GitError::CherryPickConflict { commit, files }) => {
+                if oids.len() > 1 {
+                    self.set_message(format!(
+                        "Cherry-picked {} of {} commits; {} stopped with conflicts",
+                        picked,
+                        oids.len(),
+                        commit
+                    ));
+                }
+                self.mode = AppMode::Conflict {
+                    op: ConflictOp::CherryPick,
+                    files,
+                    scroll: 0,
+                };
+                Ok(())
+            }
+            Err(e) => Err(anyhow::anyhow!(
+                "Cherry-picked {} of {} commits: {}",
+                picked,
+                oids.len(),
+                e
+            )),
+        }
+    }
+
+    /// Move through the tag list, then jump to or check out the selected tag
+    fn handle_tags_action(&mut self, action: Action) -> Result<()> {
+        let AppMode::Tags {
+            tags,
+            selected,
+            sort,
+        } = &mut self.mode
+        else {
+            return Ok(());
+        };
+        match action {
+            Action::MoveDown => *selected = (*selected + 1).min(tags.len().saturating_sub(1)),
+            Action::MoveUp => *selected = selected.saturating_sub(1),
+            Action::CycleTagSort => {
+                let name = tags[*selected].name.clone();
+                *sort = sort.next();
+                sort_tags(tags, *sort);
+                *selected = tags.iter().position(|t| t.name == name).unwrap_or(0);
+            }
+            Action::Confirm => {
+                let tag = tags[*selected].clone();
+                self.reset_mode();
+                if self.graph_layout.row_of(tag.target).is_some() {
+                    self.select_commit(tag.target);
+                } else {
+                    self.set_message(format!(
+                        "Tag '{}' points outside the loaded commits",
+                        tag.name
+                    ));
+                }
+            }
+            Action::Checkout => {
+                let target = tags[*selected].target;
+                self.reset_mode();
+                self.run_checkout(RepeatableOp::CheckoutCommit(target))?;
+            }
+            Action::ShowTags | Action::Quit | Action::Cancel => self.close_mode(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Move through the stash list, then apply, pop or drop the selected stash
+    /// Apply and pop refresh the graph; their conflicts surface as errors with the stash kept
+    fn handle_stash_list_action(&mut self, action: Action) -> Result<()> {
+        let AppMode::StashList { stashes, selected } = &mut self.mode else {
+            return Ok(());
+        };
+        match action {
+            Action::MoveDown => *selected = (*selected + 1).min(stashes.len().saturating_sub(1)),
+            Action::MoveUp => *selected = selected.saturating_sub(1),
+            Action::Confirm | Action::StashPop => {
+                let stash = stashes[*selected].clone();
+                self.reset_mode();
+                ensure_index_unlocked(&self.repo.repo)?;
+                let (result, message) = if action == Action::StashPop {
+                    (
+                        stash_pop(&mut self.repo.repo, &stash),
+                        "Applied and dropped",
+                    )
+                } else {
+                    (stash_apply(&mut self.repo.repo, &stash), "Applied")
+                };
+                // A conflicting apply still changed the working tree
+                self.refresh(true)?;
+                result?;
+                self.set_message(format!("{} stash@{{{}}}", message, stash.index));
+            }
+            Action::DropStash => {
+                let stash = stashes[*selected].clone();
+                self.request_confirm(
+                    format!("Drop stash@{{{}}}: {}?", stash.index, stash.message),
+                    ConfirmAction::DropStash(stash),
+                )?;
+            }
+            Action::ShowStashes | Action::Quit | Action::Cancel => self.close_mode(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Scroll or close a read-only popup
+    fn handle_scroll_popup_action(&mut self, action: Action) {
+        let (AppMode::RawCommit { scroll, .. }
+        | AppMode::RepoInfo { scroll, .. }
+        | AppMode::BranchDiff { scroll, .. }) = &mut self.mode
+        else {
+            return;
+        };
+        match action {
+            Action::MoveDown => *scroll = scroll.saturating_add(1),
+            Action::MoveUp => *scroll = scroll.saturating_sub(1),
+            Action::ShowRawCommit | Action::ShowRepoInfo | Action::Quit | Action::Cancel => {
+                self.close_mode()
+            }
+            _ => {}
+        }
+    }
+
+    /// Scroll the diff one line at a time (stopping at its last line), or close it
+    fn handle_diff_view_action(&mut self, action: Action) {
+        let AppMode::DiffView {
+            patch,
+            scroll_offset,
+            ..
+        } = &mut self.mode
+        else {
+            return;
+        };
+        match action {
+            Action::DiffScrollDown => {
+                let last = patch.lines().count().saturating_sub(1);
+                *scroll_offset = (*scroll_offset + 1).min(last);
+            }
+            Action::DiffScrollUp => *scroll_offset = scroll_offset.saturating_sub(1),
+            Action::OpenDiff | Action::Quit | Action::Cancel => self.close_mode(),
+            _ => {}
+        }
+    }
+
+    /// Wait for the running operation, abort it, or stay
+    fn handle_quit_blocked_action(&mut self, action: Action) {
+        let AppMode::QuitBlocked { waiting, .. } = &mut self.mode else {
+            return;
+        };
+        match action {
+            Action::WaitAndQuit => *waiting = true,
+            Action::AbortAndQuit => {
+                *waiting = true;
+                // Quits once the cancelled operation has stopped
+                self.cancel_fetch();
+            }
+            Action::Cancel => self.close_mode(),
+            _ => {}
+        }
     }
 
-    fn handle_help_action(&mut self, action: Action) {
-        if matches!(action, Action::ToggleHelp | Action::Quit | Action::Cancel) {
-            self.mode = AppMode::Normal;
-        }
+    /// Running operation that changes the repository (quitting would abandon it)
+    /// Read-only background work such as diffs does not count
+    pub fn running_mutation(&self) -> Option<&'static str> {
+        self.is_fetching().then_some("A fetch from origin")
     }
 
     fn handle_error_action(&mut self, action: Action) {
         // Close the error on any key
         if matches!(action, Action::Quit | Action::Cancel | Action::Confirm) {
-            self.mode = AppMode::Normal;
+            self.close_mode();
         }
     }
 
@@ -852,6 +3186,16 @@ impl App {
         let (title, mut input, input_action) = (title.clone(), input.clone(), input_action.clone());
 
         match action {
+            // Enter without results keeps the search open instead of leaving it
+            Action::Confirm
+                if input_action == InputAction::Search
+                    && self.search_state.fuzzy_matches.is_empty() =>
+            {
+                self.set_message("No matches");
+            }
+            Action::Confirm if input_action == InputAction::Command => {
+                self.run_command(&input)?;
+            }
             Action::Confirm => {
                 match input_action {
                     InputAction::CreateBranch => {
@@ -859,19 +3203,42 @@ impl App {
                             if let Some(node) = self.selected_commit_node() {
                                 if let Some(commit) = &node.commit {
                                     create_branch(&self.repo.repo, &input, commit.oid)?;
-                                    self.refresh(true)?;
+                                    self.last_operation =
+                                        Some(RepeatableOp::CreateBranch(input.clone()));
+                                    self.refresh_refs()?;
                                 }
                             }
                         }
                     }
+                    InputAction::RenameBranch(old_name) => {
+                        if !input.is_empty() && input != old_name {
+                            rename_branch(&self.repo.repo, &old_name, &input)?;
+                            self.refresh_refs()?;
+                            self.select_branch_by_name(&input);
+                        }
+                    }
                     InputAction::Search => {
                         // Jump to selected result and exit search mode
                         self.jump_to_search_result();
                     }
+                    InputAction::DateRange => {
+                        let range = DateRange::parse(&input, chrono::Local::now())?;
+                        self.set_date_range(range);
+                    }
+                    InputAction::Squash(oids) => {
+                        if !input.trim().is_empty() {
+                            let message = squash_message(&self.repo.repo, &oids, &input)?;
+                            squash_commits(&self.repo.repo, &oids, &message)?;
+                            self.refresh(true)?;
+                            self.set_message(format!("Squashed {} commits", oids.len()));
+                        }
+                    }
+                    // Run by the guarded arm above
+                    InputAction::Command => {}
                 }
                 // Clear search state after confirming
                 self.search_state = SearchState::default();
-                self.mode = AppMode::Normal;
+                self.reset_mode();
             }
             Action::Cancel => {
                 // Restore position when canceling search
@@ -879,7 +3246,7 @@ impl App {
                     self.restore_search_position();
                 }
                 self.search_state = SearchState::default();
-                self.mode = AppMode::Normal;
+                self.close_mode();
             }
             Action::InputChar(c) => {
                 input.push(c);
@@ -889,6 +3256,9 @@ impl App {
                     self.update_fuzzy_search(&input);
                     self.jump_to_search_result();
                 }
+                if input_action == InputAction::Command {
+                    self.update_command_completions(&input);
+                }
 
                 self.mode = AppMode::Input {
                     title,
@@ -903,7 +3273,7 @@ impl App {
                         self.restore_search_position();
                     }
                     self.search_state = SearchState::default();
-                    self.mode = AppMode::Normal;
+                    self.close_mode();
                     return Ok(());
                 }
 
@@ -914,6 +3284,9 @@ impl App {
                     self.update_fuzzy_search(&input);
                     self.jump_to_search_result();
                 }
+                if input_action == InputAction::Command {
+                    self.update_command_completions(&input);
+                }
 
                 self.mode = AppMode::Input {
                     title,
@@ -937,6 +3310,36 @@ impl App {
                 self.search_state.select_down();
                 // No graph jump - just move in dropdown
             }
+            Action::CompleteCommand => {
+                if let Some(completion) = self.command_completions.first().cloned() {
+                    self.update_command_completions(&completion);
+                    self.mode = AppMode::Input {
+                        title,
+                        input: completion,
+                        action: input_action,
+                    };
+                }
+            }
+            Action::ToggleSubjectLayout => {
+                self.subject_layout = self.subject_layout.toggle();
+            }
+            Action::CycleSearchCase => {
+                self.search_case = self.search_case.next();
+                // The full branch list does not depend on the query
+                if !input.is_empty() {
+                    self.update_fuzzy_search(&input);
+                    self.jump_to_search_result();
+                }
+            }
+            Action::MarkBranchDiff => self.mark_branch_diff()?,
+            Action::CycleBranchSort => {
+                self.branch_sort = self.branch_sort.next();
+                // Only the full branch list is sorted; fuzzy matches stay ordered by score
+                if input.is_empty() {
+                    let selected = self.search_state.selected_result().map(|r| r.branch_idx);
+                    self.show_branch_list(selected);
+                }
+            }
             _ => {}
         }
         Ok(())
@@ -945,45 +3348,339 @@ impl App {
     fn handle_confirm_action(&mut self, action: Action) -> Result<()> {
         let AppMode::Confirm {
             action: confirm_action,
+            expected,
             ..
         } = &self.mode
         else {
             return Ok(());
         };
-        let confirm_action = confirm_action.clone();
+        let (confirm_action, expected) = (confirm_action.clone(), expected.clone());
 
         match action {
             Action::Confirm => {
-                match confirm_action {
-                    ConfirmAction::DeleteBranch(name) => {
-                        delete_branch(&self.repo.repo, &name)?;
-                    }
-                    ConfirmAction::Merge(name) => {
-                        merge_branch(&self.repo.repo, &name)?;
-                    }
-                    ConfirmAction::Rebase(name) => {
-                        rebase_branch(&self.repo.repo, &name)?;
+                // The decision is made; an error from the operation replaces the dialog
+                self.reset_mode();
+                self.run_confirm_action(confirm_action, &expected)?;
+            }
+            Action::Cancel => {
+                self.close_mode();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Whether the operation asks for confirmation (per-operation config)
+    fn confirm_required(&self, action: &ConfirmAction) -> bool {
+        let confirm = &self.config.confirm;
+        match action {
+            ConfirmAction::BisectReset => confirm.bisect_reset,
+            ConfirmAction::ClearBookmarks => confirm.clear_bookmarks,
+            ConfirmAction::DeleteBranch(_) => confirm.delete_branch,
+            ConfirmAction::Merge(_) => confirm.merge,
+            ConfirmAction::Rebase(_) => confirm.rebase,
+            ConfirmAction::CherryPick(_) => confirm.cherry_pick,
+            ConfirmAction::DropStash(_) => confirm.drop_stash,
+            ConfirmAction::Checkout(_) | ConfirmAction::Quit | ConfirmAction::AbortConflict(_) => {
+                true
+            }
+        }
+    }
+
+    /// Show a confirmation dialog, or run the operation right away if its confirmation is disabled
+    fn request_confirm(&mut self, message: String, action: ConfirmAction) -> Result<()> {
+        let expected = self.expected_state(&action);
+        if self.confirm_required(&action) {
+            self.open_mode(AppMode::Confirm {
+                message,
+                action,
+                expected,
+            });
+            Ok(())
+        } else {
+            self.run_confirm_action(action, &expected)
+        }
+    }
+
+    /// The loaded state an operation relies on
+    fn expected_state(&self, action: &ConfirmAction) -> ExpectedState {
+        let branch = |name: &str| {
+            self.branches
+                .iter()
+                .find(|b| b.name == name)
+                .map(|b| (b.name.clone(), b.tip_oid))
+        };
+        match action {
+            ConfirmAction::BisectReset
+            | ConfirmAction::ClearBookmarks
+            | ConfirmAction::Checkout(_)
+            | ConfirmAction::DropStash(_)
+            | ConfirmAction::Quit => ExpectedState::default(),
+            ConfirmAction::DeleteBranch(name) => ExpectedState {
+                head: None,
+                branch: branch(name),
+            },
+            ConfirmAction::Merge(name) | ConfirmAction::Rebase(name) => ExpectedState {
+                head: self.head_oid,
+                branch: branch(name),
+            },
+            ConfirmAction::CherryPick(_) | ConfirmAction::AbortConflict(_) => ExpectedState {
+                head: self.head_oid,
+                branch: None,
+            },
+        }
+    }
+
+    /// Fail if HEAD or the branch moved since the state was captured
+    fn verify_expected(&self, expected: &ExpectedState) -> Result<(), GitError> {
+        let repo = &self.repo.repo;
+        if let Some(head) = expected.head {
+            let current = repo.head().ok().and_then(|h| h.target());
+            if current != Some(head) {
+                return Err(GitError::RepositoryChanged);
+            }
+        }
+        if let Some((name, tip)) = &expected.branch {
+            let current = repo
+                .find_branch(name, BranchType::Local)
+                .or_else(|_| repo.find_branch(name, BranchType::Remote))
+                .ok()
+                .and_then(|b| b.get().target());
+            if current != Some(*tip) {
+                return Err(GitError::RepositoryChanged);
+            }
+        }
+        Ok(())
+    }
+
+    /// Offer to run an operation again once the other git process releases the index
+    fn offer_lock_retry(&mut self, action: ConfirmAction, expected: ExpectedState) {
+        self.mode = AppMode::Confirm {
+            message: format!("{}. Retry?", GitError::IndexLocked),
+            action,
+            expected,
+        };
+    }
+
+    /// Run a confirmed operation and return to normal mode
+    /// Refuses when the repository changed since the operation was offered
+    /// and offers a retry while another git process holds the index lock
+    fn run_confirm_action(
+        &mut self,
+        action: ConfirmAction,
+        expected: &ExpectedState,
+    ) -> Result<()> {
+        self.verify_expected(expected)?;
+        if action.touches_index() && ensure_index_unlocked(&self.repo.repo).is_err() {
+            self.offer_lock_retry(action, expected.clone());
+            return Ok(());
+        }
+        if matches!(
+            action,
+            ConfirmAction::DeleteBranch(_) | ConfirmAction::Merge(_) | ConfirmAction::Rebase(_)
+        ) {
+            self.last_operation = Some(RepeatableOp::Confirmed(action.clone()));
+        }
+        match action {
+            ConfirmAction::BisectReset => {
+                if let Some(session) = self.bisect.take() {
+                    self.bisect_first_bad = None;
+                    session.reset(&self.repo.repo)?;
+                }
+            }
+            ConfirmAction::ClearBookmarks => {
+                self.bookmarks.clear();
+                self.bookmarks.save()?;
+                self.reset_mode();
+                self.set_message("Cleared all bookmarks");
+                return Ok(());
+            }
+            ConfirmAction::DeleteBranch(name) => {
+                delete_branch(&self.repo.repo, &name)?;
+                self.reset_mode();
+                return self.refresh_refs();
+            }
+            ConfirmAction::Merge(name) => {
+                let outcome = match merge_branch(&self.repo.repo, &name) {
+                    // The merge is left in progress; show what needs resolving
+                    Err(GitError::MergeConflict(files)) => {
+                        self.refresh(true)?;
+                        self.reset_mode();
+                        self.mode = AppMode::Conflict {
+                            op: ConflictOp::Merge,
+                            files,
+                            scroll: 0,
+                        };
+                        return Ok(());
                     }
+                    result => result?,
+                };
+                let message = match outcome {
+                    MergeOutcome::UpToDate => format!("Already up to date with '{}'", name),
+                    MergeOutcome::FastForwarded => format!("Fast-forwarded to '{}'", name),
+                    MergeOutcome::Merged(_) => format!("Created merge commit for '{}'", name),
+                };
+                self.set_message(message);
+            }
+            ConfirmAction::Rebase(name) => {
+                let replayed = rebase_branch(&self.repo.repo, &name)?;
+                self.set_message(format!(
+                    "Rebased {} onto '{}'",
+                    commits(replayed.len()),
+                    name
+                ));
+                // Branches stacked below the rebased one still point at the originals
+                let moves = stale_branches(&self.repo.repo, &replayed, self.head_name.as_deref())?;
+                for (original, rewritten) in replayed {
+                    self.provenance.record(original, rewritten);
                 }
                 self.refresh(true)?;
-                self.mode = AppMode::Normal;
+                self.reset_mode();
+                if !moves.is_empty() {
+                    self.mode = AppMode::RetargetBranches {
+                        checked: vec![true; moves.len()],
+                        moves,
+                        selected: 0,
+                    };
+                }
+                return Ok(());
             }
-            Action::Cancel => {
-                self.mode = AppMode::Normal;
+            ConfirmAction::CherryPick(oids) => {
+                self.reset_mode();
+                return self.cherry_pick_all(&oids);
+            }
+            ConfirmAction::Checkout(op) => {
+                self.reset_mode();
+                return self.run_checkout(*op);
+            }
+            ConfirmAction::DropStash(stash) => {
+                stash_drop(&mut self.repo.repo, &stash)?;
+                self.reset_mode();
+                self.set_message(format!("Dropped stash@{{{}}}", stash.index));
+                // Back to the remaining stashes, if any
+                let stashes = stash_list(&mut self.repo.repo)?;
+                if !stashes.is_empty() {
+                    let selected = stash.index.min(stashes.len() - 1);
+                    self.mode = AppMode::StashList { stashes, selected };
+                }
+                return Ok(());
+            }
+            ConfirmAction::Quit => {
+                self.reset_mode();
+                return self.handle_normal_action(Action::Quit);
+            }
+            ConfirmAction::AbortConflict(op) => {
+                abort_merge(&self.repo.repo)?;
+                self.set_message(format!("{} aborted", op.title()));
             }
-            _ => {}
         }
+        self.refresh(true)?;
+        self.reset_mode();
         Ok(())
     }
 
+    /// Move the selection by `delta` rows, passing over elision rows (they cannot be selected)
     fn move_selection(&mut self, delta: i32) {
-        let max = self.graph_layout.nodes.len().saturating_sub(1);
+        let nodes = &self.graph_layout.nodes;
+        let max = nodes.len().saturating_sub(1);
         let current = self.graph_list_state.selected().unwrap_or(0);
-        let new = (current as i32 + delta).clamp(0, max as i32) as usize;
+        let mut new = (current as i32 + delta).clamp(0, max as i32) as usize;
+        while nodes.get(new).is_some_and(|n| n.elided.is_some()) {
+            match new.checked_add_signed(delta.signum() as isize) {
+                Some(next) if next <= max && next != new => new = next,
+                _ => {
+                    new = current;
+                    break;
+                }
+            }
+        }
         self.graph_list_state.select(Some(new));
         self.sync_branch_selection_to_node(new);
     }
 
+    /// Files with uncommitted changes (None when the working tree is clean)
+    pub fn uncommitted_count(&self) -> Option<usize> {
+        self.graph_layout
+            .nodes
+            .first()
+            .filter(|n| n.is_uncommitted)
+            .map(|n| n.uncommitted_count)
+    }
+
+    /// Whether history has more commits than loaded so far
+    pub fn has_more_commits(&self) -> bool {
+        self.commits.len() >= self.commit_limit
+    }
+
+    /// Load the next page of history when the selection nears the last row
+    /// Stops once history is exhausted; debounced so held keys load one page at a time
+    fn load_more_near_bottom(&mut self) -> Result<()> {
+        let selected = self.graph_list_state.selected().unwrap_or(0);
+        let near_bottom =
+            selected + LOAD_MORE_THRESHOLD >= self.graph_layout.nodes.len().saturating_sub(1);
+        let exhausted = !self.has_more_commits();
+        let debounced = self
+            .last_page_load
+            .is_some_and(|t| t.elapsed() < self.load_more_debounce);
+        if !near_bottom || exhausted || debounced {
+            return Ok(());
+        }
+
+        self.last_page_load = Some(Instant::now());
+        self.extend_history(self.commit_limit + self.config.ui.commit_page_size)
+    }
+
+    /// Change the minimum time between page loads (e.g. none, for tests)
+    pub fn set_load_more_debounce(&mut self, debounce: Duration) {
+        self.load_more_debounce = debounce;
+    }
+
+    /// Load history up to `limit` commits, keeping the selected row
+    fn extend_history(&mut self, limit: usize) -> Result<()> {
+        let selected = self.graph_list_state.selected().unwrap_or(0);
+        self.commit_limit = limit;
+        self.refresh(false)?;
+        // Rows are only appended, so the same row stays selected (even when following HEAD)
+        let row = selected.min(self.graph_layout.nodes.len().saturating_sub(1));
+        self.graph_list_state.select(Some(row));
+        self.sync_branch_selection_to_node(row);
+        Ok(())
+    }
+
+    /// Find the merge base of two commits, loading more history until it is in view
+    /// The window grows to at most `MERGE_BASE_WINDOW_PAGES` pages; returns the base and
+    /// whether it is loaded (None for unrelated histories)
+    fn load_merge_base(&mut self, a: Oid, b: Oid) -> Result<Option<(Oid, bool)>> {
+        let Some(base) = self.repo.merge_base(a, b)? else {
+            return Ok(None);
+        };
+        let max_limit = self
+            .config
+            .ui
+            .commit_page_size
+            .saturating_mul(MERGE_BASE_WINDOW_PAGES);
+        while self.graph_layout.row_of(base).is_none()
+            && self.has_more_commits()
+            && self.commit_limit < max_limit
+        {
+            self.extend_history((self.commit_limit * 2).min(max_limit))?;
+        }
+        Ok(Some((base, self.graph_layout.row_of(base).is_some())))
+    }
+
+    /// Commits a merge of `tip` would bring in (`merge`) or a rebase onto it would replay
+    /// Also reports whether the graph shows where HEAD and `tip` diverge
+    fn divergence_preview(&mut self, tip: Oid, merge: bool) -> Result<(usize, bool)> {
+        let Some(head) = self.head_oid else {
+            return Ok((0, true));
+        };
+        let loaded = self
+            .load_merge_base(head, tip)?
+            .is_none_or(|(_, loaded)| loaded);
+        let (head_ahead, head_behind) = self.repo.ahead_behind(head, tip)?;
+        Ok((if merge { head_behind } else { head_ahead }, loaded))
+    }
+
     fn select_first(&mut self) {
         self.graph_list_state.select(Some(0));
         self.sync_branch_selection_to_node(0);
@@ -1049,28 +3746,21 @@ impl App {
         }
     }
 
-    /// Move to an adjacent branch within the same commit
+    /// Move to an adjacent branch within the same commit, wrapping around at either end
     fn move_branch_within_node(&mut self, delta: isize) {
         let Some(pos) = self.selected_branch_position else {
             return;
         };
-
-        let new_pos = (pos as isize + delta) as usize;
-        if new_pos >= self.branch_positions.len() {
-            return;
-        }
-
         let Some((current_node, _)) = self.branch_positions.get(pos) else {
             return;
         };
-        let Some((target_node, _)) = self.branch_positions.get(new_pos) else {
-            return;
-        };
 
-        // Only move within the same commit
-        if current_node == target_node {
-            self.selected_branch_position = Some(new_pos);
-        }
+        let same_node: Vec<usize> = (0..self.branch_positions.len())
+            .filter(|&i| self.branch_positions[i].0 == *current_node)
+            .collect();
+        let offset = same_node.iter().position(|&i| i == pos).unwrap_or(0);
+        let next = (offset as isize + delta).rem_euclid(same_node.len() as isize) as usize;
+        self.selected_branch_position = Some(same_node[next]);
     }
 
     /// Move to the left branch within the same commit
@@ -1083,6 +3773,205 @@ impl App {
         self.move_branch_within_node(1);
     }
 
+    /// Mark the upstream of the selected local branch label and say where it is
+    /// Nothing is marked when the upstream's row is not loaded yet
+    fn show_upstream_position(&mut self) {
+        let Some(branch) = self.selected_branch().filter(|b| !b.is_remote) else {
+            return;
+        };
+        let (local, Some(upstream)) = (branch.tip_oid, branch.upstream.clone()) else {
+            return;
+        };
+        let Some(upstream_oid) = self.branch_tip(&upstream).filter(|oid| *oid != local) else {
+            return;
+        };
+        let Ok((ahead, behind)) = self.repo.ahead_behind(local, upstream_oid) else {
+            return;
+        };
+
+        // From the upstream's side: the local branch's ahead is its behind
+        let position = match (behind, ahead) {
+            (0, n) => format!("{} behind", commits(n)),
+            (n, 0) => format!("{} ahead", commits(n)),
+            (a, b) => format!("{} ahead, {} behind", a, b),
+        };
+        let Some(row) = self.graph_layout.row_of(upstream_oid) else {
+            self.set_message(format!("{} is {} (not loaded)", upstream, position));
+            return;
+        };
+        self.upstream_flash = Some(upstream_oid);
+        self.reveal_row(row);
+        self.set_message(format!("{} is {} (row {})", upstream, position, row + 1));
+    }
+
+    /// Scroll so `row` shows along with the selection, if both fit on screen
+    fn reveal_row(&mut self, row: usize) {
+        let Some(selected) = self.graph_list_state.selected() else {
+            return;
+        };
+        let (top, bottom) = (selected.min(row), selected.max(row));
+        let height = self.graph_view_height;
+        if height == 0 || bottom - top >= height {
+            return;
+        }
+        let offset = self
+            .graph_list_state
+            .offset()
+            .clamp((bottom + 1).saturating_sub(height), top);
+        *self.graph_list_state.offset_mut() = offset;
+    }
+
+    /// Toggle a bookmark on the selected commit and save
+    fn toggle_bookmark(&mut self) -> Result<()> {
+        let Some(oid) = self
+            .selected_commit_node()
+            .and_then(|n| n.commit.as_ref())
+            .map(|c| c.oid)
+        else {
+            return Ok(());
+        };
+
+        let added = self.bookmarks.toggle(oid);
+        self.bookmarks.save()?;
+        self.set_message(if added {
+            "Bookmark added"
+        } else {
+            "Bookmark removed"
+        });
+        Ok(())
+    }
+
+    /// Move to the next (or previous) bookmarked commit
+    fn move_to_bookmark(&mut self, forward: bool) {
+        let current = self.graph_list_state.selected().unwrap_or(0);
+        let is_bookmarked = |node: &crate::git::graph::GraphNode| {
+            node.commit
+                .as_ref()
+                .is_some_and(|c| self.bookmarks.contains(c.oid))
+        };
+
+        let target = if forward {
+            self.graph_layout
+                .nodes
+                .iter()
+                .enumerate()
+                .skip(current + 1)
+                .find(|(_, n)| is_bookmarked(n))
+                .map(|(i, _)| i)
+        } else {
+            self.graph_layout
+                .nodes
+                .iter()
+                .enumerate()
+                .take(current)
+                .rev()
+                .find(|(_, n)| is_bookmarked(n))
+                .map(|(i, _)| i)
+        };
+
+        match target {
+            Some(idx) => {
+                self.graph_list_state.select(Some(idx));
+                self.sync_branch_selection_to_node(idx);
+            }
+            None if self.bookmarks.is_empty() => self.set_message("No bookmarks"),
+            None => self.set_message("No more bookmarks"),
+        }
+    }
+
+    /// Move to the first parent of the selected commit
+    fn jump_to_parent(&mut self) {
+        let Some(commit) = self.selected_commit_node().and_then(|n| n.commit.as_ref()) else {
+            return;
+        };
+        match commit.parent_oids.first().copied() {
+            Some(parent) if self.graph_layout.row_of(parent).is_some() => {
+                self.select_commit(parent)
+            }
+            Some(_) => self.set_message("Parent is not loaded"),
+            None => self.set_message("Root commit has no parent"),
+        }
+    }
+
+    /// Move to the loaded child of the selected commit, letting the user choose
+    /// when there are several
+    fn jump_to_child(&mut self) {
+        let Some(oid) = self
+            .selected_commit_node()
+            .and_then(|n| n.commit.as_ref())
+            .map(|c| c.oid)
+        else {
+            return;
+        };
+        match self.graph_layout.children_of(oid) {
+            [] => self.set_message("No children loaded"),
+            [child] => self.select_commit(*child),
+            children => {
+                // Children are newest first, so the last one is the closest row above
+                let selected = children.len() - 1;
+                self.open_mode(AppMode::PickChild {
+                    children: children.to_vec(),
+                    selected,
+                });
+            }
+        }
+    }
+
+    /// Mark the selected commit good or bad, starting a bisect session if needed
+    /// Checks out the next commit to test, or highlights the first bad commit when done
+    fn bisect_mark(&mut self, good: bool) -> Result<()> {
+        let Some(oid) = self
+            .selected_commit_node()
+            .and_then(|n| n.commit.as_ref())
+            .map(|c| c.oid)
+        else {
+            return Ok(());
+        };
+
+        if self.bisect.is_none() {
+            self.bisect = Some(BisectSession::start(&self.repo.repo)?);
+            self.bisect_first_bad = None;
+        }
+        let Some(session) = self.bisect.as_mut() else {
+            return Ok(());
+        };
+        if good {
+            session.mark_good(&self.repo.repo, oid)?;
+        } else {
+            session.mark_bad(&self.repo.repo, oid)?;
+        }
+
+        let step = session.next_step();
+        let remaining = session.remaining().unwrap_or(0);
+        self.bisect_first_bad = None;
+        match step {
+            BisectStep::NeedGood => self.set_message("Marked bad; now mark a good commit (+)"),
+            BisectStep::NeedBad => self.set_message("Marked good; now mark a bad commit (-)"),
+            BisectStep::Test(next) => {
+                checkout_commit(&self.repo.repo, next)?;
+                self.refresh(true)?;
+                self.select_commit(next);
+                self.set_message(format!("Bisecting: {} commits left", remaining));
+            }
+            BisectStep::Found(first_bad) => {
+                self.bisect_first_bad = Some(first_bad);
+                self.select_commit(first_bad);
+                let short_id = short_oid(first_bad);
+                self.set_message(format!("First bad commit: {} (B to end bisect)", short_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Select the row of the given commit (if loaded)
+    fn select_commit(&mut self, oid: Oid) {
+        let Some(idx) = self.graph_layout.row_of(oid) else {
+            return;
+        };
+        self.graph_list_state.select(Some(idx));
+        self.sync_branch_selection_to_node(idx);
+    }
+
     /// Get the currently selected branch
     fn selected_branch(&self) -> Option<&BranchInfo> {
         let (_, branch_name) = self
@@ -1091,6 +3980,13 @@ impl App {
         self.branches.iter().find(|b| &b.name == branch_name)
     }
 
+    /// Name and tip of the selected branch, unless it is HEAD's
+    fn selected_branch_tip(&self) -> Option<(String, Oid)> {
+        self.selected_branch()
+            .filter(|b| !b.is_head)
+            .map(|b| (b.name.clone(), b.tip_oid))
+    }
+
     /// Get the name of the currently selected branch
     pub fn selected_branch_name(&self) -> Option<&str> {
         self.selected_branch_position
@@ -1117,19 +4013,164 @@ impl App {
     }
 
     fn do_checkout(&mut self) -> Result<()> {
+        match self.checkout_target() {
+            Some(op) => self.run_checkout(op),
+            None => Ok(()),
+        }
+    }
+
+    /// What `c` checks out on the selected row: its branch, or the commit itself
+    fn checkout_target(&self) -> Option<RepeatableOp> {
         if let Some(branch) = self.selected_branch() {
-            let branch_name = branch.name.clone();
-            if branch_name.starts_with("origin/") {
+            Some(RepeatableOp::CheckoutBranch(branch.name.clone()))
+        } else {
+            self.selected_commit_node()
+                .and_then(|n| n.commit.as_ref())
+                .map(|commit| RepeatableOp::CheckoutCommit(commit.oid))
+        }
+    }
+
+    /// Selected branch if it can be deleted (local and not HEAD)
+    fn deletable_branch(&self) -> Option<&BranchInfo> {
+        self.selected_branch()
+            .filter(|branch| !branch.is_head && !branch.is_remote)
+    }
+
+    /// Selected branch if it can be renamed (local only)
+    fn renamable_branch(&self) -> Option<&BranchInfo> {
+        self.selected_branch().filter(|branch| !branch.is_remote)
+    }
+
+    /// Normal-mode actions that apply to the selected row, most important first
+    /// Uses the same checks as the handlers, so a listed key is never refused
+    pub fn available_actions(&self) -> Vec<(&'static str, &'static str)> {
+        let mut actions = Vec::new();
+        let on_commit = self
+            .selected_commit_node()
+            .is_some_and(|node| node.commit.is_some());
+        if on_commit {
+            match self.checkout_target() {
+                Some(RepeatableOp::CheckoutBranch(name)) if name.starts_with("origin/") => {
+                    actions.push(("c", "checkout (creates local)"))
+                }
+                Some(RepeatableOp::CheckoutBranch(_)) => actions.push(("c", "checkout")),
+                Some(_) => actions.push(("c", "checkout (detached)")),
+                None => {}
+            }
+            if self.deletable_branch().is_some() {
+                actions.push(("d", "delete"));
+            }
+            if self.selected_branch().is_none() {
+                actions.push(("b", "branch"));
+            }
+        }
+        actions.push(("j/k", "move"));
+        if on_commit {
+            actions.push(("Enter", "copy"));
+            if self.renamable_branch().is_some() {
+                actions.push(("e", "rename"));
+            }
+            if self.selected_branch().is_some() {
+                actions.push(("b", "branch"));
+            }
+        }
+        if !self.is_fetching() {
+            actions.push(("f", "fetch"));
+        }
+        actions.push(("?", "help"));
+        actions.push(("q", "quit"));
+        actions
+    }
+
+    /// Check out a branch or commit and remember it for repeating
+    /// A checkout blocked by untracked files or uncommitted changes opens a dialog listing
+    /// them instead of failing
+    fn run_checkout(&mut self, op: RepeatableOp) -> Result<()> {
+        let repo = &self.repo.repo;
+        if ensure_index_unlocked(repo).is_err() {
+            self.offer_lock_retry(
+                ConfirmAction::Checkout(Box::new(op)),
+                ExpectedState::default(),
+            );
+            return Ok(());
+        }
+        let result = match &op {
+            RepeatableOp::CheckoutBranch(name) if name.starts_with("origin/") => {
                 // For remote branches, create a local branch and check it out
-                checkout_remote_branch(&self.repo.repo, &branch_name)?;
-            } else {
-                checkout_branch(&self.repo.repo, &branch_name)?;
+                checkout_remote_branch(repo, name)
             }
-            self.refresh(true)?;
-        } else if let Some(node) = self.selected_commit_node() {
-            if let Some(commit) = &node.commit {
-                checkout_commit(&self.repo.repo, commit.oid)?;
-                self.refresh(true)?;
+            RepeatableOp::CheckoutBranch(name) => checkout_branch(repo, name),
+            RepeatableOp::CheckoutCommit(oid) => checkout_commit(repo, *oid),
+            _ => return Ok(()),
+        };
+        match result {
+            Ok(()) => {}
+            Err(GitError::UntrackedFilesOverwritten(paths)) => {
+                self.mode = AppMode::CheckoutBlocked {
+                    op,
+                    paths,
+                    local_changes: false,
+                    scroll: 0,
+                };
+                return Ok(());
+            }
+            Err(GitError::LocalChangesOverwritten(paths)) => {
+                self.mode = AppMode::CheckoutBlocked {
+                    op,
+                    paths,
+                    local_changes: true,
+                    scroll: 0,
+                };
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        }
+        self.last_operation = Some(op);
+        self.refresh(true)
+    }
+
+    /// Run the most recent git operation again
+    /// Destructive operations always ask for confirmation, even when disabled in config
+    fn repeat_last_operation(&mut self) -> Result<()> {
+        let Some(op) = self.last_operation.clone() else {
+            self.set_message("No operation to repeat");
+            return Ok(());
+        };
+        match op {
+            RepeatableOp::Fetch => self.fetch_manually(),
+            RepeatableOp::CheckoutBranch(_) | RepeatableOp::CheckoutCommit(_) => {
+                self.run_checkout(op)?;
+            }
+            RepeatableOp::CreateBranch(name) => {
+                self.mode = AppMode::Input {
+                    title: "New Branch Name".to_string(),
+                    input: name,
+                    action: InputAction::CreateBranch,
+                };
+            }
+            RepeatableOp::Confirmed(action) => {
+                let message = match &action {
+                    ConfirmAction::DeleteBranch(name) => format!("Delete branch '{}' again?", name),
+                    ConfirmAction::Merge(name) => {
+                        format!("Merge '{}' into current branch again?", name)
+                    }
+                    ConfirmAction::Rebase(name) => {
+                        format!("Rebase current branch onto '{}' again?", name)
+                    }
+                    ConfirmAction::BisectReset
+                    | ConfirmAction::ClearBookmarks
+                    | ConfirmAction::CherryPick(_)
+                    | ConfirmAction::Checkout(_)
+                    | ConfirmAction::DropStash(_)
+                    | ConfirmAction::Quit
+                    | ConfirmAction::AbortConflict(_) => return Ok(()),
+                };
+                let expected = self.expected_state(&action);
+                self.mode = AppMode::Confirm {
+                    message,
+                    action,
+                    expected,
+                };
             }
         }
         Ok(())
@@ -1137,7 +4178,7 @@ impl App {
 
     /// Build a flat list of (node_index, branch_name) for all branches
     /// Excludes remote branches that have a matching local branch (e.g., origin/main when main exists)
-    /// Order matches optimize_branch_display: local branches first, then remote-only branches
+    /// Order matches decoration_labels: local branches first, then remote-only branches
     fn build_branch_positions(graph_layout: &GraphLayout) -> Vec<(usize, String)> {
         graph_layout
             .nodes
@@ -1151,3 +4192,55 @@ impl App {
             .collect()
     }
 }
+
+#[cfg(test)]
+#[path = "../tests/common/repo.rs"]
+#[allow(dead_code)]
+mod test_repo;
+
+#[cfg(test)]
+mod tests {
+    use super::test_repo::TestRepo;
+    use super::*;
+
+    /// Poll the diff workers until the selected node is no longer loading
+    fn wait_for_diff(app: &mut App) {
+        for _ in 0..500 {
+            app.update_diff_cache();
+            if !app.is_diff_loading() {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("diff did not finish loading");
+    }
+
+    #[test]
+    fn test_panicking_diff_worker_shows_error_and_later_diffs_load() {
+        let test_repo = TestRepo::new();
+        let oid = test_repo.commit_file("a.txt", "a", "initial");
+        let repo = GitRepository::open(test_repo.path()).unwrap();
+        let mut app = App::with_repo(repo, Config::default()).unwrap();
+
+        // Inject a panicking job in place of the diff worker
+        app.diff_loading_oid = Some(oid);
+        app.diff_receiver = Some(worker::spawn(|| -> DiffResult {
+            panic!("malformed object")
+        }));
+        wait_for_diff(&mut app);
+
+        assert_eq!(
+            app.diff_error(),
+            Some("internal error while computing diff: malformed object")
+        );
+        assert!(app.cached_diff().is_none());
+
+        // A manual refresh clears the error and the next diff is computed normally
+        app.refresh(true).unwrap();
+        app.update_diff_cache();
+        wait_for_diff(&mut app);
+
+        assert_eq!(app.diff_error(), None);
+        assert!(app.cached_diff().is_some());
+    }
+}
@@ -0,0 +1,137 @@
+//! Persistent commit bookmarks (per repository)
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use git2::Oid;
+use serde::{Deserialize, Serialize};
+
+/// Bookmark file name, stored under `<git dir>/keifu/`
+const BOOKMARKS_FILE: &str = "bookmarks.toml";
+
+/// On-disk format
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct BookmarksFile {
+    /// Bookmarked commit OIDs (hex)
+    commits: Vec<String>,
+}
+
+/// Bookmarked commits, keyed by OID
+#[derive(Debug, Default)]
+pub struct Bookmarks {
+    /// File to save to (None disables persistence)
+    path: Option<PathBuf>,
+    oids: HashSet<Oid>,
+}
+
+impl Bookmarks {
+    /// Load bookmarks for the repository whose git dir is `git_dir`
+    /// Missing or invalid files yield an empty set
+    pub fn load(git_dir: &Path) -> Self {
+        let path = git_dir.join("keifu").join(BOOKMARKS_FILE);
+
+        let oids = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| toml::from_str::<BookmarksFile>(&content).ok())
+            .map(|file| {
+                file.commits
+                    .iter()
+                    .filter_map(|s| Oid::from_str(s).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            path: Some(path),
+            oids,
+        }
+    }
+
+    /// Save bookmarks to disk
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut commits: Vec<String> = self.oids.iter().map(|oid| oid.to_string()).collect();
+        commits.sort();
+        let content = toml::to_string(&BookmarksFile { commits })?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create bookmark directory")?;
+        }
+        fs::write(path, content).context("Failed to save bookmarks")?;
+        Ok(())
+    }
+
+    /// Toggle a bookmark, returning whether the commit is now bookmarked
+    pub fn toggle(&mut self, oid: Oid) -> bool {
+        if self.oids.remove(&oid) {
+            false
+        } else {
+            self.oids.insert(oid);
+            true
+        }
+    }
+
+    pub fn contains(&self, oid: Oid) -> bool {
+        self.oids.contains(&oid)
+    }
+
+    pub fn clear(&mut self) {
+        self.oids.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.oids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.oids.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oid(n: u8) -> Oid {
+        Oid::from_bytes(&[n; 20]).unwrap()
+    }
+
+    #[test]
+    fn test_toggle() {
+        let mut bookmarks = Bookmarks::default();
+        assert!(bookmarks.toggle(oid(1)));
+        assert!(bookmarks.contains(oid(1)));
+        assert!(!bookmarks.toggle(oid(1)));
+        assert!(bookmarks.is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut bookmarks = Bookmarks::load(dir.path());
+        assert!(bookmarks.is_empty());
+
+        bookmarks.toggle(oid(1));
+        bookmarks.toggle(oid(2));
+        bookmarks.save().unwrap();
+
+        let loaded = Bookmarks::load(dir.path());
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded.contains(oid(1)));
+        assert!(loaded.contains(oid(2)));
+    }
+
+    #[test]
+    fn test_load_invalid_file_is_empty() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("keifu")).unwrap();
+        fs::write(dir.path().join("keifu").join(BOOKMARKS_FILE), "not toml [").unwrap();
+        assert!(Bookmarks::load(dir.path()).is_empty());
+    }
+}
@@ -0,0 +1,194 @@
+//! Command palette (`:`): typed commands that map to existing actions
+
+use anyhow::{anyhow, bail, Result};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+
+/// Palette commands: name, argument and description, in completion order
+pub const COMMANDS: &[(&str, &str, &str)] = &[
+    ("goto", "<ref>", "Select a branch, tag or commit"),
+    ("checkout", "<ref>", "Check out a branch or commit"),
+    ("branch", "<name>", "Create a branch at the selected commit"),
+    ("limit", "<n>", "Load n commits of history"),
+    (
+        "open",
+        "<path>",
+        "View a file at the selected commit in $EDITOR",
+    ),
+    (
+        "reset-view",
+        "",
+        "Reset saved sort, filters and view toggles",
+    ),
+];
+
+/// Completions shown below the palette input
+pub const MAX_COMPLETIONS: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Goto(String),
+    Checkout(String),
+    Branch(String),
+    Limit(usize),
+    /// Path relative to the repository root
+    Open(String),
+    ResetView,
+}
+
+impl Command {
+    /// Parse a command line; command names may be shortened to any unambiguous prefix
+    pub fn parse(line: &str) -> Result<Self> {
+        let line = line.trim();
+        let (name, argument) = line
+            .split_once(char::is_whitespace)
+            .map(|(name, argument)| (name, argument.trim()))
+            .unwrap_or((line, ""));
+        if name.is_empty() {
+            bail!("Type a command: {}", command_names().join(", "));
+        }
+
+        let command = resolve_name(name)?;
+        let (_, usage_argument, _) = COMMANDS.iter().find(|(n, ..)| *n == command).unwrap();
+        let usage = || {
+            anyhow!(
+                "Usage: {}",
+                format!("{} {}", command, usage_argument).trim_end()
+            )
+        };
+        // Commands without an argument must not be given one; only paths may contain spaces
+        if usage_argument.is_empty() != argument.is_empty()
+            || (command != "open" && argument.contains(char::is_whitespace))
+        {
+            return Err(usage());
+        }
+
+        Ok(match command {
+            "goto" => Self::Goto(argument.to_string()),
+            "checkout" => Self::Checkout(argument.to_string()),
+            "branch" => Self::Branch(argument.to_string()),
+            "open" => Self::Open(argument.to_string()),
+            "reset-view" => Self::ResetView,
+            _ => Self::Limit(argument.parse().ok().filter(|n| *n > 0).ok_or_else(usage)?),
+        })
+    }
+}
+
+fn command_names() -> Vec<&'static str> {
+    COMMANDS.iter().map(|(name, ..)| *name).collect()
+}
+
+/// The command a (possibly shortened) name stands for
+fn resolve_name(name: &str) -> Result<&'static str> {
+    let matches: Vec<&str> = command_names()
+        .into_iter()
+        .filter(|command| command.starts_with(name))
+        .collect();
+    match matches[..] {
+        [command] => Ok(command),
+        [] => bail!("Unknown command '{}'", name),
+        _ => bail!("'{}' could be {}", name, matches.join(" or ")),
+    }
+}
+
+/// Whole-line completions for the text typed so far, best first
+/// Command names complete first; `goto` and `checkout` then complete ref names,
+/// `open` the selected commit's changed files
+pub fn completions(line: &str, refs: &[String], files: &[String]) -> Vec<String> {
+    let Some((name, argument)) = line.split_once(char::is_whitespace) else {
+        return ranked(line, command_names())
+            .into_iter()
+            .map(|name| format!("{} ", name))
+            .collect();
+    };
+    match resolve_name(name) {
+        Ok(command @ ("goto" | "checkout" | "open")) => {
+            let candidates = if command == "open" { files } else { refs };
+            ranked(argument.trim(), candidates.iter().map(String::as_str))
+                .into_iter()
+                .map(|name| format!("{} {}", command, name))
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Candidates matching `query` by fuzzy score (all of them, in order, for an empty query)
+fn ranked<'c>(query: &str, candidates: impl IntoIterator<Item = &'c str>) -> Vec<&'c str> {
+    if query.is_empty() {
+        return candidates.into_iter().take(MAX_COMPLETIONS).collect();
+    }
+    let matcher = SkimMatcherV2::default();
+    let mut scored: Vec<(i64, &str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| Some((matcher.fuzzy_match(candidate, query)?, candidate)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    scored
+        .into_iter()
+        .take(MAX_COMPLETIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_accepts_prefixes_and_validates_arguments() {
+        assert_eq!(
+            Command::parse("goto feature/x").unwrap(),
+            Command::Goto("feature/x".to_string())
+        );
+        assert_eq!(
+            Command::parse("  co  main ").unwrap_err().to_string(),
+            "Unknown command 'co'"
+        );
+        assert_eq!(
+            Command::parse("ch main").unwrap(),
+            Command::Checkout("main".to_string())
+        );
+        assert_eq!(Command::parse("l 2000").unwrap(), Command::Limit(2000));
+        assert_eq!(
+            Command::parse("limit 0").unwrap_err().to_string(),
+            "Usage: limit <n>"
+        );
+        assert_eq!(
+            Command::parse("branch two words").unwrap_err().to_string(),
+            "Usage: branch <name>"
+        );
+        assert_eq!(
+            Command::parse("open src/main.rs").unwrap(),
+            Command::Open("src/main.rs".to_string())
+        );
+        assert_eq!(
+            Command::parse("open docs/release notes.md ").unwrap(),
+            Command::Open("docs/release notes.md".to_string())
+        );
+        assert_eq!(Command::parse("reset-view").unwrap(), Command::ResetView);
+        assert_eq!(
+            Command::parse("reset-view now").unwrap_err().to_string(),
+            "Usage: reset-view"
+        );
+        assert_eq!(
+            Command::parse("frobnicate").unwrap_err().to_string(),
+            "Unknown command 'frobnicate'"
+        );
+    }
+
+    #[test]
+    fn test_completions_for_names_then_refs() {
+        let refs = vec!["main".to_string(), "feature/login".to_string()];
+        assert_eq!(completions("", &refs, &[]).len(), COMMANDS.len());
+        assert_eq!(completions("chk", &refs, &[]), ["checkout "]);
+        assert_eq!(completions("goto flog", &refs, &[]), ["goto feature/login"]);
+        // Shortened names complete to the full command
+        assert_eq!(
+            completions("ch ", &refs, &[]),
+            ["checkout main", "checkout feature/login"]
+        );
+        assert!(completions("limit 5", &refs, &[]).is_empty());
+        let files = vec!["src/main.rs".to_string(), "README.md".to_string()];
+        assert_eq!(completions("o rdme", &refs, &files), ["open README.md"]);
+    }
+}
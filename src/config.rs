@@ -2,13 +2,24 @@
 
 use std::fs;
 
+use ratatui::style::Color;
 use serde::Deserialize;
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    git::{graph::GraphDensity, BranchGrouping, BranchSort, LabelOrder},
+    graph::glyphs::GraphGlyphs,
+};
 
 /// Application configuration
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(default)]
 pub struct Config {
     pub refresh: RefreshConfig,
+    pub ui: UiConfig,
+    pub update: UpdateConfig,
+    pub confirm: ConfirmConfig,
+    pub keys: KeysConfig,
 }
 
 /// Auto-refresh configuration
@@ -25,6 +36,11 @@ pub struct RefreshConfig {
     /// Interval in seconds for remote fetch (minimum: 10, default: 60)
     #[serde(deserialize_with = "deserialize_fetch_interval")]
     pub fetch_interval: u64,
+    /// Seconds before a fetch is abandoned (minimum: 5, default: 60)
+    #[serde(deserialize_with = "deserialize_fetch_timeout")]
+    pub fetch_timeout: u64,
+    /// Fetch once right after the first frame is drawn
+    pub fetch_on_start: bool,
 }
 
 impl Default for RefreshConfig {
@@ -34,10 +50,240 @@ impl Default for RefreshConfig {
             refresh_interval: 10,
             auto_fetch: true,
             fetch_interval: 60,
+            fetch_timeout: 60,
+            fetch_on_start: false,
+        }
+    }
+}
+
+/// UI display configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Max display width of the HEAD name in the status bar (minimum: 8, default: 32)
+    #[serde(deserialize_with = "deserialize_head_name_max_width")]
+    pub head_name_max_width: usize,
+    /// High-contrast theme (no dim text, bold selectable content, wider selection)
+    pub high_contrast: bool,
+    /// Replace animations (e.g. the spinner) with static text
+    pub reduced_motion: bool,
+    /// Initial sort order of the branch list: "name", "date" or "ahead" (default: "name")
+    pub branch_sort: BranchSort,
+    /// Branch list layout: "sections" (locals, then each remote, with headers) or "interleaved"
+    pub branch_grouping: BranchGrouping,
+    /// Branch labels on a commit: "head" (HEAD branch, locals, remotes) or "name" (locals, remotes)
+    pub label_order: LabelOrder,
+    /// Show each branch's commit count ahead of the main branch in the branch list (default: false)
+    pub branch_counts: bool,
+    /// Status bar segments in display order (default: ["repo", "head", "hints"])
+    pub status_segments: StatusSegments,
+    /// Connector rows in the graph: "spacious", "normal" or "compact" (default: "normal")
+    pub graph_density: GraphDensity,
+    /// Graph line characters: "box" or "braille" (one column per lane, connectors in
+    /// half rows; font-dependent)
+    /// (default: "box")
+    pub graph_glyphs: GraphGlyphs,
+    /// Labels (branches and tags) shown per graph row before collapsing into "+N";
+    /// the selected row shows them all (minimum: 1, default: 3)
+    #[serde(deserialize_with = "deserialize_max_labels")]
+    pub max_labels: usize,
+    /// Decoration drawn before the labels of the HEAD commit (e.g. "HEAD ->", "@", "→"; default: none)
+    #[serde(deserialize_with = "deserialize_head_arrow")]
+    pub head_arrow: String,
+    /// Color of the HEAD decoration (a color name, "#rrggbb" or a 256-color index; default: "green")
+    #[serde(deserialize_with = "deserialize_color")]
+    pub head_arrow_color: Color,
+    /// Commit message lines shown in the detail pane before the rest is cut off
+    /// (minimum: 1, default: 500)
+    #[serde(deserialize_with = "deserialize_max_message_lines")]
+    pub max_message_lines: usize,
+    /// Commits loaded at startup and each time scrolling nears the bottom
+    /// (minimum: 1, default: 500)
+    #[serde(deserialize_with = "deserialize_commit_page_size")]
+    pub commit_page_size: usize,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            head_name_max_width: 32,
+            high_contrast: false,
+            reduced_motion: false,
+            branch_sort: BranchSort::default(),
+            branch_grouping: BranchGrouping::default(),
+            label_order: LabelOrder::default(),
+            branch_counts: false,
+            status_segments: StatusSegments::default(),
+            graph_density: GraphDensity::default(),
+            graph_glyphs: GraphGlyphs::default(),
+            max_labels: 3,
+            head_arrow: String::new(),
+            head_arrow_color: Color::Green,
+            max_message_lines: 500,
+            commit_page_size: crate::app::COMMIT_PAGE_SIZE,
+        }
+    }
+}
+
+/// A piece of the status bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusSegment {
+    /// Repository folder name (and a separate worktree path)
+    Repo,
+    /// HEAD branch or detached commit
+    Head,
+    /// Number of changed files in the working tree, or "clean"
+    Dirty,
+    /// Number of loaded commits
+    Commits,
+    /// Local time
+    Clock,
+    /// Key hints for the normal view
+    Hints,
+}
+
+impl StatusSegment {
+    const ALL: [StatusSegment; 6] = [
+        StatusSegment::Repo,
+        StatusSegment::Head,
+        StatusSegment::Dirty,
+        StatusSegment::Commits,
+        StatusSegment::Clock,
+        StatusSegment::Hints,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusSegment::Repo => "repo",
+            StatusSegment::Head => "head",
+            StatusSegment::Dirty => "dirty",
+            StatusSegment::Commits => "commits",
+            StatusSegment::Clock => "clock",
+            StatusSegment::Hints => "hints",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+}
+
+/// Status bar layout from `ui.status_segments`
+/// Unknown names are kept aside so they can be reported at startup instead of
+/// discarding the whole config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusSegments {
+    pub segments: Vec<StatusSegment>,
+    pub unknown: Vec<String>,
+}
+
+impl Default for StatusSegments {
+    fn default() -> Self {
+        Self {
+            segments: vec![
+                StatusSegment::Repo,
+                StatusSegment::Head,
+                StatusSegment::Hints,
+            ],
+            unknown: Vec::new(),
         }
     }
 }
 
+impl<'de> Deserialize<'de> for StatusSegments {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        let mut segments = Vec::new();
+        let mut unknown = Vec::new();
+        for name in names {
+            match StatusSegment::from_name(name.trim()) {
+                Some(segment) if !segments.contains(&segment) => segments.push(segment),
+                Some(_) => {}
+                None => unknown.push(name),
+            }
+        }
+        Ok(Self { segments, unknown })
+    }
+}
+
+/// Per-operation confirmation dialogs (set to false to run the operation immediately)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ConfirmConfig {
+    /// Confirm before deleting a branch (default: true)
+    pub delete_branch: bool,
+    /// Confirm before merging a branch (default: true)
+    pub merge: bool,
+    /// Confirm before rebasing onto a branch (default: true)
+    pub rebase: bool,
+    /// Confirm before clearing all bookmarks (default: true)
+    pub clear_bookmarks: bool,
+    /// Confirm before ending a bisect session (default: true)
+    pub bisect_reset: bool,
+    /// Confirm before cherry-picking the selected commit or visual span (default: true)
+    pub cherry_pick: bool,
+    /// Confirm before dropping a stash (default: true)
+    pub drop_stash: bool,
+}
+
+impl Default for ConfirmConfig {
+    fn default() -> Self {
+        Self {
+            delete_branch: true,
+            merge: true,
+            rebase: true,
+            clear_bookmarks: true,
+            bisect_reset: true,
+            cherry_pick: true,
+            drop_stash: true,
+        }
+    }
+}
+
+/// Key input configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct KeysConfig {
+    /// Milliseconds to wait for the second key of a sequence like `gg`
+    /// (minimum: 100, default: 1000)
+    #[serde(deserialize_with = "deserialize_sequence_timeout")]
+    pub sequence_timeout_ms: u64,
+    /// What `q` does in the graph: "back" (quit) or "confirm-quit" (default: "back")
+    pub q: QKey,
+}
+
+impl Default for KeysConfig {
+    fn default() -> Self {
+        Self {
+            sequence_timeout_ms: 1000,
+            q: QKey::default(),
+        }
+    }
+}
+
+/// `q` always goes back one level (closes the popup, dialog or visual mode it is in);
+/// this chooses what it does in the graph, where there is no level left
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QKey {
+    /// Quit
+    #[default]
+    Back,
+    /// Ask before quitting
+    ConfirmQuit,
+}
+
+/// Update check configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct UpdateConfig {
+    /// Check GitHub for a newer release on startup (opt-in, default: false)
+    pub check: bool,
+}
+
 fn deserialize_refresh_interval<'de, D>(deserializer: D) -> Result<u64, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -54,6 +300,83 @@ where
     Ok(value.max(10))
 }
 
+fn deserialize_fetch_timeout<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = u64::deserialize(deserializer)?;
+    Ok(value.max(5))
+}
+
+fn deserialize_sequence_timeout<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = u64::deserialize(deserializer)?;
+    Ok(value.max(100))
+}
+
+fn deserialize_head_name_max_width<'de, D>(deserializer: D) -> Result<usize, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = usize::deserialize(deserializer)?;
+    Ok(value.max(8))
+}
+
+fn deserialize_max_labels<'de, D>(deserializer: D) -> Result<usize, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = usize::deserialize(deserializer)?;
+    Ok(value.max(1))
+}
+
+fn deserialize_max_message_lines<'de, D>(deserializer: D) -> Result<usize, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = usize::deserialize(deserializer)?;
+    Ok(value.max(1))
+}
+
+fn deserialize_commit_page_size<'de, D>(deserializer: D) -> Result<usize, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = usize::deserialize(deserializer)?;
+    Ok(value.max(1))
+}
+
+/// Max display width of the HEAD decoration, so graph rows keep their alignment
+pub const HEAD_ARROW_MAX_WIDTH: usize = 8;
+
+/// Drop control characters and clip the decoration to HEAD_ARROW_MAX_WIDTH columns
+fn deserialize_head_arrow<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    let mut arrow = String::new();
+    let mut width = 0;
+    for c in value.trim().chars().filter(|c| !c.is_control()) {
+        width += c.width().unwrap_or(0);
+        if width > HEAD_ARROW_MAX_WIDTH {
+            break;
+        }
+        arrow.push(c);
+    }
+    Ok(arrow)
+}
+
+fn deserialize_color<'de, D>(deserializer: D) -> Result<Color, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(serde::de::Error::custom)
+}
+
 impl Config {
     /// Load config from ~/.config/keifu/config.toml
     /// Returns default config if file doesn't exist or is invalid
@@ -72,3 +395,34 @@ impl Config {
             .unwrap_or_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_segments_keep_order_and_set_aside_unknown_names() {
+        let config: Config = toml::from_str(
+            r#"
+            [ui]
+            status_segments = ["clock", "head", "branch", "clock", "hints"]
+            max_labels = 5
+            graph_glyphs = "braille"
+            "#,
+        )
+        .unwrap();
+        let status = config.ui.status_segments;
+        assert_eq!(
+            status.segments,
+            [
+                StatusSegment::Clock,
+                StatusSegment::Head,
+                StatusSegment::Hints
+            ]
+        );
+        assert_eq!(status.unknown, ["branch"]);
+        // The rest of the file still applies
+        assert_eq!(config.ui.max_labels, 5);
+        assert_eq!(config.ui.graph_glyphs, GraphGlyphs::Braille);
+    }
+}
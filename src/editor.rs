@@ -0,0 +1,165 @@
+//! Browsing a file as of a commit in the user's editor
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use tempfile::TempPath;
+
+/// Editor used when neither VISUAL nor EDITOR is set
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Read-only temporary copy of a file at a commit, removed when dropped
+#[derive(Debug)]
+pub struct CommitFile {
+    path: TempPath,
+}
+
+impl CommitFile {
+    /// Write `content` to a new temporary file named after the commit and `path`
+    /// The file name is kept last so editors still detect the language
+    pub fn create(path: &Path, short_id: &str, content: &[u8]) -> Result<Self> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let mut temp = tempfile::Builder::new()
+            .prefix(&format!("keifu-{}-", short_id))
+            .suffix(&format!("-{}", name))
+            .tempfile()
+            .context("Failed to create the temporary file")?;
+        temp.write_all(content)
+            .context("Failed to write the temporary file")?;
+        let file = Self {
+            path: temp.into_temp_path(),
+        };
+        let mut permissions = fs::metadata(file.path())?.permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(file.path(), permissions)?;
+        Ok(file)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Run the editor on the file and wait for it to exit
+    /// The terminal must be restored first; the editor takes it over
+    pub fn open(&self) -> Result<()> {
+        let editor = editor_command();
+        let words = split_command(&editor)?;
+        let (program, args) = words
+            .split_first()
+            .map(|(program, args)| (program.as_str(), args))
+            .unwrap_or((DEFAULT_EDITOR, &[]));
+        let status = Command::new(program)
+            .args(args)
+            .arg(self.path())
+            .status()
+            .with_context(|| format!("Cannot run editor '{}'", editor))?;
+        if !status.success() {
+            bail!("Editor '{}' exited with {}", editor, status);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for CommitFile {
+    fn drop(&mut self) {
+        // Read-only files cannot be removed on Windows; `TempPath` removes it afterwards
+        if let Ok(metadata) = fs::metadata(self.path()) {
+            let mut permissions = metadata.permissions();
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+            let _ = fs::set_permissions(self.path(), permissions);
+        }
+    }
+}
+
+/// Split an editor command line into words, honouring single and double quotes
+/// and backslash escapes the way a shell would (no expansion is done)
+fn split_command(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\\' => {
+                let escaped = chars.next().context("Editor command ends with '\\'")?;
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            '\'' | '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some(close) if close == c => break,
+                        // Inside double quotes a backslash only escapes `"` and itself
+                        Some('\\') if c == '"' => match chars.next() {
+                            Some(next @ ('"' | '\\')) => word.push(next),
+                            Some(next) => {
+                                word.push('\\');
+                                word.push(next);
+                            }
+                            None => bail!("Unterminated quote in editor command"),
+                        },
+                        Some(inner) => word.push(inner),
+                        None => bail!("Unterminated quote in editor command"),
+                    }
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// The editor command line from VISUAL or EDITOR (like git), `vi` when neither is set
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_command_honours_quotes() {
+        assert_eq!(split_command("vi").unwrap(), ["vi"]);
+        assert_eq!(split_command("  code --wait ").unwrap(), ["code", "--wait"]);
+        assert_eq!(
+            split_command(r#""/opt/my editor/ed" -f 'a b'"#).unwrap(),
+            ["/opt/my editor/ed", "-f", "a b"]
+        );
+        assert_eq!(
+            split_command(r#"/opt/my\ editor/ed "say \"hi\"" x''"#).unwrap(),
+            ["/opt/my editor/ed", r#"say "hi""#, "x"]
+        );
+        assert!(split_command("").unwrap().is_empty());
+        assert!(split_command("'vi").is_err());
+    }
+
+    #[test]
+    fn test_commit_file_is_unique_read_only_and_removed_on_drop() {
+        let first =
+            CommitFile::create(Path::new("src/main.rs"), "abc1234", b"fn main() {}").unwrap();
+        let second = CommitFile::create(Path::new("src/main.rs"), "abc1234", b"").unwrap();
+        assert_ne!(first.path(), second.path());
+        let name = first.path().file_name().unwrap().to_string_lossy();
+        assert!(name.starts_with("keifu-abc1234-") && name.ends_with("-main.rs"));
+        assert_eq!(fs::read(first.path()).unwrap(), b"fn main() {}");
+        assert!(fs::metadata(first.path()).unwrap().permissions().readonly());
+
+        let path = first.path().to_path_buf();
+        drop(first);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,199 @@
+//! Error type for the library API (git operations and graph loading)
+//!
+//! Display messages are written for end users; they are shown as-is in the error dialog.
+
+use std::path::PathBuf;
+
+use git2::{ErrorClass, ErrorCode};
+
+/// Result with keifu's error type
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Git repository not found at '{}'", .0.display())]
+    RepositoryNotFound(PathBuf),
+
+    #[error("Branch '{0}' not found")]
+    BranchNotFound(String),
+
+    #[error("Remote branch '{0}' not found")]
+    RemoteBranchNotFound(String),
+
+    #[error("Branch '{0}' already exists")]
+    BranchExists(String),
+
+    #[error("Invalid branch name '{0}'")]
+    InvalidBranchName(String),
+
+    #[error("Cannot delete the current branch")]
+    CurrentBranch,
+
+    #[error("Commit {0} not found")]
+    CommitNotFound(String),
+
+    /// The path (relative to the repository root) is not a file at the commit (short id)
+    #[error("No file '{}' at {commit}", .path.display())]
+    FileNotInCommit { path: PathBuf, commit: String },
+
+    /// The commit (short id) deleted the file, so it has no content there
+    #[error("'{}' was deleted in {commit}", .path.display())]
+    FileDeleted { path: PathBuf, commit: String },
+
+    /// A revspec given on the command line could not be resolved to a commit
+    #[error("Cannot resolve revision '{spec}': {message}")]
+    InvalidRevision { spec: String, message: String },
+
+    /// A date for the date range filter could not be parsed
+    #[error("Cannot parse date '{0}' (use YYYY-MM-DD or e.g. \"2 weeks ago\")")]
+    InvalidDate(String),
+
+    #[error("The start date is after the end date")]
+    ReversedDateRange,
+
+    #[error("HEAD does not point to a commit")]
+    NoHead,
+
+    #[error("HEAD is not on a branch")]
+    DetachedHead,
+
+    #[error("Branch '{0}' has no upstream")]
+    NoUpstream(String),
+
+    #[error("Repository has no working tree")]
+    BareRepository,
+
+    /// Checkout or merge would overwrite local changes
+    #[error("Local changes would be overwritten; commit or stash them first")]
+    DirtyWorktree,
+
+    /// Checkout would overwrite these untracked files (paths relative to the working tree)
+    #[error("Checkout would overwrite {} untracked file(s)", .0.len())]
+    UntrackedFilesOverwritten(Vec<PathBuf>),
+
+    /// Checkout would overwrite uncommitted changes to these files (untracked ones included)
+    #[error("You have uncommitted changes to {} file(s) that checkout would overwrite; commit or stash them first", .0.len())]
+    LocalChangesOverwritten(Vec<PathBuf>),
+
+    /// HEAD or a branch moved after a confirmation was offered (e.g. by another git process)
+    #[error("Repository changed since the dialog was opened — refresh and retry")]
+    RepositoryChanged,
+
+    /// Moving the branches onto rewritten commits failed, and these branches, already
+    /// moved, could not be put back either
+    #[error("{cause}; these branches were moved and could not be put back: {}", .branches.join(", "))]
+    RetargetRollbackFailed {
+        cause: Box<Error>,
+        branches: Vec<String>,
+    },
+
+    /// `.git/index.lock` exists: another git process (often an editor integration) is running
+    #[error("Repository is locked by another git process (index.lock exists)")]
+    IndexLocked,
+
+    /// Merge analysis found no way to merge this branch
+    #[error("Cannot merge '{0}' into HEAD")]
+    CannotMerge(String),
+
+    /// The merge stopped with conflicts in these files; the repository is left mid-merge
+    #[error("Merge stopped with conflicts in {} file(s)", .0.len())]
+    MergeConflict(Vec<String>),
+
+    /// Cherry-picking this commit (short id) stopped with conflicts in these files;
+    /// the repository is left mid-pick
+    #[error("Cherry-pick of {commit} stopped with conflicts in {} file(s)", .files.len())]
+    CherryPickConflict { commit: String, files: Vec<String> },
+
+    /// Replaying this commit (short id) conflicted; the rebase was aborted and nothing changed
+    #[error("Rebase stopped: {0} conflicts with the new base; the rebase was aborted")]
+    RebaseConflict(String),
+
+    /// The selected commits cannot be squashed (reason for the user)
+    #[error("Cannot squash: {0}")]
+    SquashRefused(&'static str),
+
+    /// The index matches HEAD, so there is nothing to commit
+    #[error("Nothing staged to commit")]
+    NothingStaged,
+
+    /// A fixup!/squash! target (short id) that autosquash could not fold the commit into
+    #[error("Commit {0} is not in HEAD's history")]
+    NotInHeadHistory(String),
+
+    #[error("No local changes to stash")]
+    NothingToStash,
+
+    /// Applying stash@{n} left conflicts in the working tree; the stash is kept
+    #[error("Applying stash@{{{0}}} left conflicts to resolve; the stash was kept")]
+    StashConflict(usize),
+
+    #[error("Repository is already bisecting (run `git bisect reset` first)")]
+    AlreadyBisecting,
+
+    #[error("The bad commit is an ancestor of a good commit")]
+    BadIsAncestorOfGood,
+
+    /// user.name or user.email is missing or invalid
+    #[error("{0} is not set in git config")]
+    MissingIdentity(&'static str),
+
+    #[error("Authentication failed: {0}")]
+    AuthFailed(String),
+
+    #[error("Fetch cancelled")]
+    FetchCancelled,
+
+    #[error("git fetch timed out after {0}s")]
+    FetchTimeout(u64),
+
+    #[error("git fetch failed: {0}")]
+    FetchFailed(String),
+
+    /// Any other libgit2 failure (shown without libgit2's class/code suffix)
+    #[error("{}", .0.message())]
+    Git(git2::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl From<git2::Error> for Error {
+    fn from(e: git2::Error) -> Self {
+        match (e.code(), e.class()) {
+            // Checkout refusing to overwrite local changes, or an operation refusing
+            // a dirty index or working tree (conflicts elsewhere, e.g. applying a
+            // patch, stay as they are)
+            (ErrorCode::Conflict, ErrorClass::Checkout) | (ErrorCode::Uncommitted, _) => {
+                Error::DirtyWorktree
+            }
+            (ErrorCode::Auth, _) => Error::AuthFailed(e.message().to_string()),
+            _ => Error::Git(e),
+        }
+    }
+}
+
+impl Error {
+    /// Whether the failure comes from another git process working on the repository
+    /// at the same time (a lock it holds, or a ref it changed under us); trying again
+    /// shortly after usually succeeds
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Git(e) => matches!(e.code(), ErrorCode::Locked | ErrorCode::Modified),
+            _ => false,
+        }
+    }
+}
+
+/// `not_found` for a lookup that found nothing; any other failure as it is
+pub(crate) fn not_found_or(e: git2::Error, not_found: impl FnOnce() -> Error) -> Error {
+    if e.code() == ErrorCode::NotFound {
+        not_found()
+    } else {
+        e.into()
+    }
+}
+
+/// Short form of an OID for messages
+pub(crate) fn short_oid(oid: git2::Oid) -> String {
+    oid.to_string()[..7].to_string()
+}
@@ -0,0 +1,223 @@
+//! Interactive bisect (midpoint search between known-good and known-bad commits)
+//!
+//! Runs entirely in keifu: the repository's own `git bisect` state is not touched.
+
+use std::collections::HashMap;
+
+use git2::{Oid, Repository, RepositoryState, Sort};
+
+use super::operations::{checkout_branch, checkout_commit};
+use crate::error::{short_oid, Error, Result};
+
+/// Where HEAD was when the session started (restored on reset)
+#[derive(Debug, Clone)]
+enum OriginalHead {
+    Branch(String),
+    Detached(Oid),
+}
+
+/// What the session needs next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BisectStep {
+    /// A good commit must be marked
+    NeedGood,
+    /// A bad commit must be marked
+    NeedBad,
+    /// Test this commit next
+    Test(Oid),
+    /// The first bad commit has been found
+    Found(Oid),
+}
+
+/// An in-progress bisect session
+#[derive(Debug)]
+pub struct BisectSession {
+    original_head: OriginalHead,
+    bad: Option<Oid>,
+    good: Vec<Oid>,
+    /// Commits that may still be the first bad one (topological, newest first; bad included)
+    candidates: Vec<Oid>,
+    /// Candidate to test next (None until good and bad are marked)
+    midpoint: Option<Oid>,
+}
+
+impl BisectSession {
+    /// Start a session, remembering the current HEAD
+    /// Fails if the repository is already in the middle of a `git bisect`
+    pub fn start(repo: &Repository) -> Result<Self> {
+        if repo.state() == RepositoryState::Bisect {
+            return Err(Error::AlreadyBisecting);
+        }
+
+        let head = repo.head().map_err(|_| Error::NoHead)?;
+        let original_head = match head.shorthand() {
+            Some(name) if head.is_branch() => OriginalHead::Branch(name.to_string()),
+            _ => OriginalHead::Detached(head.target().ok_or(Error::NoHead)?),
+        };
+
+        Ok(Self {
+            original_head,
+            bad: None,
+            good: Vec::new(),
+            candidates: Vec::new(),
+            midpoint: None,
+        })
+    }
+
+    /// Mark a commit as good
+    pub fn mark_good(&mut self, repo: &Repository, oid: Oid) -> Result<()> {
+        if self.good.contains(&oid) {
+            return Ok(());
+        }
+        self.good.push(oid);
+        if let Err(e) = self.update_candidates(repo) {
+            self.good.pop();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Mark a commit as bad
+    pub fn mark_bad(&mut self, repo: &Repository, oid: Oid) -> Result<()> {
+        let previous = self.bad.replace(oid);
+        if let Err(e) = self.update_candidates(repo) {
+            self.bad = previous;
+            // Restore the candidates computed for the previous bad commit
+            self.update_candidates(repo)?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Recompute the commits between the good commits and the bad commit
+    fn update_candidates(&mut self, repo: &Repository) -> Result<()> {
+        self.candidates.clear();
+        self.midpoint = None;
+        let Some(bad) = self.bad else {
+            return Ok(());
+        };
+        if self.good.is_empty() {
+            return Ok(());
+        }
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL)?;
+        revwalk.push(bad)?;
+        for good in &self.good {
+            revwalk.hide(*good)?;
+        }
+        let candidates = revwalk.collect::<Result<Vec<Oid>, _>>()?;
+
+        if candidates.is_empty() {
+            return Err(Error::BadIsAncestorOfGood);
+        }
+        self.midpoint = Some(midpoint(repo, &candidates)?);
+        self.candidates = candidates;
+        Ok(())
+    }
+
+    /// Commits marked good so far
+    pub fn good(&self) -> &[Oid] {
+        &self.good
+    }
+
+    /// Commit currently marked bad
+    pub fn bad(&self) -> Option<Oid> {
+        self.bad
+    }
+
+    /// Number of commits that may still be the first bad one (None until good and bad are marked)
+    pub fn remaining(&self) -> Option<usize> {
+        (!self.candidates.is_empty()).then_some(self.candidates.len())
+    }
+
+    /// Decide the next step
+    pub fn next_step(&self) -> BisectStep {
+        match (self.bad, self.good.is_empty(), self.midpoint) {
+            (None, ..) => BisectStep::NeedBad,
+            (Some(_), true, _) => BisectStep::NeedGood,
+            (Some(bad), false, _) if self.candidates.len() <= 1 => BisectStep::Found(bad),
+            (Some(_), false, Some(midpoint)) => BisectStep::Test(midpoint),
+            (Some(bad), false, None) => BisectStep::Found(bad),
+        }
+    }
+
+    /// Return HEAD to where it was when the session started
+    pub fn reset(&self, repo: &Repository) -> Result<()> {
+        match &self.original_head {
+            OriginalHead::Branch(name) => checkout_branch(repo, name),
+            OriginalHead::Detached(oid) => checkout_commit(repo, *oid),
+        }
+    }
+
+    /// Name of the original HEAD (for messages)
+    pub fn original_head_name(&self) -> String {
+        match &self.original_head {
+            OriginalHead::Branch(name) => name.clone(),
+            OriginalHead::Detached(oid) => short_oid(*oid),
+        }
+    }
+}
+
+/// The candidate that splits the others most evenly, like `git bisect`: whichever
+/// outcome the test has, the fewest candidates remain
+/// A candidate's ancestors among the candidates (itself included) remain if it is bad,
+/// the rest if it is good; ties go to the newest candidate
+/// Like git, ancestor counts follow from the parent's count for ordinary commits and
+/// only merges walk their ancestors, so a history without merges takes linear time
+fn midpoint(repo: &Repository, candidates: &[Oid]) -> Result<Oid> {
+    let count = candidates.len();
+    let position: HashMap<Oid, usize> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, oid)| (*oid, i))
+        .collect();
+    let parents = candidates
+        .iter()
+        .map(|oid| {
+            let commit = repo.find_commit(*oid)?;
+            Ok(commit
+                .parent_ids()
+                .filter_map(|parent| position.get(&parent).copied())
+                .collect())
+        })
+        .collect::<Result<Vec<Vec<usize>>>>()?;
+
+    // Candidates are listed children first, so walking them backwards counts every
+    // parent first
+    let mut reach = vec![0; count];
+    // Merge whose walk last visited each candidate
+    let mut visited_by = vec![usize::MAX; count];
+    let mut stack = Vec::new();
+    for i in (0..count).rev() {
+        reach[i] = match parents[i].as_slice() {
+            [] => 1,
+            [parent] => reach[*parent] + 1,
+            // Merge parents share ancestors, so count them once each
+            _ => {
+                let mut walked = 0;
+                visited_by[i] = i;
+                stack.push(i);
+                while let Some(j) = stack.pop() {
+                    walked += 1;
+                    for &parent in &parents[j] {
+                        if visited_by[parent] != i {
+                            visited_by[parent] = i;
+                            stack.push(parent);
+                        }
+                    }
+                }
+                walked
+            }
+        };
+    }
+
+    let mut best = (0, 0);
+    for (i, &reach) in reach.iter().enumerate() {
+        let remaining = reach.min(count - reach);
+        if remaining > best.1 {
+            best = (i, remaining);
+        }
+    }
+    Ok(candidates[best.0])
+}
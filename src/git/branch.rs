@@ -1,68 +1,216 @@
 //! Branch info structure and operations
 
-use anyhow::Result;
-use git2::{BranchType, Oid, Repository};
+use std::collections::HashMap;
 
-#[derive(Debug, Clone)]
+use git2::{BranchType, ErrorCode, Oid, Reference, Repository};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::error::Result;
+
+/// Marker appended to ref names that are not valid UTF-8
+pub const NON_UTF8_MARKER: &str = " [non-utf8]";
+
+/// Convert raw ref name bytes into a displayable name.
+/// Git allows arbitrary bytes in ref names, so invalid UTF-8 is converted
+/// lossily and tagged with NON_UTF8_MARKER.
+pub fn ref_name_lossy(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(name) => name.to_string(),
+        Err(_) => format!("{}{}", String::from_utf8_lossy(bytes), NON_UTF8_MARKER),
+    }
+}
+
+/// Branch HEAD points to while it has no commits yet (e.g. right after `git init`)
+/// None when HEAD resolves, is detached, or is broken in some other way
+pub fn unborn_head_branch(repo: &Repository) -> Option<String> {
+    match repo.head() {
+        Err(e) if e.code() == ErrorCode::UnbornBranch => {}
+        _ => return None,
+    }
+    let head = repo.find_reference("HEAD").ok()?;
+    let target = head.symbolic_target_bytes()?;
+    Some(ref_name_lossy(
+        target.strip_prefix(b"refs/heads/").unwrap_or(target),
+    ))
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct BranchInfo {
     pub name: String,
     pub is_head: bool,
     pub is_remote: bool,
     pub upstream: Option<String>,
+    #[serde(serialize_with = "serialize_oid")]
     pub tip_oid: Oid,
+    /// Commit time of the tip (Unix seconds)
+    pub tip_time: i64,
+}
+
+/// Sort order of the branch list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BranchSort {
+    #[default]
+    Name,
+    /// Most recent tip commit first
+    Date,
+    /// Most commits ahead of the main branch first
+    Ahead,
+}
+
+impl BranchSort {
+    pub fn next(self) -> Self {
+        match self {
+            BranchSort::Name => BranchSort::Date,
+            BranchSort::Date => BranchSort::Ahead,
+            BranchSort::Ahead => BranchSort::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BranchSort::Name => "name",
+            BranchSort::Date => "date",
+            BranchSort::Ahead => "ahead",
+        }
+    }
+}
+
+/// How the branch list arranges local and remote branches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BranchGrouping {
+    /// Local branches, then each remote's branches, under section headers
+    #[default]
+    Sections,
+    /// One list in sort order, locals and remotes mixed
+    Interleaved,
+}
+
+/// Order of the labels on a commit that several branches point to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelOrder {
+    /// HEAD branch, then other local branches, then remote branches (each by name)
+    #[default]
+    Head,
+    /// Local branches, then remote branches (each by name)
+    Name,
+}
+
+/// Sort the branch names of one commit for its labels (and the tab order through them)
+pub fn sort_branch_labels(
+    names: &mut [String],
+    head_branch: Option<&str>,
+    is_remote: impl Fn(&str) -> bool,
+    order: LabelOrder,
+) {
+    names.sort_by_cached_key(|name| {
+        let rank = if order == LabelOrder::Head && head_branch == Some(name.as_str()) {
+            0
+        } else if is_remote(name) {
+            2
+        } else {
+            1
+        };
+        (rank, name.clone())
+    });
+}
+
+/// Section of the branch list: "local" or the remote name
+pub fn branch_section(b: &BranchInfo) -> &str {
+    if b.is_remote {
+        b.name.split_once('/').map(|(r, _)| r).unwrap_or("")
+    } else {
+        "local"
+    }
+}
+
+/// Sort branches for display
+/// The HEAD branch stays first; with `Sections`, local branches come next, then remote
+/// branches grouped by remote.
+/// `ahead` maps tip commits to their ahead-of-main count (only used by `BranchSort::Ahead`).
+pub fn sort_branches(
+    branches: &mut [&BranchInfo],
+    sort: BranchSort,
+    grouping: BranchGrouping,
+    ahead: &HashMap<Oid, usize>,
+) {
+    fn group(b: &BranchInfo, grouping: BranchGrouping) -> (bool, bool, &str) {
+        match grouping {
+            BranchGrouping::Sections => (!b.is_head, b.is_remote, branch_section(b)),
+            BranchGrouping::Interleaved => (!b.is_head, false, ""),
+        }
+    }
+
+    branches.sort_by(|a, b| {
+        let key = match sort {
+            BranchSort::Name => std::cmp::Ordering::Equal,
+            BranchSort::Date => b.tip_time.cmp(&a.tip_time),
+            BranchSort::Ahead => {
+                let count = |x: &BranchInfo| ahead.get(&x.tip_oid).copied().unwrap_or(0);
+                count(b).cmp(&count(a))
+            }
+        };
+        group(a, grouping)
+            .cmp(&group(b, grouping))
+            .then(key)
+            .then_with(|| a.name.cmp(&b.name))
+    });
 }
 
 impl BranchInfo {
     pub fn list_all(repo: &Repository) -> Result<Vec<Self>> {
+        Self::list_with_head(repo, repo.head().ok().as_ref())
+    }
+
+    /// List branches, detecting the HEAD branch from an already-resolved HEAD reference
+    pub fn list_with_head(repo: &Repository, head: Option<&Reference>) -> Result<Vec<Self>> {
         let mut branches = Vec::new();
 
-        // Get HEAD
-        let head_oid = repo.head().ok().and_then(|r| r.target());
+        let head_oid = head.and_then(|r| r.target());
+        let head_shorthand = head.map(|h| h.shorthand_bytes().to_vec());
 
         // Local branches
         for branch_result in repo.branches(Some(BranchType::Local))? {
             let (branch, _) = branch_result?;
-            if let Some(name) = branch.name()? {
-                let reference = branch.get();
-                if let Some(oid) = reference.target() {
-                    let is_head = head_oid.map(|h| h == oid).unwrap_or(false)
-                        && repo
-                            .head()
-                            .ok()
-                            .and_then(|h| h.shorthand().map(|s| s == name))
-                            .unwrap_or(false);
-
-                    let upstream = branch
-                        .upstream()
-                        .ok()
-                        .and_then(|u| u.name().ok().flatten().map(|s| s.to_string()));
-
-                    branches.push(BranchInfo {
-                        name: name.to_string(),
-                        is_head,
-                        is_remote: false,
-                        upstream,
-                        tip_oid: oid,
-                    });
-                }
-            }
+            let name_bytes = branch.name_bytes()?.to_vec();
+            let Some(oid) = branch.get().target() else {
+                continue;
+            };
+            let is_head = head_oid.map(|h| h == oid).unwrap_or(false)
+                && head_shorthand.as_deref() == Some(name_bytes.as_slice());
+
+            let tip_time = tip_time(repo, oid);
+            let upstream = branch
+                .upstream()
+                .ok()
+                .and_then(|u| u.name_bytes().ok().map(ref_name_lossy));
+
+            branches.push(BranchInfo {
+                name: ref_name_lossy(&name_bytes),
+                is_head,
+                is_remote: false,
+                upstream,
+                tip_oid: oid,
+                tip_time,
+            });
         }
 
         // Remote branches
         for branch_result in repo.branches(Some(BranchType::Remote))? {
             let (branch, _) = branch_result?;
-            if let Some(name) = branch.name()? {
-                let reference = branch.get();
-                if let Some(oid) = reference.target() {
-                    branches.push(BranchInfo {
-                        name: name.to_string(),
-                        is_head: false,
-                        is_remote: true,
-                        upstream: None,
-                        tip_oid: oid,
-                    });
-                }
-            }
+            let Some(oid) = branch.get().target() else {
+                continue;
+            };
+            branches.push(BranchInfo {
+                name: ref_name_lossy(branch.name_bytes()?),
+                is_head: false,
+                is_remote: true,
+                upstream: None,
+                tip_oid: oid,
+                tip_time: tip_time(repo, oid),
+            });
         }
 
         // Put the HEAD branch first
@@ -71,3 +219,225 @@ impl BranchInfo {
         Ok(branches)
     }
 }
+
+/// A branch with its divergence from its upstream, as printed by `keifu branches`
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchReport {
+    #[serde(flatten)]
+    pub branch: BranchInfo,
+    /// Commits on the branch that the upstream lacks (None without a loaded upstream)
+    pub ahead: Option<usize>,
+    /// Commits on the upstream that the branch lacks
+    pub behind: Option<usize>,
+}
+
+impl BranchReport {
+    /// Column names of `tsv_line`, in order
+    pub const TSV_COLUMNS: [&'static str; 8] = [
+        "name",
+        "is_head",
+        "is_remote",
+        "upstream",
+        "tip_oid",
+        "ahead",
+        "behind",
+        "tip_time",
+    ];
+
+    /// Every branch (HEAD first), measured against its upstream
+    pub fn list(repo: &Repository) -> Result<Vec<Self>> {
+        let branches = BranchInfo::list_all(repo)?;
+        let tips: HashMap<String, Oid> = branches
+            .iter()
+            .map(|b| (b.name.clone(), b.tip_oid))
+            .collect();
+
+        branches
+            .into_iter()
+            .map(|branch| {
+                let upstream_tip = branch.upstream.as_ref().and_then(|u| tips.get(u));
+                let divergence = match upstream_tip {
+                    Some(&upstream) => Some(repo.graph_ahead_behind(branch.tip_oid, upstream)?),
+                    None => None,
+                };
+                Ok(Self {
+                    branch,
+                    ahead: divergence.map(|(ahead, _)| ahead),
+                    behind: divergence.map(|(_, behind)| behind),
+                })
+            })
+            .collect()
+    }
+
+    /// Tab-separated fields in `TSV_COLUMNS` order
+    /// Booleans are `true`/`false`, missing values are empty (ref names cannot contain tabs)
+    pub fn tsv_line(&self) -> String {
+        let optional = |count: Option<usize>| count.map(|c| c.to_string()).unwrap_or_default();
+        let branch = &self.branch;
+        [
+            branch.name.clone(),
+            branch.is_head.to_string(),
+            branch.is_remote.to_string(),
+            branch.upstream.clone().unwrap_or_default(),
+            branch.tip_oid.to_string(),
+            optional(self.ahead),
+            optional(self.behind),
+            branch.tip_time.to_string(),
+        ]
+        .join("\t")
+    }
+}
+
+/// Serialize an OID as its hex string
+fn serialize_oid<S: Serializer>(oid: &Oid, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(oid)
+}
+
+fn tip_time(repo: &Repository, oid: Oid) -> i64 {
+    repo.find_commit(oid)
+        .map(|c| c.time().seconds())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn branch(name: &str, is_head: bool, is_remote: bool, n: u8, tip_time: i64) -> BranchInfo {
+        BranchInfo {
+            name: name.to_string(),
+            is_head,
+            is_remote,
+            upstream: None,
+            tip_oid: Oid::from_bytes(&[n; 20]).unwrap(),
+            tip_time,
+        }
+    }
+
+    fn sorted(
+        branches: &[BranchInfo],
+        sort: BranchSort,
+        ahead: &HashMap<Oid, usize>,
+    ) -> Vec<String> {
+        let mut refs: Vec<&BranchInfo> = branches.iter().collect();
+        sort_branches(&mut refs, sort, BranchGrouping::Sections, ahead);
+        refs.iter().map(|b| b.name.clone()).collect()
+    }
+
+    #[test]
+    fn test_tsv_line_leaves_missing_values_empty() {
+        let mut report = BranchReport {
+            branch: branch("main", true, false, 1, 42),
+            ahead: None,
+            behind: None,
+        };
+        let oid = "01".repeat(20);
+        assert_eq!(
+            report.tsv_line(),
+            format!("main\ttrue\tfalse\t\t{}\t\t\t42", oid)
+        );
+
+        report.branch.upstream = Some("origin/main".to_string());
+        report.ahead = Some(0);
+        report.behind = Some(3);
+        let line = report.tsv_line();
+        assert_eq!(line.split('\t').count(), BranchReport::TSV_COLUMNS.len());
+        assert_eq!(
+            line,
+            format!("main\ttrue\tfalse\torigin/main\t{}\t0\t3\t42", oid)
+        );
+    }
+
+    #[test]
+    fn test_interleaved_grouping_mixes_locals_and_remotes() {
+        let branches = [
+            branch("origin/a", false, true, 1, 5),
+            branch("beta", false, false, 2, 100),
+            branch("main", true, false, 3, 0),
+            branch("origin/b", false, true, 4, 50),
+        ];
+        let mut refs: Vec<&BranchInfo> = branches.iter().collect();
+        sort_branches(
+            &mut refs,
+            BranchSort::Date,
+            BranchGrouping::Interleaved,
+            &HashMap::new(),
+        );
+        let names: Vec<&str> = refs.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, ["main", "beta", "origin/b", "origin/a"]);
+    }
+
+    #[test]
+    fn test_sort_branches_keeps_head_first_and_groups_remotes() {
+        let branches = vec![
+            branch("upstream/x", false, true, 1, 100),
+            branch("origin/a", false, true, 2, 5),
+            branch("origin/b", false, true, 3, 50),
+            branch("alpha", false, false, 4, 1),
+            branch("main", true, false, 5, 0),
+            branch("beta", false, false, 6, 100),
+            branch("gamma", false, false, 7, 50),
+        ];
+        let ahead = HashMap::from([(Oid::from_bytes(&[7; 20]).unwrap(), 3)]);
+
+        assert_eq!(
+            sorted(&branches, BranchSort::Name, &ahead),
+            [
+                "main",
+                "alpha",
+                "beta",
+                "gamma",
+                "origin/a",
+                "origin/b",
+                "upstream/x"
+            ]
+        );
+        assert_eq!(
+            sorted(&branches, BranchSort::Date, &ahead),
+            [
+                "main",
+                "beta",
+                "gamma",
+                "alpha",
+                "origin/b",
+                "origin/a",
+                "upstream/x"
+            ]
+        );
+        assert_eq!(
+            sorted(&branches, BranchSort::Ahead, &ahead),
+            [
+                "main",
+                "gamma",
+                "alpha",
+                "beta",
+                "origin/a",
+                "origin/b",
+                "upstream/x"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ref_name_lossy_valid_utf8() {
+        assert_eq!(ref_name_lossy(b"feature/x"), "feature/x");
+    }
+
+    #[test]
+    fn test_ref_name_lossy_invalid_utf8_has_marker() {
+        let name = ref_name_lossy(b"feature/\xff\xfe");
+        assert!(name.starts_with("feature/"));
+        assert!(name.ends_with(NON_UTF8_MARKER));
+    }
+
+    #[test]
+    fn test_branch_labels_put_head_then_locals_then_remotes() {
+        let mut names = ["origin/main", "zeta", "main", "alpha"].map(String::from);
+        let is_remote = |name: &str| name.starts_with("origin/");
+        sort_branch_labels(&mut names, Some("main"), is_remote, LabelOrder::Head);
+        assert_eq!(names, ["main", "alpha", "zeta", "origin/main"]);
+
+        sort_branch_labels(&mut names, Some("main"), is_remote, LabelOrder::Name);
+        assert_eq!(names, ["alpha", "main", "zeta", "origin/main"]);
+    }
+}
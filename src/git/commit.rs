@@ -1,7 +1,15 @@
 //! Commit info structure
 
+use std::path::Path;
+
 use chrono::{DateTime, Local, TimeZone};
-use git2::Oid;
+use git2::{ObjectType, Oid, Repository};
+use smallvec::SmallVec;
+
+use crate::error::{short_oid, Error, Result};
+
+/// Parent OIDs stored inline for the common 0-2 parent cases (octopus merges spill to the heap)
+pub type ParentOids = SmallVec<[Oid; 2]>;
 
 #[derive(Debug, Clone)]
 pub struct CommitInfo {
@@ -9,10 +17,13 @@ pub struct CommitInfo {
     pub short_id: String,
     pub author_name: String,
     pub author_email: String,
+    /// Committer time (shown in the graph)
     pub timestamp: DateTime<Local>,
+    /// Author time in Unix seconds (used by the date range filter)
+    pub author_time: i64,
     pub message: String,
     pub full_message: String,
-    pub parent_oids: Vec<Oid>,
+    pub parent_oids: ParentOids,
 }
 
 impl CommitInfo {
@@ -24,13 +35,14 @@ impl CommitInfo {
         let author_name = author.name().unwrap_or("Unknown").to_string();
         let author_email = author.email().unwrap_or("").to_string();
 
+        let author_time = author.when().seconds();
         let time = commit.time();
         let timestamp = Local.timestamp_opt(time.seconds(), 0).unwrap();
 
         let full_message = commit.message().unwrap_or("").to_string();
         let message = full_message.lines().next().unwrap_or("").to_string();
 
-        let parent_oids: Vec<Oid> = commit.parent_ids().collect();
+        let parent_oids: ParentOids = commit.parent_ids().collect();
 
         Self {
             oid,
@@ -38,9 +50,51 @@ impl CommitInfo {
             author_name,
             author_email,
             timestamp,
+            author_time,
             message,
             full_message,
             parent_oids,
         }
     }
 }
+
+/// The commit object as git stores it (like `git cat-file -p <oid>`)
+/// Headers (tree, parents, author, committer, signature) are followed by a blank line and the raw message
+pub fn raw_commit_object(repo: &Repository, oid: Oid) -> Result<String> {
+    let commit = repo
+        .find_commit(oid)
+        .map_err(|_| Error::CommitNotFound(short_oid(oid)))?;
+    let header = String::from_utf8_lossy(commit.raw_header_bytes());
+    let message = String::from_utf8_lossy(commit.message_raw_bytes());
+    Ok(format!("{}\n\n{}", header.trim_end_matches('\n'), message))
+}
+
+/// Content of the file at `path` (relative to the repository root) in the commit's tree
+pub fn file_at_commit(repo: &Repository, oid: Oid, path: &Path) -> Result<Vec<u8>> {
+    let commit = repo
+        .find_commit(oid)
+        .map_err(|_| Error::CommitNotFound(short_oid(oid)))?;
+    let blob = commit
+        .tree()?
+        .get_path(path)
+        .ok()
+        .filter(|entry| entry.kind() == Some(ObjectType::Blob))
+        .map(|entry| repo.find_blob(entry.id()))
+        .transpose()?;
+    match blob {
+        Some(blob) => Ok(blob.content().to_vec()),
+        None => {
+            let in_parent = commit
+                .parent(0)
+                .ok()
+                .and_then(|parent| parent.tree().ok()?.get_path(path).ok())
+                .is_some();
+            let (path, commit) = (path.to_path_buf(), short_oid(oid));
+            Err(if in_parent {
+                Error::FileDeleted { path, commit }
+            } else {
+                Error::FileNotInCommit { path, commit }
+            })
+        }
+    }
+}
@@ -1,13 +1,29 @@
 //! Commit diff information
 
-use std::path::PathBuf;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
-use git2::{Delta, Diff, DiffOptions, Oid, Repository};
+use git2::{
+    Delta, Diff, DiffFormat, DiffOptions, ErrorCode, FileMode, Oid, Patch, Repository, Tree,
+};
+
+use crate::error::{Error, Result};
 
 /// Maximum number of files to display
 const MAX_FILES_TO_DISPLAY: usize = 50;
 
+/// A symlink's content as git stores it: the path it points to
+/// Without symlink support (`core.symlinks = false`) the link is checked out as a file
+/// holding that path, which is read as is
+fn read_link_target(path: &Path) -> std::io::Result<Vec<u8>> {
+    match fs::read_link(path) {
+        Ok(target) => Ok(target.to_string_lossy().into_owned().into_bytes()),
+        Err(e) if e.kind() == ErrorKind::InvalidInput => fs::read(path),
+        Err(e) => Err(e),
+    }
+}
+
 /// File change kind
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileChangeKind {
@@ -29,6 +45,8 @@ pub struct FileDiffInfo {
     pub insertions: usize,
     /// Deletions
     pub deletions: usize,
+    /// Old and new file mode when the change altered it (e.g. 0o100644 → 0o100755)
+    pub mode_change: Option<(u32, u32)>,
 }
 
 /// Commit diff info
@@ -48,7 +66,10 @@ pub struct CommitDiffInfo {
 
 impl CommitDiffInfo {
     /// Get diff info for working tree (staged + unstaged changes)
-    pub fn from_working_tree(repo: &Repository) -> Result<Self> {
+    /// Unstaged changes go through the same filters as `git diff` (core.autocrlf, text/eol
+    /// attributes), so a file that only differs in line endings counts no changed lines.
+    /// With `raw`, the working tree bytes are compared as-is instead.
+    pub fn from_working_tree(repo: &Repository, raw: bool) -> Result<Self> {
         let head_tree = repo.head()?.peel_to_tree().ok();
 
         let mut opts = DiffOptions::new();
@@ -60,11 +81,14 @@ impl CommitDiffInfo {
         let staged_diff = repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))?;
 
         // Unstaged changes: index -> workdir
-        let unstaged_diff = repo.diff_index_to_workdir(None, Some(&mut opts))?;
+        let unstaged_result = if raw {
+            Self::unstaged_raw(repo)?
+        } else {
+            Self::from_diff(&repo.diff_index_to_workdir(None, Some(&mut opts))?)?
+        };
 
         // Merge both diffs
         let mut result = Self::from_diff(&staged_diff)?;
-        let unstaged_result = Self::from_diff(&unstaged_diff)?;
 
         // Merge unstaged files into result
         for file in unstaged_result.files {
@@ -82,6 +106,72 @@ impl CommitDiffInfo {
         Ok(result)
     }
 
+    /// Unstaged changes without checkout filters: each index blob against the file's bytes
+    /// Reads every tracked file, so it is only used when raw mode is requested
+    fn unstaged_raw(repo: &Repository) -> Result<Self> {
+        let workdir = repo.workdir().ok_or(Error::BareRepository)?;
+        let index = repo.index()?;
+
+        let mut opts = DiffOptions::new();
+        opts.context_lines(0);
+
+        let mut result = Self::default();
+        for entry in index.iter() {
+            // Skip conflict stages
+            if (entry.flags >> 12) & 0x3 != 0 {
+                continue;
+            }
+            // Submodules are repositories of their own, not files to compare
+            if entry.mode == u32::from(FileMode::Commit) {
+                continue;
+            }
+            let path = PathBuf::from(String::from_utf8_lossy(&entry.path).to_string());
+            let file = workdir.join(&path);
+            let read = if entry.mode == u32::from(FileMode::Link) {
+                read_link_target(&file)
+            } else {
+                fs::read(&file)
+            };
+            let (content, kind) = match read {
+                Ok(content) => (content, FileChangeKind::Modified),
+                Err(e) if e.kind() == ErrorKind::NotFound => (Vec::new(), FileChangeKind::Deleted),
+                Err(e) => return Err(e.into()),
+            };
+            let blob = repo.find_blob(entry.id)?;
+            if blob.content() == content.as_slice() {
+                continue;
+            }
+
+            result.total_files += 1;
+            let new_path: Option<&Path> = (kind != FileChangeKind::Deleted).then_some(&path);
+            let patch = Patch::from_blob_and_buffer(
+                &blob,
+                Some(&path),
+                &content,
+                new_path,
+                Some(&mut opts),
+            )?;
+            if patch.delta().flags().is_binary() {
+                continue;
+            }
+            let (_, insertions, deletions) = patch.line_stats()?;
+            result.total_insertions += insertions;
+            result.total_deletions += deletions;
+            if result.files.len() < MAX_FILES_TO_DISPLAY {
+                result.files.push(FileDiffInfo {
+                    path,
+                    kind,
+                    insertions,
+                    deletions,
+                    mode_change: None,
+                });
+            } else {
+                result.truncated = true;
+            }
+        }
+        Ok(result)
+    }
+
     /// Get diff info for a commit
     /// - Normal commit: diff vs parent
     /// - Merge commit: diff vs first parent
@@ -97,13 +187,24 @@ impl CommitDiffInfo {
             None
         };
 
+        Self::from_trees(repo, old_tree.as_ref(), &new_tree)
+    }
+
+    /// Get diff info from one commit's tree to another's (what `new` changes relative to `old`)
+    pub fn from_commits(repo: &Repository, old_oid: Oid, new_oid: Oid) -> Result<Self> {
+        let old_tree = repo.find_commit(old_oid)?.tree()?;
+        let new_tree = repo.find_commit(new_oid)?.tree()?;
+        Self::from_trees(repo, Some(&old_tree), &new_tree)
+    }
+
+    fn from_trees(repo: &Repository, old_tree: Option<&Tree>, new_tree: &Tree) -> Result<Self> {
         // Generate diff (performance options)
         let mut opts = DiffOptions::new();
         opts.minimal(false); // Skip minimal diff calculation
         opts.ignore_submodules(true); // Skip submodules
         opts.context_lines(0); // Set context lines to 0
 
-        let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), Some(&mut opts))?;
+        let diff = repo.diff_tree_to_tree(old_tree, Some(new_tree), Some(&mut opts))?;
 
         Self::from_diff(&diff)
     }
@@ -139,12 +240,20 @@ impl CommitDiffInfo {
                 delta.new_file().path()
             };
 
+            // Added and deleted files have no mode on one side
+            let (old_mode, new_mode) = (delta.old_file().mode(), delta.new_file().mode());
+            let mode_change = (old_mode != FileMode::Unreadable
+                && new_mode != FileMode::Unreadable
+                && old_mode != new_mode)
+                .then(|| (u32::from(old_mode), u32::from(new_mode)));
+
             if let Some(p) = path {
                 files.push(FileDiffInfo {
                     path: p.to_path_buf(),
                     kind,
                     insertions: 0,
                     deletions: 0,
+                    mode_change,
                 });
             }
         }
@@ -193,3 +302,62 @@ impl CommitDiffInfo {
         })
     }
 }
+
+/// Unified diff text of a commit, against the same parent as `CommitDiffInfo::from_commit`
+/// Binary files are shown as `[binary file]`
+pub fn commit_patch(repo: &Repository, commit_oid: Oid) -> Result<String> {
+    let commit = repo.find_commit(commit_oid)?;
+    let old_tree = if commit.parent_count() > 0 {
+        Some(commit.parent(0)?.tree()?)
+    } else {
+        None
+    };
+    let mut opts = DiffOptions::new();
+    opts.ignore_submodules(true);
+    let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&commit.tree()?), Some(&mut opts))?;
+
+    let mut patch = String::new();
+    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+        match line.origin() {
+            'B' => patch.push_str("[binary file]\n"),
+            origin @ ('+' | '-' | ' ') => {
+                patch.push(origin);
+                patch.push_str(&String::from_utf8_lossy(line.content()));
+            }
+            // File and hunk headers, "\ No newline at end of file"
+            _ => patch.push_str(&String::from_utf8_lossy(line.content())),
+        }
+        true
+    })?;
+    Ok(patch)
+}
+
+/// Two branch tips compared tree-to-tree
+#[derive(Debug, Clone)]
+pub struct BranchComparison {
+    /// Branch the diff starts from
+    pub from: String,
+    /// Branch the diff leads to
+    pub to: String,
+    pub diff: CommitDiffInfo,
+    /// Commits only on `to` and only on `from` (None when the branches share no history)
+    pub ahead_behind: Option<(usize, usize)>,
+}
+
+impl BranchComparison {
+    /// Compare the tips of `from` and `to`
+    pub fn new(repo: &Repository, from: (&str, Oid), to: (&str, Oid)) -> Result<Self> {
+        let diff = CommitDiffInfo::from_commits(repo, from.1, to.1)?;
+        let ahead_behind = match repo.merge_base(from.1, to.1) {
+            Ok(_) => Some(repo.graph_ahead_behind(to.1, from.1)?),
+            Err(e) if e.code() == ErrorCode::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            from: from.0.to_string(),
+            to: to.0.to_string(),
+            diff,
+            ahead_behind,
+        })
+    }
+}
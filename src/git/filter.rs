@@ -0,0 +1,281 @@
+//! View filters applied to the commit list before graph construction
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use git2::Oid;
+
+use super::{commit::ParentOids, graph::HiddenLinks, BranchInfo, CommitInfo};
+use crate::error::{Error, Result};
+
+/// Hides commits from the view
+///
+/// Links that pointed at a hidden commit are rewired to its first parent,
+/// so the first-parent line continues across the removed commits.
+pub struct CommitFilter {
+    /// Name shown on elision rows (e.g. "merge")
+    name: &'static str,
+    /// Hidden OID -> first parent OID (None for root commits)
+    hidden_first_parent: HashMap<Oid, Option<Oid>>,
+}
+
+impl CommitFilter {
+    /// Hide the commits matching `hide`
+    pub fn new(
+        commits: &[CommitInfo],
+        name: &'static str,
+        hide: impl Fn(&CommitInfo) -> bool,
+    ) -> Self {
+        let hidden_first_parent = commits
+            .iter()
+            .filter(|c| hide(c))
+            .map(|c| (c.oid, c.parent_oids.first().copied()))
+            .collect();
+        Self {
+            name,
+            hidden_first_parent,
+        }
+    }
+
+    /// Hide merge commits (`git log --no-merges`)
+    pub fn merges(commits: &[CommitInfo]) -> Self {
+        Self::new(commits, "merge", |c| c.parent_oids.len() >= 2)
+    }
+
+    /// Resolve an OID to the commit that is shown in its place
+    /// Follows first-parent links through hidden commits; None if nothing remains
+    pub fn resolve(&self, oid: Oid) -> Option<Oid> {
+        self.resolve_counting(oid).map(|(shown, _)| shown)
+    }
+
+    /// Like `resolve`, also returning how many hidden commits were skipped
+    fn resolve_counting(&self, oid: Oid) -> Option<(Oid, usize)> {
+        let mut current = oid;
+        // Bounded by the number of hidden commits (guards against malformed input)
+        for skipped in 0..=self.hidden_first_parent.len() {
+            match self.hidden_first_parent.get(&current) {
+                Some(Some(parent)) => current = *parent,
+                Some(None) => return None,
+                None => return Some((current, skipped)),
+            }
+        }
+        None
+    }
+
+    /// Remove hidden commits and rewire parent links through them
+    pub fn apply(&self, commits: &[CommitInfo]) -> Vec<CommitInfo> {
+        commits
+            .iter()
+            .filter(|c| !self.hidden_first_parent.contains_key(&c.oid))
+            .map(|c| {
+                let mut commit = c.clone();
+                let mut parents = ParentOids::new();
+                for parent in c.parent_oids.iter().filter_map(|p| self.resolve(*p)) {
+                    if !parents.contains(&parent) {
+                        parents.push(parent);
+                    }
+                }
+                commit.parent_oids = parents;
+                commit
+            })
+            .collect()
+    }
+
+    /// Rewired links of the shown commits that skip hidden commits
+    pub fn hidden_links(&self, commits: &[CommitInfo]) -> HiddenLinks {
+        let mut counts = HashMap::new();
+        for commit in commits
+            .iter()
+            .filter(|c| !self.hidden_first_parent.contains_key(&c.oid))
+        {
+            for parent in &commit.parent_oids {
+                if let Some((shown, skipped)) = self.resolve_counting(*parent) {
+                    if skipped > 0 {
+                        counts.entry((commit.oid, shown)).or_insert(skipped);
+                    }
+                }
+            }
+        }
+        HiddenLinks {
+            filter: self.name,
+            counts,
+        }
+    }
+
+    /// Move branch tips that point at hidden commits onto the commit shown in their place
+    pub fn apply_branches(&self, branches: &[BranchInfo]) -> Vec<BranchInfo> {
+        branches
+            .iter()
+            .filter_map(|b| {
+                let tip_oid = self.resolve(b.tip_oid)?;
+                Some(BranchInfo {
+                    tip_oid,
+                    ..b.clone()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Author date range (`git log --since/--until`), inclusive, in Unix seconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl DateRange {
+    /// Parse "SINCE..UNTIL" where either side may be empty; a single date means "since"
+    /// Dates are ISO ("2024-05-01", "2024-05-01 14:00") or relative ("2 weeks ago", "yesterday")
+    pub fn parse(input: &str, now: DateTime<Local>) -> Result<Self> {
+        let (since, until) = input.split_once("..").unwrap_or((input, ""));
+        let range = Self {
+            since: parse_date(since, now, false)?,
+            until: parse_date(until, now, true)?,
+        };
+        match (range.since, range.until) {
+            (Some(since), Some(until)) if since > until => Err(Error::ReversedDateRange),
+            _ => Ok(range),
+        }
+    }
+
+    /// Whether neither end is set (the filter shows everything)
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    pub fn contains(&self, time: i64) -> bool {
+        self.since.is_none_or(|since| time >= since) && self.until.is_none_or(|until| time <= until)
+    }
+
+    /// e.g. "2024-05-01..2024-05-07" or "2024-05-01.." (local dates)
+    pub fn label(&self) -> String {
+        let date = |time: Option<i64>| {
+            time.and_then(|t| Local.timestamp_opt(t, 0).single())
+                .map(|t| t.format("%Y-%m-%d").to_string())
+                .unwrap_or_default()
+        };
+        format!("{}..{}", date(self.since), date(self.until))
+    }
+}
+
+/// Parse one end of a date range (None when empty)
+/// `end_of_day` moves date-only values to the end of that day, so the range includes it
+fn parse_date(text: &str, now: DateTime<Local>, end_of_day: bool) -> Result<Option<i64>> {
+    let text = text.trim().to_lowercase();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let invalid = || Error::InvalidDate(text.clone());
+    let local = |naive: NaiveDateTime| {
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|t| Some(t.timestamp()))
+            .ok_or_else(invalid)
+    };
+    let day = |date: NaiveDate| {
+        let time = if end_of_day {
+            NaiveTime::from_hms_opt(23, 59, 59).unwrap_or(NaiveTime::MIN)
+        } else {
+            NaiveTime::MIN
+        };
+        local(date.and_time(time))
+    };
+
+    match text.as_str() {
+        "now" => return Ok(Some(now.timestamp())),
+        "today" => return day(now.date_naive()),
+        "yesterday" => return day(now.date_naive() - Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(&text) {
+        return Ok(Some(time.timestamp()));
+    }
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dt%H:%M:%S",
+        "%Y-%m-%dt%H:%M",
+    ] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(&text, format) {
+            return local(naive);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+        return day(date);
+    }
+
+    // Relative: "<n> <unit>[s] ago"
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let [count, unit, "ago"] = words.as_slice() else {
+        return Err(invalid());
+    };
+    let count: i64 = count.parse().map_err(|_| invalid())?;
+    let unit_seconds = match unit.trim_end_matches('s') {
+        "second" | "sec" => 1,
+        "minute" | "min" => 60,
+        "hour" => 60 * 60,
+        "day" => 24 * 60 * 60,
+        "week" => 7 * 24 * 60 * 60,
+        "month" => 30 * 24 * 60 * 60,
+        "year" => 365 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    Ok(Some(now.timestamp() - count * unit_seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 15, 12, 0, 0).unwrap()
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32, s: u32) -> Option<i64> {
+        Some(
+            Local
+                .with_ymd_and_hms(y, m, d, h, min, s)
+                .unwrap()
+                .timestamp(),
+        )
+    }
+
+    #[test]
+    fn test_parse_iso_date_range_includes_whole_days() {
+        let range = DateRange::parse("2024-05-01..2024-05-07", now()).unwrap();
+        assert_eq!(range.since, at(2024, 5, 1, 0, 0, 0));
+        assert_eq!(range.until, at(2024, 5, 7, 23, 59, 59));
+        assert_eq!(range.label(), "2024-05-01..2024-05-07");
+
+        let range = DateRange::parse("..2024-05-07 08:30", now()).unwrap();
+        assert_eq!(range.since, None);
+        assert_eq!(range.until, at(2024, 5, 7, 8, 30, 0));
+    }
+
+    #[test]
+    fn test_parse_relative_dates() {
+        let range = DateRange::parse("2 weeks ago", now()).unwrap();
+        assert_eq!(range.since, at(2024, 5, 1, 12, 0, 0));
+        assert_eq!(range.until, None);
+        assert!(range.contains(now().timestamp()));
+
+        let range = DateRange::parse("yesterday..today", now()).unwrap();
+        assert_eq!(range.since, at(2024, 5, 14, 0, 0, 0));
+        assert_eq!(range.until, at(2024, 5, 15, 23, 59, 59));
+        assert!(DateRange::parse("", now()).unwrap().is_unbounded());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_dates() {
+        assert!(matches!(
+            DateRange::parse("last tuesday", now()),
+            Err(Error::InvalidDate(_))
+        ));
+        assert!(matches!(
+            DateRange::parse("2024-05-07..2024-05-01", now()),
+            Err(Error::ReversedDateRange)
+        ));
+    }
+}
@@ -1,11 +1,16 @@
 //! Commit graph construction
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 
 use git2::Oid;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
 
-use super::{BranchInfo, CommitInfo};
-use crate::graph::colors::{ColorAssigner, UNCOMMITTED_COLOR_INDEX};
+use super::{commit::ParentOids, BranchInfo, CommitInfo};
+use crate::graph::colors::{ColorAssigner, ColorScheme, UNCOMMITTED_COLOR_INDEX};
 
 /// Graph node
 #[derive(Debug, Clone)]
@@ -26,6 +31,70 @@ pub struct GraphNode {
     pub uncommitted_count: usize,
     /// Render info for this row
     pub cells: Vec<CellType>,
+    /// For connector rows: index of the commit row the connector belongs to
+    /// (None for commit and uncommitted rows)
+    pub owner_row: Option<usize>,
+    /// For elision rows: commits hidden by a view filter on this lane
+    pub elided: Option<ElidedSpan>,
+    /// For commit rows of half-row layouts: the fork connector, drawn in the upper
+    /// half of this row instead of on a row of its own
+    pub upper_cells: Option<Vec<CellType>>,
+}
+
+impl GraphNode {
+    /// Whether this is a connector-only row
+    pub fn is_connector(&self) -> bool {
+        self.commit.is_none() && !self.is_uncommitted && self.elided.is_none()
+    }
+}
+
+/// Commits hidden by a view filter between a shown commit and one of its parents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElidedSpan {
+    pub count: usize,
+    /// Filter that hid them (e.g. "merge")
+    pub filter: &'static str,
+}
+
+impl ElidedSpan {
+    /// e.g. "14 commits hidden (merge filter)"
+    pub fn describe(&self) -> String {
+        let noun = if self.count == 1 { "commit" } else { "commits" };
+        format!("{} {} hidden ({} filter)", self.count, noun, self.filter)
+    }
+}
+
+/// How many standalone connector rows the graph uses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphDensity {
+    /// A connector row above every fork point
+    Spacious,
+    /// Connector rows only above merge commits; other connectors go on the fork point's row
+    #[default]
+    Normal,
+    /// No connector rows (overlapping glyphs are combined)
+    Compact,
+}
+
+/// Options for `build_graph_with_options`
+#[derive(Debug, Clone, Default)]
+pub struct LayoutOptions {
+    pub density: GraphDensity,
+    pub colors: ColorScheme,
+    pub hidden: HiddenLinks,
+    /// Put connectors that would take a row of their own into the fork point's
+    /// `upper_cells` (for glyph sets that can draw half-height lines)
+    pub half_rows: bool,
+}
+
+/// Parent links that cross commits hidden by a view filter
+#[derive(Debug, Clone, Default)]
+pub struct HiddenLinks {
+    /// Filter name shown on elision rows
+    pub filter: &'static str,
+    /// (child, shown parent) -> number of hidden commits between them
+    pub counts: HashMap<(Oid, Oid), usize>,
 }
 
 /// Cell types
@@ -45,26 +114,78 @@ pub enum CellType {
     MergeRight(usize),
     /// Merge from the left ╯ (branch joins from down-left)
     MergeLeft(usize),
-    /// Horizontal line
+    /// Horizontal line ─
     Horizontal(usize),
-    /// Horizontal line (lane crossing)
+    /// Horizontal line crossing an unrelated active lane ┼ (drawn in the lane's color)
     HorizontalPipe(usize, usize), // (horizontal_lane, pipe_lane)
-    /// T junction to the right ├
+    /// T junction to the right ├ (also the fork point's lane on fork connector rows)
     TeeRight(usize),
     /// T junction to the left ┤
     TeeLeft(usize),
-    /// Upward T junction (fork point) ┴
+    /// Upward T junction ┴: a fork lane that joins while the horizontal continues to
+    /// further fork lanes on the right (the rightmost fork lane uses ╯ instead)
     TeeUp(usize),
+    /// Downward T junction ┬: a lane that starts below a horizontal passing through
+    /// (only where compact density folds a fork connector into a merge commit's row)
+    TeeDown(usize),
 }
 
 /// Graph layout
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct GraphLayout {
     pub nodes: Vec<GraphNode>,
     pub max_lane: usize,
+    /// Branches whose tip is outside the loaded commits (no row to label), sorted by name
+    pub offscreen_branches: Vec<String>,
+    /// Loaded children of each commit, newest first
+    pub children: FxHashMap<Oid, SmallVec<[Oid; 2]>>,
+    /// Row of each shown commit
+    rows: FxHashMap<Oid, usize>,
+}
+
+impl GraphLayout {
+    /// Loaded children of a commit (children outside the loaded window are unknown)
+    pub fn children_of(&self, oid: Oid) -> &[Oid] {
+        self.children.get(&oid).map(|c| c.as_slice()).unwrap_or(&[])
+    }
+
+    /// Row of the given commit, if it is shown
+    pub fn row_of(&self, oid: Oid) -> Option<usize> {
+        self.rows.get(&oid).copied()
+    }
+
+    /// Re-attach branch labels to the existing rows after refs changed
+    /// The commits must be the ones the layout was built from; lanes are kept as they are
+    pub fn attach_branches(&mut self, branches: &[BranchInfo]) {
+        let mut by_tip: FxHashMap<Oid, Vec<&BranchInfo>> = FxHashMap::default();
+        for branch in branches {
+            by_tip.entry(branch.tip_oid).or_default().push(branch);
+        }
+        let mut shown = FxHashSet::default();
+        for node in &mut self.nodes {
+            node.branch_names.clear();
+            node.is_head = false;
+            let Some(tips) = node.commit.as_ref().and_then(|c| by_tip.get(&c.oid)) else {
+                continue;
+            };
+            for branch in tips {
+                node.branch_names.push(branch.name.clone());
+                node.is_head |= branch.is_head;
+                shown.insert(branch.name.as_str());
+            }
+        }
+        self.offscreen_branches = branches
+            .iter()
+            .filter(|b| !shown.contains(b.name.as_str()))
+            .map(|b| b.name.clone())
+            .collect();
+        self.offscreen_branches.sort();
+    }
 }
 
-/// Build a graph from commit list
+/// Build a graph from commit list (default density, no hidden links)
+/// Children should be listed before their parents; other orders are reordered first
+/// Timestamps are not consulted
 /// uncommitted_count: Number of uncommitted files (None if no uncommitted changes)
 /// head_commit_oid: The OID of the commit that HEAD points to (for uncommitted changes)
 pub fn build_graph(
@@ -73,15 +194,45 @@ pub fn build_graph(
     uncommitted_count: Option<usize>,
     head_commit_oid: Option<Oid>,
 ) -> GraphLayout {
+    build_graph_with_options(
+        commits,
+        branches,
+        uncommitted_count,
+        head_commit_oid,
+        &LayoutOptions::default(),
+    )
+}
+
+/// Build a graph with a connector density and, for filtered commit lists, hidden links
+/// An elision row is inserted below each commit whose parent link crosses hidden commits
+pub fn build_graph_with_options(
+    commits: &[CommitInfo],
+    branches: &[BranchInfo],
+    uncommitted_count: Option<usize>,
+    head_commit_oid: Option<Oid>,
+    options: &LayoutOptions,
+) -> GraphLayout {
+    let hidden = &options.hidden;
+    // Lanes are assigned top-down, so every child has to come before its parents
+    let commits: Cow<[CommitInfo]> = if is_topologically_ordered(commits) {
+        Cow::Borrowed(commits)
+    } else {
+        Cow::Owned(topological_order(commits))
+    };
+    let commits = commits.as_ref();
     if commits.is_empty() {
         return GraphLayout {
             nodes: Vec::new(),
             max_lane: 0,
+            offscreen_branches: branches.iter().map(|b| b.name.clone()).collect(),
+            children: FxHashMap::default(),
+            rows: FxHashMap::default(),
         };
     }
 
     // OID -> branch name mapping
-    let mut oid_to_branches: HashMap<Oid, Vec<String>> = HashMap::new();
+    let mut oid_to_branches: FxHashMap<Oid, Vec<String>> =
+        FxHashMap::with_capacity_and_hasher(branches.len(), Default::default());
     let mut head_oid: Option<Oid> = None;
     for branch in branches {
         oid_to_branches
@@ -94,17 +245,24 @@ pub fn build_graph(
     }
 
     // OID -> row index mapping
-    let oid_to_row: HashMap<Oid, usize> = commits
+    let mut oid_to_row: FxHashMap<Oid, usize> =
+        FxHashMap::with_capacity_and_hasher(commits.len(), Default::default());
+    oid_to_row.extend(commits.iter().enumerate().map(|(i, c)| (c.oid, i)));
+
+    // Branch labels that cannot be attached to any row
+    let mut offscreen_branches: Vec<String> = branches
         .iter()
-        .enumerate()
-        .map(|(i, c)| (c.oid, i))
+        .filter(|b| !oid_to_row.contains_key(&b.tip_oid))
+        .map(|b| b.name.clone())
         .collect();
+    offscreen_branches.sort();
 
     // Detect fork points (commits with multiple children)
     // parent_oid -> list of child commits
     // Check ALL parents, not just first parent, to detect fork points like
     // hotfix branches that are merged into multiple release branches
-    let mut parent_children: HashMap<Oid, Vec<Oid>> = HashMap::new();
+    let mut parent_children: FxHashMap<Oid, SmallVec<[Oid; 2]>> =
+        FxHashMap::with_capacity_and_hasher(commits.len(), Default::default());
     for commit in commits {
         for parent_oid in &commit.parent_oids {
             if oid_to_row.contains_key(parent_oid) {
@@ -116,7 +274,7 @@ pub fn build_graph(
         }
     }
     // Fork points: commits with 2+ children
-    let fork_points: std::collections::HashSet<Oid> = parent_children
+    let fork_points: FxHashSet<Oid> = parent_children
         .iter()
         .filter(|(_, children)| children.len() >= 2)
         .map(|(parent, _)| *parent)
@@ -128,13 +286,14 @@ pub fn build_graph(
     let mut max_lane: usize = 0;
 
     // Color management
-    let mut color_assigner = ColorAssigner::new();
+    let mut color_assigner = ColorAssigner::with_scheme(options.colors);
     // OID -> color index mapping
-    let mut oid_color_index: HashMap<Oid, usize> = HashMap::new();
+    let mut oid_color_index: FxHashMap<Oid, usize> =
+        FxHashMap::with_capacity_and_hasher(commits.len(), Default::default());
     // Lane -> color index mapping (keep colors during forks)
-    let mut lane_color_index: HashMap<usize, usize> = HashMap::new();
+    let mut lane_color_index: FxHashMap<usize, usize> = FxHashMap::default();
 
-    for commit in commits {
+    for (row, commit) in commits.iter().enumerate() {
         // Start processing a new row
         color_assigner.advance_row();
 
@@ -166,6 +325,7 @@ pub fn build_graph(
             .map(|(i, _)| i)
             .collect();
 
+        let mut fork_connector: Option<(usize, usize, Vec<CellType>)> = None;
         if fork_lanes.len() >= 2 {
             // Use the smallest lane as main
             let main_lane = *fork_lanes.iter().min().unwrap();
@@ -203,16 +363,8 @@ pub fn build_graph(
                 &lane_color_index,
                 max_lane,
             );
-            nodes.push(GraphNode {
-                commit: None,
-                lane: main_lane,
-                color_index: main_color,
-                branch_names: Vec::new(),
-                is_head: false,
-                is_uncommitted: false,
-                uncommitted_count: 0,
-                cells: fork_connector_cells,
-            });
+            // Emitted (or folded into the commit row) once the commit row is built
+            fork_connector = Some((main_lane, main_color, fork_connector_cells));
 
             // Release merging lanes
             for &(l, _) in &merging_lanes {
@@ -247,7 +399,7 @@ pub fn build_graph(
         // Process parent commits
         // (OID, lane, already tracked?, color index, already shown?)
         let mut parent_lanes: Vec<(Oid, usize, bool, usize, bool)> = Vec::new();
-        let valid_parents: Vec<Oid> = commit
+        let valid_parents: ParentOids = commit
             .parent_oids
             .iter()
             .filter(|oid| oid_to_row.contains_key(oid))
@@ -270,10 +422,8 @@ pub fn build_graph(
                 .iter()
                 .position(|l| l.map(|oid| oid == *parent_oid).unwrap_or(false));
 
-            // Check if parent commit has already been shown
-            let parent_already_shown = nodes
-                .iter()
-                .any(|n| n.commit.as_ref().map(|c| c.oid) == Some(*parent_oid));
+            // Check if parent commit has already been shown (rows above this one)
+            let parent_already_shown = oid_to_row.get(parent_oid).is_some_and(|&r| r < row);
 
             let (parent_lane, was_existing, parent_color) = if let Some(pl) = existing_parent_lane {
                 // If parent is a fork point, treat as fork sibling
@@ -371,6 +521,40 @@ pub fn build_graph(
 
         let is_head = head_oid.map(|h| h == commit.oid).unwrap_or(false);
 
+        // Fork connector: fold it into the commit row or emit it as its own row
+        let mut upper_cells = None;
+        let cells = match fork_connector {
+            Some((main_lane, main_color, connector)) => {
+                let is_merge = valid_parents.len() >= 2;
+                match fold_connector(options.density, is_merge, &cells, &connector) {
+                    Some(folded) => folded,
+                    None if options.half_rows => {
+                        upper_cells = Some(connector);
+                        cells
+                    }
+                    None => {
+                        // The fork point commit row is pushed right after this connector
+                        let owner_row = nodes.len() + 1;
+                        nodes.push(GraphNode {
+                            commit: None,
+                            lane: main_lane,
+                            color_index: main_color,
+                            branch_names: Vec::new(),
+                            is_head: false,
+                            is_uncommitted: false,
+                            uncommitted_count: 0,
+                            cells: connector,
+                            owner_row: Some(owner_row),
+                            elided: None,
+                            upper_cells: None,
+                        });
+                        cells
+                    }
+                }
+            }
+            None => cells,
+        };
+
         // Add commit row
         nodes.push(GraphNode {
             commit: Some(commit.clone()),
@@ -381,6 +565,9 @@ pub fn build_graph(
             is_uncommitted: false,
             uncommitted_count: 0,
             cells,
+            owner_row: None,
+            elided: None,
+            upper_cells,
         });
 
         // Handle lane merging: when a parent is already tracked on a different lane
@@ -395,11 +582,7 @@ pub fn build_graph(
             // Check if the ending lane is tracking a commit that hasn't been shown yet
             let ending_lane_oid = lanes.get(ending_lane).and_then(|o| *o);
             let ending_oid_already_shown = ending_lane_oid
-                .map(|oid| {
-                    nodes
-                        .iter()
-                        .any(|n| n.commit.as_ref().map(|c| c.oid) == Some(oid))
-                })
+                .map(|oid| oid_to_row.get(&oid).is_some_and(|&r| r <= row))
                 .unwrap_or(true);
 
             let continues_down = !ending_oid_already_shown;
@@ -426,6 +609,38 @@ pub fn build_graph(
                 }
             }
         }
+
+        // Elision rows for parent links that cross hidden commits
+        for &(parent_oid, parent_lane, _, parent_color, _) in &parent_lanes {
+            let Some(&count) = hidden.counts.get(&(commit.oid, parent_oid)) else {
+                continue;
+            };
+            // The parent may have moved to another lane during lane merging
+            let elided_lane = lanes
+                .iter()
+                .position(|l| *l == Some(parent_oid))
+                .unwrap_or(parent_lane);
+            let color = lane_color_index
+                .get(&elided_lane)
+                .copied()
+                .unwrap_or(parent_color);
+            nodes.push(GraphNode {
+                commit: None,
+                lane: elided_lane,
+                color_index: color,
+                branch_names: Vec::new(),
+                is_head: false,
+                is_uncommitted: false,
+                uncommitted_count: 0,
+                cells: build_elision_cells(&lanes, &lane_color_index, max_lane),
+                owner_row: None,
+                elided: Some(ElidedSpan {
+                    count,
+                    filter: hidden.filter,
+                }),
+                upper_cells: None,
+            });
+        }
     }
 
     // Insert uncommitted changes node at the beginning if there are uncommitted changes
@@ -531,7 +746,12 @@ pub fn build_graph(
             let mut cells = vec![CellType::Empty; required_cells];
             cells[uncommitted_lane * 2] = CellType::Commit(UNCOMMITTED_COLOR_INDEX);
 
-            // Insert uncommitted node at the beginning
+            // Insert uncommitted node at the beginning (shifting connector owners down)
+            for node in nodes.iter_mut() {
+                if let Some(row) = node.owner_row.as_mut() {
+                    *row += 1;
+                }
+            }
             nodes.insert(
                 0,
                 GraphNode {
@@ -543,12 +763,207 @@ pub fn build_graph(
                     is_uncommitted: true,
                     uncommitted_count: count,
                     cells,
+                    owner_row: None,
+                    elided: None,
+                    upper_cells: None,
                 },
             );
         }
     }
 
-    GraphLayout { nodes, max_lane }
+    let rows = nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(row, node)| Some((node.commit.as_ref()?.oid, row)))
+        .collect();
+    GraphLayout {
+        nodes,
+        max_lane,
+        offscreen_branches,
+        children: parent_children,
+        rows,
+    }
+}
+
+/// Whether every commit comes before its parents that are in the list
+fn is_topologically_ordered(commits: &[CommitInfo]) -> bool {
+    let mut seen: FxHashSet<Oid> =
+        FxHashSet::with_capacity_and_hasher(commits.len(), Default::default());
+    for commit in commits {
+        // A parent listed earlier means the order is violated
+        if commit.parent_oids.iter().any(|p| seen.contains(p)) {
+            return false;
+        }
+        seen.insert(commit.oid);
+    }
+    true
+}
+
+/// Reorder so children precede parents, otherwise keeping the input order
+fn topological_order(commits: &[CommitInfo]) -> Vec<CommitInfo> {
+    let index: FxHashMap<Oid, usize> = commits
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.oid, i))
+        .collect();
+    // Number of listed children that still have to be emitted
+    let mut pending_children = vec![0usize; commits.len()];
+    for commit in commits {
+        for parent in &commit.parent_oids {
+            if let Some(&i) = index.get(parent) {
+                pending_children[i] += 1;
+            }
+        }
+    }
+
+    // Earliest input position first among the commits that are ready
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..commits.len())
+        .filter(|&i| pending_children[i] == 0)
+        .map(Reverse)
+        .collect();
+    let mut emitted = vec![false; commits.len()];
+    let mut ordered = Vec::with_capacity(commits.len());
+    while let Some(Reverse(i)) = ready.pop() {
+        emitted[i] = true;
+        ordered.push(commits[i].clone());
+        for parent in &commits[i].parent_oids {
+            if let Some(&p) = index.get(parent) {
+                pending_children[p] -= 1;
+                if pending_children[p] == 0 {
+                    ready.push(Reverse(p));
+                }
+            }
+        }
+    }
+    // Only malformed input (cycles, duplicate OIDs) leaves commits behind
+    ordered.extend(
+        commits
+            .iter()
+            .zip(&emitted)
+            .filter(|(_, done)| !**done)
+            .map(|(c, _)| c.clone()),
+    );
+    ordered
+}
+
+/// Draw a fork connector on the fork point's own row
+/// Returns None when the connector should stay on its own row for this density
+fn fold_connector(
+    density: GraphDensity,
+    is_merge: bool,
+    row: &[CellType],
+    connector: &[CellType],
+) -> Option<Vec<CellType>> {
+    match density {
+        GraphDensity::Spacious => return None,
+        GraphDensity::Normal if is_merge => return None,
+        _ => {}
+    }
+    let mut folded = row.to_vec();
+    if folded.len() < connector.len() {
+        folded.resize(connector.len(), CellType::Empty);
+    }
+
+    for (idx, &cell) in connector.iter().enumerate() {
+        folded[idx] = match (folded[idx], cell) {
+            // Pipes are already on the row; the T junction sits under the commit node
+            (_, CellType::Empty | CellType::Pipe(_) | CellType::TeeRight(_)) => continue,
+            (CellType::Empty, cell) => cell,
+            (CellType::Pipe(p), CellType::Horizontal(h) | CellType::HorizontalPipe(h, _)) => {
+                CellType::HorizontalPipe(h, p)
+            }
+            (existing, cell) => combine_cells(existing, cell),
+        };
+    }
+    Some(folded)
+}
+
+/// Single glyph for a commit-row cell that a folded connector also passes through
+/// The glyph gets the arms of both; lines that continue down keep the row's color
+fn combine_cells(existing: CellType, connector: CellType) -> CellType {
+    let (Some(row_arms), Some(connector_arms)) = (arms(existing), arms(connector)) else {
+        return existing;
+    };
+    let row_color = color_of(existing);
+    let connector_color = color_of(connector);
+    let combined = row_arms | connector_arms;
+    let color = if combined & DOWN != 0 {
+        row_color
+    } else {
+        connector_color
+    };
+    match combined {
+        c if c == UP | DOWN | LEFT | RIGHT => CellType::HorizontalPipe(connector_color, row_color),
+        c if c == UP | DOWN | LEFT => CellType::TeeLeft(color),
+        c if c == UP | DOWN | RIGHT => CellType::TeeRight(color),
+        c if c == UP | LEFT | RIGHT => CellType::TeeUp(color),
+        c if c == DOWN | LEFT | RIGHT => CellType::TeeDown(color),
+        c if c == UP | LEFT => CellType::MergeLeft(color),
+        c if c == DOWN | LEFT => CellType::BranchLeft(color),
+        c if c == UP | RIGHT => CellType::MergeRight(color),
+        c if c == DOWN | RIGHT => CellType::BranchRight(color),
+        c if c == LEFT | RIGHT => CellType::Horizontal(color),
+        c if c == UP | DOWN => CellType::Pipe(color),
+        _ => existing,
+    }
+}
+
+pub(crate) const UP: u8 = 1;
+pub(crate) const DOWN: u8 = 2;
+pub(crate) const LEFT: u8 = 4;
+pub(crate) const RIGHT: u8 = 8;
+
+/// Directions a line glyph reaches out to (None for empty cells and commit nodes)
+pub(crate) fn arms(cell: CellType) -> Option<u8> {
+    Some(match cell {
+        CellType::Empty | CellType::Commit(_) => return None,
+        CellType::Pipe(_) => UP | DOWN,
+        CellType::BranchRight(_) => DOWN | RIGHT,
+        CellType::BranchLeft(_) => DOWN | LEFT,
+        CellType::MergeRight(_) => UP | RIGHT,
+        CellType::MergeLeft(_) => UP | LEFT,
+        CellType::Horizontal(_) => LEFT | RIGHT,
+        CellType::HorizontalPipe(..) => UP | DOWN | LEFT | RIGHT,
+        CellType::TeeRight(_) => UP | DOWN | RIGHT,
+        CellType::TeeLeft(_) => UP | DOWN | LEFT,
+        CellType::TeeUp(_) => UP | LEFT | RIGHT,
+        CellType::TeeDown(_) => DOWN | LEFT | RIGHT,
+    })
+}
+
+/// Color a glyph is drawn in (the vertical lane's color for crossings)
+fn color_of(cell: CellType) -> usize {
+    match cell {
+        CellType::Empty => 0,
+        CellType::HorizontalPipe(_, c)
+        | CellType::Pipe(c)
+        | CellType::Commit(c)
+        | CellType::BranchRight(c)
+        | CellType::BranchLeft(c)
+        | CellType::MergeRight(c)
+        | CellType::MergeLeft(c)
+        | CellType::Horizontal(c)
+        | CellType::TeeRight(c)
+        | CellType::TeeLeft(c)
+        | CellType::TeeUp(c)
+        | CellType::TeeDown(c) => c,
+    }
+}
+
+/// Build cells for an elision row (a pipe on every active lane)
+fn build_elision_cells(
+    active_lanes: &[Option<Oid>],
+    lane_color_index: &FxHashMap<usize, usize>,
+    max_lane: usize,
+) -> Vec<CellType> {
+    let mut cells = vec![CellType::Empty; (max_lane + 1) * 2];
+    for (lane_idx, lane_oid) in active_lanes.iter().enumerate() {
+        if lane_oid.is_some() && lane_idx * 2 < cells.len() {
+            let color = lane_color_index.get(&lane_idx).copied().unwrap_or(lane_idx);
+            cells[lane_idx * 2] = CellType::Pipe(color);
+        }
+    }
+    cells
 }
 
 /// Build cells for one row - color index version
@@ -558,8 +973,8 @@ fn build_row_cells_with_colors(
     commit_color: usize,
     parent_lanes: &[(Oid, usize, bool, usize, bool)],
     active_lanes: &[Option<Oid>],
-    oid_color_index: &HashMap<Oid, usize>,
-    lane_color_index: &HashMap<usize, usize>,
+    oid_color_index: &FxHashMap<Oid, usize>,
+    lane_color_index: &FxHashMap<usize, usize>,
     max_lane: usize,
 ) -> Vec<CellType> {
     let mut cells = vec![CellType::Empty; (max_lane + 1) * 2];
@@ -603,12 +1018,7 @@ fn build_row_cells_with_colors(
             // Horizontal line to the right from the commit position
             for col in (commit_lane * 2 + 1)..(parent_lane * 2) {
                 if col < cells.len() {
-                    let existing = cells[col];
-                    if let CellType::Pipe(pl) = existing {
-                        cells[col] = CellType::HorizontalPipe(parent_color, pl);
-                    } else if existing == CellType::Empty {
-                        cells[col] = CellType::Horizontal(parent_color);
-                    }
+                    cells[col] = extend_horizontal(cells[col], parent_color);
                 }
             }
             // End marker
@@ -631,12 +1041,7 @@ fn build_row_cells_with_colors(
             // Use the parent's color for the connection line
             for col in (parent_lane * 2 + 1)..(commit_lane * 2) {
                 if col < cells.len() {
-                    let existing = cells[col];
-                    if let CellType::Pipe(pl) = existing {
-                        cells[col] = CellType::HorizontalPipe(parent_color, pl);
-                    } else if existing == CellType::Empty {
-                        cells[col] = CellType::Horizontal(parent_color);
-                    }
+                    cells[col] = extend_horizontal(cells[col], parent_color);
                 }
             }
             // Start marker
@@ -659,6 +1064,19 @@ fn build_row_cells_with_colors(
     cells
 }
 
+/// A cell that a parent's horizontal line also passes through
+/// (an octopus merge's line continues past the lanes of its nearer parents)
+fn extend_horizontal(existing: CellType, color: usize) -> CellType {
+    match existing {
+        CellType::Empty => CellType::Horizontal(color),
+        CellType::Pipe(pl) => CellType::HorizontalPipe(color, pl),
+        CellType::BranchLeft(c) | CellType::BranchRight(c) => CellType::TeeDown(c),
+        CellType::MergeLeft(c) | CellType::MergeRight(c) => CellType::TeeUp(c),
+        CellType::TeeLeft(c) | CellType::TeeRight(c) => CellType::HorizontalPipe(color, c),
+        other => other,
+    }
+}
+
 /// Build fork connector row cells (multiple branches from the same parent)
 /// Example: ├─┴─╯ (main lane connecting to multiple branch lanes)
 fn build_fork_connector_cells(
@@ -666,8 +1084,8 @@ fn build_fork_connector_cells(
     main_color: usize,
     merging_lanes: &[(usize, usize)], // (lane, color_index)
     active_lanes: &[Option<Oid>],
-    oid_color_index: &HashMap<Oid, usize>,
-    lane_color_index: &HashMap<usize, usize>,
+    oid_color_index: &FxHashMap<Oid, usize>,
+    lane_color_index: &FxHashMap<usize, usize>,
     max_lane: usize,
 ) -> Vec<CellType> {
     let mut cells = vec![CellType::Empty; (max_lane + 1) * 2];
@@ -702,30 +1120,28 @@ fn build_fork_connector_cells(
     // Rightmost merging lane
     let rightmost_lane = *merging_lane_nums.last().unwrap_or(&main_lane);
 
-    // Draw connectors to merging lanes
-    for &(merge_lane, merge_color) in merging_lanes {
-        // Horizontal line from main lane to merging lane
-        for col in (main_lane * 2 + 1)..(merge_lane * 2) {
-            if col < cells.len() {
-                let existing = cells[col];
-                if let CellType::Pipe(pl) = existing {
-                    cells[col] = CellType::HorizontalPipe(merge_color, pl);
-                } else if matches!(existing, CellType::Empty | CellType::Horizontal(_)) {
-                    cells[col] = CellType::Horizontal(merge_color);
-                }
-            }
+    // Draw connectors left to right, one segment per merging lane, so every cell gets a
+    // single glyph: ─ (or ┼ over an unrelated lane) in the color of the lane the segment
+    // leads to, then ┴ on middle lanes and ╯ on the rightmost one
+    let mut sorted_lanes: Vec<(usize, usize)> = merging_lanes.to_vec();
+    sorted_lanes.sort_unstable();
+    let mut segment_start = main_lane * 2 + 1;
+    for (merge_lane, merge_color) in sorted_lanes {
+        let end_idx = merge_lane * 2;
+        for col in segment_start..end_idx.min(cells.len()) {
+            cells[col] = match cells[col] {
+                CellType::Pipe(pl) => CellType::HorizontalPipe(merge_color, pl),
+                _ => CellType::Horizontal(merge_color),
+            };
         }
+        segment_start = segment_start.max(end_idx + 1);
 
-        // End of merge lane
-        let end_idx = merge_lane * 2;
         if end_idx < cells.len() {
-            if merge_lane == rightmost_lane {
-                // Rightmost lane uses ╯
-                cells[end_idx] = CellType::MergeLeft(merge_color);
+            cells[end_idx] = if merge_lane == rightmost_lane {
+                CellType::MergeLeft(merge_color)
             } else {
-                // Middle lanes use ┴
-                cells[end_idx] = CellType::TeeUp(merge_color);
-            }
+                CellType::TeeUp(merge_color)
+            };
         }
     }
 
@@ -0,0 +1,77 @@
+//! Adding patterns to the repository's .gitignore
+
+use std::fs;
+use std::io::ErrorKind;
+
+use git2::Repository;
+
+use crate::error::{Error, Result};
+
+/// Append `pattern` to the .gitignore at the top of the working tree
+/// Returns false when the pattern is already listed (the file is left alone)
+pub fn add_pattern(repo: &Repository, pattern: &str) -> Result<bool> {
+    let path = repo
+        .workdir()
+        .ok_or(Error::BareRepository)?
+        .join(".gitignore");
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    match append_pattern(&contents, pattern) {
+        Some(updated) => {
+            fs::write(&path, updated)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Contents with `pattern` on its own line at the end, or None when it is already listed
+/// An empty pattern counts as listed
+fn append_pattern(contents: &str, pattern: &str) -> Option<String> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() || contents.lines().any(|line| line.trim_end() == pattern) {
+        return None;
+    }
+    let mut updated = contents.to_string();
+    // Keep the last existing line intact
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    updated.push_str(pattern);
+    updated.push('\n');
+    Some(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_pattern_ends_lines_and_skips_duplicates() {
+        assert_eq!(append_pattern("", "target/").as_deref(), Some("target/\n"));
+        assert_eq!(
+            append_pattern("*.log\n", "target/").as_deref(),
+            Some("*.log\ntarget/\n")
+        );
+        // A missing final newline is added before the new line
+        assert_eq!(
+            append_pattern("*.log", "target/").as_deref(),
+            Some("*.log\ntarget/\n")
+        );
+        assert_eq!(
+            append_pattern("*.log\r\ntarget/\r\n", "target/"),
+            None,
+            "CRLF files still match"
+        );
+        assert_eq!(append_pattern("*.log\n", "*.log  "), None);
+        assert_eq!(append_pattern("*.log\n", " "), None);
+        // A commented-out pattern is not a duplicate
+        assert_eq!(
+            append_pattern("# target/\n", "target/").as_deref(),
+            Some("# target/\ntarget/\n")
+        );
+    }
+}
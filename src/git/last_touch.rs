@@ -0,0 +1,94 @@
+//! Who last changed a file before a commit (annotations in the changed files list)
+
+use std::path::Path;
+
+use git2::{Commit, Oid, Repository};
+
+use crate::error::Result;
+
+/// Commits walked per path before giving up
+pub const WALK_LIMIT: usize = 100;
+
+/// Most recent earlier change to a path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastTouch {
+    pub author: String,
+    /// Seconds from that change to the annotated commit
+    pub age: i64,
+}
+
+impl LastTouch {
+    /// Last commit before `oid` that changed `path`, following first parents
+    /// None when the path has no earlier change within `WALK_LIMIT` commits
+    pub fn before(repo: &Repository, oid: Oid, path: &Path) -> Result<Option<Self>> {
+        let commit = repo.find_commit(oid)?;
+        let Ok(parent) = commit.parent(0) else {
+            return Ok(None);
+        };
+
+        let mut walk = repo.revwalk()?;
+        walk.push(parent.id())?;
+        walk.simplify_first_parent()?;
+        for id in walk.take(WALK_LIMIT) {
+            let candidate = repo.find_commit(id?)?;
+            let previous = candidate.parent(0).ok().and_then(|p| entry_id(&p, path));
+            if entry_id(&candidate, path) != previous {
+                return Ok(Some(Self {
+                    author: String::from_utf8_lossy(candidate.author().name_bytes()).to_string(),
+                    age: commit.time().seconds() - candidate.time().seconds(),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// "last touched by yuki, 12d before"
+    pub fn describe(&self) -> String {
+        format!(
+            "last touched by {}, {} before",
+            self.author,
+            short_age(self.age)
+        )
+    }
+}
+
+/// Blob (or tree) at `path` in a commit
+fn entry_id(commit: &Commit, path: &Path) -> Option<Oid> {
+    commit
+        .tree()
+        .ok()?
+        .get_path(path)
+        .ok()
+        .map(|entry| entry.id())
+}
+
+/// Compact age: "45m", "5h", "12d", "3mo", "2y"
+fn short_age(seconds: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    // Commit dates can go backwards (rebases, skewed clocks)
+    let seconds = seconds.max(0);
+    match seconds {
+        s if s < HOUR => format!("{}m", s / MINUTE),
+        s if s < DAY => format!("{}h", s / HOUR),
+        s if s < 60 * DAY => format!("{}d", s / DAY),
+        s if s < 365 * DAY => format!("{}mo", s / (30 * DAY)),
+        s => format!("{}y", s / (365 * DAY)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_age_units() {
+        assert_eq!(short_age(-5), "0m");
+        assert_eq!(short_age(45 * 60), "45m");
+        assert_eq!(short_age(5 * 3600 + 59), "5h");
+        assert_eq!(short_age(12 * 86400), "12d");
+        assert_eq!(short_age(90 * 86400), "3mo");
+        assert_eq!(short_age(800 * 86400), "2y");
+    }
+}
@@ -1,14 +1,23 @@
 //! Git layer
 
+pub mod bisect;
 pub mod branch;
 pub mod commit;
 pub mod diff;
+pub mod filter;
 pub mod graph;
+pub mod ignore;
+pub mod last_touch;
 pub mod operations;
+pub mod repo_info;
 pub mod repository;
+pub mod series;
+pub mod signature;
+pub mod stash;
+pub mod tag;
 
-pub use branch::BranchInfo;
+pub use branch::{BranchGrouping, BranchInfo, BranchReport, BranchSort, LabelOrder};
 pub use commit::CommitInfo;
-pub use diff::{CommitDiffInfo, FileChangeKind, FileDiffInfo};
-pub use graph::build_graph;
-pub use repository::{GitRepository, WorkingTreeStatus};
+pub use diff::{commit_patch, BranchComparison, CommitDiffInfo, FileChangeKind, FileDiffInfo};
+pub use graph::{build_graph, build_graph_with_options};
+pub use repository::{GitRepository, RepoLocation, RepoSnapshot, WalkScope, WorkingTreeStatus};
@@ -1,32 +1,147 @@
 //! Git operations (checkout, merge, rebase, branch operations)
 
-use std::process::Command;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use git2::build::CheckoutBuilder;
+use git2::{
+    Branch, BranchType, CheckoutNotificationType, Commit, ErrorCode, Oid, Repository, ResetType,
+    Signature, Status, StatusOptions, Tree,
+};
+
+use super::branch::ref_name_lossy;
+use crate::error::{not_found_or, short_oid, Error, Result};
+
+fn find_local_branch<'r>(repo: &'r Repository, name: &str) -> Result<Branch<'r>> {
+    repo.find_branch(name, BranchType::Local)
+        .map_err(|e| not_found_or(e, || Error::BranchNotFound(name.to_string())))
+}
+
+/// A local branch, else a remote-tracking one (e.g. an upstream like "origin/main")
+fn find_any_branch<'r>(repo: &'r Repository, name: &str) -> Result<Branch<'r>> {
+    match repo.find_branch(name, BranchType::Local) {
+        Err(e) if e.code() == ErrorCode::NotFound => repo
+            .find_branch(name, BranchType::Remote)
+            .map_err(|e| not_found_or(e, || Error::BranchNotFound(name.to_string()))),
+        result => Ok(result?),
+    }
+}
+
+fn find_commit(repo: &Repository, oid: Oid) -> Result<Commit<'_>> {
+    repo.find_commit(oid)
+        .map_err(|e| not_found_or(e, || Error::CommitNotFound(short_oid(oid))))
+}
+
+/// Map a failure to create or rename a branch to the name-related variants
+fn branch_name_error(e: git2::Error, name: &str) -> Error {
+    match e.code() {
+        ErrorCode::Exists => Error::BranchExists(name.to_string()),
+        ErrorCode::InvalidSpec => Error::InvalidBranchName(name.to_string()),
+        _ => e.into(),
+    }
+}
 
-use anyhow::{bail, Context, Result};
-use git2::{BranchType, Oid, Repository};
+/// Safe checkout of a tree into the working directory (nothing local is ever overwritten)
+/// Conflicting files are reported by path: untracked ones alone as `UntrackedFilesOverwritten`,
+/// anything with uncommitted changes to tracked files as `LocalChangesOverwritten`
+fn checkout_tree(repo: &Repository, tree: &Tree) -> Result<()> {
+    let mut untracked = Vec::new();
+    let mut modified = Vec::new();
+    let result = {
+        let mut builder = CheckoutBuilder::new();
+        builder
+            .safe()
+            .notify_on(CheckoutNotificationType::CONFLICT)
+            .notify(|_, path, baseline, _, _| {
+                // Files missing from HEAD have no baseline: they are untracked in the working tree
+                match (path, baseline) {
+                    (Some(path), None) => untracked.push(path.to_path_buf()),
+                    (Some(path), Some(_)) => modified.push(path.to_path_buf()),
+                    (None, _) => {}
+                }
+                true
+            });
+        repo.checkout_tree(tree.as_object(), Some(&mut builder))
+    };
+    match result {
+        Err(e) if e.code() == ErrorCode::Conflict && !modified.is_empty() => {
+            modified.extend(untracked);
+            modified.sort();
+            Err(Error::LocalChangesOverwritten(modified))
+        }
+        Err(e) if e.code() == ErrorCode::Conflict && !untracked.is_empty() => {
+            Err(Error::UntrackedFilesOverwritten(untracked))
+        }
+        result => Ok(result?),
+    }
+}
+
+/// Fail with `IndexLocked` while another git process holds the index lock
+pub fn ensure_index_unlocked(repo: &Repository) -> Result<()> {
+    if repo.path().join("index.lock").exists() {
+        return Err(Error::IndexLocked);
+    }
+    Ok(())
+}
+
+/// Delete untracked files (and untracked directories, with their contents) so a
+/// checkout can write over them
+/// Paths that are no longer untracked are left alone
+pub fn remove_untracked_files(repo: &Repository, paths: &[PathBuf]) -> Result<()> {
+    let workdir = repo.workdir().ok_or(Error::BareRepository)?;
+    for path in paths {
+        let full_path = workdir.join(path);
+        let is_dir = fs::symlink_metadata(&full_path).is_ok_and(|metadata| metadata.is_dir());
+        if !is_dir {
+            if repo.status_file(path)? == Status::WT_NEW {
+                fs::remove_file(full_path)?;
+            }
+        } else if only_untracked_under(repo, path)? {
+            fs::remove_dir_all(full_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether everything in the directory at `path` is untracked
+fn only_untracked_under(repo: &Repository, path: &Path) -> Result<bool> {
+    let mut options = StatusOptions::new();
+    options
+        .pathspec(path)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_unmodified(true);
+    let statuses = repo.statuses(Some(&mut options))?;
+    Ok(statuses
+        .iter()
+        .all(|entry| entry.status() == Status::WT_NEW))
+}
 
 /// Checkout a branch
 pub fn checkout_branch(repo: &Repository, branch_name: &str) -> Result<()> {
-    let branch = repo
-        .find_branch(branch_name, BranchType::Local)
-        .context(format!("Branch '{}' not found", branch_name))?;
+    let branch = find_local_branch(repo, branch_name)?;
 
     let reference = branch.get();
     let commit = reference.peel_to_commit()?;
     let tree = commit.tree()?;
 
-    repo.checkout_tree(tree.as_object(), None)?;
-    repo.set_head(reference.name().unwrap())?;
+    checkout_tree(repo, &tree)?;
+    repo.set_head_bytes(reference.name_bytes())?;
 
     Ok(())
 }
 
 /// Checkout a commit (detached HEAD)
 pub fn checkout_commit(repo: &Repository, oid: Oid) -> Result<()> {
-    let commit = repo.find_commit(oid).context("Commit not found")?;
+    let commit = find_commit(repo, oid)?;
     let tree = commit.tree()?;
 
-    repo.checkout_tree(tree.as_object(), None)?;
+    checkout_tree(repo, &tree)?;
     repo.set_head_detached(oid)?;
 
     Ok(())
@@ -37,12 +152,12 @@ pub fn checkout_remote_branch(repo: &Repository, remote_branch: &str) -> Result<
     // Extract "branch-name" from "origin/branch-name"
     let local_name = remote_branch
         .strip_prefix("origin/")
-        .context("Invalid remote branch format")?;
+        .ok_or_else(|| Error::RemoteBranchNotFound(remote_branch.to_string()))?;
 
     // Look up the remote branch
     let remote_ref = repo
         .find_branch(remote_branch, BranchType::Remote)
-        .context(format!("Remote branch '{}' not found", remote_branch))?;
+        .map_err(|_| Error::RemoteBranchNotFound(remote_branch.to_string()))?;
 
     let remote_commit = remote_ref.get().peel_to_commit()?;
     let remote_oid = remote_commit.id();
@@ -66,11 +181,11 @@ pub fn checkout_remote_branch(repo: &Repository, remote_branch: &str) -> Result<
             if is_current_branch {
                 // Cannot force update current branch with repo.branch()
                 // Update the reference directly after checkout
-                repo.checkout_tree(tree.as_object(), None)?;
+                checkout_tree(repo, &tree)?;
                 repo.reference(&refname, remote_oid, true, "Update to remote")?;
             } else {
                 repo.branch(local_name, &remote_commit, true)?; // Overwrite with force=true
-                repo.checkout_tree(tree.as_object(), None)?;
+                checkout_tree(repo, &tree)?;
                 repo.set_head(&refname)?;
             }
             return Ok(());
@@ -80,13 +195,13 @@ pub fn checkout_remote_branch(repo: &Repository, remote_branch: &str) -> Result<
     // No local branch -> create and track
     let mut local_branch = repo
         .branch(local_name, &remote_commit, false)
-        .context(format!("Failed to create local branch '{}'", local_name))?;
+        .map_err(|e| branch_name_error(e, local_name))?;
 
     // Set upstream
     local_branch.set_upstream(Some(remote_branch))?;
 
     // Checkout
-    repo.checkout_tree(tree.as_object(), None)?;
+    checkout_tree(repo, &tree)?;
     repo.set_head(&format!("refs/heads/{}", local_name))?;
 
     Ok(())
@@ -94,33 +209,49 @@ pub fn checkout_remote_branch(repo: &Repository, remote_branch: &str) -> Result<
 
 /// Create a new branch
 pub fn create_branch(repo: &Repository, branch_name: &str, from_oid: Oid) -> Result<()> {
-    let commit = repo.find_commit(from_oid).context("Commit not found")?;
+    let commit = find_commit(repo, from_oid)?;
 
     repo.branch(branch_name, &commit, false)
-        .context(format!("Failed to create branch '{}'", branch_name))?;
+        .map_err(|e| branch_name_error(e, branch_name))?;
+
+    Ok(())
+}
+
+/// Rename a local branch
+pub fn rename_branch(repo: &Repository, old_name: &str, new_name: &str) -> Result<()> {
+    let mut branch = find_local_branch(repo, old_name)?;
+
+    branch
+        .rename(new_name, false)
+        .map_err(|e| branch_name_error(e, new_name))?;
 
     Ok(())
 }
 
 /// Delete a branch
 pub fn delete_branch(repo: &Repository, branch_name: &str) -> Result<()> {
-    let mut branch = repo
-        .find_branch(branch_name, BranchType::Local)
-        .context(format!("Branch '{}' not found", branch_name))?;
+    let mut branch = find_local_branch(repo, branch_name)?;
 
     if branch.is_head() {
-        bail!("Cannot delete current branch");
+        return Err(Error::CurrentBranch);
     }
 
     branch.delete()?;
     Ok(())
 }
 
-/// Perform a merge
-pub fn merge_branch(repo: &Repository, branch_name: &str) -> Result<()> {
-    let branch = repo
-        .find_branch(branch_name, BranchType::Local)
-        .context(format!("Branch '{}' not found", branch_name))?;
+/// What a merge did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeOutcome {
+    UpToDate,
+    FastForwarded,
+    /// A merge commit was created
+    Merged(Oid),
+}
+
+/// Perform a merge (of a local or remote-tracking branch)
+pub fn merge_branch(repo: &Repository, branch_name: &str) -> Result<MergeOutcome> {
+    let branch = find_any_branch(repo, branch_name)?;
 
     let reference = branch.get();
     let annotated_commit = repo.reference_to_annotated_commit(reference)?;
@@ -128,87 +259,475 @@ pub fn merge_branch(repo: &Repository, branch_name: &str) -> Result<()> {
     let (analysis, _) = repo.merge_analysis(&[&annotated_commit])?;
 
     if analysis.is_up_to_date() {
-        return Ok(());
+        return Ok(MergeOutcome::UpToDate);
+    }
+
+    // HEAD's branch has no commits yet (also reported as a fast-forward)
+    if analysis.is_unborn() {
+        return Err(Error::NoHead);
     }
 
     if analysis.is_fast_forward() {
-        // Fast-forward merge
-        let target_oid = reference.target().unwrap();
-        let target_commit = repo.find_commit(target_oid)?;
-        let tree = target_commit.tree()?;
+        fast_forward_head(
+            repo,
+            annotated_commit.id(),
+            &format!("Fast-forward merge: {}", branch_name),
+        )?;
+        return Ok(MergeOutcome::FastForwarded);
+    }
 
-        repo.checkout_tree(tree.as_object(), None)?;
+    if !analysis.is_normal() {
+        return Err(Error::CannotMerge(branch_name.to_string()));
+    }
 
-        let mut head_ref = repo.head()?;
-        head_ref.set_target(target_oid, &format!("Fast-forward merge: {}", branch_name))?;
+    // Normal merge
+    repo.merge(&[&annotated_commit], None, None)?;
 
-        return Ok(());
+    if repo.index()?.has_conflicts() {
+        return Err(Error::MergeConflict(conflicted_paths(repo)?));
     }
 
-    if analysis.is_normal() {
-        // Normal merge
-        repo.merge(&[&annotated_commit], None, None)?;
+    // Create a merge commit
+    let signature = commit_signature(repo)?;
+    let head = repo.head()?;
+    let head_commit = head.peel_to_commit()?;
+    let merge_commit = repo.find_commit(annotated_commit.id())?;
+    let tree_oid = repo.index()?.write_tree()?;
+    let tree = repo.find_tree(tree_oid)?;
+
+    let oid = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &format!("Merge branch '{}'", branch_name),
+        &tree,
+        &[&head_commit, &merge_commit],
+    )?;
+
+    repo.cleanup_state()?;
+    Ok(MergeOutcome::Merged(oid))
+}
 
-        if repo.index()?.has_conflicts() {
-            bail!("Merge conflict occurred. Please resolve manually.");
+/// Paths with unresolved conflicts in the index, sorted
+pub fn conflicted_paths(repo: &Repository) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for conflict in repo.index()?.conflicts()? {
+        let conflict = conflict?;
+        // Each side may be missing (e.g. deleted on one branch), but not all of them
+        let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+        if let Some(entry) = entry {
+            paths.push(String::from_utf8_lossy(&entry.path).to_string());
         }
+    }
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
 
-        // Create a merge commit
-        let signature = repo.signature()?;
-        let head = repo.head()?;
-        let head_commit = head.peel_to_commit()?;
-        let merge_commit = repo.find_commit(annotated_commit.id())?;
-        let tree_oid = repo.index()?.write_tree()?;
-        let tree = repo.find_tree(tree_oid)?;
+/// Abandon a merge or cherry-pick that stopped with conflicts (`git merge --abort`)
+/// Resets the index and working tree to HEAD, so other local changes are discarded too
+pub fn abort_merge(repo: &Repository) -> Result<()> {
+    let head = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|_| Error::NoHead)?;
+    repo.reset(head.as_object(), ResetType::Hard, None)?;
+    repo.cleanup_state()?;
+    Ok(())
+}
+
+/// Move HEAD's branch forward to `target`, checking out its tree first
+fn fast_forward_head(repo: &Repository, target: Oid, reflog_message: &str) -> Result<()> {
+    let tree = find_commit(repo, target)?.tree()?;
+    checkout_tree(repo, &tree)?;
+    repo.head()?.set_target(target, reflog_message)?;
+    Ok(())
+}
 
-        repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            &format!("Merge branch '{}'", branch_name),
-            &tree,
-            &[&head_commit, &merge_commit],
+/// Outcome of bringing the HEAD branch up to its upstream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PullOutcome {
+    UpToDate,
+    FastForwarded,
+    /// Both sides have commits; nothing was changed
+    Diverged,
+}
+
+/// HEAD branch and the name of its upstream (e.g. "origin/main")
+pub fn head_upstream(repo: &Repository) -> Result<(String, String)> {
+    let head = repo.head().map_err(|_| Error::NoHead)?;
+    if !head.is_branch() {
+        return Err(Error::DetachedHead);
+    }
+    let name = head.shorthand().unwrap_or_default().to_string();
+    let upstream = find_local_branch(repo, &name)?
+        .upstream()
+        .map_err(|_| Error::NoUpstream(name.clone()))?;
+    let upstream = upstream
+        .name()?
+        .ok_or_else(|| Error::NoUpstream(name.clone()))?
+        .to_string();
+    Ok((name, upstream))
+}
+
+/// Fast-forward the HEAD branch to its upstream, like `git pull --ff-only` after a fetch
+/// A diverged branch is left alone so the caller can offer a merge
+pub fn fast_forward_to_upstream(repo: &Repository) -> Result<PullOutcome> {
+    let (name, upstream) = head_upstream(repo)?;
+    let upstream_ref = find_any_branch(repo, &upstream)?;
+    let annotated_commit = repo.reference_to_annotated_commit(upstream_ref.get())?;
+    let (analysis, _) = repo.merge_analysis(&[&annotated_commit])?;
+
+    if analysis.is_up_to_date() {
+        Ok(PullOutcome::UpToDate)
+    } else if analysis.is_fast_forward() {
+        fast_forward_head(
+            repo,
+            annotated_commit.id(),
+            &format!("pull: fast-forward {} to {}", name, upstream),
         )?;
+        Ok(PullOutcome::FastForwarded)
+    } else {
+        Ok(PullOutcome::Diverged)
+    }
+}
 
-        repo.cleanup_state()?;
+/// Cherry-pick a commit onto HEAD, keeping its author and message
+/// A conflicting pick is left in progress with the conflicts written out, like git;
+/// HEAD does not move
+pub fn cherry_pick(repo: &Repository, oid: Oid) -> Result<Oid> {
+    let commit = find_commit(repo, oid)?;
+    let head = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|_| Error::NoHead)?;
+
+    let mut index = repo.cherrypick_commit(&commit, &head, 0, None)?;
+    if index.has_conflicts() {
+        repo.cherrypick(&commit, None)?;
+        return Err(Error::CherryPickConflict {
+            commit: short_oid(oid),
+            files: conflicted_paths(repo)?,
+        });
     }
+    let tree = repo.find_tree(index.write_tree_to(repo)?)?;
+    checkout_tree(repo, &tree)?;
+
+    let author = commit.author();
+    let committer = commit_signature(repo)?;
+    let message = String::from_utf8_lossy(commit.message_bytes());
+    let new_oid = repo.commit(Some("HEAD"), &author, &committer, &message, &tree, &[&head])?;
+    Ok(new_oid)
+}
 
-    Ok(())
+/// Check that commits (oldest first) can be squashed and return the commit they sit on
+/// They must be HEAD and its first-parent line, without merges, and the worktree must be clean
+pub fn check_squash<'r>(repo: &'r Repository, oids: &[Oid]) -> Result<Commit<'r>> {
+    if oids.len() < 2 {
+        return Err(Error::SquashRefused("select at least two commits"));
+    }
+    let mut commit = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|_| Error::NoHead)?;
+    for (i, &oid) in oids.iter().rev().enumerate() {
+        if commit.id() != oid {
+            return Err(Error::SquashRefused(if i == 0 {
+                "the range must end at HEAD"
+            } else {
+                "the range must follow HEAD's first-parent history"
+            }));
+        }
+        if commit.parent_count() > 1 {
+            return Err(Error::SquashRefused("merge commits cannot be squashed"));
+        }
+        commit = commit
+            .parent(0)
+            .map_err(|_| Error::SquashRefused("the root commit cannot be squashed"))?;
+    }
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false).include_ignored(false);
+    if !repo.statuses(Some(&mut opts))?.is_empty() {
+        return Err(Error::DirtyWorktree);
+    }
+    Ok(commit)
+}
+
+/// Squash commits ending at HEAD (oldest first) into one commit with the given message
+/// Soft-resets to the range base and commits HEAD's tree, keeping the oldest commit's author
+pub fn squash_commits(repo: &Repository, oids: &[Oid], message: &str) -> Result<Oid> {
+    let base = check_squash(repo, oids)?;
+    let oldest = find_commit(repo, oids[0])?;
+    let tree = find_commit(repo, oids[oids.len() - 1])?.tree()?;
+    let author = oldest.author();
+    let committer = commit_signature(repo)?;
+
+    repo.reset(base.as_object(), ResetType::Soft, None)?;
+    let new_oid = repo.commit(Some("HEAD"), &author, &committer, message, &tree, &[&base])?;
+    Ok(new_oid)
+}
+
+/// Marker commit for `git rebase --autosquash`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutosquashKind {
+    /// Fold the changes in, keeping the target's message
+    Fixup,
+    /// Fold the changes in and edit the combined message
+    Squash,
+}
+
+impl AutosquashKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            AutosquashKind::Fixup => "fixup",
+            AutosquashKind::Squash => "squash",
+        }
+    }
+}
+
+/// Commit the staged changes on HEAD as `fixup! <subject>` (or `squash!`) of `target`
+/// Like `git commit --fixup`, so a later `git rebase --autosquash` folds it into the target
+pub fn commit_autosquash(repo: &Repository, target: Oid, kind: AutosquashKind) -> Result<Oid> {
+    let target = find_commit(repo, target)?;
+    let head = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|_| Error::NoHead)?;
+    if head.id() != target.id() && !repo.graph_descendant_of(head.id(), target.id())? {
+        return Err(Error::NotInHeadHistory(short_oid(target.id())));
+    }
+
+    // Staged from outside keifu, so reload the index from disk
+    let mut index = repo.index()?;
+    index.read(false)?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    if tree.id() == head.tree_id() {
+        return Err(Error::NothingStaged);
+    }
+
+    let subject = String::from_utf8_lossy(target.summary_bytes().unwrap_or_default());
+    let message = format!("{}! {}", kind.label(), subject);
+    let signature = commit_signature(repo)?;
+    let new_oid = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &message,
+        &tree,
+        &[&head],
+    )?;
+    Ok(new_oid)
+}
+
+/// Identity for new commits from the effective config
+/// Reads a snapshot of all levels, so `include` and `includeIf` identities apply
+pub fn commit_signature(repo: &Repository) -> Result<Signature<'static>> {
+    let config = repo.config()?.snapshot()?;
+    let name = config
+        .get_string("user.name")
+        .map_err(|_| Error::MissingIdentity("user.name"))?;
+    let email = config
+        .get_string("user.email")
+        .map_err(|_| Error::MissingIdentity("user.email"))?;
+    Ok(Signature::now(&name, &email)?)
 }
 
 /// Perform a rebase (simple implementation)
-pub fn rebase_branch(repo: &Repository, onto_branch: &str) -> Result<()> {
-    let onto = repo
-        .find_branch(onto_branch, BranchType::Local)
-        .context(format!("Branch '{}' not found", onto_branch))?;
+/// Returns (original, rewritten) pairs for the replayed commits; commits whose changes
+/// the new base already has are skipped
+/// On any failure (a conflict included) the rebase is aborted, leaving the repository as it was
+pub fn rebase_branch(repo: &Repository, onto_branch: &str) -> Result<Vec<(Oid, Oid)>> {
+    let onto = find_any_branch(repo, onto_branch)?;
 
     let onto_annotated = repo.reference_to_annotated_commit(onto.get())?;
 
     let mut rebase = repo.rebase(None, Some(&onto_annotated), None, None)?;
 
-    while let Some(op) = rebase.next() {
-        let _operation = op?;
-        let signature = repo.signature()?;
-        rebase.commit(None, &signature, None)?;
+    match replay(repo, &mut rebase) {
+        Ok(rewritten) => Ok(rewritten),
+        Err(e) => {
+            rebase.abort()?;
+            Err(e)
+        }
     }
+}
 
+/// Commit every rebase operation in turn, then finish the rebase
+fn replay(repo: &Repository, rebase: &mut git2::Rebase<'_>) -> Result<Vec<(Oid, Oid)>> {
+    let signature = commit_signature(repo)?;
+    let mut rewritten = Vec::new();
+    while let Some(op) = rebase.next() {
+        let original = op?.id();
+        match rebase.commit(None, &signature, None) {
+            Ok(new) => rewritten.push((original, new)),
+            // Already applied upstream: nothing to commit, like `git rebase` dropping it
+            Err(e) if e.code() == ErrorCode::Applied => {}
+            Err(e) if e.code() == ErrorCode::Unmerged => {
+                return Err(Error::RebaseConflict(short_oid(original)));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
     rebase.finish(None)?;
+    Ok(rewritten)
+}
+
+/// A local branch to move from an original commit to its rewrite
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchRetarget {
+    pub name: String,
+    /// Tip before the rewrite
+    pub from: Oid,
+    /// Rewritten counterpart of `from`
+    pub to: Oid,
+}
+
+/// Local branches left on commits that a rebase replayed (original → rewritten pairs)
+/// The rebased branch itself (`head_branch`) already moved and is not listed
+pub fn stale_branches(
+    repo: &Repository,
+    rewritten: &[(Oid, Oid)],
+    head_branch: Option<&str>,
+) -> Result<Vec<BranchRetarget>> {
+    let mut stale = Vec::new();
+    for branch in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch?;
+        let name = ref_name_lossy(branch.get().shorthand_bytes());
+        if Some(name.as_str()) == head_branch {
+            continue;
+        }
+        let Some(tip) = branch.get().target() else {
+            continue;
+        };
+        if let Some(&(_, to)) = rewritten.iter().find(|(original, _)| *original == tip) {
+            stale.push(BranchRetarget {
+                name,
+                from: tip,
+                to,
+            });
+        }
+    }
+    stale.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(stale)
+}
 
+/// Move every branch onto its rewritten commit, or none of them
+/// Each branch must still be at `from`; when one cannot be moved, those already
+/// moved are put back before the error is returned, and any that cannot be are named in it
+pub fn retarget_branches(repo: &Repository, moves: &[BranchRetarget]) -> Result<()> {
+    for (done, retarget) in moves.iter().enumerate() {
+        let refname = format!("refs/heads/{}", retarget.name);
+        let log = format!("keifu: retarget onto {}", short_oid(retarget.to));
+        if let Err(e) = repo.reference_matching(&refname, retarget.to, true, retarget.from, &log) {
+            let cause = match e.code() {
+                ErrorCode::Modified | ErrorCode::NotFound => Error::RepositoryChanged,
+                _ => e.into(),
+            };
+            let stuck: Vec<String> = moves[..done]
+                .iter()
+                .rev()
+                .filter(|undo| {
+                    repo.reference_matching(
+                        &format!("refs/heads/{}", undo.name),
+                        undo.from,
+                        true,
+                        undo.to,
+                        "keifu: roll back retarget",
+                    )
+                    .is_err()
+                })
+                .map(|undo| undo.name.clone())
+                .collect();
+            if stuck.is_empty() {
+                return Err(cause);
+            }
+            return Err(Error::RetargetRollbackFailed {
+                cause: Box::new(cause),
+                branches: stuck,
+            });
+        }
+    }
     Ok(())
 }
 
 /// Fetch from origin remote using git command
-pub fn fetch_origin(repo_path: &str) -> Result<()> {
-    let output = Command::new("git")
+/// `repo_path` is the working tree or git dir; GIT_DIR from our environment is not passed on
+/// The fetch is killed when `timeout` elapses or `cancel` is set. On Unix git runs in its
+/// own process group, so its helpers (ssh, git-remote-https) are killed with it; on
+/// Windows only git itself is, and a helper may linger until it notices git is gone
+pub fn fetch_origin(repo_path: &str, timeout: Duration, cancel: &AtomicBool) -> Result<()> {
+    let mut command = Command::new("git");
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command
         .args(["fetch", "origin"])
         .current_dir(repo_path)
-        .output()
-        .context("Failed to execute git fetch")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("git fetch failed: {}", stderr.trim());
+        .env_remove("GIT_DIR")
+        .env_remove("GIT_WORK_TREE")
+        // Fail instead of waiting for credentials on a terminal we own
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::FetchFailed(format!("could not run git ({})", e)))?;
+
+    // Drained while waiting: git blocks once the pipe is full (lots of progress output)
+    let mut stderr_pipe = child.stderr.take().expect("stderr is piped");
+    let stderr_reader = thread::spawn(move || {
+        let mut stderr = Vec::new();
+        let _ = stderr_pipe.read_to_end(&mut stderr);
+        stderr
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if cancel.load(Ordering::Relaxed) {
+            kill_fetch(&mut child);
+            return Err(Error::FetchCancelled);
+        }
+        if started.elapsed() >= timeout {
+            kill_fetch(&mut child);
+            return Err(Error::FetchTimeout(timeout.as_secs()));
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    if !status.success() {
+        let stderr = stderr_reader.join().unwrap_or_default();
+        let stderr = String::from_utf8_lossy(&stderr).trim().to_string();
+        return Err(if is_auth_failure(&stderr) {
+            Error::AuthFailed(stderr)
+        } else {
+            Error::FetchFailed(stderr)
+        });
     }
 
     Ok(())
 }
+
+/// Kill a running fetch (with its process group on Unix) and reap git
+fn kill_fetch(child: &mut Child) {
+    #[cfg(unix)]
+    // SAFETY: kill() only sends a signal; git leads its own group (`process_group(0)`)
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Whether git's stderr reports missing or rejected credentials
+fn is_auth_failure(stderr: &str) -> bool {
+    const MARKERS: [&str; 4] = [
+        "Authentication failed",
+        "could not read Username",
+        "terminal prompts disabled",
+        "Permission denied (publickey",
+    ];
+    MARKERS.iter().any(|m| stderr.contains(m))
+}
@@ -0,0 +1,166 @@
+//! Repository summary for the info popup
+//!
+//! Cheap values are read directly; the commit count and on-disk size are computed on a worker.
+
+use std::fs;
+use std::path::Path;
+
+use git2::Repository;
+
+use super::branch::{ref_name_lossy, unborn_head_branch};
+use crate::error::Result;
+
+/// Values read directly from the repository (each is None when unavailable)
+#[derive(Debug, Clone, Default)]
+pub struct RepoInfo {
+    pub path: Option<String>,
+    pub head: Option<String>,
+    pub upstream: Option<String>,
+    /// (name, url) for each remote
+    pub remotes: Vec<(String, Option<String>)>,
+    pub default_branch: Option<String>,
+    pub is_bare: bool,
+    pub is_shallow: bool,
+    pub has_submodules: Option<bool>,
+}
+
+/// Values that need a full history walk or a directory scan
+#[derive(Debug, Clone, Default)]
+pub struct RepoStats {
+    /// Commits reachable from all branches
+    pub total_commits: Option<usize>,
+    /// Size of the .git directory in bytes
+    pub git_dir_size: Option<u64>,
+}
+
+impl RepoInfo {
+    pub fn collect(repo: &Repository) -> Self {
+        let head = repo.head().ok();
+        let upstream = head
+            .as_ref()
+            .filter(|h| h.is_branch())
+            .and_then(|h| h.name())
+            .and_then(|name| repo.branch_upstream_name(name).ok())
+            .map(|buf| {
+                let name = ref_name_lossy(&buf);
+                name.strip_prefix("refs/remotes/")
+                    .or_else(|| name.strip_prefix("refs/heads/"))
+                    .map(str::to_string)
+                    .unwrap_or(name)
+            });
+
+        let remotes = repo
+            .remotes()
+            .map(|names| {
+                names
+                    .iter()
+                    .flatten()
+                    .map(|name| {
+                        let url = repo
+                            .find_remote(name)
+                            .ok()
+                            .and_then(|r| r.url().map(str::to_string));
+                        (name.to_string(), url)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let path = repo
+            .workdir()
+            .unwrap_or_else(|| repo.path())
+            .canonicalize()
+            .ok()
+            .map(|p| p.to_string_lossy().to_string());
+
+        Self {
+            path,
+            head: head
+                .as_ref()
+                .map(|h| ref_name_lossy(h.shorthand_bytes()))
+                .or_else(|| unborn_head_branch(repo).map(|name| format!("(unborn) {}", name))),
+            upstream,
+            remotes,
+            default_branch: default_branch(repo),
+            is_bare: repo.is_bare(),
+            is_shallow: repo.is_shallow(),
+            has_submodules: repo.submodules().ok().map(|s| !s.is_empty()),
+        }
+    }
+}
+
+impl RepoStats {
+    /// Walk all branches and scan the .git directory (slow on large repositories)
+    pub fn compute(repo_path: &str) -> Self {
+        let Ok(repo) = Repository::open(repo_path) else {
+            return Self::default();
+        };
+        Self {
+            total_commits: count_commits(&repo).ok(),
+            git_dir_size: dir_size(repo.path()).ok(),
+        }
+    }
+}
+
+/// Default branch from refs/remotes/origin/HEAD, falling back to init.defaultBranch
+fn default_branch(repo: &Repository) -> Option<String> {
+    let from_origin = repo
+        .find_reference("refs/remotes/origin/HEAD")
+        .ok()
+        .and_then(|r| r.symbolic_target().map(str::to_string))
+        .and_then(|target| {
+            target
+                .strip_prefix("refs/remotes/origin/")
+                .map(str::to_string)
+        });
+    from_origin.or_else(|| repo.config().ok()?.get_string("init.defaultBranch").ok())
+}
+
+fn count_commits(repo: &Repository) -> Result<usize> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_glob("refs/heads")?;
+    revwalk.push_glob("refs/remotes")?;
+    Ok(revwalk.count())
+}
+
+fn dir_size(path: &Path) -> std::io::Result<u64> {
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        total += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(total)
+}
+
+/// Human-readable byte size (e.g. "1.5 MiB")
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
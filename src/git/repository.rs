@@ -1,64 +1,272 @@
 //! Repository operation wrapper
 
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
-use anyhow::{Context, Result};
 use git2::Repository;
 
 use git2::Oid;
+use serde::{Deserialize, Serialize};
 
-use super::{BranchInfo, CommitInfo};
+use super::{
+    branch::{ref_name_lossy, unborn_head_branch},
+    tag::tags_by_commit,
+    BranchInfo, CommitInfo,
+};
+use crate::error::{not_found_or, Error, Result};
+
+/// A consistent view of the repository taken at one point in time
+/// Commits are walked from exactly the branch tips listed in `branches`,
+/// and HEAD is resolved once, so labels and HEAD detection always agree with the commit list.
+#[derive(Debug, Clone)]
+pub struct RepoSnapshot {
+    pub commits: Vec<CommitInfo>,
+    pub branches: Vec<BranchInfo>,
+    pub head_oid: Option<Oid>,
+    pub head_name: Option<String>,
+    /// Branch HEAD points to when it has no commits yet
+    pub unborn_branch: Option<String>,
+    /// Merge base of HEAD's branch and its upstream when both are ahead and behind
+    pub upstream_fork_point: Option<Oid>,
+    /// Tag names per commit
+    pub tags: HashMap<Oid, Vec<String>>,
+}
+
+/// Which history a snapshot walks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WalkScope {
+    /// Everything reachable from all local and remote branches
+    #[default]
+    AllBranches,
+    /// First-parent history of HEAD only
+    CurrentBranch,
+}
 
 pub struct GitRepository {
     pub repo: Repository,
     pub path: String,
 }
 
+/// Where a repository lives, for reopening it on worker threads
+/// Keeps a working tree given by GIT_WORK_TREE, which reopening the git dir alone would lose
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoLocation {
+    pub git_dir: PathBuf,
+    /// None for bare repositories
+    pub workdir: Option<PathBuf>,
+}
+
+impl RepoLocation {
+    pub fn of(repo: &Repository) -> Self {
+        Self {
+            git_dir: repo.path().to_path_buf(),
+            workdir: repo.workdir().map(Path::to_path_buf),
+        }
+    }
+
+    /// Open the repository again with the same working tree
+    pub fn open(&self) -> Result<Repository> {
+        let repo = Repository::open(&self.git_dir)?;
+        if let Some(workdir) = &self.workdir {
+            if repo.workdir() != Some(workdir.as_path()) {
+                repo.set_workdir(workdir, false)?;
+            }
+        }
+        Ok(repo)
+    }
+
+    /// Working tree when it is not the git dir's parent (GIT_WORK_TREE, core.worktree)
+    pub fn separate_workdir(&self) -> Option<&Path> {
+        let workdir = self.workdir.as_deref()?;
+        let usual = self.git_dir.parent().is_some_and(|parent| {
+            self.git_dir.file_name() == Some(".git".as_ref()) && same_dir(parent, workdir)
+        });
+        (!usual).then_some(workdir)
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a.components().eq(b.components()),
+    }
+}
+
 impl GitRepository {
     /// Discover a repository from the current directory
+    /// Honors GIT_DIR, GIT_WORK_TREE and GIT_CEILING_DIRECTORIES like git does
     pub fn discover() -> Result<Self> {
-        let repo = Repository::discover(".")
-            .context("Git repository not found. Please run inside a Git repository.")?;
-        let path = repo
-            .workdir()
-            .unwrap_or_else(|| repo.path())
-            .to_string_lossy()
-            .to_string();
-        Ok(Self { repo, path })
+        let repo = Repository::open_from_env().map_err(|_| {
+            let path = std::env::var_os("GIT_DIR")
+                .map(PathBuf::from)
+                .or_else(|| std::env::current_dir().ok())
+                .unwrap_or_else(|| ".".into());
+            Error::RepositoryNotFound(path)
+        })?;
+        Ok(Self::from_repo(repo))
+    }
+
+    /// Discover a repository containing `path` (GIT_DIR and GIT_WORK_TREE are ignored)
+    pub fn discover_at<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let repo = Repository::discover(path.as_ref())
+            .map_err(|_| Error::RepositoryNotFound(path.as_ref().to_path_buf()))?;
+        Ok(Self::from_repo(repo))
     }
 
-    /// Open a repository from a specified path
+    /// Open a repository from a specified path (GIT_DIR and GIT_WORK_TREE are ignored)
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let repo = Repository::open(path.as_ref())
-            .context("Git repository not found at specified path.")?;
-        let path_str = repo
+        let repo = Repository::open(path.as_ref()).map_err(|e| {
+            not_found_or(e, || Error::RepositoryNotFound(path.as_ref().to_path_buf()))
+        })?;
+        Ok(Self::from_repo(repo))
+    }
+
+    /// Display path: the working tree, or the git dir of a bare repository
+    fn from_repo(repo: Repository) -> Self {
+        let path = repo
             .workdir()
             .unwrap_or_else(|| repo.path())
             .to_string_lossy()
             .to_string();
-        Ok(Self {
-            repo,
-            path: path_str,
+        Self { repo, path }
+    }
+
+    /// Take a consistent snapshot of commits, branches and HEAD
+    pub fn snapshot(&self, max_count: usize) -> Result<RepoSnapshot> {
+        self.snapshot_scoped(max_count, WalkScope::AllBranches)
+    }
+
+    /// Take a snapshot limited to the given scope
+    /// With `CurrentBranch`, only branches pointing into HEAD's first-parent line are kept
+    pub fn snapshot_scoped(&self, max_count: usize, scope: WalkScope) -> Result<RepoSnapshot> {
+        self.snapshot_pinned(max_count, scope, None)
+    }
+
+    /// Take a snapshot that also contains `pinned` (all-branches scope only)
+    /// The walk starts from `pinned` too and goes past `max_count` until it is loaded
+    pub fn snapshot_pinned(
+        &self,
+        max_count: usize,
+        scope: WalkScope,
+        pinned: Option<Oid>,
+    ) -> Result<RepoSnapshot> {
+        // Resolve HEAD once and derive everything else from it
+        let head = self.repo.head().ok();
+        let head_name = head.as_ref().map(|h| ref_name_lossy(h.shorthand_bytes()));
+        let head_oid = head
+            .as_ref()
+            .and_then(|h| h.peel_to_commit().ok())
+            .map(|c| c.id());
+
+        // Read the refs once and walk from exactly those tips
+        let mut branches = BranchInfo::list_with_head(&self.repo, head.as_ref())?;
+        let commits = match scope {
+            WalkScope::AllBranches => {
+                let tips = branches.iter().map(|b| b.tip_oid).chain(pinned);
+                self.walk_commits(tips, max_count, false, pinned)?
+            }
+            WalkScope::CurrentBranch => {
+                let mut commits = self.walk_commits(head_oid.into_iter(), max_count, true, None)?;
+                // Drop merged-in parents so the line is drawn as a single lane
+                for commit in &mut commits {
+                    commit.parent_oids.truncate(1);
+                }
+                let loaded: HashSet<Oid> = commits.iter().map(|c| c.oid).collect();
+                branches.retain(|b| loaded.contains(&b.tip_oid));
+                commits
+            }
+        };
+
+        let upstream_fork_point = head.as_ref().and_then(|h| self.upstream_fork_point(h));
+
+        let unborn_branch = match head {
+            Some(_) => None,
+            None => unborn_head_branch(&self.repo),
+        };
+
+        Ok(RepoSnapshot {
+            commits,
+            branches,
+            head_oid,
+            head_name,
+            unborn_branch,
+            upstream_fork_point,
+            tags: tags_by_commit(&self.repo)?,
         })
     }
 
+    /// Where the HEAD branch and its upstream diverged
+    /// None unless an upstream exists and the branch is both ahead and behind
+    fn upstream_fork_point(&self, head: &git2::Reference) -> Option<Oid> {
+        if !head.is_branch() {
+            return None;
+        }
+        let local_oid = head.target()?;
+        let branch = git2::Branch::wrap(self.repo.find_reference(head.name()?).ok()?);
+        let upstream_oid = branch.upstream().ok()?.get().target()?;
+
+        let (ahead, behind) = self.repo.graph_ahead_behind(local_oid, upstream_oid).ok()?;
+        if ahead == 0 || behind == 0 {
+            return None;
+        }
+        self.repo.merge_base(local_oid, upstream_oid).ok()
+    }
+
     /// Get commit history (newest first)
     pub fn get_commits(&self, max_count: usize) -> Result<Vec<CommitInfo>> {
-        let mut revwalk = self.repo.revwalk()?;
-        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
-
         // Include all branches
+        let mut tips = Vec::new();
         for branch_result in self.repo.branches(None)? {
             let (branch, _) = branch_result?;
             if let Some(oid) = branch.get().target() {
-                revwalk.push(oid)?;
+                tips.push(oid);
             }
         }
+        self.walk_commits(tips.into_iter(), max_count, false, None)
+    }
+
+    /// Resolve a revspec (branch, tag, `HEAD~3`, hash...) to a commit
+    pub fn resolve_commit(&self, spec: &str) -> Result<Oid> {
+        self.repo
+            .revparse_single(spec)
+            .and_then(|object| object.peel_to_commit())
+            .map(|commit| commit.id())
+            .map_err(|e| Error::InvalidRevision {
+                spec: spec.to_string(),
+                message: e.message().to_string(),
+            })
+    }
+
+    /// Walk commits reachable from the given tips (newest first)
+    /// Stops after `max_count` commits, or later if `until` has not been reached yet
+    fn walk_commits(
+        &self,
+        tips: impl Iterator<Item = Oid>,
+        max_count: usize,
+        first_parent: bool,
+        until: Option<Oid>,
+    ) -> Result<Vec<CommitInfo>> {
+        let mut revwalk = self.repo.revwalk()?;
+        // Topological first: commits with identical timestamps must still come before their parents
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        if first_parent {
+            revwalk.simplify_first_parent()?;
+        }
+
+        for oid in tips {
+            revwalk.push(oid)?;
+        }
 
         let mut commits = Vec::new();
-        for oid_result in revwalk.take(max_count) {
+        let mut reached = until.is_none();
+        for oid_result in revwalk {
+            if commits.len() >= max_count && reached {
+                break;
+            }
             let oid = oid_result?;
+            reached |= until == Some(oid);
             let commit = self.repo.find_commit(oid)?;
             commits.push(CommitInfo::from_git2_commit(&commit));
         }
@@ -71,12 +279,54 @@ impl GitRepository {
         BranchInfo::list_all(&self.repo)
     }
 
+    /// Tip of the main branch: the local `main` or `master`, falling back to HEAD
+    pub fn main_oid(&self) -> Option<Oid> {
+        ["main", "master"]
+            .iter()
+            .find_map(|name| {
+                self.repo
+                    .find_branch(name, git2::BranchType::Local)
+                    .ok()
+                    .and_then(|b| b.get().target())
+            })
+            .or_else(|| self.head_oid())
+    }
+
+    /// Commits `local` is ahead of and behind `upstream`
+    pub fn ahead_behind(&self, local: Oid, upstream: Oid) -> Result<(usize, usize)> {
+        Ok(self.repo.graph_ahead_behind(local, upstream)?)
+    }
+
+    /// Best common ancestor of two commits (None for unrelated histories)
+    pub fn merge_base(&self, a: Oid, b: Oid) -> Result<Option<Oid>> {
+        match self.repo.merge_base(a, b) {
+            Ok(base) => Ok(Some(base)),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Count commits each tip is ahead of the main branch (see `main_oid`)
+    pub fn ahead_of_main(&self, tips: &[Oid]) -> HashMap<Oid, usize> {
+        let Some(main_oid) = self.main_oid() else {
+            return HashMap::new();
+        };
+
+        tips.iter()
+            .filter_map(|&tip| {
+                let (ahead, _) = self.repo.graph_ahead_behind(tip, main_oid).ok()?;
+                Some((tip, ahead))
+            })
+            .collect()
+    }
+
     /// Get the current HEAD name
+    /// Non-UTF8 names are converted lossily (see `ref_name_lossy`)
     pub fn head_name(&self) -> Option<String> {
         self.repo
             .head()
             .ok()
-            .and_then(|h| h.shorthand().map(|s| s.to_string()))
+            .map(|h| ref_name_lossy(h.shorthand_bytes()))
     }
 
     /// Get the current HEAD commit OID
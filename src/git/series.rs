@@ -0,0 +1,151 @@
+//! Patch export and statistics for a series of commits
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use git2::{Commit, Diff, EmailCreateOptions, Oid, Repository};
+
+use crate::error::{short_oid, Error, Result};
+
+/// Totals over a series of commits
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeriesStats {
+    pub commits: usize,
+    pub authors: usize,
+    /// Distinct paths touched by any commit
+    pub files: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl SeriesStats {
+    /// One-line summary (e.g. "3 commits by 2 authors, 4 files changed, +10 -2")
+    pub fn summary(&self) -> String {
+        let plural =
+            |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+        format!(
+            "{} by {}, {} changed, +{} -{}",
+            plural(self.commits, "commit"),
+            plural(self.authors, "author"),
+            plural(self.files, "file"),
+            self.insertions,
+            self.deletions
+        )
+    }
+}
+
+/// Diff of a commit against its first parent (or an empty tree for a root commit)
+fn first_parent_diff<'r>(repo: &'r Repository, commit: &Commit) -> Result<Diff<'r>> {
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    Ok(repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?)
+}
+
+fn find_commit(repo: &Repository, oid: Oid) -> Result<Commit<'_>> {
+    repo.find_commit(oid)
+        .map_err(|_| Error::CommitNotFound(short_oid(oid)))
+}
+
+/// Compute statistics for the given commits (merges are diffed against the first parent)
+pub fn series_stats(repo: &Repository, oids: &[Oid]) -> Result<SeriesStats> {
+    let mut authors = HashSet::new();
+    let mut files = HashSet::new();
+    let mut stats = SeriesStats {
+        commits: oids.len(),
+        ..SeriesStats::default()
+    };
+    for oid in oids {
+        let commit = find_commit(repo, *oid)?;
+        authors.insert(commit.author().email_bytes().to_vec());
+        let diff = first_parent_diff(repo, &commit)?;
+        for delta in diff.deltas() {
+            if let Some(path) = delta.new_file().path().or(delta.old_file().path()) {
+                files.insert(path.to_path_buf());
+            }
+        }
+        let diff_stats = diff.stats()?;
+        stats.insertions += diff_stats.insertions();
+        stats.deletions += diff_stats.deletions();
+    }
+    stats.authors = authors.len();
+    stats.files = files.len();
+    Ok(stats)
+}
+
+/// Message for squashing commits (oldest first): the subject, then each original message
+pub fn squash_message(repo: &Repository, oids: &[Oid], subject: &str) -> Result<String> {
+    let mut message = format!("{}\n", subject.trim());
+    for oid in oids {
+        let commit = find_commit(repo, *oid)?;
+        let original = String::from_utf8_lossy(commit.message_bytes());
+        message.push('\n');
+        message.push_str(original.trim_end());
+        message.push('\n');
+    }
+    Ok(message)
+}
+
+/// Write one mbox patch per commit into `dir`, numbered in the given order
+/// (`0001-subject.patch`, like `git format-patch`). Returns the written paths.
+pub fn export_patches(repo: &Repository, oids: &[Oid], dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for (i, oid) in oids.iter().enumerate() {
+        let commit = find_commit(repo, *oid)?;
+        let diff = first_parent_diff(repo, &commit)?;
+        let summary =
+            String::from_utf8_lossy(commit.summary_bytes().unwrap_or_default()).to_string();
+        let body = String::from_utf8_lossy(commit.body_bytes().unwrap_or_default()).to_string();
+        let email = git2::Email::from_diff(
+            &diff,
+            i + 1,
+            oids.len(),
+            oid,
+            summary.as_str(),
+            body.as_str(),
+            &commit.author(),
+            &mut EmailCreateOptions::new(),
+        )?;
+
+        let path = dir.join(format!("{:04}-{}.patch", i + 1, patch_slug(&summary)));
+        fs::write(&path, email.as_slice())?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// File name part derived from a commit subject, as `git format-patch` does
+fn patch_slug(subject: &str) -> String {
+    const MAX_LEN: usize = 52;
+    let mut slug = String::new();
+    for ch in subject.chars() {
+        if ch.is_ascii_alphanumeric() || ch == '_' || ch == '.' {
+            slug.push(ch);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= MAX_LEN {
+            break;
+        }
+    }
+    slug.trim_end_matches(['-', '.']).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch_slug() {
+        assert_eq!(
+            patch_slug("Fix: crash on empty repo!"),
+            "Fix-crash-on-empty-repo"
+        );
+        assert_eq!(patch_slug("  leading spaces"), "leading-spaces");
+        assert_eq!(patch_slug("日本語のみ"), "");
+        assert!(patch_slug(&"a".repeat(80)).len() <= 52);
+    }
+}
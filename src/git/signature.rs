@@ -0,0 +1,325 @@
+//! Who signed a commit, read from the signature block without verifying it
+
+use std::fs;
+
+use git2::{ErrorCode, Oid, Repository};
+
+use crate::error::Result;
+
+/// Signer metadata of a signed commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureInfo {
+    /// OpenPGP signature by a 64-bit key ID (uppercase hex)
+    OpenPgp { key_id: String },
+    /// SSH signature; the principal comes from `gpg.ssh.allowedSignersFile` when listed there
+    Ssh {
+        key_type: String,
+        principal: Option<String>,
+    },
+    /// A signature in a format not parsed here (e.g. X.509)
+    Unknown,
+}
+
+impl SignatureInfo {
+    /// Signature of a commit; None when the commit is not signed
+    pub fn of(repo: &Repository, oid: Oid) -> Result<Option<Self>> {
+        let block = match repo.extract_signature(&oid, None) {
+            Ok((signature, _)) => String::from_utf8_lossy(&signature).to_string(),
+            Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut info = Self::parse(&block);
+        if let Self::Ssh { principal, .. } = &mut info {
+            *principal = allowed_signers(repo).and_then(|signers| {
+                let (key_type, key) = ssh_key(&block)?;
+                principal_for(&signers, &key_type, &key)
+            });
+        }
+        Ok(Some(info))
+    }
+
+    /// Parse an armored signature block
+    pub fn parse(block: &str) -> Self {
+        if let Some(key_id) = dearmor(block, "PGP SIGNATURE").and_then(|data| openpgp_key_id(&data))
+        {
+            return Self::OpenPgp { key_id };
+        }
+        if let Some((key_type, _)) = ssh_key(block) {
+            return Self::Ssh {
+                key_type,
+                principal: None,
+            };
+        }
+        Self::Unknown
+    }
+
+    /// "openpgp key 4AEE18F83AFDEB23", "ssh-ed25519 key, principal dev@example.com"
+    pub fn describe(&self) -> String {
+        match self {
+            Self::OpenPgp { key_id } => format!("openpgp key {}", key_id),
+            Self::Ssh {
+                key_type,
+                principal: Some(principal),
+            } => format!("{} key, principal {}", key_type, principal),
+            Self::Ssh { key_type, .. } => format!("{} key", key_type),
+            Self::Unknown => "signature present".to_string(),
+        }
+    }
+}
+
+/// Base64 payload between the BEGIN and END lines of `label`
+/// Armor headers ("Version: ...") and the OpenPGP checksum line ("=MQTs") are skipped
+fn dearmor(block: &str, label: &str) -> Option<Vec<u8>> {
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    let mut lines = block.lines().map(str::trim);
+    lines.find(|line| *line == begin)?;
+    let mut payload = String::new();
+    for line in lines {
+        if line == end {
+            return base64_decode(&payload);
+        }
+        if !line.contains(':') && !line.starts_with('=') {
+            payload.push_str(line);
+        }
+    }
+    None
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.bytes().take_while(|c| *c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Issuer key ID of an OpenPGP signature packet (RFC 4880 / RFC 9580)
+fn openpgp_key_id(data: &[u8]) -> Option<String> {
+    let (tag, body) = openpgp_packet(data)?;
+    if tag != 2 {
+        return None;
+    }
+    let key_id = match body.first()? {
+        // Version, hashed length (5), type, creation time, key ID
+        3 => body.get(7..15)?,
+        version @ 4..=6 => {
+            let width = if *version == 6 { 4 } else { 2 };
+            let mut rest = body.get(4..)?;
+            let mut fingerprint = None;
+            // Hashed, then unhashed subpackets
+            for _ in 0..2 {
+                let len = be_number(rest.get(..width)?);
+                let area = rest.get(width..width + len)?;
+                rest = &rest[width + len..];
+                for (kind, data) in subpackets(area) {
+                    match kind {
+                        16 if data.len() == 8 => return Some(hex(data)),
+                        33 if data.len() > 8 => fingerprint = Some(data),
+                        _ => {}
+                    }
+                }
+            }
+            // Version 4 key IDs are the end of the fingerprint, later versions the start
+            let fingerprint = fingerprint?;
+            match fingerprint[0] {
+                4 => &fingerprint[fingerprint.len() - 8..],
+                _ => &fingerprint[1..9],
+            }
+        }
+        _ => return None,
+    };
+    Some(hex(key_id))
+}
+
+/// Tag and body of the first packet
+fn openpgp_packet(data: &[u8]) -> Option<(u8, &[u8])> {
+    let header = *data.first()?;
+    if header & 0x80 == 0 {
+        return None;
+    }
+    let (tag, len, start) = if header & 0x40 != 0 {
+        let first = usize::from(*data.get(1)?);
+        let (len, start) = match first {
+            0..=191 => (first, 2),
+            192..=223 => (((first - 192) << 8) + usize::from(*data.get(2)?) + 192, 3),
+            255 => (be_number(data.get(2..6)?), 6),
+            // Partial body lengths: the signature is all that follows
+            _ => (data.len() - 2, 2),
+        };
+        (header & 0x3f, len, start)
+    } else {
+        let (len, start) = match header & 0x03 {
+            0 => (usize::from(*data.get(1)?), 2),
+            1 => (be_number(data.get(1..3)?), 3),
+            2 => (be_number(data.get(1..5)?), 5),
+            _ => (data.len() - 1, 1),
+        };
+        ((header >> 2) & 0x0f, len, start)
+    };
+    Some((tag, data.get(start..start + len)?))
+}
+
+/// (type, data) of each well-formed subpacket, stopping at the first malformed one
+fn subpackets(mut area: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    std::iter::from_fn(move || {
+        let first = usize::from(*area.first()?);
+        let (len, start) = match first {
+            0..=191 => (first, 1),
+            192..=254 => (((first - 192) << 8) + usize::from(*area.get(1)?) + 192, 2),
+            _ => (be_number(area.get(1..5)?), 5),
+        };
+        let packet = area.get(start..start + len)?;
+        area = &area[start + len..];
+        // The high bit marks critical subpackets
+        let (kind, data) = packet.split_first()?;
+        Some((kind & 0x7f, data))
+    })
+}
+
+/// Key type and public key blob of an SSH signature (OpenSSH PROTOCOL.sshsig)
+fn ssh_key(block: &str) -> Option<(String, Vec<u8>)> {
+    let data = dearmor(block, "SSH SIGNATURE")?;
+    let rest = data.strip_prefix(b"SSHSIG")?;
+    // Version, then the public key
+    let (key, _) = ssh_string(rest.get(4..)?)?;
+    let (key_type, _) = ssh_string(key)?;
+    Some((String::from_utf8_lossy(key_type).to_string(), key.to_vec()))
+}
+
+/// A length-prefixed SSH string and what follows it
+fn ssh_string(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let len = be_number(data.get(..4)?);
+    Some((data.get(4..4 + len)?, &data[4 + len..]))
+}
+
+/// Contents of the allowed signers file from the git config
+fn allowed_signers(repo: &Repository) -> Option<String> {
+    let path = repo
+        .config()
+        .ok()?
+        .get_path("gpg.ssh.allowedSignersFile")
+        .ok()?;
+    fs::read_to_string(path).ok()
+}
+
+/// Principals listed for a key in an allowed signers file
+/// Lines are "principals [options] key-type base64-key [comment]"
+fn principal_for(signers: &str, key_type: &str, key: &[u8]) -> Option<String> {
+    signers.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.first()?.starts_with('#') {
+            return None;
+        }
+        let at = fields.iter().position(|field| *field == key_type)?;
+        let listed = base64_decode(fields.get(at + 1)?)?;
+        (at > 0 && listed == key).then(|| fields[0].to_string())
+    })
+}
+
+fn be_number(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .fold(0, |number, byte| (number << 8) | usize::from(*byte))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `gpg --armor --detach-sign` with an ed25519 key (issuer fingerprint and key ID subpackets)
+    const OPENPGP: &str = "-----BEGIN PGP SIGNATURE-----
+
+iHUEABYIAB0WIQQubvVjZ/Yd5gmah8RLRBXXlx1I/wUCatKOawAKCRBLRBXXlx1I
+/0N7AP4jz8KnHSBlwjSv3c1LgS3EmnYvRmk7qtHocGbSykRzSwEAysDJBuD2T/pa
+3yna3Vo/BlQiCZe5LhLFrYkoOJKATwQ=
+=MQTs
+-----END PGP SIGNATURE-----
+";
+
+    /// `ssh-keygen -Y sign -n git` with an ed25519 key
+    const SSH: &str = "-----BEGIN SSH SIGNATURE-----
+U1NIU0lHAAAAAQAAADMAAAALc3NoLWVkMjU1MTkAAAAgkreU5VAO6GcjG5AOqXAhzZ3zaq
+xzeSTKl66TiP2TuRoAAAADZ2l0AAAAAAAAAAZzaGE1MTIAAABTAAAAC3NzaC1lZDI1NTE5
+AAAAQIF/bJXwgsx016knOHAdR6PaFYdHEn30U31cfbfSHz06c6++qnRHkNDcOw2QqUBCNt
+JWmDeoWn4ipqYlidbW0Ag=
+-----END SSH SIGNATURE-----
+";
+
+    const SSH_PUBLIC_KEY: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIJK3lOVQDuhnIxuQDqlwIc2d82qsc3kkypeuk4j9k7ka";
+
+    #[test]
+    fn test_openpgp_key_id() {
+        assert_eq!(
+            SignatureInfo::parse(OPENPGP).describe(),
+            "openpgp key 4B4415D7971D48FF"
+        );
+        // Armor headers are skipped
+        let with_header = OPENPGP.replace("-----\n\n", "-----\nVersion: GnuPG v2\n\n");
+        assert_eq!(
+            SignatureInfo::parse(&with_header),
+            SignatureInfo::parse(OPENPGP)
+        );
+    }
+
+    #[test]
+    fn test_ssh_key_type_and_principal() {
+        assert_eq!(SignatureInfo::parse(SSH).describe(), "ssh-ed25519 key");
+
+        let (key_type, key) = ssh_key(SSH).unwrap();
+        let signers = format!(
+            "# team keys\nother@example.com ssh-rsa AAAAB3Nz\ndev@example.com,dev@work namespaces=\"git\" {}\n",
+            SSH_PUBLIC_KEY
+        );
+        assert_eq!(
+            principal_for(&signers, &key_type, &key).as_deref(),
+            Some("dev@example.com,dev@work")
+        );
+        assert_eq!(
+            principal_for("other@example.com ssh-rsa AAAAB3Nz", &key_type, &key),
+            None
+        );
+    }
+
+    #[test]
+    fn test_garbage_falls_back_to_signature_present() {
+        let truncated = OPENPGP.replace(
+            "/0N7AP4jz8KnHSBlwjSv3c1LgS3EmnYvRmk7qtHocGbSykRzSwEAysDJBuD2T/pa\n",
+            "",
+        );
+        for block in [
+            "",
+            "not a signature",
+            "-----BEGIN SIGNED MESSAGE-----\nMIIG\n-----END SIGNED MESSAGE-----\n",
+            "-----BEGIN PGP SIGNATURE-----\n\n!!!!\n-----END PGP SIGNATURE-----\n",
+            "-----BEGIN SSH SIGNATURE-----\nU1NIU0lH\n-----END SSH SIGNATURE-----\n",
+            &truncated,
+        ] {
+            assert_eq!(
+                SignatureInfo::parse(block),
+                SignatureInfo::Unknown,
+                "{:?}",
+                block
+            );
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Stash entries: save, list, apply, pop and drop
+
+use git2::{ErrorCode, Oid, Repository, StashApplyOptions, StashFlags};
+
+use super::operations::commit_signature;
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StashInfo {
+    /// Position in the stash list (`stash@{index}`), 0 being the newest
+    pub index: usize,
+    /// Stash message, e.g. "WIP on main: 1a2b3c4 subject"
+    pub message: String,
+    /// The stash commit
+    pub oid: Oid,
+    /// When the stash was made (Unix seconds)
+    pub time: i64,
+}
+
+/// Stash local changes, untracked files included (`git stash -u`)
+/// Without a message git's "WIP on <branch>: <commit>" is used
+pub fn stash_save(repo: &mut Repository, message: Option<&str>) -> Result<Oid> {
+    let signature = commit_signature(repo)?;
+    repo.stash_save2(&signature, message, Some(StashFlags::INCLUDE_UNTRACKED))
+        .map_err(|e| match e.code() {
+            ErrorCode::NotFound => Error::NothingToStash,
+            _ => e.into(),
+        })
+}
+
+/// List stashes, newest first
+pub fn stash_list(repo: &mut Repository) -> Result<Vec<StashInfo>> {
+    let mut entries = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        entries.push((index, message.to_string(), *oid));
+        true
+    })?;
+    entries
+        .into_iter()
+        .map(|(index, message, oid)| {
+            let time = repo.find_commit(oid)?.time().seconds();
+            Ok(StashInfo {
+                index,
+                message,
+                oid,
+                time,
+            })
+        })
+        .collect()
+}
+
+/// Apply a stash to the working tree and index, keeping it in the list
+/// Conflicting changes are written with conflict markers and reported as `StashConflict`
+pub fn stash_apply(repo: &mut Repository, stash: &StashInfo) -> Result<()> {
+    let index = find_stash(repo, stash)?;
+    repo.stash_apply(index, Some(&mut StashApplyOptions::new()))
+        .map_err(|e| apply_error(e, index))?;
+    if repo.index()?.has_conflicts() {
+        return Err(Error::StashConflict(index));
+    }
+    Ok(())
+}
+
+/// Apply a stash, then drop it
+/// Like `git stash pop`, the stash is kept when applying it conflicts
+/// (libgit2's own pop drops it regardless)
+pub fn stash_pop(repo: &mut Repository, stash: &StashInfo) -> Result<()> {
+    stash_apply(repo, stash)?;
+    stash_drop(repo, stash)
+}
+
+/// Remove a stash from the list
+pub fn stash_drop(repo: &mut Repository, stash: &StashInfo) -> Result<()> {
+    let index = find_stash(repo, stash)?;
+    Ok(repo.stash_drop(index)?)
+}
+
+/// Current position of a listed stash
+/// Fails when it is gone or another stash took its place (e.g. `git stash` ran meanwhile)
+fn find_stash(repo: &mut Repository, stash: &StashInfo) -> Result<usize> {
+    let mut found = None;
+    repo.stash_foreach(|index, _, oid| {
+        if *oid == stash.oid {
+            found = Some(index);
+        }
+        found.is_none()
+    })?;
+    found.ok_or(Error::RepositoryChanged)
+}
+
+fn apply_error(e: git2::Error, index: usize) -> Error {
+    match e.code() {
+        ErrorCode::MergeConflict => Error::StashConflict(index),
+        _ => e.into(),
+    }
+}
@@ -0,0 +1,237 @@
+//! Tag info structure and sorting
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use git2::{DescribeFormatOptions, DescribeOptions, ErrorCode, ObjectType, Oid, Repository};
+
+use super::branch::ref_name_lossy;
+use crate::error::Result;
+
+#[derive(Debug, Clone)]
+pub struct TagInfo {
+    pub name: String,
+    /// Commit the tag points to (annotated tags are peeled)
+    pub target: Oid,
+    /// Tagger name (annotated tags only)
+    pub tagger: Option<String>,
+    /// First line of the tag message (annotated tags only)
+    pub message: Option<String>,
+    /// Tagger time, or the target's commit time for lightweight tags (Unix seconds)
+    pub time: i64,
+}
+
+/// Sort order of the tag list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TagSort {
+    /// Highest version first (numeric parts compared as numbers)
+    #[default]
+    Version,
+    /// Most recent first
+    Date,
+}
+
+impl TagSort {
+    pub fn next(self) -> Self {
+        match self {
+            TagSort::Version => TagSort::Date,
+            TagSort::Date => TagSort::Version,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TagSort::Version => "version",
+            TagSort::Date => "date",
+        }
+    }
+}
+
+impl TagInfo {
+    /// List tags that point to commits (tags of trees or blobs are skipped)
+    pub fn list_all(repo: &Repository) -> Result<Vec<Self>> {
+        let mut tags = Vec::new();
+        for reference in repo.references_glob("refs/tags/*")? {
+            let reference = reference?;
+            let Ok(commit) = reference.peel_to_commit() else {
+                continue;
+            };
+            let name = ref_name_lossy(reference.shorthand_bytes());
+            let annotated = reference
+                .peel(ObjectType::Tag)
+                .ok()
+                .and_then(|object| object.into_tag().ok());
+
+            let (tagger, message, time) = match &annotated {
+                Some(tag) => {
+                    let signature = tag.tagger();
+                    (
+                        signature
+                            .as_ref()
+                            .map(|s| String::from_utf8_lossy(s.name_bytes()).to_string()),
+                        tag.message_bytes().map(|m| {
+                            let message = String::from_utf8_lossy(m);
+                            message.lines().next().unwrap_or("").to_string()
+                        }),
+                        signature
+                            .map(|s| s.when().seconds())
+                            .unwrap_or_else(|| commit.time().seconds()),
+                    )
+                }
+                None => (None, None, commit.time().seconds()),
+            };
+
+            tags.push(Self {
+                name,
+                target: commit.id(),
+                tagger,
+                message,
+                time,
+            });
+        }
+        Ok(tags)
+    }
+}
+
+/// Tag names per commit (annotated tags peeled), sorted by version
+pub fn tags_by_commit(repo: &Repository) -> Result<HashMap<Oid, Vec<String>>> {
+    let mut tags: HashMap<Oid, Vec<String>> = HashMap::new();
+    for reference in repo.references_glob("refs/tags/*")? {
+        let reference = reference?;
+        if let Ok(commit) = reference.peel_to_commit() {
+            tags.entry(commit.id())
+                .or_default()
+                .push(ref_name_lossy(reference.shorthand_bytes()));
+        }
+    }
+    for names in tags.values_mut() {
+        names.sort_by(|a, b| compare_versions(b, a));
+    }
+    Ok(tags)
+}
+
+/// Most recent tag reachable from a commit and how many commits follow it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagDistance {
+    pub tag: String,
+    /// Commits since the tag (0 when the commit is tagged)
+    pub commits: usize,
+}
+
+impl TagDistance {
+    /// Like `git describe --tags`; None when no tag is reachable
+    pub fn of(repo: &Repository, oid: Oid) -> Result<Option<Self>> {
+        let object = repo.find_object(oid, Some(ObjectType::Commit))?;
+        let describe = match object.describe(DescribeOptions::new().describe_tags()) {
+            Ok(describe) => describe,
+            Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        // "<tag>-<count>-g<hash>"; tag names may contain dashes themselves
+        let described = describe.format(Some(
+            DescribeFormatOptions::new().always_use_long_format(true),
+        ))?;
+        let mut parts = described.rsplitn(3, '-');
+        let (_hash, count, tag) = (parts.next(), parts.next(), parts.next());
+        Ok(count
+            .and_then(|c| c.parse().ok())
+            .zip(tag)
+            .map(|(commits, tag)| Self {
+                tag: tag.to_string(),
+                commits,
+            }))
+    }
+
+    /// "14 commits since v1.2.0" (or "at v1.2.0" on the tagged commit)
+    pub fn describe(&self) -> String {
+        match self.commits {
+            0 => format!("at {}", self.tag),
+            1 => format!("1 commit since {}", self.tag),
+            n => format!("{} commits since {}", n, self.tag),
+        }
+    }
+}
+
+/// Sort tags for display
+pub fn sort_tags(tags: &mut [TagInfo], sort: TagSort) {
+    tags.sort_by(|a, b| match sort {
+        TagSort::Version => compare_versions(&b.name, &a.name),
+        TagSort::Date => b.time.cmp(&a.time).then_with(|| a.name.cmp(&b.name)),
+    });
+}
+
+/// Compare names so that digit runs order numerically ("v1.10" > "v1.9")
+/// A pre-release suffix sorts below the release ("v1.0-rc1" < "v1.0")
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(ca), Some(cb)) = (a.chars().next(), b.chars().next()) else {
+            return match (a.is_empty(), b.is_empty()) {
+                (true, false) if b.starts_with('-') => Ordering::Greater,
+                (false, true) if a.starts_with('-') => Ordering::Less,
+                _ => a.len().cmp(&b.len()),
+            };
+        };
+        let ordering = if ca.is_ascii_digit() && cb.is_ascii_digit() {
+            let (na, rest_a) = split_digits(a);
+            let (nb, rest_b) = split_digits(b);
+            a = rest_a;
+            b = rest_b;
+            let (na, nb) = (na.trim_start_matches('0'), nb.trim_start_matches('0'));
+            na.len().cmp(&nb.len()).then_with(|| na.cmp(nb))
+        } else {
+            a = &a[ca.len_utf8()..];
+            b = &b[cb.len_utf8()..];
+            ca.cmp(&cb)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str, time: i64) -> TagInfo {
+        TagInfo {
+            name: name.to_string(),
+            target: Oid::zero(),
+            tagger: None,
+            message: None,
+            time,
+        }
+    }
+
+    fn sorted(tags: &[TagInfo], sort: TagSort) -> Vec<String> {
+        let mut tags = tags.to_vec();
+        sort_tags(&mut tags, sort);
+        tags.into_iter().map(|t| t.name).collect()
+    }
+
+    #[test]
+    fn test_sort_tags_by_version_and_date() {
+        let tags = vec![
+            tag("v1.9.0", 300),
+            tag("v1.10.0", 200),
+            tag("v1.10.0-rc1", 100),
+            tag("v2.0.0", 50),
+            tag("v1.2", 400),
+        ];
+
+        assert_eq!(
+            sorted(&tags, TagSort::Version),
+            ["v2.0.0", "v1.10.0", "v1.10.0-rc1", "v1.9.0", "v1.2"]
+        );
+        assert_eq!(
+            sorted(&tags, TagSort::Date),
+            ["v1.2", "v1.9.0", "v1.10.0", "v1.10.0-rc1", "v2.0.0"]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! Branch color management
 
 use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashSet, VecDeque};
 
 /// Per-lane color palette (11-color rotation)
@@ -32,9 +33,22 @@ pub fn get_color_by_index(color_index: usize) -> Color {
 /// Main branch color (light blue)
 pub const MAIN_BRANCH_COLOR: usize = 9; // Color::LightBlue
 
+/// How lane colors are picked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorScheme {
+    /// Penalty-based: neighbors, recent rows and fork siblings get distinct colors,
+    /// and the main branch is always blue
+    #[default]
+    Balanced,
+    /// The palette in lane order (`lane % palette size`): a lane keeps its color
+    Rotation,
+}
+
 /// Color assignment to vary colors when lanes are reused
 #[derive(Debug)]
 pub struct ColorAssigner {
+    scheme: ColorScheme,
     /// Current color index assigned to each lane
     lane_colors: Vec<Option<usize>>,
     /// Last color index used per lane (for reuse)
@@ -59,7 +73,12 @@ pub struct ColorAssigner {
 
 impl ColorAssigner {
     pub fn new() -> Self {
+        Self::with_scheme(ColorScheme::default())
+    }
+
+    pub fn with_scheme(scheme: ColorScheme) -> Self {
         Self {
+            scheme,
             lane_colors: Vec::new(),
             lane_last_color: Vec::new(),
             next_color_index: 0,
@@ -123,6 +142,13 @@ impl ColorAssigner {
     ) -> usize {
         self.ensure_capacity(lane);
 
+        if self.scheme == ColorScheme::Rotation {
+            let color = lane % LANE_COLORS.len();
+            self.lane_colors[lane] = Some(color);
+            self.lane_last_color[lane] = color;
+            return color;
+        }
+
         // Compute penalties for each color
         let mut color_penalties: [f64; 11] = [0.0; 11];
 
@@ -166,24 +192,16 @@ impl ColorAssigner {
             }
         }
 
-        // Choose the best color (lowest penalty)
-        let mut best_color = self.next_color_index;
-        let mut best_penalty = f64::MAX;
-
-        for candidate in 0..LANE_COLORS.len() {
-            let color_idx = (self.next_color_index + candidate) % LANE_COLORS.len();
-
-            // Skip reserved colors when use_reserved is false
-            if !use_reserved && self.reserved_colors.contains(&color_idx) {
-                continue;
-            }
-
-            let penalty = color_penalties[color_idx];
-            if penalty < best_penalty {
-                best_penalty = penalty;
-                best_color = color_idx;
-            }
-        }
+        // Choose the best color (lowest penalty), never the color of an adjacent lane:
+        // parallel lines of the same color cannot be told apart
+        let neighbors = self.neighbor_colors(lane);
+        let best_color = self
+            .lowest_penalty(&color_penalties, use_reserved, |color| {
+                !neighbors.contains(&Some(color))
+            })
+            // Only a palette smaller than three usable colors could run out
+            .or_else(|| self.lowest_penalty(&color_penalties, use_reserved, |_| true))
+            .unwrap_or(self.next_color_index);
 
         // Update state
         self.lane_colors[lane] = Some(best_color);
@@ -208,6 +226,35 @@ impl ColorAssigner {
         best_color
     }
 
+    /// Colors of the active lanes on either side of `lane`
+    fn neighbor_colors(&self, lane: usize) -> [Option<usize>; 2] {
+        let left = lane
+            .checked_sub(1)
+            .and_then(|l| self.get_lane_color_index(l));
+        [left, self.get_lane_color_index(lane + 1)]
+    }
+
+    /// Allowed color with the lowest penalty, trying colors from `next_color_index` on
+    /// Reserved colors are skipped unless `use_reserved` is set
+    fn lowest_penalty(
+        &self,
+        penalties: &[f64; 11],
+        use_reserved: bool,
+        allowed: impl Fn(usize) -> bool,
+    ) -> Option<usize> {
+        let mut best: Option<(usize, f64)> = None;
+        for candidate in 0..LANE_COLORS.len() {
+            let color_idx = (self.next_color_index + candidate) % LANE_COLORS.len();
+            if (!use_reserved && self.reserved_colors.contains(&color_idx)) || !allowed(color_idx) {
+                continue;
+            }
+            if best.is_none_or(|(_, penalty)| penalties[color_idx] < penalty) {
+                best = Some((color_idx, penalties[color_idx]));
+            }
+        }
+        best.map(|(color, _)| color)
+    }
+
     /// Assign a color to a new branch (do not use reserved colors)
     pub fn assign_color(&mut self, lane: usize) -> usize {
         self.assign_color_advanced(lane, false, false)
@@ -219,7 +266,11 @@ impl ColorAssigner {
     }
 
     /// Assign a color to the main branch (fixed blue, reserve it)
+    /// The rotation scheme colors it by lane like any other branch
     pub fn assign_main_color(&mut self, lane: usize) -> usize {
+        if self.scheme == ColorScheme::Rotation {
+            return self.assign_color_advanced(lane, false, true);
+        }
         self.ensure_capacity(lane);
         let color = MAIN_BRANCH_COLOR;
         self.lane_colors[lane] = Some(color);
@@ -0,0 +1,206 @@
+//! Glyph sets for drawing graph cells
+
+use serde::Deserialize;
+
+use crate::git::graph::{arms, CellType, DOWN, LEFT, RIGHT, UP};
+
+/// How graph cells are drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphGlyphs {
+    /// Box-drawing characters, two columns per lane
+    #[default]
+    Box,
+    /// Braille dots, one column per lane (needs a font with fine Braille glyphs)
+    Braille,
+}
+
+impl GraphGlyphs {
+    /// Terminal columns used by a graph with lanes up to `max_lane`
+    pub fn width(self, max_lane: usize) -> usize {
+        match self {
+            GraphGlyphs::Box => (max_lane + 1) * 2,
+            GraphGlyphs::Braille => max_lane + 1,
+        }
+    }
+}
+
+/// Box-drawing character for a cell
+pub fn box_glyph(cell: CellType, is_head: bool) -> char {
+    match cell {
+        CellType::Empty => ' ',
+        CellType::Pipe(_) => '│',
+        // HEAD uses a double circle, others use a filled circle
+        CellType::Commit(_) if is_head => '◉',
+        CellType::Commit(_) => '●',
+        CellType::BranchRight(_) => '╭',
+        CellType::BranchLeft(_) => '╮',
+        CellType::MergeRight(_) => '╰',
+        CellType::MergeLeft(_) => '╯',
+        CellType::Horizontal(_) => '─',
+        CellType::HorizontalPipe(..) => '┼',
+        CellType::TeeRight(_) => '├',
+        CellType::TeeLeft(_) => '┤',
+        CellType::TeeUp(_) => '┴',
+        CellType::TeeDown(_) => '┬',
+    }
+}
+
+/// Braille dot bit for a column (0-1) and row (0-3) of the 2x4 dot grid
+fn dot(col: usize, row: usize) -> u8 {
+    const BITS: [[u8; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+    BITS[col][row]
+}
+
+/// Dot rows a cell is drawn over: the whole character or one half of it
+#[derive(Debug, Clone, Copy)]
+enum Band {
+    Full,
+    Upper,
+    Lower,
+}
+
+impl Band {
+    /// (top, link, bottom) dot rows; links run along the link row
+    fn rows(self) -> (usize, usize, usize) {
+        match self {
+            Band::Full => (0, 1, 3),
+            Band::Upper => (0, 1, 1),
+            Band::Lower => (2, 2, 3),
+        }
+    }
+}
+
+/// Braille characters for a row of cells, each paired with the cell that colors it
+/// A lane and the gap to its right share a character: the lane runs down the left dot
+/// column and links run along the second dot row, so joins sit in the upper half
+/// With `upper` (a fork connector folded into this row), the connector takes the
+/// upper two dot rows and the row's own cells the lower two
+pub fn braille_glyphs(
+    cells: &[CellType],
+    upper: Option<&[CellType]>,
+    is_head: bool,
+) -> Vec<(char, CellType)> {
+    let at = |row: &[CellType], idx: usize| row.get(idx).copied().unwrap_or(CellType::Empty);
+    let width = cells.len().max(upper.map_or(0, |u| u.len()));
+    (0..width)
+        .step_by(2)
+        .map(|idx| {
+            let (lane, gap) = (at(cells, idx), at(cells, idx + 1));
+            let band = if upper.is_some() {
+                Band::Lower
+            } else {
+                Band::Full
+            };
+            let mut dots = pair_dots(lane, gap, band, is_head);
+            let mut color_cell = if lane == CellType::Empty { gap } else { lane };
+            if let Some(upper) = upper {
+                let (lane, gap) = (at(upper, idx), at(upper, idx + 1));
+                dots |= pair_dots(lane, gap, Band::Upper, false);
+                if color_cell == CellType::Empty {
+                    color_cell = if lane == CellType::Empty { gap } else { lane };
+                }
+            }
+            let ch = char::from_u32(0x2800 + dots as u32).unwrap_or(' ');
+            (ch, color_cell)
+        })
+        .collect()
+}
+
+/// Dots of a lane cell and the gap to its right, drawn over `band`
+fn pair_dots(lane: CellType, gap: CellType, band: Band, is_head: bool) -> u8 {
+    let (top, link, bottom) = band.rows();
+    let column = |col: usize| (top..=bottom).map(|row| dot(col, row)).sum::<u8>();
+    let mut dots = match lane {
+        CellType::Commit(_) if is_head => column(0) | column(1),
+        // A node is the lane line widened over the middle rows
+        CellType::Commit(_) => match band {
+            Band::Full => column(0) | dot(1, 1) | dot(1, 2),
+            Band::Upper | Band::Lower => column(0) | dot(1, link),
+        },
+        cell => lane_dots(arms(cell).unwrap_or(0), band),
+    };
+    if arms(gap).is_some_and(|a| a & (LEFT | RIGHT) != 0) {
+        dots |= dot(1, link);
+    }
+    dots
+}
+
+/// Dots of a lane cell with the given arms
+fn lane_dots(arms: u8, band: Band) -> u8 {
+    let (top, link, bottom) = band.rows();
+    let mut dots = 0;
+    if arms & UP != 0 {
+        dots |= (top..=link).map(|row| dot(0, row)).sum::<u8>();
+    }
+    if arms & DOWN != 0 {
+        dots |= (link..=bottom).map(|row| dot(0, row)).sum::<u8>();
+    }
+    if arms & (LEFT | RIGHT) != 0 {
+        dots |= dot(0, link);
+    }
+    if arms & RIGHT != 0 {
+        dots |= dot(1, link);
+    }
+    dots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn braille(cells: &[CellType]) -> String {
+        braille_glyphs(cells, None, false)
+            .into_iter()
+            .map(|(ch, _)| ch)
+            .collect()
+    }
+
+    #[test]
+    fn test_braille_uses_one_column_per_lane() {
+        // ●─╮ / │ │ / ├─╯
+        let merge = [
+            CellType::Commit(0),
+            CellType::Horizontal(1),
+            CellType::BranchLeft(1),
+            CellType::Empty,
+        ];
+        assert_eq!(braille(&merge), "⡷⡆");
+        assert_eq!(
+            braille(&[CellType::Pipe(0), CellType::Empty, CellType::Pipe(1)]),
+            "⡇⡇"
+        );
+        let fork = [
+            CellType::TeeRight(0),
+            CellType::Horizontal(1),
+            CellType::MergeLeft(1),
+            CellType::Empty,
+        ];
+        assert_eq!(braille(&fork), "⡗⠃");
+        assert_eq!(GraphGlyphs::Braille.width(1), merge.len() / 2);
+    }
+
+    #[test]
+    fn test_braille_draws_a_folded_connector_in_the_upper_half() {
+        // ├─╯ above the fork point's ●
+        let connector = [
+            CellType::TeeRight(0),
+            CellType::Horizontal(1),
+            CellType::MergeLeft(1),
+        ];
+        let glyphs = braille_glyphs(&[CellType::Commit(0)], Some(&connector), false);
+        let text: String = glyphs.iter().map(|(ch, _)| *ch).collect();
+        assert_eq!(text, "⡷⠃");
+        // The row's own cells color the character; the connector fills in where it is empty
+        assert_eq!(glyphs[0].1, CellType::Commit(0));
+        assert_eq!(glyphs[1].1, CellType::MergeLeft(1));
+    }
+
+    #[test]
+    fn test_braille_colors_follow_the_lane_cell() {
+        let glyphs = braille_glyphs(&[CellType::Empty, CellType::Horizontal(3)], None, true);
+        assert_eq!(glyphs, [('⠐', CellType::Horizontal(3))]);
+        let glyphs = braille_glyphs(&[CellType::Commit(2), CellType::Empty], None, true);
+        assert_eq!(glyphs, [('⣿', CellType::Commit(2))]);
+    }
+}
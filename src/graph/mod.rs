@@ -1,3 +1,5 @@
 //! Graph rendering layer
 
 pub mod colors;
+pub mod glyphs;
+pub mod text;
@@ -0,0 +1,166 @@
+//! Plain-text rendering of graph rows (for copying outside the terminal)
+
+use crate::git::graph::{CellType, GraphNode};
+
+/// ASCII stand-in for a graph cell (`*` commit, `@` HEAD, `|` lane, `-` link, `+` corners)
+pub fn ascii_glyph(cell: CellType, is_head: bool) -> char {
+    match cell {
+        CellType::Empty => ' ',
+        CellType::Pipe(_) => '|',
+        CellType::Commit(_) if is_head => '@',
+        CellType::Commit(_) => '*',
+        CellType::Horizontal(_) => '-',
+        CellType::HorizontalPipe(..)
+        | CellType::BranchRight(_)
+        | CellType::BranchLeft(_)
+        | CellType::MergeRight(_)
+        | CellType::MergeLeft(_)
+        | CellType::TeeRight(_)
+        | CellType::TeeLeft(_)
+        | CellType::TeeUp(_)
+        | CellType::TeeDown(_) => '+',
+    }
+}
+
+/// One line per row, like `git log --graph --oneline --decorate`
+/// `head_name` is the checked-out branch, shown as "HEAD -> name"
+pub fn rows_to_text(nodes: &[GraphNode], head_name: Option<&str>) -> String {
+    // Pad the graph to the widest row so the text columns line up
+    let graph_width = nodes
+        .iter()
+        .flat_map(|n| std::iter::once(&n.cells).chain(&n.upper_cells))
+        .map(|cells| cells.len())
+        .max()
+        .unwrap_or(0);
+    let mut text = String::new();
+    for node in nodes {
+        // A connector folded into the row's upper half gets its own line again
+        if let Some(upper) = &node.upper_cells {
+            let line: String = upper.iter().map(|cell| ascii_glyph(*cell, false)).collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        let mut line: String = node
+            .cells
+            .iter()
+            .map(|cell| ascii_glyph(*cell, node.is_head))
+            .collect();
+        line.push_str(&" ".repeat(graph_width - node.cells.len() + 1));
+
+        if node.is_uncommitted {
+            line.push_str(&format!("uncommitted changes ({})", node.uncommitted_count));
+        } else if let Some(span) = node.elided {
+            line.push_str(&format!("... {} ...", span.describe()));
+        } else if let Some(commit) = &node.commit {
+            line.push_str(&commit.short_id);
+            line.push(' ');
+            let decoration = decoration(node, head_name);
+            if !decoration.is_empty() {
+                line.push_str(&format!("({}) ", decoration.join(", ")));
+            }
+            line.push_str(&commit.message);
+        }
+
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// Ref names for a commit row, with HEAD first
+fn decoration(node: &GraphNode, head_name: Option<&str>) -> Vec<String> {
+    let mut names = Vec::new();
+    let head_branch = head_name.filter(|name| node.branch_names.iter().any(|b| b == name));
+    if node.is_head {
+        names.push(match head_branch {
+            Some(name) => format!("HEAD -> {}", name),
+            None => "HEAD".to_string(),
+        });
+    }
+    names.extend(
+        node.branch_names
+            .iter()
+            .filter(|name| !node.is_head || Some(name.as_str()) != head_branch)
+            .cloned(),
+    );
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+    use git2::Oid;
+
+    use super::*;
+    use crate::git::{
+        build_graph, build_graph_with_options,
+        graph::{GraphDensity, LayoutOptions},
+        BranchInfo, CommitInfo,
+    };
+
+    fn commit(id: u8, parents: &[u8]) -> CommitInfo {
+        CommitInfo {
+            oid: Oid::from_bytes(&[id; 20]).unwrap(),
+            short_id: format!("c{}", id),
+            author_name: "test".to_string(),
+            author_email: "test@example.com".to_string(),
+            timestamp: Local::now(),
+            author_time: 0,
+            message: format!("commit {}", id),
+            full_message: format!("commit {}", id),
+            parent_oids: parents
+                .iter()
+                .map(|p| Oid::from_bytes(&[*p; 20]).unwrap())
+                .collect(),
+        }
+    }
+
+    fn branch(name: &str, tip: u8, is_head: bool) -> BranchInfo {
+        BranchInfo {
+            name: name.to_string(),
+            tip_oid: Oid::from_bytes(&[tip; 20]).unwrap(),
+            is_head,
+            is_remote: false,
+            upstream: None,
+            tip_time: 0,
+        }
+    }
+
+    #[test]
+    fn test_rows_to_text_uses_ascii_and_decorations() {
+        let commits = vec![commit(3, &[1]), commit(2, &[1]), commit(1, &[])];
+        let branches = vec![
+            branch("main", 3, true),
+            branch("origin/main", 3, false),
+            branch("topic", 2, false),
+        ];
+        let layout = build_graph(&commits, &branches, None, None);
+
+        let text = rows_to_text(&layout.nodes, Some("main"));
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "@    c3 (HEAD -> main, origin/main) commit 3");
+        assert_eq!(lines[1], "| *  c2 (topic) commit 2");
+        assert_eq!(lines[2], "*-+  c1 commit 1");
+        assert_eq!(lines.len(), 3);
+        assert!(text.is_ascii());
+    }
+
+    #[test]
+    fn test_rows_to_text_gives_half_row_connectors_a_line() {
+        let commits = vec![commit(3, &[1]), commit(2, &[1]), commit(1, &[])];
+        let layout_with = |half_rows| {
+            let options = LayoutOptions {
+                density: GraphDensity::Spacious,
+                half_rows,
+                ..Default::default()
+            };
+            build_graph_with_options(&commits, &[], None, None, &options)
+        };
+        let half = layout_with(true);
+        assert_eq!(half.nodes.len(), commits.len());
+        assert_eq!(
+            rows_to_text(&half.nodes, None),
+            rows_to_text(&layout_with(false).nodes, None)
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Per-commit marks shown in the gutter column left of the graph lanes
+
+use std::collections::HashMap;
+
+use git2::Oid;
+use ratatui::style::Color;
+
+/// Gutter width in cells while any mark is present (symbol + space)
+pub const GUTTER_WIDTH: usize = 2;
+
+/// Marks, in priority order (earlier variants win when several apply to one commit)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GutterMark {
+    /// Upstream of the selected branch label, until the next key
+    Upstream,
+    /// First bad commit found by bisect
+    BisectFirstBad,
+    /// Marked bad during bisect
+    BisectBad,
+    /// Marked good during bisect
+    BisectGood,
+    /// Where the current branch and its upstream diverged
+    UpstreamFork,
+    /// Commit given with `--rev`
+    StartRev,
+    /// Bookmarked commit
+    Bookmark,
+    /// Made by a cherry-pick or rebase in this session
+    Rewritten,
+    /// Branch matching the current search
+    SearchMatch,
+}
+
+impl GutterMark {
+    pub fn symbol(self) -> char {
+        match self {
+            GutterMark::Upstream => '⇡',
+            GutterMark::BisectFirstBad => '✗',
+            GutterMark::BisectBad => '-',
+            GutterMark::BisectGood => '+',
+            GutterMark::UpstreamFork => '⋔',
+            GutterMark::StartRev => '\'',
+            GutterMark::Bookmark => '★',
+            GutterMark::Rewritten => '↻',
+            GutterMark::SearchMatch => '›',
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            GutterMark::Upstream => Color::LightCyan,
+            GutterMark::BisectFirstBad | GutterMark::BisectBad => Color::Red,
+            GutterMark::BisectGood => Color::Green,
+            GutterMark::UpstreamFork => Color::Magenta,
+            GutterMark::StartRev => Color::Blue,
+            GutterMark::Bookmark => Color::Yellow,
+            GutterMark::Rewritten => Color::DarkGray,
+            GutterMark::SearchMatch => Color::Cyan,
+        }
+    }
+}
+
+/// Highest-priority mark per commit
+#[derive(Debug, Default)]
+pub struct Gutter {
+    marks: HashMap<Oid, GutterMark>,
+}
+
+impl Gutter {
+    pub fn clear(&mut self) {
+        self.marks.clear();
+    }
+
+    /// Add a mark, keeping the existing one if it has higher priority
+    pub fn add(&mut self, oid: Oid, mark: GutterMark) {
+        self.marks
+            .entry(oid)
+            .and_modify(|existing| *existing = (*existing).min(mark))
+            .or_insert(mark);
+    }
+
+    pub fn get(&self, oid: Oid) -> Option<GutterMark> {
+        self.marks.get(&oid).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+
+    /// Current gutter width (collapses to zero when there are no marks)
+    pub fn width(&self) -> usize {
+        if self.is_empty() {
+            0
+        } else {
+            GUTTER_WIDTH
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oid(n: u8) -> Oid {
+        Oid::from_bytes(&[n; 20]).unwrap()
+    }
+
+    #[test]
+    fn test_highest_priority_mark_wins() {
+        let mut gutter = Gutter::default();
+        gutter.add(oid(1), GutterMark::SearchMatch);
+        gutter.add(oid(1), GutterMark::Bookmark);
+        gutter.add(oid(1), GutterMark::SearchMatch);
+        assert_eq!(gutter.get(oid(1)), Some(GutterMark::Bookmark));
+
+        gutter.add(oid(1), GutterMark::BisectFirstBad);
+        assert_eq!(gutter.get(oid(1)), Some(GutterMark::BisectFirstBad));
+        assert_eq!(gutter.get(oid(2)), None);
+    }
+
+    #[test]
+    fn test_width_collapses_without_marks() {
+        let mut gutter = Gutter::default();
+        assert_eq!(gutter.width(), 0);
+        gutter.add(oid(1), GutterMark::Bookmark);
+        assert_eq!(gutter.width(), GUTTER_WIDTH);
+        gutter.clear();
+        assert_eq!(gutter.width(), 0);
+    }
+}
@@ -1,10 +1,121 @@
 //! Keybindings
 
+use std::time::{Duration, Instant};
+
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use crate::action::Action;
 use crate::app::AppMode;
 
+/// A two-key sequence in normal mode
+pub struct KeySequence {
+    pub prefix: char,
+    pub key: char,
+    pub action: Action,
+    /// Shown in the help popup
+    pub description: &'static str,
+}
+
+/// Normal mode sequences; a prefix alone keeps its single-key meaning once the timeout passes
+pub const KEY_SEQUENCES: &[KeySequence] = &[
+    KeySequence {
+        prefix: 'g',
+        key: 'g',
+        action: Action::GoToTop,
+        description: "Go to top",
+    },
+    KeySequence {
+        prefix: 'g',
+        key: 'e',
+        action: Action::GoToBottom,
+        description: "Go to bottom",
+    },
+    KeySequence {
+        prefix: 'g',
+        key: 'h',
+        action: Action::JumpToHead,
+        description: "Jump to HEAD",
+    },
+];
+
+/// Resolves multi-key sequences before keys are mapped to actions
+/// After a prefix key nothing is dispatched until the next key or the timeout:
+/// a matching key runs the sequence, Esc cancels, and anything else runs the prefix's
+/// own action before being handled as usual
+#[derive(Debug, Clone)]
+pub struct KeySequencer {
+    timeout: Duration,
+    /// Prefix typed and when
+    pending: Option<(char, Instant)>,
+}
+
+impl KeySequencer {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            pending: None,
+        }
+    }
+
+    /// Prefix waiting for its second key (shown in the status bar)
+    pub fn pending(&self) -> Option<char> {
+        self.pending.map(|(prefix, _)| prefix)
+    }
+
+    /// Actions for a key press, in order (none while a sequence is pending)
+    pub fn feed(&mut self, key: KeyEvent, mode: &AppMode, now: Instant) -> Vec<Action> {
+        let mut actions = Vec::new();
+        if let Some((prefix, _)) = self.pending.take() {
+            if key.code == KeyCode::Esc {
+                return actions;
+            }
+            if let Some(sequence) = plain_char(key).and_then(|c| {
+                KEY_SEQUENCES
+                    .iter()
+                    .find(|s| s.prefix == prefix && s.key == c)
+            }) {
+                actions.push(sequence.action.clone());
+                return actions;
+            }
+            actions.extend(prefix_action(prefix));
+        }
+
+        match plain_char(key) {
+            Some(c)
+                if matches!(mode, AppMode::Normal)
+                    && KEY_SEQUENCES.iter().any(|s| s.prefix == c) =>
+            {
+                self.pending = Some((c, now));
+            }
+            _ => actions.extend(map_key_to_action(key, mode)),
+        }
+        actions
+    }
+
+    /// The prefix's own action once the timeout has passed without a second key
+    pub fn tick(&mut self, now: Instant) -> Option<Action> {
+        let (prefix, since) = self.pending?;
+        if now.duration_since(since) < self.timeout {
+            return None;
+        }
+        self.pending = None;
+        prefix_action(prefix)
+    }
+}
+
+/// The character of an unmodified (or shifted) key press
+fn plain_char(key: KeyEvent) -> Option<char> {
+    match (key.modifiers, key.code) {
+        (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c)) => Some(c),
+        _ => None,
+    }
+}
+
+/// What a sequence prefix does on its own
+fn prefix_action(prefix: char) -> Option<Action> {
+    map_normal_mode(KeyEvent::new(KeyCode::Char(prefix), KeyModifiers::NONE))
+}
+
 pub fn map_key_to_action(key: KeyEvent, mode: &AppMode) -> Option<Action> {
     match mode {
         AppMode::Normal => map_normal_mode(key),
@@ -12,12 +123,27 @@ pub fn map_key_to_action(key: KeyEvent, mode: &AppMode) -> Option<Action> {
         AppMode::Input { action, .. } => {
             if *action == crate::app::InputAction::Search {
                 map_search_mode(key)
+            } else if *action == crate::app::InputAction::Command {
+                map_command_mode(key)
             } else {
                 map_input_mode(key)
             }
         }
         AppMode::Confirm { .. } => map_confirm_mode(key),
         AppMode::Error { .. } => map_error_mode(key),
+        AppMode::RawCommit { .. } | AppMode::RepoInfo { .. } | AppMode::BranchDiff { .. } => {
+            map_scroll_popup_mode(key)
+        }
+        AppMode::DiffView { .. } => map_diff_view_mode(key),
+        AppMode::Visual { .. } => map_visual_mode(key),
+        AppMode::Tags { .. } => map_tags_mode(key),
+        AppMode::StashList { .. } => map_stash_list_mode(key),
+        AppMode::CheckoutBlocked { .. } => map_checkout_blocked_mode(key),
+        AppMode::RetargetBranches { .. } => map_retarget_mode(key),
+        AppMode::Conflict { .. } => map_conflict_mode(key),
+        AppMode::CommitMenu { .. } => map_commit_menu_mode(key),
+        AppMode::PickChild { .. } => map_pick_child_mode(key),
+        AppMode::QuitBlocked { .. } => map_quit_blocked_mode(key),
     }
 }
 
@@ -54,6 +180,26 @@ fn map_normal_mode(key: KeyEvent) -> Option<Action> {
             Some(Action::PrevBranch)
         }
 
+        // Bookmark jump (braces work with or without Shift depending on keyboard layout)
+        (_, KeyCode::Char('}')) => Some(Action::NextBookmark),
+        (_, KeyCode::Char('{')) => Some(Action::PrevBookmark),
+
+        // Parent / child jump (< and > work with or without Shift depending on keyboard layout)
+        (_, KeyCode::Char('<')) => Some(Action::JumpToParent),
+        (_, KeyCode::Char('>')) => Some(Action::JumpToChild),
+        (_, KeyCode::Char('\'')) => Some(Action::JumpToStartRev),
+        // % works with or without Shift depending on keyboard layout
+        (_, KeyCode::Char('%')) => Some(Action::JumpToProvenance),
+
+        // Bookmarks
+        (KeyModifiers::SHIFT, KeyCode::Char('M')) => Some(Action::ToggleBookmark),
+        (KeyModifiers::CONTROL, KeyCode::Char('x')) => Some(Action::ClearBookmarks),
+
+        // Bisect (+/- work with or without Shift depending on keyboard layout)
+        (_, KeyCode::Char('+')) => Some(Action::BisectGood),
+        (_, KeyCode::Char('-')) => Some(Action::BisectBad),
+        (KeyModifiers::SHIFT, KeyCode::Char('B')) => Some(Action::BisectReset),
+
         // Branch selection within same commit
         (KeyModifiers::NONE, KeyCode::Char('h')) | (KeyModifiers::NONE, KeyCode::Left) => {
             Some(Action::BranchLeft)
@@ -66,19 +212,45 @@ fn map_normal_mode(key: KeyEvent) -> Option<Action> {
         (KeyModifiers::NONE, KeyCode::Enter) => Some(Action::CopyHash),
         (KeyModifiers::NONE, KeyCode::Char('c')) => Some(Action::Checkout),
         (KeyModifiers::NONE, KeyCode::Char('b')) => Some(Action::CreateBranch),
+        (KeyModifiers::NONE, KeyCode::Char('e')) => Some(Action::RenameBranch),
         (KeyModifiers::NONE, KeyCode::Char('d')) => Some(Action::DeleteBranch),
         (KeyModifiers::NONE, KeyCode::Char('f')) => Some(Action::Fetch),
-        // TODO: merge and rebase will be implemented in the future
-        // (KeyModifiers::NONE, KeyCode::Char('m')) => Some(Action::Merge),
-        // (KeyModifiers::NONE, KeyCode::Char('r')) => Some(Action::Rebase),
+        (KeyModifiers::SHIFT, KeyCode::Char('F')) => Some(Action::Pull),
+        (KeyModifiers::CONTROL, KeyCode::Char('c')) => Some(Action::CancelFetch),
+        (KeyModifiers::NONE, KeyCode::Char('.')) => Some(Action::RepeatLastOperation),
+        (KeyModifiers::SHIFT, KeyCode::Char('C')) => Some(Action::ShowCommitMenu),
+        (KeyModifiers::NONE, KeyCode::Char('m')) => Some(Action::Merge),
+        (KeyModifiers::NONE, KeyCode::Char('r')) => Some(Action::Rebase),
+        (KeyModifiers::NONE, KeyCode::Char('p')) => Some(Action::CherryPick),
+        (KeyModifiers::NONE, KeyCode::Char('s')) => Some(Action::Stash),
+        (KeyModifiers::SHIFT, KeyCode::Char('S')) => Some(Action::ShowStashes),
 
         // UI
         (KeyModifiers::NONE, KeyCode::Char('/')) => Some(Action::Search),
+        (_, KeyCode::Char(':')) => Some(Action::CommandPalette),
         (KeyModifiers::SHIFT, KeyCode::Char('R')) => Some(Action::Refresh),
-        (KeyModifiers::NONE, KeyCode::Char('?')) => Some(Action::ToggleHelp),
-        (KeyModifiers::NONE, KeyCode::Char('q')) | (KeyModifiers::NONE, KeyCode::Esc) => {
-            Some(Action::Quit)
+        (KeyModifiers::SHIFT, KeyCode::Char('N')) => Some(Action::ToggleHideMerges),
+        (KeyModifiers::NONE, KeyCode::Char('z')) => Some(Action::ToggleCompactGraph),
+        (KeyModifiers::SHIFT, KeyCode::Char('L')) => Some(Action::ToggleLaneColors),
+        (KeyModifiers::SHIFT, KeyCode::Char('D')) => Some(Action::FilterDates),
+        (KeyModifiers::SHIFT, KeyCode::Char('X')) => Some(Action::ClearDateRange),
+        (KeyModifiers::NONE, KeyCode::Char('a')) => Some(Action::ToggleWalkScope),
+        (KeyModifiers::SHIFT, KeyCode::Char('H')) => Some(Action::ToggleFollowHead),
+        (KeyModifiers::NONE, KeyCode::Char('w')) => Some(Action::ToggleRawDiff),
+        (KeyModifiers::SHIFT, KeyCode::Char('A')) => Some(Action::ToggleFileAnnotations),
+        (KeyModifiers::NONE, KeyCode::Char('o')) => Some(Action::ShowRawCommit),
+        (KeyModifiers::NONE, KeyCode::Char(' ')) | (KeyModifiers::NONE, KeyCode::Char('v')) => {
+            Some(Action::OpenDiff)
         }
+        (KeyModifiers::NONE, KeyCode::Char('i')) => Some(Action::ShowRepoInfo),
+        (KeyModifiers::SHIFT, KeyCode::Char('T')) => Some(Action::ShowTags),
+        (KeyModifiers::SHIFT, KeyCode::Char('V')) => Some(Action::ToggleVisual),
+        (KeyModifiers::SHIFT, KeyCode::Char('U')) => Some(Action::CopyReleaseUrl),
+        (KeyModifiers::NONE, KeyCode::Char('u')) => Some(Action::DismissUpdate),
+        (KeyModifiers::SHIFT, KeyCode::Char('Y')) => Some(Action::CopyView),
+        (KeyModifiers::NONE, KeyCode::Char('?')) | (_, KeyCode::F(1)) => Some(Action::ToggleHelp),
+        (KeyModifiers::NONE, KeyCode::Char('q')) => Some(Action::Back),
+        (KeyModifiers::NONE, KeyCode::Esc) => Some(Action::Quit),
 
         _ => None,
     }
@@ -86,7 +258,35 @@ fn map_normal_mode(key: KeyEvent) -> Option<Action> {
 
 fn map_help_mode(key: KeyEvent) -> Option<Action> {
     match key.code {
-        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') => Some(Action::ToggleHelp),
+        KeyCode::Char('q') => Some(Action::Back),
+        KeyCode::Esc | KeyCode::Char('?') | KeyCode::F(1) => Some(Action::ToggleHelp),
+        _ => None,
+    }
+}
+
+fn map_scroll_popup_mode(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveUp),
+        KeyCode::Char('o') => Some(Action::ShowRawCommit),
+        KeyCode::Char('i') => Some(Action::ShowRepoInfo),
+        KeyCode::Char('?') | KeyCode::F(1) => Some(Action::ToggleHelp),
+        KeyCode::Char('q') => Some(Action::Back),
+        KeyCode::Esc => Some(Action::Cancel),
+        _ => None,
+    }
+}
+
+fn map_diff_view_mode(key: KeyEvent) -> Option<Action> {
+    match (key.modifiers, key.code) {
+        (KeyModifiers::CONTROL, KeyCode::Char('e')) => Some(Action::DiffScrollDown),
+        (KeyModifiers::CONTROL, KeyCode::Char('y')) => Some(Action::DiffScrollUp),
+        (KeyModifiers::NONE, KeyCode::Char(' ')) | (KeyModifiers::NONE, KeyCode::Char('v')) => {
+            Some(Action::OpenDiff)
+        }
+        (KeyModifiers::NONE, KeyCode::Char('?')) | (_, KeyCode::F(1)) => Some(Action::ToggleHelp),
+        (KeyModifiers::NONE, KeyCode::Char('q')) => Some(Action::Back),
+        (_, KeyCode::Esc) => Some(Action::Cancel),
         _ => None,
     }
 }
@@ -101,6 +301,13 @@ fn map_input_mode(key: KeyEvent) -> Option<Action> {
     }
 }
 
+fn map_command_mode(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Tab => Some(Action::CompleteCommand),
+        _ => map_input_mode(key),
+    }
+}
+
 fn map_search_mode(key: KeyEvent) -> Option<Action> {
     match (key.modifiers, key.code) {
         // Navigation in dropdown (Tab doesn't move graph)
@@ -110,6 +317,12 @@ fn map_search_mode(key: KeyEvent) -> Option<Action> {
         (KeyModifiers::CONTROL, KeyCode::Char('j')) => Some(Action::SearchSelectDown),
         (KeyModifiers::NONE, KeyCode::Tab) => Some(Action::SearchSelectDownQuiet),
         (KeyModifiers::SHIFT, KeyCode::BackTab) => Some(Action::SearchSelectUpQuiet),
+        (KeyModifiers::CONTROL, KeyCode::Char('s')) => Some(Action::CycleBranchSort),
+        (KeyModifiers::CONTROL, KeyCode::Char('t')) => Some(Action::CycleSearchCase),
+        (KeyModifiers::CONTROL, KeyCode::Char('l')) => Some(Action::ToggleSubjectLayout),
+        (KeyModifiers::CONTROL, KeyCode::Char('d')) => Some(Action::MarkBranchDiff),
+        // ? is typed into the query, so help is on F1 here
+        (_, KeyCode::F(1)) => Some(Action::ToggleHelp),
         // Standard input actions
         (_, KeyCode::Enter) => Some(Action::Confirm),
         (_, KeyCode::Esc) => Some(Action::Cancel),
@@ -123,13 +336,207 @@ fn map_confirm_mode(key: KeyEvent) -> Option<Action> {
     match key.code {
         KeyCode::Char('y') | KeyCode::Enter => Some(Action::Confirm),
         KeyCode::Char('n') | KeyCode::Esc => Some(Action::Cancel),
+        KeyCode::Char('q') => Some(Action::Back),
+        _ => None,
+    }
+}
+
+fn map_visual_mode(key: KeyEvent) -> Option<Action> {
+    match (key.modifiers, key.code) {
+        (KeyModifiers::NONE, KeyCode::Char('j')) | (KeyModifiers::NONE, KeyCode::Down) => {
+            Some(Action::MoveDown)
+        }
+        (KeyModifiers::NONE, KeyCode::Char('k')) | (KeyModifiers::NONE, KeyCode::Up) => {
+            Some(Action::MoveUp)
+        }
+        (KeyModifiers::CONTROL, KeyCode::Char('d')) => Some(Action::PageDown),
+        (KeyModifiers::CONTROL, KeyCode::Char('u')) => Some(Action::PageUp),
+        (KeyModifiers::NONE, KeyCode::Char('g')) | (KeyModifiers::NONE, KeyCode::Home) => {
+            Some(Action::GoToTop)
+        }
+        (KeyModifiers::SHIFT, KeyCode::Char('G')) | (KeyModifiers::NONE, KeyCode::End) => {
+            Some(Action::GoToBottom)
+        }
+        (KeyModifiers::NONE, KeyCode::Char('y')) | (KeyModifiers::NONE, KeyCode::Enter) => {
+            Some(Action::CopyHash)
+        }
+        (KeyModifiers::NONE, KeyCode::Char('p')) => Some(Action::CherryPick),
+        (KeyModifiers::NONE, KeyCode::Char('x')) => Some(Action::ExportPatches),
+        (KeyModifiers::NONE, KeyCode::Char('s')) => Some(Action::ShowSeriesStats),
+        (KeyModifiers::SHIFT, KeyCode::Char('S')) => Some(Action::Squash),
+        (KeyModifiers::SHIFT, KeyCode::Char('V')) | (KeyModifiers::NONE, KeyCode::Esc) => {
+            Some(Action::Cancel)
+        }
+        (KeyModifiers::NONE, KeyCode::Char('q')) => Some(Action::Back),
+        (_, KeyCode::Char('?')) | (_, KeyCode::F(1)) => Some(Action::ToggleHelp),
+        _ => None,
+    }
+}
+
+fn map_tags_mode(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveUp),
+        KeyCode::Char('s') => Some(Action::CycleTagSort),
+        KeyCode::Enter => Some(Action::Confirm),
+        KeyCode::Char('c') => Some(Action::Checkout),
+        KeyCode::Char('T') => Some(Action::ShowTags),
+        KeyCode::Char('?') | KeyCode::F(1) => Some(Action::ToggleHelp),
+        KeyCode::Char('q') => Some(Action::Back),
+        KeyCode::Esc => Some(Action::Cancel),
+        _ => None,
+    }
+}
+
+fn map_stash_list_mode(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveUp),
+        KeyCode::Enter => Some(Action::Confirm),
+        KeyCode::Char('p') => Some(Action::StashPop),
+        KeyCode::Char('d') => Some(Action::DropStash),
+        KeyCode::Char('S') => Some(Action::ShowStashes),
+        KeyCode::Char('?') | KeyCode::F(1) => Some(Action::ToggleHelp),
+        KeyCode::Char('q') => Some(Action::Back),
+        KeyCode::Esc => Some(Action::Cancel),
+        _ => None,
+    }
+}
+
+fn map_checkout_blocked_mode(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveUp),
+        KeyCode::Char('f') => Some(Action::OverwriteUntracked),
+        KeyCode::Char('s') => Some(Action::StashAndRetry),
+        KeyCode::Char('q') => Some(Action::Back),
+        KeyCode::Char('n') | KeyCode::Esc => Some(Action::Cancel),
+        _ => None,
+    }
+}
+
+fn map_conflict_mode(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveUp),
+        KeyCode::Char('a') => Some(Action::AbortMerge),
+        KeyCode::Char('q') => Some(Action::Back),
+        KeyCode::Char('n') | KeyCode::Esc => Some(Action::Cancel),
+        _ => None,
+    }
+}
+
+fn map_retarget_mode(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveUp),
+        KeyCode::Char(' ') => Some(Action::ToggleChecked),
+        KeyCode::Enter => Some(Action::Confirm),
+        KeyCode::Char('q') => Some(Action::Back),
+        KeyCode::Char('n') | KeyCode::Esc => Some(Action::Cancel),
+        _ => None,
+    }
+}
+
+fn map_commit_menu_mode(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('f') => Some(Action::CommitFixup),
+        KeyCode::Char('s') => Some(Action::CommitSquash),
+        KeyCode::Char('q') => Some(Action::Back),
+        KeyCode::Char('C') | KeyCode::Esc => Some(Action::Cancel),
+        _ => None,
+    }
+}
+
+fn map_pick_child_mode(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveUp),
+        KeyCode::Enter | KeyCode::Char('>') => Some(Action::Confirm),
+        KeyCode::Char('q') => Some(Action::Back),
+        KeyCode::Esc => Some(Action::Cancel),
+        _ => None,
+    }
+}
+
+fn map_quit_blocked_mode(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('w') => Some(Action::WaitAndQuit),
+        KeyCode::Char('a') => Some(Action::AbortAndQuit),
+        KeyCode::Char('q') => Some(Action::Back),
+        KeyCode::Char('n') | KeyCode::Esc => Some(Action::Cancel),
         _ => None,
     }
 }
 
 fn map_error_mode(key: KeyEvent) -> Option<Action> {
     match key.code {
-        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => Some(Action::Cancel),
+        KeyCode::Char('q') => Some(Action::Back),
+        KeyCode::Esc | KeyCode::Enter => Some(Action::Cancel),
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(1000);
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_sequence_completes_with_its_second_key() {
+        let mut keys = KeySequencer::new(TIMEOUT);
+        let now = Instant::now();
+        assert!(keys.feed(key('g'), &AppMode::Normal, now).is_empty());
+        assert_eq!(keys.pending(), Some('g'));
+        assert_eq!(
+            keys.feed(key('h'), &AppMode::Normal, now),
+            [Action::JumpToHead]
+        );
+        assert_eq!(keys.pending(), None);
+        assert_eq!(keys.tick(now + TIMEOUT), None);
+    }
+
+    #[test]
+    fn test_prefix_falls_back_to_its_own_action() {
+        let mut keys = KeySequencer::new(TIMEOUT);
+        let now = Instant::now();
+        keys.feed(key('g'), &AppMode::Normal, now);
+        assert_eq!(keys.tick(now + TIMEOUT / 2), None);
+        assert_eq!(keys.tick(now + TIMEOUT), Some(Action::GoToTop));
+        assert_eq!(keys.pending(), None);
+
+        // Another key runs the prefix first, then itself
+        keys.feed(key('g'), &AppMode::Normal, now);
+        assert_eq!(
+            keys.feed(key('j'), &AppMode::Normal, now),
+            [Action::GoToTop, Action::MoveDown]
+        );
+    }
+
+    #[test]
+    fn test_esc_cancels_a_pending_prefix() {
+        let mut keys = KeySequencer::new(TIMEOUT);
+        let now = Instant::now();
+        keys.feed(key('g'), &AppMode::Normal, now);
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert!(keys.feed(esc, &AppMode::Normal, now).is_empty());
+        assert_eq!(keys.tick(now + TIMEOUT), None);
+        // Esc without a pending prefix still quits
+        assert_eq!(keys.feed(esc, &AppMode::Normal, now), [Action::Quit]);
+    }
+
+    #[test]
+    fn test_prefixes_are_plain_keys_outside_normal_mode() {
+        let mut keys = KeySequencer::new(TIMEOUT);
+        assert_eq!(
+            keys.feed(key('g'), &AppMode::Visual { anchor: 0 }, Instant::now()),
+            [Action::GoToTop]
+        );
+        assert_eq!(keys.pending(), None);
+    }
+}
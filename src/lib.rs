@@ -2,11 +2,23 @@
 
 pub mod action;
 pub mod app;
+pub mod bookmarks;
+pub mod command;
 pub mod config;
+pub mod editor;
+pub mod error;
 pub mod event;
 pub mod git;
 pub mod graph;
+pub mod gutter;
 pub mod keybindings;
+pub mod provenance;
+pub mod recent;
 pub mod search;
 pub mod tui;
 pub mod ui;
+pub mod update;
+pub mod view_settings;
+pub mod worker;
+
+pub use error::{Error, Result};
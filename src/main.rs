@@ -1,13 +1,22 @@
 //! keifu: a TUI tool that shows Git commit graphs
 
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use crossterm::event::KeyCode;
 
 use keifu::{
     app::App,
+    config::Config,
     event::{get_key_event, poll_event},
-    keybindings::map_key_to_action,
-    tui, ui,
+    git::{BranchReport, GitRepository},
+    recent::RecentRepos,
+    tui,
+    ui::{self, repo_picker::RepoPicker, theme::Theme},
+    worker, Error,
 };
 
 #[derive(Parser)]
@@ -16,35 +25,140 @@ use keifu::{
     version,
     about = "A TUI tool to visualize Git commit graphs with branch genealogy"
 )]
-struct Cli {}
+struct Cli {
+    /// Repository to open (any directory inside it; default: the current directory)
+    #[arg(value_name = "PATH", conflicts_with = "git_dir")]
+    path: Option<PathBuf>,
+
+    /// Open with this revision selected (branch, tag, commit hash, HEAD~3, ...)
+    #[arg(long, value_name = "REVSPEC")]
+    rev: Option<String>,
+
+    /// Open this git directory (overrides GIT_DIR and GIT_WORK_TREE)
+    #[arg(long, value_name = "PATH")]
+    git_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print every branch with its divergence from its upstream, for scripts
+    #[command(after_help = BRANCHES_HELP)]
+    Branches {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = BranchFormat::Json)]
+        format: BranchFormat,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BranchFormat {
+    /// A JSON array of branch objects
+    Json,
+    /// One tab-separated line per branch, without a header
+    Tsv,
+}
+
+const BRANCHES_HELP: &str = "\
+Branches are listed HEAD first, then local, then remote.
+
+Fields, in TSV column order:
+  name       branch name (remote branches as origin/main)
+  is_head    true for the checked-out branch
+  is_remote  true for remote-tracking branches
+  upstream   upstream branch name (empty/null when none)
+  tip_oid    full hex commit id of the tip
+  ahead      commits the upstream lacks (empty/null without an upstream)
+  behind     upstream commits the branch lacks (empty/null without an upstream)
+  tip_time   commit time of the tip, Unix seconds
+TSV booleans are true/false. New columns are only ever appended.
+
+Exit status: 0 on success, 3 when no repository is found, 1 on other errors.";
+
+/// Exit status of `keifu branches` when no repository is found
+/// (2 is taken by command line errors)
+const EXIT_NOT_A_REPO: i32 = 3;
 
 fn main() -> Result<()> {
-    Cli::parse();
+    let cli = Cli::parse();
+    if let Some(Command::Branches { format }) = cli.command {
+        print_branches(&cli, format);
+    }
     // Restore the terminal on panic
+    // Worker panics are caught and shown in the UI, so the terminal is left as is
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
+        if worker::is_worker_thread() {
+            return;
+        }
         let _ = tui::restore();
         original_hook(panic_info);
     }));
 
     // Initialize application
-    let mut app = App::new()?;
+    let config = Config::load();
+    let mut recent = RecentRepos::load();
+    let repo = match open_repository(&cli) {
+        Ok(repo) => repo,
+        Err(Error::RepositoryNotFound(path)) => {
+            // Only a plain `keifu` falls back to the recent repositories
+            let picked = if cli.path.is_none() && cli.git_dir.is_none() {
+                pick_recent_repo(&recent.existing(), Theme::from_config(&config.ui))?
+            } else {
+                None
+            };
+            match picked {
+                Some(picked) => GitRepository::discover_at(picked)?,
+                None => {
+                    eprintln!(
+                        "keifu: not a git repository (or any parent up to {}): {}\n\
+                         Run keifu inside a repository or pass a path: keifu <path>",
+                        path.ancestors().last().unwrap_or(&path).display(),
+                        path.display()
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+        Err(e) => return Err(e.into()),
+    };
+    recent.record(Path::new(&repo.path));
+    // Failing to remember the repository must not keep it from opening
+    let _ = recent.save();
+    let mut app = App::with_repo(repo, config)?;
+    if let Some(rev) = &cli.rev {
+        app.start_at_rev(rev)?;
+    }
 
     // Initialize terminal
     let mut terminal = tui::init()?;
 
     // Main loop
+    let mut first_frame = true;
     loop {
         // Render
         terminal.draw(|frame| {
             ui::draw(frame, &mut app);
         })?;
 
+        // The startup fetch must not delay the first interactive frame
+        if first_frame {
+            first_frame = false;
+            app.start_startup_fetch();
+        }
+
         // Check if async fetch has completed
         app.update_fetch_status();
 
+        // Check if the update check has completed
+        app.update_update_check();
+        app.update_repo_stats();
+
         // Auto-refresh check
         app.check_auto_refresh();
+        app.save_view_settings_if_due();
 
         // Exit check
         if app.should_quit {
@@ -52,21 +166,37 @@ fn main() -> Result<()> {
         }
 
         // Event handling
+        let mut actions = Vec::new();
         if let Some(event) = poll_event()? {
             if let Some(key) = get_key_event(&event) {
-                if let Some(action) = map_key_to_action(key, &app.mode) {
-                    if let Err(e) = app.handle_action(action) {
-                        // Show errors in the UI
-                        app.show_error(format!("{}", e));
-                    }
-                }
+                actions = app.key_sequencer.feed(key, &app.mode, Instant::now());
             }
             // Resize events trigger redraw automatically
         }
+        // A sequence prefix that timed out runs on its own
+        actions.extend(app.key_sequencer.tick(Instant::now()));
+        for action in actions {
+            if let Err(e) = app.handle_action(action) {
+                // Show errors in the UI
+                app.show_error(format!("{}", e));
+            }
+        }
+
+        // Hand the terminal to the editor and take it back afterwards
+        if let Some(file) = app.take_editor_file() {
+            tui::restore()?;
+            let opened = file.open();
+            terminal = tui::init()?;
+            if let Err(e) = opened {
+                app.show_error(format!("{:#}", e));
+            }
+        }
     }
 
     // Restore terminal
     tui::restore()?;
+    // Failing to remember the view must not hide the exit message
+    let _ = app.flush_view_settings();
 
     // Print message if any
     if let Some(msg) = app.exit_message {
@@ -75,3 +205,69 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Open the repository named on the command line, or the one around the current directory
+fn open_repository(cli: &Cli) -> keifu::Result<GitRepository> {
+    match (&cli.git_dir, &cli.path) {
+        (Some(git_dir), _) => GitRepository::open(git_dir),
+        (None, Some(path)) => GitRepository::discover_at(path),
+        (None, None) => GitRepository::discover(),
+    }
+}
+
+/// Print the branch report and exit with a status scripts can tell apart
+fn print_branches(cli: &Cli, format: BranchFormat) -> ! {
+    let code = match branches_output(cli, format) {
+        Ok(output) => {
+            print!("{}", output);
+            0
+        }
+        Err(e) => {
+            eprintln!("keifu: {:#}", e);
+            match e.downcast_ref::<Error>() {
+                Some(Error::RepositoryNotFound(_)) => EXIT_NOT_A_REPO,
+                _ => 1,
+            }
+        }
+    };
+    std::process::exit(code)
+}
+
+fn branches_output(cli: &Cli, format: BranchFormat) -> Result<String> {
+    let repo = open_repository(cli)?;
+    let report = BranchReport::list(&repo.repo)?;
+    Ok(match format {
+        BranchFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+        BranchFormat::Tsv => report
+            .iter()
+            .map(|branch| branch.tsv_line() + "\n")
+            .collect(),
+    })
+}
+
+/// Let the user choose one of `repos` on a minimal screen (None when declined or not a terminal)
+fn pick_recent_repo(repos: &[&Path], theme: Theme) -> Result<Option<PathBuf>> {
+    if repos.is_empty() || !std::io::stdout().is_terminal() {
+        return Ok(None);
+    }
+    let mut terminal = tui::TuiGuard::init()?;
+    let mut selected = 0;
+    let picked = loop {
+        terminal.draw(|frame| {
+            frame.render_widget(RepoPicker::new(repos, selected, theme), frame.area());
+        })?;
+        let Some(key) = poll_event()?.as_ref().and_then(get_key_event) else {
+            continue;
+        };
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => selected = (selected + 1) % repos.len(),
+            KeyCode::Up | KeyCode::Char('k') => {
+                selected = selected.checked_sub(1).unwrap_or(repos.len() - 1)
+            }
+            KeyCode::Enter => break Some(repos[selected].to_path_buf()),
+            KeyCode::Esc | KeyCode::Char('q') => break None,
+            _ => {}
+        }
+    };
+    Ok(picked)
+}
@@ -0,0 +1,65 @@
+//! Where commits rewritten by keifu (cherry-pick, rebase) came from, for the session
+
+use std::collections::HashMap;
+
+use git2::Oid;
+
+/// Original ↔ rewritten commit pairs recorded by operations run in this session
+#[derive(Debug, Default)]
+pub struct Provenance {
+    /// Rewritten commit → the commit it was made from
+    origins: HashMap<Oid, Oid>,
+    /// Original commit → its latest rewrite
+    rewrites: HashMap<Oid, Oid>,
+}
+
+impl Provenance {
+    /// Record that `rewritten` was made from `original`
+    pub fn record(&mut self, original: Oid, rewritten: Oid) {
+        self.origins.insert(rewritten, original);
+        self.rewrites.insert(original, rewritten);
+    }
+
+    /// Commit a rewritten commit was made from
+    pub fn origin_of(&self, oid: Oid) -> Option<Oid> {
+        self.origins.get(&oid).copied()
+    }
+
+    /// Latest rewrite of an original commit
+    pub fn rewrite_of(&self, oid: Oid) -> Option<Oid> {
+        self.rewrites.get(&oid).copied()
+    }
+
+    /// The other side of a pair: the original of a rewritten commit, or the rewrite of an original
+    pub fn counterpart(&self, oid: Oid) -> Option<Oid> {
+        self.origin_of(oid).or_else(|| self.rewrite_of(oid))
+    }
+
+    /// Rewritten commits
+    pub fn rewritten(&self) -> impl Iterator<Item = Oid> + '_ {
+        self.origins.keys().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oid(n: u8) -> Oid {
+        Oid::from_bytes(&[n; 20]).unwrap()
+    }
+
+    #[test]
+    fn test_counterpart_goes_both_ways_and_follows_latest_rewrite() {
+        let mut provenance = Provenance::default();
+        provenance.record(oid(1), oid(2));
+        assert_eq!(provenance.counterpart(oid(2)), Some(oid(1)));
+        assert_eq!(provenance.counterpart(oid(1)), Some(oid(2)));
+
+        // Picking the same commit again: both copies know their origin
+        provenance.record(oid(1), oid(3));
+        assert_eq!(provenance.origin_of(oid(2)), Some(oid(1)));
+        assert_eq!(provenance.counterpart(oid(1)), Some(oid(3)));
+        assert_eq!(provenance.counterpart(oid(4)), None);
+    }
+}
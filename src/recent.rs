@@ -0,0 +1,104 @@
+//! Recently opened repositories (offered when keifu starts outside a repository)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Number of repositories remembered
+const MAX_RECENT: usize = 10;
+
+/// On-disk format
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct RecentFile {
+    /// Most recent first
+    repos: Vec<PathBuf>,
+}
+
+/// Recently opened repository paths, most recent first
+#[derive(Debug, Default)]
+pub struct RecentRepos {
+    /// File to save to (None disables persistence)
+    path: Option<PathBuf>,
+    repos: Vec<PathBuf>,
+}
+
+impl RecentRepos {
+    /// Load from ~/.local/share/keifu/recent_repos.toml
+    pub fn load() -> Self {
+        match dirs::data_dir() {
+            Some(dir) => Self::load_from(dir.join("keifu").join("recent_repos.toml")),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from `path`; missing or invalid files yield an empty list
+    pub fn load_from(path: PathBuf) -> Self {
+        let repos = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| toml::from_str::<RecentFile>(&content).ok())
+            .map(|file| file.repos)
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            repos,
+        }
+    }
+
+    /// Move `repo` to the front, dropping the oldest entries beyond the limit
+    pub fn record(&mut self, repo: &Path) {
+        self.repos.retain(|r| r != repo);
+        self.repos.insert(0, repo.to_path_buf());
+        self.repos.truncate(MAX_RECENT);
+    }
+
+    /// Remembered repositories that still exist
+    pub fn existing(&self) -> Vec<&Path> {
+        self.repos
+            .iter()
+            .map(PathBuf::as_path)
+            .filter(|r| r.exists())
+            .collect()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let content = toml::to_string(&RecentFile {
+            repos: self.repos.clone(),
+        })?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create data directory")?;
+        }
+        fs::write(path, content).context("Failed to save recent repositories")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_moves_to_front_and_persists() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("recent_repos.toml");
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+        fs::create_dir(&a).unwrap();
+        fs::create_dir(&b).unwrap();
+
+        let mut recent = RecentRepos::load_from(file.clone());
+        recent.record(&a);
+        recent.record(&b);
+        recent.record(&a);
+        recent.record(&dir.path().join("gone"));
+        recent.save().unwrap();
+
+        let loaded = RecentRepos::load_from(file);
+        // Paths that no longer exist are not offered
+        assert_eq!(loaded.existing(), [a.as_path(), b.as_path()]);
+    }
+}
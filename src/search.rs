@@ -2,6 +2,7 @@
 
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
+use serde::{Deserialize, Serialize};
 
 /// Result of a fuzzy search match
 #[derive(Debug, Clone)]
@@ -14,15 +15,90 @@ pub struct FuzzySearchResult {
     pub matched_indices: Vec<usize>,
 }
 
-/// Performs fuzzy search on branch names
+/// Where each branch's tip commit subject goes in the branch list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SubjectLayout {
+    /// Dimmed after the name, in the space left on the line
+    #[default]
+    Inline,
+    /// On a line of its own below the name
+    TwoLine,
+}
+
+impl SubjectLayout {
+    pub fn toggle(self) -> Self {
+        match self {
+            SubjectLayout::Inline => SubjectLayout::TwoLine,
+            SubjectLayout::TwoLine => SubjectLayout::Inline,
+        }
+    }
+
+    /// Lines each branch takes in the list
+    pub fn lines_per_branch(self) -> usize {
+        match self {
+            SubjectLayout::Inline => 1,
+            SubjectLayout::TwoLine => 2,
+        }
+    }
+}
+
+/// How letter case is matched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchCase {
+    /// Case-insensitive unless the query has an uppercase letter
+    #[default]
+    Smart,
+    Sensitive,
+    Insensitive,
+}
+
+impl SearchCase {
+    pub fn next(self) -> Self {
+        match self {
+            SearchCase::Smart => SearchCase::Sensitive,
+            SearchCase::Sensitive => SearchCase::Insensitive,
+            SearchCase::Insensitive => SearchCase::Smart,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchCase::Smart => "smart case",
+            SearchCase::Sensitive => "case-sensitive",
+            SearchCase::Insensitive => "ignore case",
+        }
+    }
+
+    fn matcher(self) -> SkimMatcherV2 {
+        let matcher = SkimMatcherV2::default();
+        match self {
+            SearchCase::Smart => matcher.smart_case(),
+            SearchCase::Sensitive => matcher.respect_case(),
+            SearchCase::Insensitive => matcher.ignore_case(),
+        }
+    }
+}
+
+/// Performs fuzzy search on branch names with smart case
 ///
 /// Returns results sorted by score (descending), then by branch name (ascending) for ties.
 pub fn fuzzy_search_branches(query: &str, branches: &[(usize, String)]) -> Vec<FuzzySearchResult> {
+    fuzzy_search_branches_with(query, branches, SearchCase::default())
+}
+
+/// Performs fuzzy search on branch names, matching case as given
+pub fn fuzzy_search_branches_with(
+    query: &str,
+    branches: &[(usize, String)],
+    case: SearchCase,
+) -> Vec<FuzzySearchResult> {
     if query.is_empty() {
         return Vec::new();
     }
 
-    let matcher = SkimMatcherV2::default();
+    let matcher = case.matcher();
     let mut results: Vec<FuzzySearchResult> = branches
         .iter()
         .enumerate()
@@ -97,6 +173,24 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn test_case_modes() {
+        let branches = vec![(0, "fix/API".to_string()), (1, "fix/api".to_string())];
+        let matched = |query, case| -> Vec<usize> {
+            let mut idx: Vec<usize> = fuzzy_search_branches_with(query, &branches, case)
+                .iter()
+                .map(|r| r.branch_idx)
+                .collect();
+            idx.sort();
+            idx
+        };
+        // Smart case: lowercase matches both, uppercase only the exact case
+        assert_eq!(matched("api", SearchCase::Smart), [0, 1]);
+        assert_eq!(matched("API", SearchCase::Smart), [0]);
+        assert_eq!(matched("api", SearchCase::Sensitive), [1]);
+        assert_eq!(matched("API", SearchCase::Insensitive), [0, 1]);
+    }
+
     #[test]
     fn test_results_sorted_by_score() {
         let branches = vec![
@@ -1,6 +1,7 @@
 //! Terminal control (raw mode, alternate screen)
 
 use std::io::{self, Stdout};
+use std::ops::{Deref, DerefMut};
 
 use anyhow::Result;
 use crossterm::{
@@ -27,3 +28,33 @@ pub fn restore() -> Result<()> {
     execute!(io::stdout(), LeaveAlternateScreen)?;
     Ok(())
 }
+
+/// A terminal set up by `init` that is restored when dropped, so early returns
+/// (`?` on a failed draw or read) do not leave it in raw mode
+pub struct TuiGuard(Tui);
+
+impl TuiGuard {
+    pub fn init() -> Result<Self> {
+        init().map(Self)
+    }
+}
+
+impl Deref for TuiGuard {
+    type Target = Tui;
+
+    fn deref(&self) -> &Tui {
+        &self.0
+    }
+}
+
+impl DerefMut for TuiGuard {
+    fn deref_mut(&mut self) -> &mut Tui {
+        &mut self.0
+    }
+}
+
+impl Drop for TuiGuard {
+    fn drop(&mut self) {
+        let _ = restore();
+    }
+}
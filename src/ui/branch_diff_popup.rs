@@ -0,0 +1,82 @@
+//! Branch diff popup (files that differ between two branch tips)
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+
+use super::{commit_detail::CommitDetailWidget, theme::Theme};
+use crate::git::BranchComparison;
+
+pub struct BranchDiffPopup<'a> {
+    comparison: &'a BranchComparison,
+    scroll: u16,
+    theme: Theme,
+}
+
+impl<'a> BranchDiffPopup<'a> {
+    pub fn new(comparison: &'a BranchComparison, scroll: u16, theme: Theme) -> Self {
+        Self {
+            comparison,
+            scroll,
+            theme,
+        }
+    }
+
+    fn lines(&self, width: usize) -> Vec<Line<'a>> {
+        let comparison = self.comparison;
+        let history = match comparison.ahead_behind {
+            Some((ahead, behind)) => format!(
+                "{} has {} commits not on {}, which has {} not on {}",
+                comparison.to, ahead, comparison.from, behind, comparison.to
+            ),
+            None => "No shared history: the tips are compared file by file".to_string(),
+        };
+        let mut lines = vec![
+            Line::from(Span::styled(history, self.theme.muted_style())),
+            Line::from(""),
+        ];
+        if comparison.diff.total_files == 0 {
+            lines.push(Line::from(Span::styled(
+                "The two tips have identical trees",
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+        } else {
+            lines.extend(CommitDetailWidget::file_list_lines(
+                &comparison.diff,
+                width,
+                &self.theme,
+            ));
+        }
+        lines
+    }
+}
+
+impl Widget for BranchDiffPopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .title(format!(
+                " {} → {} ",
+                self.comparison.from, self.comparison.to
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .style(Style::default().bg(Color::Black));
+
+        let lines = self.lines(block.inner(area).width as usize);
+        let paragraph = Paragraph::new(lines)
+            .style(
+                self.theme
+                    .selectable_style(Style::default().fg(Color::White)),
+            )
+            .block(block)
+            .scroll((self.scroll, 0));
+
+        Widget::render(paragraph, area, buf);
+    }
+}
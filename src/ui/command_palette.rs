@@ -0,0 +1,96 @@
+//! Command palette: input line with completions below
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Widget},
+};
+
+use super::theme::Theme;
+use crate::command::COMMANDS;
+
+pub struct CommandPalette<'a> {
+    input: &'a str,
+    completions: &'a [String],
+    theme: Theme,
+}
+
+impl<'a> CommandPalette<'a> {
+    pub fn new(input: &'a str, completions: &'a [String], theme: Theme) -> Self {
+        Self {
+            input,
+            completions,
+            theme,
+        }
+    }
+
+    /// Borders, input line, completions and hint
+    pub fn height(completions: usize) -> u16 {
+        (4 + completions) as u16
+    }
+}
+
+impl Widget for CommandPalette<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .title(" Command ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height < 2 {
+            return;
+        }
+
+        let input_line = Line::from(vec![
+            Span::styled(" :", Style::default().fg(Color::Cyan)),
+            Span::styled(
+                self.input,
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::UNDERLINED),
+            ),
+            Span::styled("_", Style::default().fg(Color::Cyan)),
+        ]);
+        buf.set_line(inner.x, inner.y, &input_line, inner.width);
+
+        // Rows between the input line and the hint
+        let rows = (inner.height - 2) as usize;
+        for (i, completion) in self.completions.iter().take(rows).enumerate() {
+            let style = if i == 0 {
+                self.theme.selection
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let mut spans = vec![Span::styled(format!("  {}", completion), style)];
+            // Command names come with their usage
+            if let Some((_, argument, description)) = COMMANDS
+                .iter()
+                .find(|(name, ..)| completion.trim_end() == *name)
+            {
+                spans.push(Span::styled(
+                    format!("{}  {}", argument, description),
+                    self.theme.muted_style(),
+                ));
+            }
+            buf.set_line(
+                inner.x,
+                inner.y + 1 + i as u16,
+                &Line::from(spans),
+                inner.width,
+            );
+        }
+
+        buf.set_string(
+            inner.x,
+            inner.y + inner.height - 1,
+            "  Tab: complete  Enter: run  Esc: cancel",
+            self.theme.muted_style(),
+        );
+    }
+}
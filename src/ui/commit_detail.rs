@@ -8,45 +8,148 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph, Widget, Wrap},
 };
 
-use crate::app::App;
-use crate::git::{CommitDiffInfo, FileChangeKind};
-
-use super::{render_placeholder_block, MIN_WIDGET_HEIGHT, MIN_WIDGET_WIDTH};
+use crate::app::{App, AppMode};
+use crate::git::{last_touch::LastTouch, CommitDiffInfo, FileChangeKind};
+
+use super::{
+    diff_bar::{DiffBar, StatsLayout},
+    render_placeholder_block,
+    theme::Theme,
+    MIN_WIDGET_HEIGHT, MIN_WIDGET_WIDTH,
+};
 
 /// Width threshold for switching to vertical layout
 /// When panel width would be <= 28 chars, use vertical layout
 const VERTICAL_LAYOUT_THRESHOLD: u16 = 56;
 
+/// Characters of a message line kept before it is cut with "…"
+const MESSAGE_LINE_MAX_CHARS: usize = 1000;
+
+/// Previous-change annotation of a changed file row
+enum FileAnnotation<'t> {
+    Pending,
+    Found(&'t LastTouch),
+    /// No earlier change within the walk limit (e.g. a new file)
+    None,
+}
+
 pub struct CommitDetailWidget<'a> {
     commit_lines: Vec<Line<'a>>,
     file_lines: Vec<Line<'a>>,
+    /// Diff text and scroll offset while the diff view is open (replaces the file list)
+    diff: Option<(Vec<Line<'a>>, usize)>,
+    theme: Theme,
 }
 
 impl<'a> CommitDetailWidget<'a> {
-    pub fn new(app: &App) -> Self {
-        let commit_lines = Self::build_commit_lines(app);
-        let file_lines = Self::build_file_lines(app);
+    /// `width` is the width of the whole detail area (used to fit the diff bars)
+    pub fn new(app: &App, width: u16) -> Self {
+        let theme = Theme::from_config(&app.config.ui);
+        let commit_lines = Self::build_commit_lines(app, &theme);
+        let file_lines = Self::build_file_lines(app, Self::file_pane_width(width), &theme);
+        // Kept open under the help popup too
+        let diff = app
+            .mode_stack()
+            .iter()
+            .chain([&app.mode])
+            .find_map(|mode| match mode {
+                AppMode::DiffView {
+                    patch,
+                    scroll_offset,
+                    ..
+                } => Some((Self::diff_lines(patch, &theme), *scroll_offset)),
+                _ => None,
+            });
         Self {
             commit_lines,
             file_lines,
+            diff,
+            theme,
         }
     }
 
-    fn build_file_lines(app: &App) -> Vec<Line<'a>> {
+    /// Unified diff lines colored like `git diff`
+    fn diff_lines(patch: &str, theme: &Theme) -> Vec<Line<'a>> {
+        if patch.is_empty() {
+            return vec![Line::from(Span::styled("No changes", theme.muted_style()))];
+        }
+        patch
+            .lines()
+            .map(|line| {
+                let style = if line.starts_with("diff ")
+                    || line.starts_with("index ")
+                    || line.starts_with("+++ ")
+                    || line.starts_with("--- ")
+                {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else if line.starts_with("@@") {
+                    Style::default().fg(Color::Cyan)
+                } else if line.starts_with('+') {
+                    Style::default().fg(Color::Green)
+                } else if line.starts_with('-') {
+                    Style::default().fg(Color::Red)
+                } else if line == "[binary file]" {
+                    theme.muted_style()
+                } else {
+                    Style::default()
+                };
+                Line::from(Span::styled(line.to_string(), style))
+            })
+            .collect()
+    }
+
+    /// Width inside the borders of the changed files pane
+    fn file_pane_width(width: u16) -> usize {
+        let pane = if width <= VERTICAL_LAYOUT_THRESHOLD {
+            width
+        } else {
+            width / 2
+        };
+        pane.saturating_sub(2) as usize
+    }
+
+    fn build_file_lines(app: &App, pane_width: usize, theme: &Theme) -> Vec<Line<'a>> {
         if app.is_diff_loading() {
+            let text = match theme.spinner(super::theme::spinner_tick()) {
+                Some(frame) => format!("{} Loading...", frame),
+                None => "Loading...".to_string(),
+            };
+            return vec![Line::from(Span::styled(text, theme.muted_style()))];
+        }
+        if let Some(error) = app.diff_error() {
             return vec![Line::from(Span::styled(
-                "Loading...",
-                Style::default().fg(Color::DarkGray),
+                error.to_string(),
+                Style::default().fg(Color::Red),
             ))];
         }
-        Self::build_file_list_lines_from(app.cached_diff())
+        let diff = app.cached_diff();
+        let annotations = match diff {
+            Some(diff) if app.file_annotations() && !Self::uncommitted_selected(app) => diff
+                .files
+                .iter()
+                .map(|file| match app.last_touch(&file.path) {
+                    Some(Some(touch)) => FileAnnotation::Found(touch),
+                    Some(None) => FileAnnotation::None,
+                    None => FileAnnotation::Pending,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        Self::build_file_list_lines_from(diff, &annotations, pane_width, theme)
+    }
+
+    fn uncommitted_selected(app: &App) -> bool {
+        app.graph_list_state
+            .selected()
+            .and_then(|i| app.graph_layout.nodes.get(i))
+            .is_some_and(|node| node.is_uncommitted)
     }
 
-    fn build_commit_lines(app: &App) -> Vec<Line<'a>> {
+    fn build_commit_lines(app: &App, theme: &Theme) -> Vec<Line<'a>> {
         let Some(selected) = app.graph_list_state.selected() else {
             return vec![Line::from(Span::styled(
                 "Select a commit",
-                Style::default().fg(Color::DarkGray),
+                theme.muted_style(),
             ))];
         };
 
@@ -59,24 +162,35 @@ impl<'a> CommitDetailWidget<'a> {
             return vec![
                 Line::from(Span::styled(
                     "Uncommitted Changes",
-                    Style::default()
-                        .fg(Color::DarkGray)
-                        .add_modifier(Modifier::BOLD),
+                    theme.muted_style().add_modifier(Modifier::BOLD),
                 )),
                 Line::from(""),
                 Line::from(Span::styled(
                     format!("{} files with changes", node.uncommitted_count),
-                    Style::default().fg(Color::DarkGray),
+                    theme.muted_style(),
                 )),
             ];
         }
 
-        // Handle connector rows (no commit)
-        let Some(commit) = &node.commit else {
+        // Handle elision rows
+        if let Some(span) = node.elided {
             return vec![Line::from(Span::styled(
-                "(connector line)",
-                Style::default().fg(Color::DarkGray),
+                span.describe(),
+                theme.muted_style(),
             ))];
+        }
+
+        // Handle connector rows (no commit): attribute them to their owning commit
+        let Some(commit) = &node.commit else {
+            let owner = node
+                .owner_row
+                .and_then(|row| app.graph_layout.nodes.get(row))
+                .and_then(|n| n.commit.as_ref());
+            let text = match owner {
+                Some(c) => format!("(connector line for {})", c.short_id),
+                None => "(connector line)".to_string(),
+            };
+            return vec![Line::from(Span::styled(text, theme.muted_style()))];
         };
 
         // Build commit detail lines
@@ -86,6 +200,15 @@ impl<'a> CommitDetailWidget<'a> {
                 Span::styled("Commit: ", Style::default().add_modifier(Modifier::BOLD)),
                 Span::styled(commit.oid.to_string(), Style::default().fg(Color::Yellow)),
             ]),
+        ];
+        // Original of a commit cherry-picked or rebased in this session
+        if let Some(origin) = app.provenance.origin_of(commit.oid) {
+            lines[0].spans.push(Span::styled(
+                format!(" (from {})", &origin.to_string()[..7]),
+                theme.muted_style(),
+            ));
+        }
+        lines.extend([
             // Author
             Line::from(vec![
                 Span::styled("Author: ", Style::default().add_modifier(Modifier::BOLD)),
@@ -99,10 +222,58 @@ impl<'a> CommitDetailWidget<'a> {
                 Span::styled("Date:   ", Style::default().add_modifier(Modifier::BOLD)),
                 Span::styled(
                     commit.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
-                    Style::default().fg(Color::DarkGray),
+                    theme.muted_style(),
                 ),
             ]),
-        ];
+        ]);
+
+        // Every branch and tag, including those collapsed into "+N" in the graph
+        let tags = app
+            .commit_tags
+            .get(&commit.oid)
+            .map_or(&[][..], Vec::as_slice);
+        if !node.branch_names.is_empty() || !tags.is_empty() {
+            let mut spans = vec![Span::styled(
+                "Refs:   ",
+                Style::default().add_modifier(Modifier::BOLD),
+            )];
+            let refs = node
+                .branch_names
+                .iter()
+                .map(|name| (name.clone(), Style::default().fg(Color::Green)))
+                .chain(
+                    tags.iter()
+                        .map(|tag| (format!("tag: {}", tag), Style::default().fg(Color::Yellow))),
+                );
+            for (i, (text, style)) in refs.enumerate() {
+                if i > 0 {
+                    spans.push(Span::raw(", "));
+                }
+                spans.push(Span::styled(text, style));
+            }
+            lines.push(Line::from(spans));
+        }
+
+        // Release context (shown once the background worker has computed it)
+        if let Some(distance) = app.tag_distance(commit.oid) {
+            let text = match distance {
+                Some(distance) => {
+                    Span::styled(distance.describe(), Style::default().fg(Color::Yellow))
+                }
+                None => Span::styled("(no prior tag)", theme.muted_style()),
+            };
+            lines.push(Line::from(vec![
+                Span::styled("Since:  ", Style::default().add_modifier(Modifier::BOLD)),
+                text,
+            ]));
+        }
+
+        if let Some(Some(signature)) = app.signature(commit.oid) {
+            lines.push(Line::from(vec![
+                Span::styled("Signed: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(signature.describe(), Style::default().fg(Color::Cyan)),
+            ]));
+        }
 
         // Parent commits
         if !commit.parent_oids.is_empty() {
@@ -113,27 +284,99 @@ impl<'a> CommitDetailWidget<'a> {
                 .collect();
             lines.push(Line::from(vec![
                 Span::styled("Parent: ", Style::default().add_modifier(Modifier::BOLD)),
-                Span::styled(parents.join(", "), Style::default().fg(Color::DarkGray)),
+                Span::styled(parents.join(", "), theme.muted_style()),
             ]));
         }
 
+        // Child commits (only those within the loaded window are known)
+        let children = app.graph_layout.children_of(commit.oid);
+        if children.is_empty() {
+            lines.push(Line::from(vec![
+                Span::styled("Children: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled("none loaded", theme.muted_style()),
+            ]));
+        } else {
+            lines.push(Line::from(Span::styled(
+                "Children:",
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            // The child being chosen while picking one to jump to
+            let picked = match &app.mode {
+                AppMode::PickChild { children, selected } => children.get(*selected),
+                _ => None,
+            };
+            for child in children {
+                let child_commit = app
+                    .graph_layout
+                    .row_of(*child)
+                    .and_then(|row| app.graph_layout.nodes[row].commit.as_ref());
+                let (short_id, subject) = match child_commit {
+                    Some(c) => (c.short_id.clone(), c.message.clone()),
+                    None => (child.to_string()[..7].to_string(), String::new()),
+                };
+                let line = Line::from(vec![
+                    Span::styled(
+                        format!("  {} ", short_id),
+                        theme.selectable_style(theme.muted_style()),
+                    ),
+                    Span::raw(subject),
+                ]);
+                lines.push(if picked == Some(child) {
+                    line.patch_style(theme.selection)
+                } else {
+                    line
+                });
+            }
+        }
+
         lines.push(Line::from(""));
 
-        // Message
-        for line in commit.full_message.lines() {
-            lines.push(Line::from(Span::raw(line.to_string())));
+        // Message, capped so a huge one doesn't cost a huge line list every frame
+        let mut message = commit.full_message.lines();
+        for line in message.by_ref().take(app.config.ui.max_message_lines) {
+            lines.push(Line::from(Span::raw(clip_message_line(line))));
+        }
+        if message.next().is_some() {
+            lines.push(Line::from(Span::styled(
+                "… message truncated, press o to view in full",
+                theme.muted_style(),
+            )));
         }
 
         lines
     }
 
-    fn build_file_list_lines_from(diff: Option<&CommitDiffInfo>) -> Vec<Line<'a>> {
+    /// File list of a diff without annotations (for popups that reuse the layout)
+    pub(super) fn file_list_lines(
+        diff: &CommitDiffInfo,
+        pane_width: usize,
+        theme: &Theme,
+    ) -> Vec<Line<'a>> {
+        Self::build_file_list_lines_from(Some(diff), &[], pane_width, theme)
+    }
+
+    /// `annotations` has one entry per file, or none when annotations are off
+    fn build_file_list_lines_from(
+        diff: Option<&CommitDiffInfo>,
+        annotations: &[FileAnnotation],
+        pane_width: usize,
+        theme: &Theme,
+    ) -> Vec<Line<'a>> {
         let mut lines = Vec::new();
 
         let Some(diff) = diff else {
             return lines;
         };
 
+        // Bars are scaled against the largest file in the commit
+        let layout = StatsLayout::for_width(pane_width);
+        let max_changes = diff
+            .files
+            .iter()
+            .map(|f| f.insertions + f.deletions)
+            .max()
+            .unwrap_or(0);
+
         // Header row
         lines.push(Line::from(vec![
             Span::styled(
@@ -154,7 +397,7 @@ impl<'a> CommitDetailWidget<'a> {
         lines.push(Line::from(""));
 
         // File list
-        for file in &diff.files {
+        for (i, file) in diff.files.iter().enumerate() {
             let (indicator, color) = match file.kind {
                 FileChangeKind::Added => ("A", Color::Green),
                 FileChangeKind::Modified => ("M", Color::Yellow),
@@ -169,19 +412,54 @@ impl<'a> CommitDetailWidget<'a> {
                 Span::styled(format!(" {} ", indicator), Style::default().fg(color)),
                 Span::raw(path_str),
             ];
+            // Permission changes often come without any line change
+            if let Some((old_mode, new_mode)) = file.mode_change {
+                spans.push(Span::styled(
+                    format!(" (mode {:o} → {:o})", old_mode, new_mode),
+                    theme.muted_style(),
+                ));
+            }
 
             // Only show diff stats if there are actual changes (skip for binary files)
             if file.insertions > 0 || file.deletions > 0 {
+                if layout.numbers {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        format!("+{}", file.insertions),
+                        Style::default().fg(Color::Green),
+                    ));
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        format!("-{}", file.deletions),
+                        Style::default().fg(Color::Red),
+                    ));
+                }
+                let bar = DiffBar::new(
+                    file.insertions,
+                    file.deletions,
+                    max_changes,
+                    layout.bar_width,
+                );
                 spans.push(Span::raw(" "));
-                spans.push(Span::styled(
-                    format!("+{}", file.insertions),
-                    Style::default().fg(Color::Green),
-                ));
-                spans.push(Span::raw(" "));
-                spans.push(Span::styled(
-                    format!("-{}", file.deletions),
-                    Style::default().fg(Color::Red),
-                ));
+                spans.push(Span::styled(bar.added, Style::default().fg(Color::Green)));
+                spans.push(Span::styled(bar.deleted, Style::default().fg(Color::Red)));
+                spans.push(Span::styled(bar.empty, theme.muted_style()));
+            }
+
+            let annotation = match annotations.get(i) {
+                Some(FileAnnotation::Found(touch)) => Some(touch.describe()),
+                Some(FileAnnotation::None) => Some("no earlier change".to_string()),
+                Some(FileAnnotation::Pending) => Some(
+                    theme
+                        .spinner(super::theme::spinner_tick())
+                        .unwrap_or("...")
+                        .to_string(),
+                ),
+                None => None,
+            };
+            if let Some(annotation) = annotation {
+                spans.push(Span::raw("  "));
+                spans.push(Span::styled(annotation, theme.muted_style()));
             }
 
             lines.push(Line::from(spans));
@@ -195,7 +473,7 @@ impl<'a> CommitDetailWidget<'a> {
                     "  ...and {} more files",
                     diff.total_files - diff.files.len()
                 ),
-                Style::default().fg(Color::DarkGray),
+                theme.muted_style(),
             )));
         }
 
@@ -206,7 +484,7 @@ impl<'a> CommitDetailWidget<'a> {
 impl<'a> Widget for CommitDetailWidget<'a> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         if area.width < MIN_WIDGET_WIDTH || area.height < MIN_WIDGET_HEIGHT {
-            render_placeholder_block(area, buf);
+            render_placeholder_block(area, buf, &self.theme);
             return;
         }
 
@@ -217,16 +495,21 @@ impl<'a> Widget for CommitDetailWidget<'a> {
             Direction::Horizontal
         };
 
+        // The diff takes most of the panel
+        let split = if self.diff.is_some() { 30 } else { 50 };
         let chunks = Layout::default()
             .direction(direction)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .constraints([
+                Constraint::Percentage(split),
+                Constraint::Percentage(100 - split),
+            ])
             .split(area);
 
         // Left: commit info
         let left_block = Block::default()
             .title(" Commit Detail ")
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::DarkGray));
+            .border_style(self.theme.border_style());
 
         let left_paragraph = Paragraph::new(self.commit_lines)
             .block(left_block)
@@ -234,11 +517,21 @@ impl<'a> Widget for CommitDetailWidget<'a> {
 
         Widget::render(left_paragraph, chunks[0], buf);
 
-        // Right: file list
+        // Right: diff when open, otherwise the file list
+        if let Some((lines, scroll_offset)) = self.diff {
+            let block = Block::default()
+                .title(" Diff ")
+                .borders(Borders::ALL)
+                .border_style(self.theme.border_style());
+            let scroll = u16::try_from(scroll_offset).unwrap_or(u16::MAX);
+            let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+            Widget::render(paragraph, chunks[1], buf);
+            return;
+        }
         let right_block = Block::default()
             .title(" Changed Files ")
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::DarkGray));
+            .border_style(self.theme.border_style());
 
         let right_paragraph = Paragraph::new(self.file_lines)
             .block(right_block)
@@ -247,3 +540,11 @@ impl<'a> Widget for CommitDetailWidget<'a> {
         Widget::render(right_paragraph, chunks[1], buf);
     }
 }
+
+/// Cut an overly long message line (the full text is in the raw commit popup)
+fn clip_message_line(line: &str) -> String {
+    match line.char_indices().nth(MESSAGE_LINE_MAX_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
@@ -1,5 +1,7 @@
 //! Input and confirmation dialog widgets
 
+use std::path::PathBuf;
+
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
@@ -8,6 +10,9 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph, Widget},
 };
 
+use super::theme::Theme;
+use crate::{app::ConflictOp, error::short_oid, git::operations::BranchRetarget};
+
 /// Truncate a string to fit within max_width, adding "..." if needed
 fn truncate_with_ellipsis(s: &str, max_width: usize) -> String {
     if s.len() <= max_width {
@@ -21,11 +26,16 @@ fn truncate_with_ellipsis(s: &str, max_width: usize) -> String {
 pub struct InputDialog<'a> {
     title: &'a str,
     input: &'a str,
+    theme: Theme,
 }
 
 impl<'a> InputDialog<'a> {
-    pub fn new(title: &'a str, input: &'a str) -> Self {
-        Self { title, input }
+    pub fn new(title: &'a str, input: &'a str, theme: Theme) -> Self {
+        Self {
+            title,
+            input,
+            theme,
+        }
     }
 }
 
@@ -43,7 +53,7 @@ impl<'a> Widget for InputDialog<'a> {
             .fg(Color::White)
             .add_modifier(Modifier::UNDERLINED);
 
-        let hint_style = Style::default().fg(Color::DarkGray);
+        let hint_style = self.theme.muted_style();
         let lines = vec![
             Line::from(""),
             Line::from(vec![
@@ -81,12 +91,16 @@ impl<'a> Widget for ConfirmDialog<'a> {
             .border_style(Style::default().fg(Color::Yellow))
             .style(Style::default().bg(Color::Black));
 
-        let lines = vec![
-            Line::from(""),
-            Line::from(Span::styled(
-                format!("  {}", self.message),
-                Style::default().fg(Color::White),
-            )),
+        // The first line asks the question; further lines are notes
+        let mut lines = vec![Line::from("")];
+        for (i, text) in self.message.lines().enumerate() {
+            let color = if i == 0 { Color::White } else { Color::Yellow };
+            lines.push(Line::from(Span::styled(
+                format!("  {}", text),
+                Style::default().fg(color),
+            )));
+        }
+        lines.extend([
             Line::from(""),
             Line::from(vec![
                 Span::styled(
@@ -102,24 +116,276 @@ impl<'a> Widget for ConfirmDialog<'a> {
                 ),
                 Span::raw(": No"),
             ]),
-        ];
+        ]);
 
         let paragraph = Paragraph::new(lines).block(block);
         Widget::render(paragraph, area, buf);
     }
 }
 
+/// Files that block a checkout, with the ways to resolve it
+pub struct CheckoutBlockedDialog<'a> {
+    target: &'a str,
+    paths: &'a [PathBuf],
+    /// Some files have uncommitted changes, so overwriting is not offered
+    local_changes: bool,
+    scroll: u16,
+    theme: Theme,
+}
+
+impl<'a> CheckoutBlockedDialog<'a> {
+    pub fn new(
+        target: &'a str,
+        paths: &'a [PathBuf],
+        local_changes: bool,
+        scroll: u16,
+        theme: Theme,
+    ) -> Self {
+        Self {
+            target,
+            paths,
+            local_changes,
+            scroll,
+            theme,
+        }
+    }
+}
+
+impl<'a> Widget for CheckoutBlockedDialog<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let title = if self.local_changes {
+            " Uncommitted changes would be overwritten "
+        } else {
+            " Untracked files would be overwritten "
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height < 2 {
+            return;
+        }
+
+        // File list scrolls; the key hints stay on the last row
+        let mut lines = vec![Line::from(Span::styled(
+            format!("  Checking out '{}' would overwrite:", self.target),
+            Style::default().fg(Color::White),
+        ))];
+        lines.extend(self.paths.iter().map(|path| {
+            Line::from(Span::styled(
+                format!("    {}", path.display()),
+                Style::default().fg(Color::Red),
+            ))
+        }));
+        let list_area = Rect {
+            height: inner.height - 1,
+            ..inner
+        };
+        Widget::render(
+            Paragraph::new(lines).scroll((self.scroll, 0)),
+            list_area,
+            buf,
+        );
+
+        let key_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let mut hints = Vec::new();
+        if !self.local_changes {
+            hints.push(Span::styled("  f", key_style));
+            hints.push(Span::raw(": overwrite them"));
+        }
+        hints.extend([
+            Span::styled("  s", key_style),
+            Span::raw(": stash (incl. untracked) and retry  "),
+            Span::styled("Esc", key_style),
+            Span::styled(": cancel", self.theme.muted_style()),
+        ]);
+        let hints = Line::from(hints);
+        buf.set_line(inner.x, inner.bottom() - 1, &hints, inner.width);
+    }
+}
+
+/// Files a merge or cherry-pick left conflicted, with the ways out
+pub struct ConflictDialog<'a> {
+    op: ConflictOp,
+    files: &'a [String],
+    scroll: u16,
+    theme: Theme,
+}
+
+impl<'a> ConflictDialog<'a> {
+    pub fn new(op: ConflictOp, files: &'a [String], scroll: u16, theme: Theme) -> Self {
+        Self {
+            op,
+            files,
+            scroll,
+            theme,
+        }
+    }
+}
+
+impl Widget for ConflictDialog<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .title(format!(" {} stopped with conflicts ", self.op.title()))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Red))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height < 2 {
+            return;
+        }
+
+        // The file list scrolls; the key hints stay on the last row
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!(
+                    "  Resolve these files in your editor and commit, or abort the {}:",
+                    self.op.label()
+                ),
+                Style::default().fg(Color::White),
+            )),
+            Line::from(""),
+        ];
+        lines.extend(self.files.iter().map(|file| {
+            Line::from(Span::styled(
+                format!("    {}", file),
+                Style::default().fg(Color::Red),
+            ))
+        }));
+        let list_area = Rect {
+            height: inner.height - 1,
+            ..inner
+        };
+        Paragraph::new(lines)
+            .scroll((self.scroll, 0))
+            .render(list_area, buf);
+
+        let key_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let hints = Line::from(vec![
+            Span::styled("  a", key_style),
+            Span::raw(format!(": abort {} (reset to HEAD)  ", self.op.label())),
+            Span::styled("Esc", key_style),
+            Span::styled(": resolve in editor", self.theme.muted_style()),
+        ]);
+        buf.set_line(inner.x, inner.bottom() - 1, &hints, inner.width);
+    }
+}
+
+/// Checklist of branches a rebase left behind, to move onto the rewritten commits
+pub struct RetargetDialog<'a> {
+    moves: &'a [BranchRetarget],
+    checked: &'a [bool],
+    selected: usize,
+    theme: Theme,
+}
+
+impl<'a> RetargetDialog<'a> {
+    pub fn new(
+        moves: &'a [BranchRetarget],
+        checked: &'a [bool],
+        selected: usize,
+        theme: Theme,
+    ) -> Self {
+        Self {
+            moves,
+            checked,
+            selected,
+            theme,
+        }
+    }
+}
+
+impl Widget for RetargetDialog<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .title(" Move branches onto the rebased commits? ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height < 2 {
+            return;
+        }
+
+        // The list scrolls with the selection; the key hints stay on the last row
+        let height = inner.height as usize - 1;
+        let offset = self.selected.saturating_sub(height - 1);
+        for (i, retarget) in self.moves.iter().enumerate().skip(offset).take(height) {
+            let y = inner.y + (i - offset) as u16;
+            let mark = if self.checked[i] { "[x]" } else { "[ ]" };
+            let mut line = Line::from(vec![
+                Span::styled(format!("  {} ", mark), Style::default().fg(Color::White)),
+                Span::styled(
+                    retarget.name.clone(),
+                    self.theme
+                        .selectable_style(Style::default().fg(Color::Green)),
+                ),
+                Span::styled(
+                    format!(
+                        "  {} → {}",
+                        short_oid(retarget.from),
+                        short_oid(retarget.to)
+                    ),
+                    self.theme.muted_style(),
+                ),
+            ]);
+            if i == self.selected {
+                line = line.patch_style(self.theme.selection);
+                buf.set_style(
+                    Rect {
+                        y,
+                        height: 1,
+                        ..inner
+                    },
+                    self.theme.selection,
+                );
+            }
+            buf.set_line(inner.x, y, &line, inner.width);
+        }
+
+        let key_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let hints = Line::from(vec![
+            Span::styled("  Space", key_style),
+            Span::raw(": toggle  "),
+            Span::styled("Enter", key_style),
+            Span::raw(": move checked  "),
+            Span::styled("Esc", key_style),
+            Span::styled(": leave all", self.theme.muted_style()),
+        ]);
+        buf.set_line(inner.x, inner.bottom() - 1, &hints, inner.width);
+    }
+}
+
 /// Branch info popup (shown when multiple branches exist on selected node)
 pub struct BranchInfoPopup<'a> {
     branches: &'a [&'a str],
     selected_branch: Option<&'a str>,
+    theme: Theme,
 }
 
 impl<'a> BranchInfoPopup<'a> {
-    pub fn new(branches: &'a [&'a str], selected_branch: Option<&'a str>) -> Self {
+    pub fn new(branches: &'a [&'a str], selected_branch: Option<&'a str>, theme: Theme) -> Self {
         Self {
             branches,
             selected_branch,
+            theme,
         }
     }
 }
@@ -151,7 +417,8 @@ impl<'a> Widget for BranchInfoPopup<'a> {
                     .bg(Color::Blue)
                     .add_modifier(Modifier::BOLD)
             } else {
-                Style::default().fg(Color::White)
+                self.theme
+                    .selectable_style(Style::default().fg(Color::White))
             };
 
             let prefix = if is_selected { "▶ " } else { "  " };
@@ -166,3 +433,76 @@ impl<'a> Widget for BranchInfoPopup<'a> {
         }
     }
 }
+
+/// Quit requested while an operation that changes the repository is still running
+pub struct QuitBlockedDialog<'a> {
+    /// e.g. "A fetch from origin"
+    operation: &'a str,
+    /// Waiting for the operation to finish (or to stop after aborting) before quitting
+    waiting: bool,
+    theme: Theme,
+}
+
+impl<'a> QuitBlockedDialog<'a> {
+    pub fn new(operation: &'a str, waiting: bool, theme: Theme) -> Self {
+        Self {
+            operation,
+            waiting,
+            theme,
+        }
+    }
+}
+
+impl Widget for QuitBlockedDialog<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .title(" Quit ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .style(Style::default().bg(Color::Black));
+
+        let key_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let lines = if self.waiting {
+            let text = match self.theme.spinner(super::theme::spinner_tick()) {
+                Some(frame) => format!("  {} Waiting for it to finish...", frame),
+                None => "  Waiting for it to finish...".to_string(),
+            };
+            vec![
+                Line::from(""),
+                Line::from(Span::styled(
+                    format!("  {} is still running.", self.operation),
+                    Style::default().fg(Color::White),
+                )),
+                Line::from(Span::styled(text, self.theme.muted_style())),
+                Line::from(vec![
+                    Span::styled("  Esc", key_style),
+                    Span::raw(": stop waiting"),
+                ]),
+            ]
+        } else {
+            vec![
+                Line::from(""),
+                Line::from(Span::styled(
+                    format!("  {} is still running.", self.operation),
+                    Style::default().fg(Color::White),
+                )),
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled("  w", key_style),
+                    Span::raw(": wait and quit  "),
+                    Span::styled("a", key_style),
+                    Span::raw(": abort and quit  "),
+                    Span::styled("Esc", key_style),
+                    Span::raw(": cancel"),
+                ]),
+            ]
+        };
+
+        let paragraph = Paragraph::new(lines).block(block);
+        Widget::render(paragraph, area, buf);
+    }
+}
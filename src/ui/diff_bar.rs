@@ -0,0 +1,144 @@
+//! Proportional +/- bars for the changed file list (like GitHub's diffstat squares)
+
+/// Cells of a bar on panes wide enough for it
+const BAR_WIDTH: usize = 5;
+/// Cells of a bar on very narrow panes
+const NARROW_BAR_WIDTH: usize = 3;
+/// Pane width (inside borders) from which the +/- numbers are shown next to the bar
+const NUMBERS_MIN_WIDTH: usize = 40;
+/// Pane width below which the bar shrinks
+const FULL_BAR_MIN_WIDTH: usize = 24;
+
+/// Left-aligned partial blocks, one to seven eighths of a cell
+const PARTIAL_BLOCKS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+const FULL_BLOCK: char = '█';
+/// Unused part of a bar
+pub const EMPTY_CELL: char = '░';
+
+/// How the stats column fits a pane of the given inner width
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatsLayout {
+    /// Bar width in cells
+    pub bar_width: usize,
+    /// Whether "+12 -3" is shown as well
+    pub numbers: bool,
+}
+
+impl StatsLayout {
+    pub fn for_width(pane_width: usize) -> Self {
+        Self {
+            bar_width: if pane_width < FULL_BAR_MIN_WIDTH {
+                NARROW_BAR_WIDTH
+            } else {
+                BAR_WIDTH
+            },
+            numbers: pane_width >= NUMBERS_MIN_WIDTH,
+        }
+    }
+}
+
+/// Text of one file's bar: insertions, deletions and the unused remainder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffBar {
+    pub added: String,
+    pub deleted: String,
+    pub empty: String,
+}
+
+impl DiffBar {
+    /// Bar for a file scaled against `max_changes`, the largest file in the commit
+    /// Any change is visible (at least an eighth of a cell per non-zero side)
+    pub fn new(insertions: usize, deletions: usize, max_changes: usize, width: usize) -> Self {
+        let changes = insertions + deletions;
+        let capacity = width * 8;
+        let sides = usize::from(insertions > 0) + usize::from(deletions > 0);
+        let total = if changes == 0 || max_changes == 0 {
+            0
+        } else {
+            ((changes * capacity + max_changes / 2) / max_changes).clamp(sides, capacity)
+        };
+
+        let mut added = (total * insertions + changes / 2) / changes.max(1);
+        if insertions > 0 && added == 0 {
+            added = 1;
+        }
+        if deletions > 0 {
+            // Leave room for the deletions
+            added = added
+                .min(total.saturating_sub(1))
+                .min(width.saturating_sub(1) * 8);
+        }
+        let added_cells = added.div_ceil(8);
+        // A partial cell ends each side, so deletions start on the next cell
+        let deleted = (total - added).min((width - added_cells) * 8);
+        let deleted_cells = deleted.div_ceil(8);
+
+        Self {
+            added: blocks(added),
+            deleted: blocks(deleted),
+            empty: EMPTY_CELL
+                .to_string()
+                .repeat(width - added_cells - deleted_cells),
+        }
+    }
+}
+
+/// Full blocks followed by a partial block for the remaining eighths
+fn blocks(eighths: usize) -> String {
+    let mut text = FULL_BLOCK.to_string().repeat(eighths / 8);
+    if let Some(partial) = (eighths % 8).checked_sub(1) {
+        text.push(PARTIAL_BLOCKS[partial]);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(insertions: usize, deletions: usize, max: usize) -> String {
+        let bar = DiffBar::new(insertions, deletions, max, 5);
+        format!("{}|{}|{}", bar.added, bar.deleted, bar.empty)
+    }
+
+    #[test]
+    fn test_bars_scale_against_largest_file() {
+        // The largest file fills the bar
+        assert_eq!(bar(60, 20, 80), "███▊|█|");
+        // Half the changes, half the cells (with an eighth of precision)
+        assert_eq!(bar(40, 0, 80), "██▌||░░");
+        assert_eq!(bar(0, 10, 80), "|▋|░░░░");
+        // Tiny changes stay visible on both sides
+        assert_eq!(bar(1, 1, 1000), "▏|▏|░░░");
+        assert_eq!(bar(0, 0, 80), "||░░░░░");
+        // A lopsided largest file still shows its deletions
+        assert_eq!(bar(79, 1, 80), "████|█|");
+    }
+
+    #[test]
+    fn test_bar_never_exceeds_its_width() {
+        for insertions in 0..40 {
+            for deletions in 0..40 {
+                let bar = DiffBar::new(insertions, deletions, 79, 5);
+                let cells = [&bar.added, &bar.deleted, &bar.empty]
+                    .iter()
+                    .map(|s| s.chars().count())
+                    .sum::<usize>();
+                assert_eq!(cells, 5, "{} {}: {:?}", insertions, deletions, bar);
+            }
+        }
+    }
+
+    #[test]
+    fn test_narrow_panes_drop_numbers_then_shrink_bar() {
+        assert_eq!(
+            StatsLayout::for_width(60),
+            StatsLayout {
+                bar_width: 5,
+                numbers: true
+            }
+        );
+        assert!(!StatsLayout::for_width(30).numbers);
+        assert_eq!(StatsLayout::for_width(20).bar_width, 3);
+    }
+}
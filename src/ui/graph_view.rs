@@ -5,25 +5,29 @@ use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, StatefulWidget},
+    widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, StatefulWidget},
 };
 use unicode_width::UnicodeWidthChar;
 
 use crate::{
     app::App,
     git::graph::{CellType, GraphNode},
-    graph::colors::get_color_by_index,
+    graph::{
+        colors::get_color_by_index,
+        glyphs::{box_glyph, braille_glyphs, GraphGlyphs},
+    },
+    gutter::Gutter,
 };
 
-use super::{render_placeholder_block, MIN_WIDGET_HEIGHT, MIN_WIDGET_WIDTH};
+use super::{render_placeholder_block, theme::Theme, MIN_WIDGET_HEIGHT, MIN_WIDGET_WIDTH};
 
 /// VS16 (U+FE0F) variation selector for emoji presentation
-const VS16: char = '\u{FE0F}';
+pub(crate) const VS16: char = '\u{FE0F}';
 
 /// Calculate character width considering VS16 emoji presentation sequence.
 /// If `next_char` is VS16, the character has emoji presentation width (2).
 /// VS16 itself has no width.
-fn char_width_with_vs16(c: char, next_char: Option<char>) -> usize {
+pub(crate) fn char_width_with_vs16(c: char, next_char: Option<char>) -> usize {
     if next_char == Some(VS16) {
         2
     } else if c == VS16 {
@@ -35,7 +39,7 @@ fn char_width_with_vs16(c: char, next_char: Option<char>) -> usize {
 
 /// Calculate display width of a string.
 /// Handles VS16 which changes preceding character to emoji presentation (width 2).
-fn display_width(s: &str) -> usize {
+pub(crate) fn display_width(s: &str) -> usize {
     let chars: Vec<char> = s.chars().collect();
     let mut width = 0;
     let mut i = 0;
@@ -55,16 +59,21 @@ fn display_width(s: &str) -> usize {
 
 pub struct GraphViewWidget<'a> {
     items: Vec<ListItem<'a>>,
+    theme: Theme,
 }
 
 impl<'a> GraphViewWidget<'a> {
     pub fn new(app: &App, width: u16) -> Self {
         let max_lane = app.graph_layout.max_lane;
-        // Actual width minus borders
-        let inner_width = width.saturating_sub(2) as usize;
+        let theme = Theme::from_config(&app.config.ui);
+        // Actual width minus borders and the selection symbol column
+        let inner_width = (width.saturating_sub(2) as usize)
+            .saturating_sub(display_width(theme.selection_symbol));
 
         // Get the currently selected branch name
         let selected_branch_name = app.selected_branch_name();
+        let editing_label = app.editing_branch_label();
+        let visual_range = app.visual_range();
 
         let items: Vec<ListItem> = app
             .graph_layout
@@ -73,41 +82,64 @@ impl<'a> GraphViewWidget<'a> {
             .enumerate()
             .map(|(idx, node)| {
                 let is_selected = app.graph_list_state.selected() == Some(idx);
+                let is_first_bad = node
+                    .commit
+                    .as_ref()
+                    .is_some_and(|c| app.bisect_first_bad == Some(c.oid));
                 let line = render_graph_line(
                     node,
                     max_lane,
                     is_selected,
+                    &app.gutter,
                     inner_width,
-                    selected_branch_name,
+                    match editing_label {
+                        Some(input) if is_selected => BranchLabels::Editing(input),
+                        _ => BranchLabels::Show {
+                            selected: selected_branch_name,
+                            tags: node
+                                .commit
+                                .as_ref()
+                                .and_then(|c| app.commit_tags.get(&c.oid))
+                                .map_or(&[], Vec::as_slice),
+                            // The selected row shows every label
+                            limit: (!is_selected).then_some(app.config.ui.max_labels),
+                            head_arrow: &app.config.ui.head_arrow,
+                        },
+                    },
+                    &theme,
                 );
-                ListItem::new(line)
+                // Highlight the first bad commit found by bisect
+                if is_first_bad {
+                    ListItem::new(line).style(Style::default().bg(Color::Red))
+                } else if visual_range.as_ref().is_some_and(|r| r.contains(&idx)) {
+                    ListItem::new(line).style(theme.visual)
+                } else {
+                    ListItem::new(line)
+                }
             })
             .collect();
 
-        Self { items }
+        Self { items, theme }
     }
 }
 
-/// Optimize branch name display
-/// - If a local branch matches its origin/xxx, show "xxx <-> origin"
-/// - Otherwise, show each name separately
-/// - Render in bold with the graph color, wrapped in brackets
+/// Max width for a single label (e.g., "[fix/feature-name]")
+const MAX_LABEL_WIDTH: usize = 40;
+
+/// All labels of a row: local branches, tags, then remotes, with branches in the node's order
+/// (the HEAD branch first unless `ui.label_order` says otherwise)
+/// - If a local branch matches its origin/xxx, show "xxx <-> origin" (the remote is not repeated)
+/// - Branches render in bold with the graph color, wrapped in brackets; tags as yellow `<tag>`
 /// - Selected branch is shown with inverted colors
-fn optimize_branch_display(
+fn decoration_labels(
     branch_names: &[String],
+    tags: &[String],
     is_head: bool,
     color_index: usize,
     selected_branch_name: Option<&str>,
 ) -> Vec<(String, Style)> {
     use std::collections::HashSet;
 
-    if branch_names.is_empty() {
-        return Vec::new();
-    }
-
-    // Max width for a single branch label (e.g., "[fix/feature-name]")
-    const MAX_LABEL_WIDTH: usize = 40;
-
     // Split local and remote branches (HashSet for O(1) lookup)
     let local_branches: HashSet<&str> = branch_names
         .iter()
@@ -150,7 +182,7 @@ fn optimize_branch_display(
             return label;
         }
 
-        let abbrev = abbreviate_branch_label(name, abbrev_width, 0);
+        let abbrev = abbreviate_branch_label(name, abbrev_width);
         if let Some(s) = suffix {
             abbrev.replace(']', &format!(" {}]", s))
         } else {
@@ -158,15 +190,16 @@ fn optimize_branch_display(
         }
     };
 
-    // Process branches in original order (matches tab order from filter_remote_duplicates)
-    let mut result: Vec<(String, Style)> = Vec::new();
+    // Branches keep the node's order within a group (matches the tab order)
+    let mut labels: Vec<(String, Style)> = Vec::new();
+    let mut remotes: Vec<(String, Style)> = Vec::new();
     for name in branch_names {
         if let Some(local_name) = name.strip_prefix("origin/") {
             // Remote branch: skip if matching local exists
             if local_branches.contains(local_name) {
                 continue;
             }
-            result.push((make_label(name, None), make_style(name)));
+            remotes.push((make_label(name, None), make_style(name)));
         } else {
             // Local branch: check for matching remote
             let remote_name = format!("origin/{}", name);
@@ -175,34 +208,83 @@ fn optimize_branch_display(
             } else {
                 None
             };
-            result.push((make_label(name, suffix), make_style(name)));
+            labels.push((make_label(name, suffix), make_style(name)));
         }
     }
 
-    // Collapse multiple branches to single + count
-    if result.len() > 1 {
-        // Find selected index directly from branch_names, clamped to result bounds
-        let selected_idx = selected_branch_name
-            .and_then(|sel| {
-                branch_names
-                    .iter()
-                    .position(|n| n == sel || n.ends_with(&format!("/{}", sel)))
-            })
-            .unwrap_or(0)
-            .min(result.len().saturating_sub(1));
-
-        let (label, style) = &result[selected_idx];
-        let clean_name = label
-            .trim_start_matches('[')
-            .split([']', ' '])
-            .next()
-            .unwrap_or(label);
-        let abbreviated = abbreviate_branch_label(clean_name, MAX_LABEL_WIDTH, result.len() - 1);
+    let tag_style = Style::default().fg(Color::Yellow);
+    labels.extend(tags.iter().map(|tag| {
+        let name = truncate_to_width(tag, MAX_LABEL_WIDTH - 2);
+        (format!("<{}>", name), tag_style)
+    }));
+    labels.extend(remotes);
+    labels
+}
 
-        return vec![(abbreviated, *style)];
+/// Labels that fit in `max_width` columns (one-space separators included), at most `limit`
+/// of them (None: no limit), then a "+N" counting the rest; the first label is always kept
+fn fit_labels(
+    labels: Vec<(String, Style)>,
+    limit: Option<usize>,
+    max_width: usize,
+    more_style: Style,
+) -> Vec<(String, Style)> {
+    let total = labels.len();
+    let mut kept = Vec::new();
+    let mut used = 0;
+    for (i, label) in labels.into_iter().enumerate() {
+        let width = display_width(&label.0) + usize::from(i > 0);
+        // Room for the "+N" of whatever follows this label
+        let rest = total - i - 1;
+        let more_width = if rest > 0 {
+            format!(" +{}", rest).len()
+        } else {
+            0
+        };
+        let within_limit = limit.is_none_or(|limit| i < limit);
+        if i > 0 && (!within_limit || used + width + more_width > max_width) {
+            break;
+        }
+        used += width;
+        kept.push(label);
+    }
+    let hidden = total - kept.len();
+    if hidden > 0 {
+        kept.push((format!("+{}", hidden), more_style));
     }
+    kept
+}
 
-    result
+/// How branch labels are drawn on a row
+enum BranchLabels<'s> {
+    /// Normal labels, highlighting the selected branch, after the HEAD decoration (if any)
+    Show {
+        selected: Option<&'s str>,
+        tags: &'s [String],
+        /// Labels shown before the rest collapse into "+N" (None: as many as fit)
+        limit: Option<usize>,
+        head_arrow: &'s str,
+    },
+    /// A single editable field holding the given input
+    Editing(&'s str),
+}
+
+/// Spans for a branch label being edited in place: `[input_]` with an underlined field and cursor
+fn editing_label_spans(input: &str) -> Vec<(String, Style)> {
+    let bracket_style = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    vec![
+        ("[".to_string(), bracket_style),
+        (
+            input.to_string(),
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::UNDERLINED),
+        ),
+        ("_".to_string(), Style::default().fg(Color::Cyan)),
+        ("]".to_string(), bracket_style),
+    ]
 }
 
 /// Truncate a string to the specified display width.
@@ -255,24 +337,17 @@ fn compute_right_side_visibility(remaining_for_content: usize) -> (bool, bool, b
     }
 }
 
-/// Abbreviate branch name to max_width, showing "+N" if more branches exist
+/// Abbreviate branch name to max_width (brackets included)
 /// Uses format: prefix/head...tail (preserving last 5 chars)
-fn abbreviate_branch_label(name: &str, max_width: usize, extra_count: usize) -> String {
+fn abbreviate_branch_label(name: &str, max_width: usize) -> String {
     const TAIL_LEN: usize = 5;
     const ELLIPSIS: &str = "...";
 
-    let suffix = if extra_count > 0 {
-        format!(" +{}", extra_count)
-    } else {
-        String::new()
-    };
-
-    let suffix_len = display_width(&suffix);
-    let available = max_width.saturating_sub(suffix_len).saturating_sub(2); // -2 for brackets
+    let available = max_width.saturating_sub(2); // -2 for brackets
 
     // If name fits, return as-is
     if display_width(name) <= available {
-        return format!("[{}]{}", name, suffix);
+        return format!("[{}]", name);
     }
 
     // Find "/" position to preserve prefix
@@ -302,20 +377,22 @@ fn abbreviate_branch_label(name: &str, max_width: usize, extra_count: usize) ->
     if head_available == 0 {
         // Not enough space for head, just show truncated name
         let truncated = truncate_to_width(name, available.saturating_sub(3));
-        return format!("[{}...]{}", truncated, suffix);
+        return format!("[{}...]", truncated);
     }
 
     let head = truncate_to_width(rest, head_available);
 
-    format!("[{}{}{}{}]{}", prefix, head, ELLIPSIS, tail, suffix)
+    format!("[{}{}{}{}]", prefix, head, ELLIPSIS, tail)
 }
 
 fn render_graph_line<'a>(
     node: &GraphNode,
     max_lane: usize,
     is_selected: bool,
+    gutter: &Gutter,
     total_width: usize,
-    selected_branch_name: Option<&str>,
+    labels: BranchLabels,
+    theme: &Theme,
 ) -> Line<'a> {
     let mut spans: Vec<Span> = Vec::new();
 
@@ -323,39 +400,63 @@ fn render_graph_line<'a>(
     spans.push(Span::raw(" "));
     let mut left_width: usize = 1;
 
+    // Gutter column for per-commit marks (zero width when no marks are active)
+    let gutter_width = gutter.width();
+    if gutter_width > 0 {
+        let mark = node.commit.as_ref().and_then(|c| gutter.get(c.oid));
+        match mark {
+            Some(mark) => spans.push(Span::styled(
+                format!("{:<width$}", mark.symbol(), width = gutter_width),
+                Style::default()
+                    .fg(mark.color())
+                    .add_modifier(Modifier::BOLD),
+            )),
+            None => spans.push(Span::raw(" ".repeat(gutter_width))),
+        }
+        left_width += gutter_width;
+    }
+
     // Render cells
-    for cell in &node.cells {
-        let (ch, color) = match cell {
-            CellType::Empty => (' ', Color::Reset),
-            CellType::Pipe(color_idx) => ('│', get_color_by_index(*color_idx)),
+    let glyphs: Vec<(char, CellType)> = match theme.graph_glyphs {
+        GraphGlyphs::Box => node
+            .cells
+            .iter()
+            .map(|cell| (box_glyph(*cell, node.is_head), *cell))
+            .collect(),
+        GraphGlyphs::Braille => {
+            braille_glyphs(&node.cells, node.upper_cells.as_deref(), node.is_head)
+        }
+    };
+    for (ch, cell) in glyphs {
+        let color = match cell {
+            CellType::Empty => Color::Reset,
             CellType::Commit(color_idx) => {
-                // HEAD uses a double circle, others use a filled circle
-                let ch = if node.is_head { '◉' } else { '●' };
                 // Main branch (blue) stays blue; other HEADs are green
-                let is_main = *color_idx == crate::graph::colors::MAIN_BRANCH_COLOR;
-                let color = if node.is_head && !is_main {
+                let is_main = color_idx == crate::graph::colors::MAIN_BRANCH_COLOR;
+                if node.is_head && !is_main {
                     Color::Green
                 } else {
-                    get_color_by_index(*color_idx)
-                };
-                (ch, color)
-            }
-            CellType::BranchRight(color_idx) => ('╭', get_color_by_index(*color_idx)),
-            CellType::BranchLeft(color_idx) => ('╮', get_color_by_index(*color_idx)),
-            CellType::MergeRight(color_idx) => ('╰', get_color_by_index(*color_idx)),
-            CellType::MergeLeft(color_idx) => ('╯', get_color_by_index(*color_idx)),
-            CellType::Horizontal(color_idx) => ('─', get_color_by_index(*color_idx)),
-            CellType::HorizontalPipe(_h_color_idx, p_color_idx) => {
-                // Vertical and horizontal lines cross (use pipe color)
-                ('┼', get_color_by_index(*p_color_idx))
+                    get_color_by_index(color_idx)
+                }
             }
-            CellType::TeeRight(color_idx) => ('├', get_color_by_index(*color_idx)),
-            CellType::TeeLeft(color_idx) => ('┤', get_color_by_index(*color_idx)),
-            CellType::TeeUp(color_idx) => ('┴', get_color_by_index(*color_idx)),
+            // Vertical and horizontal lines cross (use pipe color)
+            CellType::HorizontalPipe(_, color_idx)
+            | CellType::Pipe(color_idx)
+            | CellType::BranchRight(color_idx)
+            | CellType::BranchLeft(color_idx)
+            | CellType::MergeRight(color_idx)
+            | CellType::MergeLeft(color_idx)
+            | CellType::Horizontal(color_idx)
+            | CellType::TeeRight(color_idx)
+            | CellType::TeeLeft(color_idx)
+            | CellType::TeeUp(color_idx)
+            | CellType::TeeDown(color_idx) => get_color_by_index(color_idx),
         };
 
         // Draw all line glyphs in bold
-        let style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+        let style = Style::default()
+            .fg(theme.color(color))
+            .add_modifier(Modifier::BOLD);
 
         let ch_str = ch.to_string();
         let ch_width = display_width(&ch_str);
@@ -364,10 +465,10 @@ fn render_graph_line<'a>(
     }
 
     // Padding to align graph width (display width based)
-    let graph_display_width = (max_lane + 1) * 2;
-    if left_width < graph_display_width + 1 {
-        // +1 accounts for the start marker
-        let padding = graph_display_width + 1 - left_width;
+    let graph_display_width = theme.graph_glyphs.width(max_lane);
+    let graph_end = graph_display_width + 1 + gutter_width; // start marker and gutter
+    if left_width < graph_end {
+        let padding = graph_end - left_width;
         spans.push(Span::raw(" ".repeat(padding)));
         left_width += padding;
     }
@@ -384,6 +485,13 @@ fn render_graph_line<'a>(
         return Line::from(spans);
     }
 
+    // Elision row: commits hidden by a filter
+    if let Some(span) = node.elided {
+        let text = format!("· · · {} · · ·", span.describe());
+        spans.push(Span::styled(text, theme.muted_style()));
+        return Line::from(spans);
+    }
+
     // Early return for connector-only rows
     let commit = match &node.commit {
         Some(c) => c,
@@ -393,23 +501,13 @@ fn render_graph_line<'a>(
     // Style definitions
     let hash_style = Style::default().fg(Color::Yellow);
     let author_style = Style::default().fg(Color::Cyan);
-    let date_style = Style::default().fg(Color::DarkGray);
+    let date_style = theme.muted_style();
     let msg_style = if is_selected {
         Style::default().add_modifier(Modifier::BOLD)
     } else {
-        Style::default()
+        theme.selectable_style(Style::default())
     };
 
-    // === Left-aligned: branch names + message ===
-
-    // Optimize branch names (compact when local matches origin/local)
-    let branch_display = optimize_branch_display(
-        &node.branch_names,
-        node.is_head,
-        node.color_index,
-        selected_branch_name,
-    );
-
     // === Right-aligned: date author hash (fixed width) ===
     let date = commit.timestamp.format("%Y-%m-%d").to_string(); // 10 chars
     let author = truncate_to_width(&commit.author_name, 8);
@@ -417,14 +515,6 @@ fn render_graph_line<'a>(
     let hash = truncate_to_width(&commit.short_id, 7);
     let hash_formatted = format!("{:<7}", hash); // fixed 7 chars
 
-    // Calculate branch width first (before rendering)
-    let branch_width: usize = branch_display
-        .iter()
-        .enumerate()
-        .map(|(i, (label, _))| display_width(label) + if i > 0 { 1 } else { 0 })
-        .sum::<usize>()
-        + if !branch_display.is_empty() { 1 } else { 0 };
-
     // Calculate remaining space for branch + message + right info
     let graph_width = left_width;
     let remaining_for_content = total_width.saturating_sub(graph_width);
@@ -433,11 +523,54 @@ fn render_graph_line<'a>(
     let (show_date, show_author, show_hash, right_width) =
         compute_right_side_visibility(remaining_for_content);
 
+    // === Left-aligned: branch names + message ===
+
+    // Labels in priority order, limited by count and by the width left of the right block
+    // While a label is being renamed, it is replaced by an editable field
+    let (branch_display, label_separator) = match labels {
+        BranchLabels::Editing(input) => (editing_label_spans(input), ""),
+        BranchLabels::Show {
+            selected,
+            tags,
+            limit,
+            head_arrow,
+        } => {
+            // Also marks a detached HEAD, which has no labels
+            let arrow = (node.is_head && !head_arrow.is_empty()).then_some(head_arrow);
+            let arrow_width = arrow.map_or(0, |a| display_width(a) + 1);
+            let labels = decoration_labels(
+                &node.branch_names,
+                tags,
+                node.is_head,
+                node.color_index,
+                selected,
+            );
+            // -1 for the space before the message
+            let max_width = remaining_for_content.saturating_sub(right_width + arrow_width + 1);
+            let mut labels = fit_labels(labels, limit, max_width, theme.muted_style());
+            if let Some(arrow) = arrow {
+                let style = Style::default()
+                    .fg(theme.head_arrow)
+                    .add_modifier(Modifier::BOLD);
+                labels.insert(0, (arrow.to_string(), style));
+            }
+            (labels, " ")
+        }
+    };
+
+    // Calculate branch width first (before rendering)
+    let branch_width: usize = branch_display
+        .iter()
+        .enumerate()
+        .map(|(i, (label, _))| display_width(label) + if i > 0 { label_separator.len() } else { 0 })
+        .sum::<usize>()
+        + if !branch_display.is_empty() { 1 } else { 0 };
+
     // Render branch labels
     for (i, (label, style)) in branch_display.iter().enumerate() {
-        if i > 0 {
-            spans.push(Span::raw(" "));
-            left_width += 1;
+        if i > 0 && !label_separator.is_empty() {
+            spans.push(Span::raw(label_separator));
+            left_width += label_separator.len();
         }
         left_width += display_width(label);
         spans.push(Span::styled(label.clone(), *style));
@@ -489,23 +622,277 @@ impl<'a> StatefulWidget for GraphViewWidget<'a> {
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         if area.width < MIN_WIDGET_WIDTH || area.height < MIN_WIDGET_HEIGHT {
-            render_placeholder_block(area, buf);
+            render_placeholder_block(area, buf, &self.theme);
             return;
         }
 
         let block = Block::default()
             .title(" Commits ")
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::DarkGray));
-
-        let highlight_style = Style::default()
-            .bg(Color::DarkGray)
-            .add_modifier(Modifier::BOLD);
+            .border_style(self.theme.border_style());
 
         let list = List::new(self.items)
             .block(block)
-            .highlight_style(highlight_style);
+            .highlight_style(self.theme.selection)
+            .highlight_symbol(self.theme.selection_symbol)
+            .highlight_spacing(HighlightSpacing::Always);
 
         StatefulWidget::render(list, area, buf, state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::CommitInfo;
+    use crate::gutter::GutterMark;
+    use git2::Oid;
+
+    fn commit_node(n: u8, cells: Vec<CellType>) -> GraphNode {
+        GraphNode {
+            commit: Some(CommitInfo {
+                oid: Oid::from_bytes(&[n; 20]).unwrap(),
+                short_id: format!("{:07}", n),
+                author_name: "test".to_string(),
+                author_email: "test@example.com".to_string(),
+                timestamp: chrono::Local::now(),
+                author_time: 0,
+                message: format!("commit {}", n),
+                full_message: format!("commit {}", n),
+                parent_oids: Default::default(),
+            }),
+            lane: 0,
+            color_index: 0,
+            branch_names: Vec::new(),
+            is_head: false,
+            is_uncommitted: false,
+            uncommitted_count: 0,
+            cells,
+            owner_row: None,
+            elided: None,
+            upper_cells: None,
+        }
+    }
+
+    fn line_text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    fn render(node: &GraphNode, gutter: &Gutter) -> String {
+        line_text(&render_graph_line(
+            node,
+            1,
+            false,
+            gutter,
+            80,
+            BranchLabels::Show {
+                selected: None,
+                tags: &[],
+                limit: Some(3),
+                head_arrow: "",
+            },
+            &Theme::default(),
+        ))
+    }
+
+    #[test]
+    fn test_gutter_marks_keep_lanes_aligned() {
+        let marked = commit_node(1, vec![CellType::Commit(0), CellType::Empty]);
+        let unmarked = commit_node(
+            2,
+            vec![CellType::Pipe(0), CellType::Empty, CellType::Commit(1)],
+        );
+
+        let mut gutter = Gutter::default();
+        gutter.add(marked.commit.as_ref().unwrap().oid, GutterMark::Bookmark);
+
+        let marked_line = render(&marked, &gutter);
+        let unmarked_line = render(&unmarked, &gutter);
+        assert!(marked_line.starts_with(" ★ ●"), "{:?}", marked_line);
+        assert!(unmarked_line.starts_with("   │"), "{:?}", unmarked_line);
+        assert_eq!(display_width(&marked_line), display_width(&unmarked_line));
+    }
+
+    #[test]
+    fn test_empty_gutter_has_no_width() {
+        let node = commit_node(1, vec![CellType::Commit(0), CellType::Empty]);
+        let line = render(&node, &Gutter::default());
+        assert!(line.starts_with(" ●"), "{:?}", line);
+
+        // Marks on other commits widen every row by the same amount
+        let mut gutter = Gutter::default();
+        gutter.add(Oid::from_bytes(&[9; 20]).unwrap(), GutterMark::Bookmark);
+        let widened = render(&node, &gutter);
+        assert!(widened.starts_with("   ●"), "{:?}", widened);
+    }
+
+    #[test]
+    fn test_braille_glyphs_halve_the_graph_width() {
+        let node = commit_node(
+            1,
+            vec![
+                CellType::Commit(0),
+                CellType::Horizontal(1),
+                CellType::BranchLeft(1),
+                CellType::Empty,
+            ],
+        );
+        let theme = Theme {
+            graph_glyphs: GraphGlyphs::Braille,
+            ..Theme::default()
+        };
+        let line = line_text(&render_graph_line(
+            &node,
+            1,
+            false,
+            &Gutter::default(),
+            80,
+            BranchLabels::Editing(""),
+            &theme,
+        ));
+        // Start marker, one column per lane, separator
+        assert!(line.starts_with(" ⡷⡆ [_]"), "{:?}", line);
+    }
+
+    #[test]
+    fn test_editing_label_replaces_branch_labels() {
+        let mut node = commit_node(1, vec![CellType::Commit(0), CellType::Empty]);
+        node.branch_names = vec!["feature".to_string(), "other".to_string()];
+
+        let line = line_text(&render_graph_line(
+            &node,
+            1,
+            true,
+            &Gutter::default(),
+            80,
+            BranchLabels::Editing("feat"),
+            &Theme::default(),
+        ));
+        assert!(line.contains("[feat_] commit 1"), "{:?}", line);
+        assert!(!line.contains("other"), "{:?}", line);
+        assert_eq!(display_width(&line), 80);
+    }
+
+    #[test]
+    fn test_head_arrow_precedes_head_labels() {
+        let mut node = commit_node(1, vec![CellType::Commit(0), CellType::Empty]);
+        node.is_head = true;
+        node.branch_names = vec!["main".to_string()];
+        let render_with = |node: &GraphNode, arrow: &str| {
+            line_text(&render_graph_line(
+                node,
+                1,
+                false,
+                &Gutter::default(),
+                80,
+                BranchLabels::Show {
+                    selected: None,
+                    tags: &[],
+                    limit: Some(3),
+                    head_arrow: arrow,
+                },
+                &Theme::default(),
+            ))
+        };
+
+        let line = render_with(&node, "HEAD ->");
+        assert!(line.contains("HEAD -> [main] commit 1"), "{:?}", line);
+        assert_eq!(display_width(&line), 80);
+
+        // Detached HEAD: the arrow alone
+        node.branch_names.clear();
+        let line = render_with(&node, "→");
+        assert!(line.contains("→ commit 1"), "{:?}", line);
+        assert_eq!(display_width(&line), 80);
+
+        node.is_head = false;
+        assert!(!render_with(&node, "@").contains('@'));
+    }
+
+    /// Release commit with a HEAD branch, another local, two remotes (one tracked) and three tags
+    /// Branch names are in label order, as sorted by the app
+    fn decorated_node() -> GraphNode {
+        let mut node = commit_node(1, vec![CellType::Commit(0), CellType::Empty]);
+        node.is_head = true;
+        node.branch_names = ["main", "hotfix", "origin/main", "origin/release"]
+            .map(String::from)
+            .to_vec();
+        node
+    }
+
+    const TAGS: [&str; 3] = ["v2.0.0", "v2.0.0-rc2", "latest"];
+
+    fn render_decorated(width: usize, limit: Option<usize>) -> Line<'static> {
+        let tags = TAGS.map(String::from);
+        render_graph_line(
+            &decorated_node(),
+            1,
+            limit.is_none(),
+            &Gutter::default(),
+            width,
+            BranchLabels::Show {
+                selected: None,
+                tags: &tags,
+                limit,
+                head_arrow: "",
+            },
+            &Theme::default(),
+        )
+    }
+
+    #[test]
+    fn test_labels_follow_priority_and_collapse_into_count() {
+        let tags = TAGS.map(String::from);
+        let node = decorated_node();
+        let labels = decoration_labels(&node.branch_names, &tags, true, 0, None);
+        let texts: Vec<&str> = labels.iter().map(|(text, _)| text.as_str()).collect();
+        // Local branches, tags, then remotes without a local counterpart
+        assert_eq!(
+            texts,
+            [
+                "[main ↔ origin]",
+                "[hotfix]",
+                "<v2.0.0>",
+                "<v2.0.0-rc2>",
+                "<latest>",
+                "[origin/release]"
+            ]
+        );
+
+        let line = render_decorated(120, Some(3));
+        let text = line_text(&line);
+        assert!(
+            text.contains("[main ↔ origin] [hotfix] <v2.0.0> +3 commit 1"),
+            "{:?}",
+            text
+        );
+        assert_eq!(display_width(&text), 120);
+        let more = line.spans.iter().find(|s| s.content == "+3").unwrap();
+        assert_eq!(more.style, Theme::default().muted_style());
+    }
+
+    #[test]
+    fn test_selected_row_expands_labels_within_width() {
+        let text = line_text(&render_decorated(120, None));
+        assert!(
+            text.contains("<latest> [origin/release] commit 1"),
+            "{:?}",
+            text
+        );
+        assert!(!text.contains('+'), "{:?}", text);
+        assert_eq!(display_width(&text), 120);
+
+        // Too narrow for all of them: as many as fit, then the count
+        let text = line_text(&render_decorated(60, None));
+        assert!(
+            text.contains("[hotfix] <v2.0.0> <v2.0.0-rc2> +2 comm"),
+            "{:?}",
+            text
+        );
+        assert_eq!(display_width(&text), 60);
+
+        // The first label is kept even when nothing else fits
+        let text = line_text(&render_decorated(24, None));
+        assert!(text.contains("[main ↔ origin] +5"), "{:?}", text);
+    }
+}
@@ -8,7 +8,12 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph, Widget},
 };
 
-pub struct HelpPopup;
+use crate::{config::QKey, keybindings::KEY_SEQUENCES};
+
+pub struct HelpPopup {
+    /// What `q` does in the graph (`keys.q`)
+    pub q: QKey,
+}
 
 impl Widget for HelpPopup {
     fn render(self, area: Rect, buf: &mut Buffer) {
@@ -23,7 +28,12 @@ impl Widget for HelpPopup {
             .fg(Color::Yellow)
             .add_modifier(Modifier::BOLD);
 
-        let lines = vec![
+        let q_description = match self.q {
+            QKey::Back => "Back: close popup / dialog, else quit",
+            QKey::ConfirmQuit => "Back: close popup / dialog, else quit (asks)",
+        };
+
+        let mut lines = vec![
             Line::from(Span::styled("Navigation", header_style)),
             Line::from(vec![
                 Span::styled("  j / ↓      ", key_style),
@@ -59,7 +69,7 @@ impl Widget for HelpPopup {
             ]),
             Line::from(vec![
                 Span::styled("  g / Home   ", key_style),
-                Span::styled("Go to top", desc_style),
+                Span::styled("Go to top (g after a pause)", desc_style),
             ]),
             Line::from(vec![
                 Span::styled("  G / End    ", key_style),
@@ -69,6 +79,34 @@ impl Widget for HelpPopup {
                 Span::styled("  @          ", key_style),
                 Span::styled("Jump to HEAD (current branch)", desc_style),
             ]),
+            Line::from(vec![
+                Span::styled("  } / {      ", key_style),
+                Span::styled("Next / previous bookmark", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  < / >      ", key_style),
+                Span::styled("Jump to parent / child commit", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  '          ", key_style),
+                Span::styled("Jump back to --rev commit", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  %          ", key_style),
+                Span::styled("Jump between rewritten / original", desc_style),
+            ]),
+        ];
+        // Key sequences come from the same table the key handling uses
+        lines.extend(KEY_SEQUENCES.iter().map(|sequence| {
+            Line::from(vec![
+                Span::styled(
+                    format!("  {:<11}", format!("{}{}", sequence.prefix, sequence.key)),
+                    key_style,
+                ),
+                Span::styled(sequence.description, desc_style),
+            ])
+        }));
+        lines.extend(vec![
             Line::from(""),
             Line::from(Span::styled("Git Operations", header_style)),
             Line::from(vec![
@@ -79,6 +117,10 @@ impl Widget for HelpPopup {
                 Span::styled("  b          ", key_style),
                 Span::styled("Create new branch", desc_style),
             ]),
+            Line::from(vec![
+                Span::styled("  e          ", key_style),
+                Span::styled("Rename branch (in place)", desc_style),
+            ]),
             Line::from(vec![
                 Span::styled("  d          ", key_style),
                 Span::styled("Delete branch", desc_style),
@@ -87,15 +129,80 @@ impl Widget for HelpPopup {
                 Span::styled("  f          ", key_style),
                 Span::styled("Fetch from origin", desc_style),
             ]),
-            // TODO: merge and rebase will be implemented in the future
-            // Line::from(vec![
-            //     Span::styled("  m          ", key_style),
-            //     Span::styled("Merge branch", desc_style),
-            // ]),
-            // Line::from(vec![
-            //     Span::styled("  r          ", key_style),
-            //     Span::styled("Rebase onto branch", desc_style),
-            // ]),
+            Line::from(vec![
+                Span::styled("  F          ", key_style),
+                Span::styled("Pull (fetch, fast-forward HEAD branch)", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  Ctrl+c     ", key_style),
+                Span::styled("Cancel running fetch", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  .          ", key_style),
+                Span::styled("Repeat last git operation", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  C f / C s  ", key_style),
+                Span::styled("Commit staged as fixup! / squash!", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  m          ", key_style),
+                Span::styled("Merge branch (a: abort on conflict)", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  r          ", key_style),
+                Span::styled("Rebase onto branch", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  p          ", key_style),
+                Span::styled("Cherry-pick selected commit onto HEAD", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  s          ", key_style),
+                Span::styled("Stash local changes (incl. untracked)", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  S          ", key_style),
+                Span::styled("Stashes: Enter apply, p pop, d drop", desc_style),
+            ]),
+            Line::from(""),
+            Line::from(Span::styled("Visual Mode", header_style)),
+            Line::from(vec![
+                Span::styled("  V          ", key_style),
+                Span::styled("Start / leave a span", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  p / x      ", key_style),
+                Span::styled("Cherry-pick / export patches", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  y / s      ", key_style),
+                Span::styled("Copy hashes / show stats", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  S          ", key_style),
+                Span::styled("Squash span ending at HEAD", desc_style),
+            ]),
+            Line::from(""),
+            Line::from(Span::styled("Bookmarks", header_style)),
+            Line::from(vec![
+                Span::styled("  M          ", key_style),
+                Span::styled("Toggle bookmark on commit", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  Ctrl+x     ", key_style),
+                Span::styled("Clear all bookmarks", desc_style),
+            ]),
+            Line::from(""),
+            Line::from(Span::styled("Bisect", header_style)),
+            Line::from(vec![
+                Span::styled("  + / -      ", key_style),
+                Span::styled("Mark commit good / bad", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  B          ", key_style),
+                Span::styled("End bisect (restore HEAD)", desc_style),
+            ]),
             Line::from(""),
             Line::from(Span::styled("Search", header_style)),
             Line::from(vec![
@@ -114,25 +221,108 @@ impl Widget for HelpPopup {
                 Span::styled("  Enter      ", key_style),
                 Span::styled("Jump to selected branch", desc_style),
             ]),
+            Line::from(vec![
+                Span::styled("  C-s        ", key_style),
+                Span::styled("Cycle branch sort (empty query)", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  C-t        ", key_style),
+                Span::styled("Cycle case: smart / sensitive / ignore", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  C-l        ", key_style),
+                Span::styled("Tip subject: after the name / own line", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  C-d        ", key_style),
+                Span::styled("Mark branch, then diff it with another", desc_style),
+            ]),
             Line::from(vec![
                 Span::styled("  Esc        ", key_style),
                 Span::styled("Cancel search", desc_style),
             ]),
             Line::from(""),
             Line::from(Span::styled("Other", header_style)),
+            Line::from(vec![
+                Span::styled("  :          ", key_style),
+                Span::styled(
+                    "Command palette (goto, checkout, branch, limit, open)",
+                    desc_style,
+                ),
+            ]),
             Line::from(vec![
                 Span::styled("  R          ", key_style),
                 Span::styled("Refresh", desc_style),
             ]),
             Line::from(vec![
-                Span::styled("  ?          ", key_style),
+                Span::styled("  N          ", key_style),
+                Span::styled("Toggle merge commits (no-merges)", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  z          ", key_style),
+                Span::styled("Toggle compact graph (no connector rows)", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  L          ", key_style),
+                Span::styled("Toggle lane colors (balanced / by lane)", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  D / X      ", key_style),
+                Span::styled("Filter by author date / clear", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  a          ", key_style),
+                Span::styled("Toggle this branch / all branches", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  H          ", key_style),
+                Span::styled("Toggle follow HEAD", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  w          ", key_style),
+                Span::styled("Toggle raw working tree diff", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  A          ", key_style),
+                Span::styled("Toggle who last touched each file", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  o          ", key_style),
+                Span::styled("Show raw commit object", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  Space / v  ", key_style),
+                Span::styled("Show diff (Ctrl+e/Ctrl+y to scroll)", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  i          ", key_style),
+                Span::styled("Show repository info", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  T          ", key_style),
+                Span::styled("Browse tags", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  U / u      ", key_style),
+                Span::styled("Copy release URL / dismiss (update notice)", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  Y          ", key_style),
+                Span::styled("Copy visible graph as text", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled("  ? / F1     ", key_style),
                 Span::styled("Toggle this help", desc_style),
             ]),
             Line::from(vec![
-                Span::styled("  q / Esc    ", key_style),
-                Span::styled("Quit", desc_style),
+                Span::styled("  q          ", key_style),
+                Span::styled(q_description, desc_style),
             ]),
-        ];
+            Line::from(vec![
+                Span::styled("  Esc        ", key_style),
+                Span::styled("Quit / close popup", desc_style),
+            ]),
+        ]);
 
         let block = Block::default()
             .title(" Help ")
@@ -1,11 +1,20 @@
 //! UI components
 
+pub mod branch_diff_popup;
+pub mod command_palette;
 pub mod commit_detail;
 pub mod dialog;
+pub mod diff_bar;
 pub mod graph_view;
 pub mod help_popup;
+pub mod raw_commit_popup;
+pub mod repo_info_popup;
+pub mod repo_picker;
 pub mod search_dropdown;
+pub mod stash_list_popup;
 pub mod status_bar;
+pub mod tag_list_popup;
+pub mod theme;
 
 use ratatui::{
     buffer::Buffer,
@@ -18,12 +27,22 @@ use ratatui::{
 use crate::app::{App, AppMode, InputAction};
 
 use self::{
+    branch_diff_popup::BranchDiffPopup,
+    command_palette::CommandPalette,
     commit_detail::CommitDetailWidget,
-    dialog::{BranchInfoPopup, ConfirmDialog, InputDialog},
+    dialog::{
+        BranchInfoPopup, CheckoutBlockedDialog, ConfirmDialog, ConflictDialog, InputDialog,
+        QuitBlockedDialog, RetargetDialog,
+    },
     graph_view::GraphViewWidget,
     help_popup::HelpPopup,
+    raw_commit_popup::RawCommitPopup,
+    repo_info_popup::RepoInfoPopup,
     search_dropdown::{calculate_dropdown_height, SearchDropdown},
+    stash_list_popup::StashListPopup,
     status_bar::StatusBar,
+    tag_list_popup::TagListPopup,
+    theme::Theme,
 };
 
 /// Minimum terminal width required for rendering
@@ -36,10 +55,10 @@ pub const MIN_WIDGET_WIDTH: u16 = 12;
 pub const MIN_WIDGET_HEIGHT: u16 = 3;
 
 /// Render a placeholder block when widget area is too small
-pub fn render_placeholder_block(area: Rect, buf: &mut Buffer) {
+pub fn render_placeholder_block(area: Rect, buf: &mut Buffer, theme: &Theme) {
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::DarkGray));
+        .border_style(theme.border_style());
     block.render(area, buf);
 }
 
@@ -49,6 +68,7 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
     app.update_diff_cache();
 
     let area = frame.area();
+    let theme = Theme::from_config(&app.config.ui);
 
     // Check minimum terminal size to prevent buffer overflow panics
     if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
@@ -80,22 +100,63 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
     let detail_area = content_vertical[1];
 
     // Render widgets
+    app.graph_view_height = graph_area.height.saturating_sub(2) as usize; // minus borders
     frame.render_stateful_widget(
         GraphViewWidget::new(app, graph_area.width),
         graph_area,
         &mut app.graph_list_state,
     );
-    frame.render_widget(CommitDetailWidget::new(app), detail_area);
+    frame.render_widget(CommitDetailWidget::new(app, detail_area.width), detail_area);
     frame.render_widget(StatusBar::new(app), status_area);
 
     // Branch info popup (when multiple branches exist on selected node)
-    render_branch_info_popup(frame, app, graph_area);
+    render_branch_info_popup(frame, app, graph_area, theme);
 
-    // Popups
-    match &app.mode {
+    // Popups of the modes the active one interrupted, then the active one on top
+    for mode in app.mode_stack().iter().chain([&app.mode]) {
+        render_mode_popup(frame, app, mode, area, theme);
+    }
+}
+
+/// Render the popup or dialog of a mode (nothing for modes without one)
+fn render_mode_popup(frame: &mut Frame, app: &App, mode: &AppMode, area: Rect, theme: Theme) {
+    match mode {
         AppMode::Help => {
             let popup_area = centered_rect(60, 70, area);
-            frame.render_widget(HelpPopup, popup_area);
+            frame.render_widget(
+                HelpPopup {
+                    q: app.config.keys.q,
+                },
+                popup_area,
+            );
+        }
+        AppMode::RawCommit {
+            oid,
+            content,
+            scroll,
+        } => {
+            let popup_area = centered_rect(80, 70, area);
+            frame.render_widget(
+                RawCommitPopup::new(*oid, content, *scroll, theme),
+                popup_area,
+            );
+        }
+        AppMode::BranchDiff { comparison, scroll } => {
+            let popup_area = centered_rect(80, 70, area);
+            frame.render_widget(BranchDiffPopup::new(comparison, *scroll, theme), popup_area);
+        }
+        AppMode::RepoInfo { info, scroll } => {
+            let popup_area = centered_rect(70, 60, area);
+            frame.render_widget(
+                RepoInfoPopup::new(
+                    info,
+                    app.repo_stats.as_ref(),
+                    app.commits.len(),
+                    *scroll,
+                    theme,
+                ),
+                popup_area,
+            );
         }
         AppMode::Input {
             input,
@@ -104,7 +165,11 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
         } => {
             // Search dropdown at bottom of screen
             let results = app.search_results();
-            let height = calculate_dropdown_height(results.len());
+            let height = calculate_dropdown_height(
+                results.len(),
+                app.search_sections(),
+                app.subject_layout(),
+            );
             let popup_area = bottom_rect(60, height, area);
             frame.render_widget(
                 SearchDropdown::new(
@@ -112,24 +177,93 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
                     results,
                     &app.branch_positions,
                     app.search_selection(),
-                ),
+                    app.branch_sort(),
+                    app.search_sections(),
+                    theme,
+                )
+                .counts(app.search_counts())
+                .subjects(app.search_subjects(), app.subject_layout())
+                .case(app.search_case())
+                .diff_mark(app.search_diff_mark()),
                 popup_area,
             );
         }
+        AppMode::Input {
+            input,
+            action: InputAction::Command,
+            ..
+        } => {
+            let completions = app.command_completions();
+            let popup_area = bottom_rect(60, CommandPalette::height(completions.len()), area);
+            frame.render_widget(CommandPalette::new(input, completions, theme), popup_area);
+        }
+        AppMode::Input {
+            action: InputAction::RenameBranch(_),
+            ..
+        } => {
+            // Edited in place on the graph row
+        }
         AppMode::Input { title, input, .. } => {
             let popup_area = centered_rect(50, 20, area);
-            frame.render_widget(InputDialog::new(title, input), popup_area);
+            frame.render_widget(InputDialog::new(title, input, theme), popup_area);
         }
         AppMode::Confirm { message, .. } => {
             let popup_area = centered_rect(50, 20, area);
             frame.render_widget(ConfirmDialog::new(message), popup_area);
         }
+        AppMode::Tags {
+            tags,
+            selected,
+            sort,
+        } => {
+            let popup_area = centered_rect(80, 60, area);
+            frame.render_widget(TagListPopup::new(tags, *selected, *sort, theme), popup_area);
+        }
+        AppMode::StashList { stashes, selected } => {
+            let popup_area = centered_rect(80, 60, area);
+            frame.render_widget(StashListPopup::new(stashes, *selected, theme), popup_area);
+        }
+        AppMode::CheckoutBlocked {
+            op,
+            paths,
+            local_changes,
+            scroll,
+        } => {
+            let popup_area = centered_rect(60, 50, area);
+            let target = op.target_label();
+            frame.render_widget(
+                CheckoutBlockedDialog::new(&target, paths, *local_changes, *scroll, theme),
+                popup_area,
+            );
+        }
+        AppMode::Conflict { op, files, scroll } => {
+            let popup_area = centered_rect(60, 40, area);
+            frame.render_widget(ConflictDialog::new(*op, files, *scroll, theme), popup_area);
+        }
+        AppMode::RetargetBranches {
+            moves,
+            checked,
+            selected,
+        } => {
+            let popup_area = centered_rect(60, 40, area);
+            frame.render_widget(
+                RetargetDialog::new(moves, checked, *selected, theme),
+                popup_area,
+            );
+        }
+        AppMode::QuitBlocked { operation, waiting } => {
+            let popup_area = centered_rect(50, 20, area);
+            frame.render_widget(
+                QuitBlockedDialog::new(operation, *waiting, theme),
+                popup_area,
+            );
+        }
         _ => {}
     }
 }
 
 /// Render branch info popup when multiple branches exist on selected node
-fn render_branch_info_popup(frame: &mut Frame, app: &App, graph_area: Rect) {
+fn render_branch_info_popup(frame: &mut Frame, app: &App, graph_area: Rect, theme: Theme) {
     let selected_branches = app.selected_node_branches();
 
     // Only show popup in Normal mode with multiple branches
@@ -165,7 +299,7 @@ fn render_branch_info_popup(frame: &mut Frame, app: &App, graph_area: Rect) {
 
     let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
     frame.render_widget(
-        BranchInfoPopup::new(&selected_branches, app.selected_branch_name()),
+        BranchInfoPopup::new(&selected_branches, app.selected_branch_name(), theme),
         popup_area,
     );
 }
@@ -0,0 +1,52 @@
+//! Raw commit object popup (`git cat-file -p` view)
+
+use git2::Oid;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+
+use super::theme::Theme;
+
+pub struct RawCommitPopup<'a> {
+    oid: Oid,
+    content: &'a str,
+    scroll: u16,
+    theme: Theme,
+}
+
+impl<'a> RawCommitPopup<'a> {
+    pub fn new(oid: Oid, content: &'a str, scroll: u16, theme: Theme) -> Self {
+        Self {
+            oid,
+            content,
+            scroll,
+            theme,
+        }
+    }
+}
+
+impl Widget for RawCommitPopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .title(format!(" commit {} ", self.oid))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .style(Style::default().bg(Color::Black));
+
+        // No wrapping: keep lines exactly as stored (full 40-char OIDs, signature blocks)
+        let paragraph = Paragraph::new(self.content)
+            .style(
+                self.theme
+                    .selectable_style(Style::default().fg(Color::White)),
+            )
+            .block(block)
+            .scroll((self.scroll, 0));
+
+        Widget::render(paragraph, area, buf);
+    }
+}
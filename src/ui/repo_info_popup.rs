@@ -0,0 +1,125 @@
+//! Repository info popup
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+
+use super::theme::{spinner_tick, Theme};
+use crate::git::repo_info::{format_size, RepoInfo, RepoStats};
+
+const UNAVAILABLE: &str = "unavailable";
+
+pub struct RepoInfoPopup<'a> {
+    info: &'a RepoInfo,
+    /// None while the worker is still running
+    stats: Option<&'a RepoStats>,
+    loaded_commits: usize,
+    scroll: u16,
+    theme: Theme,
+}
+
+impl<'a> RepoInfoPopup<'a> {
+    pub fn new(
+        info: &'a RepoInfo,
+        stats: Option<&'a RepoStats>,
+        loaded_commits: usize,
+        scroll: u16,
+        theme: Theme,
+    ) -> Self {
+        Self {
+            info,
+            stats,
+            loaded_commits,
+            scroll,
+            theme,
+        }
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let label_style = Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD);
+        let value_style = Style::default().fg(Color::White);
+        let muted_style = self.theme.muted_style();
+
+        let row = |label: &str, value: Option<String>| {
+            let (text, style) = match value {
+                Some(v) => (v, value_style),
+                None => (UNAVAILABLE.to_string(), muted_style),
+            };
+            Line::from(vec![
+                Span::styled(format!("  {:<15}", label), label_style),
+                Span::styled(text, style),
+            ])
+        };
+
+        // Slow values show a placeholder until the worker finishes
+        let pending = match self.theme.spinner(spinner_tick()) {
+            Some(frame) => format!("{} computing", frame),
+            None => "computing...".to_string(),
+        };
+        let slow = |value: Option<Option<String>>| match value {
+            Some(v) => v,
+            None => Some(pending.clone()),
+        };
+
+        let info = self.info;
+        let yes_no = |b: bool| if b { "yes" } else { "no" }.to_string();
+        let mut lines = vec![
+            row("Path", info.path.clone()),
+            row("HEAD", info.head.clone()),
+            row(
+                "Upstream",
+                Some(info.upstream.clone().unwrap_or_else(|| "none".to_string())),
+            ),
+            row("Default branch", info.default_branch.clone()),
+            row(
+                "Commits",
+                slow(self.stats.map(|s| {
+                    s.total_commits
+                        .map(|total| format!("{} loaded / {} total", self.loaded_commits, total))
+                })),
+            ),
+            row(
+                "Size (.git)",
+                slow(self.stats.map(|s| s.git_dir_size.map(format_size))),
+            ),
+            row("Bare", Some(yes_no(info.is_bare))),
+            row("Shallow", Some(yes_no(info.is_shallow))),
+            row("Submodules", info.has_submodules.map(yes_no)),
+            Line::from(""),
+        ];
+
+        if info.remotes.is_empty() {
+            lines.push(row("Remotes", Some("none".to_string())));
+        }
+        for (i, (name, url)) in info.remotes.iter().enumerate() {
+            let label = if i == 0 { "Remotes" } else { "" };
+            let url = url.as_deref().unwrap_or(UNAVAILABLE);
+            lines.push(row(label, Some(format!("{}  {}", name, url))));
+        }
+        lines
+    }
+}
+
+impl Widget for RepoInfoPopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .title(" Repository ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .style(Style::default().bg(Color::Black));
+
+        let paragraph = Paragraph::new(self.lines())
+            .block(block)
+            .scroll((self.scroll, 0));
+
+        Widget::render(paragraph, area, buf);
+    }
+}
@@ -0,0 +1,79 @@
+//! Recent repository picker, shown before the main UI when started outside a repository
+
+use std::path::Path;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Widget},
+};
+
+use super::theme::Theme;
+
+pub struct RepoPicker<'a> {
+    repos: &'a [&'a Path],
+    selected: usize,
+    theme: Theme,
+}
+
+impl<'a> RepoPicker<'a> {
+    pub fn new(repos: &'a [&'a Path], selected: usize, theme: Theme) -> Self {
+        Self {
+            repos,
+            selected,
+            theme,
+        }
+    }
+}
+
+impl Widget for RepoPicker<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .title(" Not a git repository — open a recent one? ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let height = inner.height as usize;
+        if height < 2 {
+            return;
+        }
+        // The last row holds the key hints
+        let list_height = height - 1;
+        let offset = self.selected.saturating_sub(list_height - 1);
+
+        for (i, repo) in self.repos.iter().enumerate().skip(offset).take(list_height) {
+            let y = inner.y + (i - offset) as u16;
+            let mut line = Line::from(Span::styled(
+                format!(" {}", repo.display()),
+                self.theme
+                    .selectable_style(Style::default().fg(Color::White)),
+            ));
+            if i == self.selected {
+                line = line.patch_style(self.theme.selection);
+                buf.set_style(
+                    Rect {
+                        y,
+                        height: 1,
+                        ..inner
+                    },
+                    self.theme.selection,
+                );
+            }
+            buf.set_line(inner.x, y, &line, inner.width);
+        }
+
+        buf.set_string(
+            inner.x,
+            inner.y + inner.height - 1,
+            " ↑↓: select  Enter: open  Esc: quit",
+            self.theme.muted_style(),
+        );
+    }
+}
@@ -1,6 +1,9 @@
 //! Search dropdown widget with fuzzy matching
 
-use crate::search::FuzzySearchResult;
+use super::graph_view::{char_width_with_vs16, display_width, VS16};
+use super::theme::Theme;
+use crate::git::BranchSort;
+use crate::search::{FuzzySearchResult, SearchCase, SubjectLayout};
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
@@ -10,6 +13,8 @@ use ratatui::{
 };
 
 const MAX_VISIBLE_RESULTS: usize = 7;
+/// Columns an inline subject needs to be worth showing
+const MIN_INLINE_SUBJECT_WIDTH: usize = 8;
 
 /// Search dropdown widget showing input field and fuzzy search results
 pub struct SearchDropdown<'a> {
@@ -17,6 +22,68 @@ pub struct SearchDropdown<'a> {
     results: &'a [FuzzySearchResult],
     branch_names: &'a [(usize, String)],
     selected_index: Option<usize>,
+    sort: BranchSort,
+    /// Section header of each result (empty for no headers)
+    sections: &'a [String],
+    /// Commits ahead of the main branch of each result (empty when not shown)
+    counts: &'a [Option<usize>],
+    /// Tip commit subject of each result (empty when not shown)
+    subjects: &'a [String],
+    subject_layout: SubjectLayout,
+    case: SearchCase,
+    /// Branch marked as the start of a branch diff
+    diff_mark: Option<&'a str>,
+    theme: Theme,
+}
+
+/// A dropdown row below the input line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row<'s> {
+    Header(&'s str),
+    /// Index into the results
+    Result(usize),
+    /// Second line of a result in the two-line layout
+    Subject(usize),
+}
+
+/// Results with a header before each new section (`sections` is empty or one per result)
+/// In the two-line layout each result is followed by its subject row
+fn dropdown_rows(result_count: usize, sections: &[String], layout: SubjectLayout) -> Vec<Row<'_>> {
+    let mut rows = Vec::with_capacity(result_count * layout.lines_per_branch());
+    for i in 0..result_count {
+        if let Some(section) = sections.get(i) {
+            if i == 0 || sections.get(i - 1) != Some(section) {
+                rows.push(Row::Header(section));
+            }
+        }
+        rows.push(Row::Result(i));
+        if layout == SubjectLayout::TwoLine {
+            rows.push(Row::Subject(i));
+        }
+    }
+    rows
+}
+
+/// Rows shown at most (a two-line result counts twice)
+fn max_visible_rows(layout: SubjectLayout) -> usize {
+    MAX_VISIBLE_RESULTS * layout.lines_per_branch()
+}
+
+/// First row to show so the selected result, with its subject row, is visible
+/// Never starts on a subject row, which would be cut off from its branch name
+fn scroll_offset(rows: &[Row], selected: usize, visible_count: usize) -> usize {
+    let Some(selected_row) = rows.iter().position(|row| *row == Row::Result(selected)) else {
+        return 0;
+    };
+    let last_row = match rows.get(selected_row + 1) {
+        Some(Row::Subject(_)) => selected_row + 1,
+        _ => selected_row,
+    };
+    let offset = (last_row + 1).saturating_sub(visible_count);
+    match rows.get(offset) {
+        Some(Row::Subject(_)) => offset + 1,
+        _ => offset,
+    }
 }
 
 impl<'a> SearchDropdown<'a> {
@@ -25,12 +92,56 @@ impl<'a> SearchDropdown<'a> {
         results: &'a [FuzzySearchResult],
         branch_names: &'a [(usize, String)],
         selected_index: Option<usize>,
+        sort: BranchSort,
+        sections: &'a [String],
+        theme: Theme,
     ) -> Self {
         Self {
             input,
             results,
             branch_names,
             selected_index,
+            sort,
+            sections,
+            counts: &[],
+            subjects: &[],
+            subject_layout: SubjectLayout::default(),
+            case: SearchCase::default(),
+            diff_mark: None,
+            theme,
+        }
+    }
+
+    /// Show a dim commit count after each branch name
+    pub fn counts(mut self, counts: &'a [Option<usize>]) -> Self {
+        self.counts = counts;
+        self
+    }
+
+    /// Show each result's tip commit subject, after the name or on a line below it
+    pub fn subjects(mut self, subjects: &'a [String], layout: SubjectLayout) -> Self {
+        self.subjects = subjects;
+        self.subject_layout = layout;
+        self
+    }
+
+    /// Case matching shown in the title
+    pub fn case(mut self, case: SearchCase) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Marked branch shown in the title
+    pub fn diff_mark(mut self, diff_mark: Option<&'a str>) -> Self {
+        self.diff_mark = diff_mark;
+        self
+    }
+
+    /// " (12)" after the name of result `i`
+    fn count_suffix(&self, i: usize) -> String {
+        match self.counts.get(i).copied().flatten() {
+            Some(count) => format!(" ({})", count),
+            None => String::new(),
         }
     }
 
@@ -49,52 +160,70 @@ impl<'a> SearchDropdown<'a> {
         max_width: usize,
     ) -> Vec<Span<'a>> {
         let name = self.get_branch_name(result);
-        let matched_set: std::collections::HashSet<usize> =
-            result.matched_indices.iter().copied().collect();
-
-        let mut spans = Vec::new();
-        let mut current_segment = String::new();
-        let mut current_is_matched = false;
-
-        for (char_idx, ch) in name.chars().enumerate() {
-            if char_idx >= max_width.saturating_sub(3) {
-                current_segment.push_str("...");
-                break;
-            }
+        highlight_segments(name, &result.matched_indices, max_width)
+            .into_iter()
+            .map(|(text, is_matched)| Span::styled(text, self.highlight_style(is_matched)))
+            .collect()
+    }
 
-            let is_matched = matched_set.contains(&char_idx);
+    /// Get style for highlighted/non-highlighted text
+    fn highlight_style(&self, is_matched: bool) -> Style {
+        if is_matched {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            self.theme
+                .selectable_style(Style::default().fg(Color::White))
+        }
+    }
+}
 
-            if is_matched != current_is_matched && !current_segment.is_empty() {
-                spans.push(Span::styled(
-                    std::mem::take(&mut current_segment),
-                    highlight_style(current_is_matched),
-                ));
-            }
+/// Split a name into (text, matched) segments that fit in `max_width` display columns
+/// `matched_indices` are char indices. Names that do not fit are cut at a char boundary
+/// (never inside a wide char) and end with an unmatched "...".
+fn highlight_segments(
+    name: &str,
+    matched_indices: &[usize],
+    max_width: usize,
+) -> Vec<(String, bool)> {
+    const ELLIPSIS: &str = "...";
+    let budget = if display_width(name) <= max_width {
+        max_width
+    } else {
+        max_width.saturating_sub(ELLIPSIS.len())
+    };
 
-            current_segment.push(ch);
-            current_is_matched = is_matched;
+    let chars: Vec<char> = name.chars().collect();
+    let mut segments: Vec<(String, bool)> = Vec::new();
+    let mut used = 0;
+    let mut truncated = false;
+    for (char_idx, &ch) in chars.iter().enumerate() {
+        let width = char_width_with_vs16(ch, chars.get(char_idx + 1).copied());
+        if used + width > budget {
+            truncated = true;
+            break;
         }
-
-        if !current_segment.is_empty() {
-            spans.push(Span::styled(
-                current_segment,
-                highlight_style(current_is_matched),
-            ));
+        used += width;
+
+        // A variation selector stays with the glyph it modifies
+        let is_matched = match segments.last() {
+            Some((_, matched)) if ch == VS16 => *matched,
+            _ => matched_indices.contains(&char_idx),
+        };
+        match segments.last_mut() {
+            Some((text, matched)) if *matched == is_matched => text.push(ch),
+            _ => segments.push((ch.to_string(), is_matched)),
         }
-
-        spans
     }
-}
 
-/// Get style for highlighted/non-highlighted text
-fn highlight_style(is_matched: bool) -> Style {
-    if is_matched {
-        Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Color::White)
+    if truncated {
+        match segments.last_mut() {
+            Some((text, false)) => text.push_str(ELLIPSIS),
+            _ => segments.push((ELLIPSIS.to_string(), false)),
+        }
     }
+    segments
 }
 
 impl<'a> Widget for SearchDropdown<'a> {
@@ -103,11 +232,20 @@ impl<'a> Widget for SearchDropdown<'a> {
 
         // Calculate dynamic height based on results
         let has_results = !self.results.is_empty();
-        let visible_count = self.results.len().min(MAX_VISIBLE_RESULTS);
+        let rows = dropdown_rows(self.results.len(), self.sections, self.subject_layout);
+        let visible_count = rows.len().min(max_visible_rows(self.subject_layout));
 
         // Build block with cyan border (matching InputDialog style)
+        let mut title = format!(
+            " Search branches (sort: {}, {}) ",
+            self.sort.label(),
+            self.case.label()
+        );
+        if let Some(mark) = self.diff_mark {
+            title.push_str(&format!("diff from {} ", mark));
+        }
         let block = Block::default()
-            .title(" Search branches ")
+            .title(title)
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Cyan))
             .style(Style::default().bg(Color::Black));
@@ -139,27 +277,21 @@ impl<'a> Widget for SearchDropdown<'a> {
         if has_results && y < inner.y + inner.height {
             // Draw separator line
             let separator = "─".repeat(inner.width as usize);
-            buf.set_string(inner.x, y, &separator, Style::default().fg(Color::DarkGray));
+            buf.set_string(inner.x, y, &separator, self.theme.muted_style());
             y += 1;
 
             // Calculate scroll offset to keep selected item visible
             let selected = self.selected_index.unwrap_or(0);
-            let scroll_offset = if selected >= visible_count {
-                selected - visible_count + 1
-            } else {
-                0
-            };
+            let scroll_offset = scroll_offset(&rows, selected, visible_count);
 
             let has_more_above = scroll_offset > 0;
-            let has_more_below = scroll_offset + visible_count < self.results.len();
+            let has_more_below = scroll_offset + visible_count < rows.len();
 
             // Render results with scroll
             let max_name_width = inner.width.saturating_sub(4) as usize;
 
-            for (display_idx, (i, result)) in self
-                .results
+            for (display_idx, row) in rows
                 .iter()
-                .enumerate()
                 .skip(scroll_offset)
                 .take(visible_count)
                 .enumerate()
@@ -168,7 +300,46 @@ impl<'a> Widget for SearchDropdown<'a> {
                     break;
                 }
 
+                let marker = if display_idx == 0 && has_more_above {
+                    "↑ "
+                } else if display_idx == visible_count - 1 && has_more_below {
+                    "↓ "
+                } else {
+                    "  "
+                };
+                let i = match *row {
+                    Row::Header(section) => {
+                        let line = Line::from(vec![
+                            Span::styled(marker, self.theme.muted_style()),
+                            Span::styled(
+                                section.to_string(),
+                                self.theme.muted_style().add_modifier(Modifier::BOLD),
+                            ),
+                        ]);
+                        buf.set_line(inner.x, y, &line, inner.width);
+                        y += 1;
+                        continue;
+                    }
+                    Row::Subject(i) => {
+                        let subject = self.subjects.get(i).map(String::as_str).unwrap_or("");
+                        let text: String = highlight_segments(subject, &[], max_name_width)
+                            .into_iter()
+                            .map(|(text, _)| text)
+                            .collect();
+                        let line = Line::from(vec![
+                            Span::styled(marker, self.theme.muted_style()),
+                            Span::styled(format!("  {}", text), self.theme.muted_style()),
+                        ]);
+                        buf.set_line(inner.x, y, &line, inner.width);
+                        y += 1;
+                        continue;
+                    }
+                    Row::Result(i) => i,
+                };
+                let result = &self.results[i];
                 let is_selected = self.selected_index == Some(i);
+                let suffix = self.count_suffix(i);
+                let name_width = max_name_width.saturating_sub(suffix.len());
 
                 // Show scroll indicators on first/last visible items
                 let prefix = if display_idx == 0 && has_more_above {
@@ -195,14 +366,17 @@ impl<'a> Widget for SearchDropdown<'a> {
                     if is_selected {
                         Style::default().fg(Color::Cyan)
                     } else {
-                        Style::default().fg(Color::DarkGray)
+                        self.theme.muted_style()
                     },
                 )];
 
                 if is_selected {
                     // For selected item, use inverted colors without per-char highlighting
                     let name = self.get_branch_name(result);
-                    let display_name: String = name.chars().take(max_name_width).collect();
+                    let display_name: String = highlight_segments(name, &[], name_width)
+                        .into_iter()
+                        .map(|(text, _)| text)
+                        .collect();
                     spans.push(Span::styled(
                         display_name,
                         Style::default()
@@ -212,7 +386,27 @@ impl<'a> Widget for SearchDropdown<'a> {
                     ));
                 } else {
                     // For non-selected items, show match highlighting
-                    spans.extend(self.render_highlighted_name(result, max_name_width));
+                    spans.extend(self.render_highlighted_name(result, name_width));
+                }
+                let used: usize = spans.iter().map(|s| display_width(&s.content)).sum();
+                if !suffix.is_empty() {
+                    spans.push(Span::styled(suffix.clone(), self.theme.muted_style()));
+                }
+                if self.subject_layout == SubjectLayout::Inline {
+                    let subject = self.subjects.get(i).map(String::as_str).unwrap_or("");
+                    // Two spaces between the name and the subject
+                    let room =
+                        (inner.width as usize).saturating_sub(used + display_width(&suffix) + 2);
+                    if !subject.is_empty() && room >= MIN_INLINE_SUBJECT_WIDTH {
+                        let text: String = highlight_segments(subject, &[], room)
+                            .into_iter()
+                            .map(|(text, _)| text)
+                            .collect();
+                        spans.push(Span::styled(
+                            format!("  {}", text),
+                            self.theme.muted_style(),
+                        ));
+                    }
                 }
 
                 let line = Line::from(spans);
@@ -226,7 +420,7 @@ impl<'a> Widget for SearchDropdown<'a> {
             let hint = select_hint_text(inner.width as usize, has_results, self.input.is_empty());
             if !hint.is_empty() {
                 let hint_y = inner.y + inner.height - 1;
-                buf.set_string(inner.x, hint_y, hint, Style::default().fg(Color::DarkGray));
+                buf.set_string(inner.x, hint_y, hint, self.theme.muted_style());
             }
         }
     }
@@ -236,7 +430,9 @@ impl<'a> Widget for SearchDropdown<'a> {
 fn select_hint_text(width: usize, has_results: bool, input_empty: bool) -> &'static str {
     if has_results {
         match width {
-            40.. => "  ↑↓: select  Enter: jump  Esc: cancel",
+            64.. => "  ↑↓: select  Enter: jump  ^S: sort  ^T: case  Esc: cancel",
+            54..=63 => "  ↑↓: select  Enter: jump  ^S: sort  Esc: cancel",
+            40..=53 => "  ↑↓: select  Enter: jump  Esc: cancel",
             28..=39 => "  ↑↓/Tab  Enter  Esc",
             16..=27 => "  ↑↓ Enter Esc",
             _ => "",
@@ -255,13 +451,127 @@ fn select_hint_text(width: usize, has_results: bool, input_empty: bool) -> &'sta
 }
 
 /// Calculate the required height for the search dropdown
-pub fn calculate_dropdown_height(result_count: usize) -> u16 {
-    // Input line (1) + separator (1 if results) + results (up to MAX) + hint (1) + borders (2)
+pub fn calculate_dropdown_height(
+    result_count: usize,
+    sections: &[String],
+    layout: SubjectLayout,
+) -> u16 {
+    // Input line (1) + separator (1 if results) + rows (up to MAX) + hint (1) + borders (2)
     let base_height = 4; // borders + input + hint
+    let row_count = dropdown_rows(result_count, sections, layout).len();
     let results_height = if result_count > 0 {
-        1 + result_count.min(MAX_VISIBLE_RESULTS) // separator + results
+        1 + row_count.min(max_visible_rows(layout)) // separator + results and headers
     } else {
         0
     };
     (base_height + results_height) as u16
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render segments as text with matched parts in brackets
+    fn show(name: &str, matched: &[usize], max_width: usize) -> String {
+        highlight_segments(name, matched, max_width)
+            .into_iter()
+            .map(|(text, is_matched)| {
+                if is_matched {
+                    format!("[{}]", text)
+                } else {
+                    text
+                }
+            })
+            .collect()
+    }
+
+    fn visible_width(name: &str, matched: &[usize], max_width: usize) -> usize {
+        highlight_segments(name, matched, max_width)
+            .iter()
+            .map(|(text, _)| display_width(text))
+            .sum()
+    }
+
+    #[test]
+    fn test_ascii_fits_without_ellipsis() {
+        assert_eq!(show("feature", &[0, 1], 7), "[fe]ature");
+        assert_eq!(show("feature", &[0, 1], 6), "[fe]a...");
+    }
+
+    #[test]
+    fn test_cjk_truncates_by_display_width() {
+        // "release/" (8) + 3 CJK chars (2 columns each) = 14 columns
+        let name = "release/日本語";
+        assert_eq!(show(name, &[8, 9], 14), "release/[日本]語");
+        assert_eq!(show(name, &[8, 9], 13), "release/[日]...");
+        // The first CJK char would straddle the boundary, so it is dropped entirely
+        assert_eq!(show(name, &[8, 9], 12), "release/...");
+        assert_eq!(show(name, &[7, 8], 12), "release[/]...");
+        for max_width in 3..=14 {
+            assert!(visible_width(name, &[8], max_width) <= max_width);
+        }
+    }
+
+    #[test]
+    fn test_emoji_highlight_follows_char_indices() {
+        // "🚀" is wide; "❤️" is a base char plus VS16 (one wide glyph, two chars)
+        let name = "a🚀b❤️c";
+        assert_eq!(show(name, &[1, 2], 7), "a[🚀b]❤️c");
+        assert_eq!(show(name, &[3], 7), "a🚀b[❤️]c");
+        assert_eq!(show(name, &[5], 7), "a🚀b❤️[c]");
+        assert_eq!(show(name, &[1], 6), "a[🚀]...");
+        for max_width in 3..=7 {
+            assert!(visible_width(name, &[1, 3, 5], max_width) <= max_width);
+        }
+    }
+
+    #[test]
+    fn test_rows_start_each_section_with_a_header() {
+        let sections: Vec<String> = ["local", "local", "origin", "upstream"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            dropdown_rows(4, &sections, SubjectLayout::Inline),
+            [
+                Row::Header("local"),
+                Row::Result(0),
+                Row::Result(1),
+                Row::Header("origin"),
+                Row::Result(2),
+                Row::Header("upstream"),
+                Row::Result(3),
+            ]
+        );
+        assert_eq!(
+            dropdown_rows(2, &[], SubjectLayout::Inline),
+            [Row::Result(0), Row::Result(1)]
+        );
+    }
+
+    #[test]
+    fn test_two_line_scrolling_keeps_the_selection_whole() {
+        let sections: Vec<String> = ["local", "origin", "origin", "origin"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let rows = dropdown_rows(4, &sections, SubjectLayout::TwoLine);
+        assert_eq!(rows.len(), 10);
+        assert_eq!(
+            rows[..3],
+            [Row::Header("local"), Row::Result(0), Row::Subject(0)]
+        );
+        // The last result's subject row is the last visible row
+        assert_eq!(scroll_offset(&rows, 3, 4), 6);
+        assert_eq!(scroll_offset(&rows, 2, 4), 4);
+        // Starting at result 1's subject row would split it from its name
+        assert_eq!(rows[5], Row::Subject(1));
+        assert_eq!(scroll_offset(&rows, 2, 3), 6);
+        assert_eq!(scroll_offset(&rows, 0, 4), 0);
+    }
+
+    #[test]
+    fn test_matched_tail_is_cut_before_ellipsis() {
+        assert_eq!(show("日本語ブランチ", &[0, 1, 2, 3], 9), "[日本語]...");
+    }
+}
@@ -0,0 +1,84 @@
+//! Stash list popup
+
+use chrono::{Local, TimeZone};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Widget},
+};
+
+use super::theme::Theme;
+use crate::git::stash::StashInfo;
+
+pub struct StashListPopup<'a> {
+    stashes: &'a [StashInfo],
+    selected: usize,
+    theme: Theme,
+}
+
+impl<'a> StashListPopup<'a> {
+    pub fn new(stashes: &'a [StashInfo], selected: usize, theme: Theme) -> Self {
+        Self {
+            stashes,
+            selected,
+            theme,
+        }
+    }
+
+    fn row(&self, stash: &StashInfo) -> Line<'static> {
+        let date = Local
+            .timestamp_opt(stash.time, 0)
+            .single()
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        Line::from(vec![
+            Span::styled(
+                format!(" stash@{{{}}} ", stash.index),
+                self.theme
+                    .selectable_style(Style::default().fg(Color::Yellow)),
+            ),
+            Span::styled(format!("{} ", date), self.theme.muted_style()),
+            Span::styled(stash.message.clone(), Style::default().fg(Color::White)),
+        ])
+    }
+}
+
+impl Widget for StashListPopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .title(format!(" Stashes ({}) ", self.stashes.len()))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let height = inner.height as usize;
+        if height == 0 {
+            return;
+        }
+        // Keep the selected row in view
+        let offset = self.selected.saturating_sub(height - 1);
+
+        for (i, stash) in self.stashes.iter().enumerate().skip(offset).take(height) {
+            let y = inner.y + (i - offset) as u16;
+            let mut line = self.row(stash);
+            if i == self.selected {
+                line = line.patch_style(self.theme.selection);
+                buf.set_style(
+                    Rect {
+                        y,
+                        height: 1,
+                        ..inner
+                    },
+                    self.theme.selection,
+                );
+            }
+            buf.set_line(inner.x, y, &line, inner.width);
+        }
+    }
+}
@@ -7,17 +7,106 @@ use ratatui::{
     text::{Line, Span},
     widgets::Widget,
 };
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+use super::theme::{spinner_tick, Theme};
 use crate::app::{App, AppMode, InputAction};
+use crate::config::StatusSegment;
+
+/// Ellipsis used when clipping the HEAD name
+const ELLIPSIS: char = '…';
+
+/// Clip a name to max_width display columns by replacing its middle with an ellipsis.
+/// Both the prefix (e.g. "feature/") and the tail (e.g. a ticket number) stay visible.
+fn truncate_middle(name: &str, max_width: usize) -> String {
+    if name.width() <= max_width {
+        return name.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+
+    let budget = max_width - 1; // -1 for the ellipsis
+    let head_budget = budget.div_ceil(2);
+    let tail_budget = budget - head_budget;
+
+    let mut head = String::new();
+    let mut head_width = 0;
+    for c in name.chars() {
+        let w = c.width().unwrap_or(0);
+        if head_width + w > head_budget {
+            break;
+        }
+        head.push(c);
+        head_width += w;
+    }
+
+    let mut tail: Vec<char> = Vec::new();
+    let mut tail_width = 0;
+    for c in name.chars().rev() {
+        let w = c.width().unwrap_or(0);
+        if tail_width + w > tail_budget {
+            break;
+        }
+        tail.push(c);
+        tail_width += w;
+    }
+
+    let mut result = head;
+    result.push(ELLIPSIS);
+    result.extend(tail.into_iter().rev());
+    result
+}
+
+/// Append key hints in priority order, stopping at the first one that no longer fits
+fn push_hints<'a>(
+    spans: &mut Vec<Span<'a>>,
+    hints: &[(&'a str, &'a str)],
+    max_width: usize,
+    key_style: Style,
+    desc_style: Style,
+) {
+    let mut used: usize = spans.iter().map(|s| s.content.width()).sum();
+    for (key, label) in hints {
+        let width = key.width() + label.width() + 3;
+        if used + width > max_width {
+            break;
+        }
+        used += width;
+        spans.push(Span::styled(format!(" {} ", key), key_style));
+        spans.push(Span::styled(format!("{} ", label), desc_style));
+    }
+}
 
 pub struct StatusBar<'a> {
     mode: &'a AppMode,
+    /// Segments in display order (`ui.status_segments`)
+    segments: &'a [StatusSegment],
     repo_path: &'a str,
-    head_name: Option<&'a str>,
+    /// Working tree outside the git dir's parent (GIT_WORK_TREE, core.worktree)
+    separate_workdir: Option<String>,
+    head_name: Option<String>,
+    head_name_max_width: usize,
+    /// Files with uncommitted changes (None: clean)
+    uncommitted_count: Option<usize>,
+    /// Loaded commits, and whether history has more
+    commit_count: usize,
+    more_commits: bool,
+    /// Local time ("14:05")
+    clock: String,
+    view_indicators: Vec<String>,
+    update_version: Option<&'a str>,
     error_message: Option<&'a str>,
     message: Option<&'a str>,
+    message_warning: bool,
     is_fetching: bool,
+    /// The startup fetch is running (a quiet indicator instead of a message)
+    startup_fetch: bool,
     search_info: Option<String>,
+    /// Sequence prefix waiting for its second key
+    pending_key: Option<char>,
+    hints: Vec<(&'static str, &'static str)>,
+    theme: Theme,
 }
 
 impl<'a> StatusBar<'a> {
@@ -45,54 +134,94 @@ impl<'a> StatusBar<'a> {
 
         Self {
             mode: &app.mode,
+            segments: &app.config.ui.status_segments.segments,
             repo_path: &app.repo_path,
-            head_name: app.head_name.as_deref(),
+            separate_workdir: app
+                .repo_location
+                .separate_workdir()
+                .map(|path| path.display().to_string()),
+            head_name: app.head_label(),
+            head_name_max_width: app.config.ui.head_name_max_width,
+            uncommitted_count: app.uncommitted_count(),
+            commit_count: app.commits.len(),
+            more_commits: app.has_more_commits(),
+            clock: chrono::Local::now().format("%H:%M").to_string(),
+            view_indicators: app.view_indicators(),
+            update_version: app.update_notice.as_ref().map(|n| n.version.as_str()),
             error_message,
             message: app.get_message(),
+            message_warning: app.message_is_warning(),
             is_fetching: app.is_fetching(),
+            startup_fetch: app.is_startup_fetch(),
             search_info,
+            pending_key: app.key_sequencer.pending(),
+            hints: app.available_actions(),
+            theme: Theme::from_config(&app.config.ui),
         }
     }
 }
 
-impl<'a> Widget for StatusBar<'a> {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        let key_style = Style::default()
-            .fg(Color::Black)
-            .bg(Color::Cyan)
-            .add_modifier(Modifier::BOLD);
-        let desc_style = Style::default().fg(Color::White);
-        let mode_style = Style::default()
-            .fg(Color::Black)
-            .bg(Color::Yellow)
-            .add_modifier(Modifier::BOLD);
-        let repo_style = Style::default()
-            .fg(Color::Black)
-            .bg(Color::Magenta)
-            .add_modifier(Modifier::BOLD);
-
-        let mut spans: Vec<Span> = Vec::new();
-
-        // Show the repository name (folder name) on the left
+impl<'a> StatusBar<'a> {
+    /// Repository folder name (and the working tree when it is elsewhere)
+    fn push_repo(&self, spans: &mut Vec<Span<'a>>, repo_style: Style) {
         let repo_name = std::path::Path::new(self.repo_path)
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or(self.repo_path);
         spans.push(Span::styled(format!(" {} ", repo_name), repo_style));
         spans.push(Span::raw(" "));
+        if let Some(workdir) = &self.separate_workdir {
+            spans.push(Span::styled(
+                format!("worktree {} ", workdir),
+                self.theme.muted_style(),
+            ));
+        }
+    }
 
-        // HEAD branch
-        if let Some(head) = self.head_name {
+    fn push_head(&self, spans: &mut Vec<Span<'a>>) {
+        if let Some(head) = &self.head_name {
             spans.push(Span::styled(
-                format!(" {} ", head),
+                format!(" {} ", truncate_middle(head, self.head_name_max_width)),
                 Style::default().fg(Color::Black).bg(Color::Green),
             ));
             spans.push(Span::raw(" "));
         }
+    }
+
+    /// View options, update notice, then the mode's message and keys
+    /// `key_hints`: show the normal view's key hints (the hints segment)
+    fn push_activity(
+        &self,
+        spans: &mut Vec<Span<'a>>,
+        key_hints: bool,
+        max_width: usize,
+        key_style: Style,
+        desc_style: Style,
+    ) {
+        // Active view options (e.g. "[no-merges]")
+        for indicator in &self.view_indicators {
+            spans.push(Span::styled(
+                indicator.clone(),
+                Style::default().fg(Color::Yellow),
+            ));
+            spans.push(Span::raw(" "));
+        }
+
+        // Available update (dismissed by copying its URL)
+        if let Some(version) = self.update_version {
+            spans.push(Span::styled(
+                format!(" v{} available (U copy URL, u dismiss) ", version),
+                Style::default().fg(Color::Black).bg(Color::LightGreen),
+            ));
+            spans.push(Span::raw(" "));
+        }
 
         // Key hints (vary by mode)
         match self.mode {
             AppMode::Normal => match self.message {
+                Some(msg) if self.message_warning => {
+                    spans.push(Span::styled(format!(" {} ", msg), self.theme.muted_style()));
+                }
                 Some(msg) => {
                     // Yellow for in-progress, Cyan for success
                     let bg = if self.is_fetching {
@@ -104,10 +233,31 @@ impl<'a> Widget for StatusBar<'a> {
                         .fg(Color::Black)
                         .bg(bg)
                         .add_modifier(Modifier::BOLD);
-                    spans.push(Span::styled(format!(" {} ", msg), msg_style));
+                    // Spinner while fetching (static text with reduced motion)
+                    let spinner = self
+                        .theme
+                        .spinner(spinner_tick())
+                        .filter(|_| self.is_fetching);
+                    let text = match spinner {
+                        Some(frame) => format!(" {} {} ", frame, msg),
+                        None => format!(" {} ", msg),
+                    };
+                    spans.push(Span::styled(text, msg_style));
                     spans.push(Span::raw("  "));
+                    if self.is_fetching {
+                        spans.push(Span::styled(" C-c ", key_style));
+                        spans.push(Span::styled("cancel", desc_style));
+                    }
                 }
                 None => {
+                    if self.startup_fetch {
+                        let spinner = self.theme.spinner(spinner_tick()).unwrap_or("…");
+                        spans.push(Span::styled(
+                            format!(" {} fetching ", spinner),
+                            self.theme.muted_style(),
+                        ));
+                    }
+
                     // Show search info if available
                     if let Some(info) = &self.search_info {
                         let search_style = Style::default()
@@ -118,25 +268,39 @@ impl<'a> Widget for StatusBar<'a> {
                         spans.push(Span::raw("  "));
                     }
 
-                    spans.push(Span::styled(" j/k ", key_style));
-                    spans.push(Span::styled("move ", desc_style));
-                    spans.push(Span::styled(" Enter ", key_style));
-                    spans.push(Span::styled("copy ", desc_style));
-                    spans.push(Span::styled(" b ", key_style));
-                    spans.push(Span::styled("branch ", desc_style));
-                    spans.push(Span::styled(" f ", key_style));
-                    spans.push(Span::styled("fetch ", desc_style));
-                    spans.push(Span::styled(" ? ", key_style));
-                    spans.push(Span::styled("help ", desc_style));
-                    spans.push(Span::styled(" q ", key_style));
-                    spans.push(Span::styled("quit", desc_style));
+                    if key_hints {
+                        push_hints(spans, &self.hints, max_width, key_style, desc_style);
+                    }
                 }
             },
             AppMode::Help => {
                 spans.push(Span::styled(" Esc/q ", key_style));
                 spans.push(Span::styled("close help", desc_style));
             }
+            AppMode::DiffView { .. } => {
+                spans.push(Span::styled(" Ctrl+e/Ctrl+y ", key_style));
+                spans.push(Span::styled("scroll ", desc_style));
+                spans.push(Span::styled(" Esc/q ", key_style));
+                spans.push(Span::styled("close", desc_style));
+            }
+            AppMode::RawCommit { .. } | AppMode::RepoInfo { .. } | AppMode::BranchDiff { .. } => {
+                spans.push(Span::styled(" j/k ", key_style));
+                spans.push(Span::styled("scroll ", desc_style));
+                spans.push(Span::styled(" Esc/q ", key_style));
+                spans.push(Span::styled("close", desc_style));
+            }
             AppMode::Input { .. } => {
+                // Short notices such as "No matches" after Enter in search
+                if let Some(msg) = self.message {
+                    spans.push(Span::styled(
+                        format!(" {} ", msg),
+                        Style::default()
+                            .fg(Color::Black)
+                            .bg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                    spans.push(Span::raw("  "));
+                }
                 spans.push(Span::styled(" Enter ", key_style));
                 spans.push(Span::styled("confirm ", desc_style));
                 spans.push(Span::styled(" Esc ", key_style));
@@ -148,6 +312,96 @@ impl<'a> Widget for StatusBar<'a> {
                 spans.push(Span::styled(" n ", key_style));
                 spans.push(Span::styled("no", desc_style));
             }
+            AppMode::Visual { .. } => {
+                spans.push(Span::styled(" j/k ", key_style));
+                spans.push(Span::styled("extend ", desc_style));
+                spans.push(Span::styled(" p ", key_style));
+                spans.push(Span::styled("cherry-pick ", desc_style));
+                spans.push(Span::styled(" x ", key_style));
+                spans.push(Span::styled("export patches ", desc_style));
+                spans.push(Span::styled(" y ", key_style));
+                spans.push(Span::styled("copy hashes ", desc_style));
+                spans.push(Span::styled(" s ", key_style));
+                spans.push(Span::styled("stats ", desc_style));
+                spans.push(Span::styled(" S ", key_style));
+                spans.push(Span::styled("squash ", desc_style));
+                spans.push(Span::styled(" Esc ", key_style));
+                spans.push(Span::styled("exit", desc_style));
+            }
+            AppMode::Tags { .. } => {
+                spans.push(Span::styled(" Enter ", key_style));
+                spans.push(Span::styled("jump ", desc_style));
+                spans.push(Span::styled(" c ", key_style));
+                spans.push(Span::styled("checkout ", desc_style));
+                spans.push(Span::styled(" s ", key_style));
+                spans.push(Span::styled("sort ", desc_style));
+                spans.push(Span::styled(" Esc ", key_style));
+                spans.push(Span::styled("close", desc_style));
+            }
+            AppMode::StashList { .. } => {
+                spans.push(Span::styled(" Enter ", key_style));
+                spans.push(Span::styled("apply ", desc_style));
+                spans.push(Span::styled(" p ", key_style));
+                spans.push(Span::styled("pop ", desc_style));
+                spans.push(Span::styled(" d ", key_style));
+                spans.push(Span::styled("drop ", desc_style));
+                spans.push(Span::styled(" Esc ", key_style));
+                spans.push(Span::styled("close", desc_style));
+            }
+            AppMode::CheckoutBlocked { local_changes, .. } => {
+                if !local_changes {
+                    spans.push(Span::styled(" f ", key_style));
+                    spans.push(Span::styled("overwrite ", desc_style));
+                }
+                spans.push(Span::styled(" s ", key_style));
+                spans.push(Span::styled("stash and retry ", desc_style));
+                spans.push(Span::styled(" Esc ", key_style));
+                spans.push(Span::styled("cancel", desc_style));
+            }
+            AppMode::Conflict { op, .. } => {
+                spans.push(Span::styled(" a ", key_style));
+                spans.push(Span::styled(format!("abort {} ", op.label()), desc_style));
+                spans.push(Span::styled(" Esc ", key_style));
+                spans.push(Span::styled("resolve in editor", desc_style));
+            }
+            AppMode::RetargetBranches { .. } => {
+                spans.push(Span::styled(" Space ", key_style));
+                spans.push(Span::styled("toggle ", desc_style));
+                spans.push(Span::styled(" Enter ", key_style));
+                spans.push(Span::styled("move checked ", desc_style));
+                spans.push(Span::styled(" Esc ", key_style));
+                spans.push(Span::styled("leave all", desc_style));
+            }
+            AppMode::CommitMenu { target } => {
+                spans.push(Span::styled(
+                    format!(" staged changes into {}: ", &target.to_string()[..7]),
+                    desc_style,
+                ));
+                spans.push(Span::styled(" f ", key_style));
+                spans.push(Span::styled("fixup! ", desc_style));
+                spans.push(Span::styled(" s ", key_style));
+                spans.push(Span::styled("squash! ", desc_style));
+                spans.push(Span::styled(" Esc ", key_style));
+                spans.push(Span::styled("cancel", desc_style));
+            }
+            AppMode::PickChild { .. } => {
+                spans.push(Span::styled(" j/k ", key_style));
+                spans.push(Span::styled("choose child ", desc_style));
+                spans.push(Span::styled(" Enter ", key_style));
+                spans.push(Span::styled("jump ", desc_style));
+                spans.push(Span::styled(" Esc ", key_style));
+                spans.push(Span::styled("cancel", desc_style));
+            }
+            AppMode::QuitBlocked { waiting, .. } => {
+                if !waiting {
+                    spans.push(Span::styled(" w ", key_style));
+                    spans.push(Span::styled("wait ", desc_style));
+                    spans.push(Span::styled(" a ", key_style));
+                    spans.push(Span::styled("abort ", desc_style));
+                }
+                spans.push(Span::styled(" Esc ", key_style));
+                spans.push(Span::styled("cancel", desc_style));
+            }
             AppMode::Error { .. } => {
                 // In error mode, show the message and hide key hints
                 let error_style = Style::default()
@@ -162,24 +416,242 @@ impl<'a> Widget for StatusBar<'a> {
                 }
             }
         }
+    }
+}
 
-        let line = Line::from(spans);
-        buf.set_line(area.x, area.y, &line, area.width);
+impl<'a> Widget for StatusBar<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let key_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Cyan)
+            .add_modifier(Modifier::BOLD);
+        let desc_style = Style::default().fg(Color::White);
+        let mode_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let repo_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
 
-        // Show the mode on the right (only for non-Normal modes)
+        // Mode badge on the right (only for non-Normal modes, or a pending key sequence)
+        // Reserved before writing the left content so it is never overwritten
+        let pending = self.pending_key.map(|key| format!(" {}… ", key));
         let mode_text = match self.mode {
-            AppMode::Normal => None,
+            AppMode::Normal => pending.as_deref(),
             AppMode::Help => Some(" HELP "),
             AppMode::Input { .. } => Some(" INPUT "),
             AppMode::Confirm { .. } => Some(" CONFIRM "),
             AppMode::Error { .. } => Some(" ERROR "),
+            AppMode::RawCommit { .. } => Some(" RAW "),
+            AppMode::DiffView { .. } => Some(" PATCH "),
+            AppMode::RepoInfo { .. } => Some(" INFO "),
+            AppMode::BranchDiff { .. } => Some(" DIFF "),
+            AppMode::Visual { .. } => Some(" VISUAL "),
+            AppMode::Tags { .. } => Some(" TAGS "),
+            AppMode::StashList { .. } => Some(" STASH "),
+            AppMode::CheckoutBlocked { .. } | AppMode::RetargetBranches { .. } => Some(" CONFIRM "),
+            AppMode::Conflict { .. } => Some(" CONFLICT "),
+            AppMode::CommitMenu { .. } => Some(" COMMIT "),
+            AppMode::PickChild { .. } => Some(" CHILD "),
+            AppMode::QuitBlocked { .. } => Some(" QUIT "),
         };
-        if let Some(text) = mode_text {
-            let mode_len = text.len() as u16;
-            if area.width > mode_len {
-                let x = area.x + area.width - mode_len;
-                buf.set_string(x, area.y, text, mode_style);
+        let badge_width = mode_text
+            .map(|t| t.width() as u16)
+            .filter(|w| *w < area.width)
+            .unwrap_or(0);
+
+        let mut spans: Vec<Span> = Vec::new();
+        let max_width = (area.width - badge_width) as usize;
+
+        let mut activity_shown = false;
+        for segment in self.segments {
+            match segment {
+                StatusSegment::Repo => self.push_repo(&mut spans, repo_style),
+                StatusSegment::Head => self.push_head(&mut spans),
+                StatusSegment::Dirty => {
+                    let (text, style) = match self.uncommitted_count {
+                        Some(count) => (
+                            format!(" ● {} changed ", count),
+                            Style::default().fg(Color::Yellow),
+                        ),
+                        None => (" clean ".to_string(), self.theme.muted_style()),
+                    };
+                    spans.push(Span::styled(text, style));
+                    spans.push(Span::raw(" "));
+                }
+                StatusSegment::Commits => {
+                    let more = if self.more_commits { "+" } else { "" };
+                    spans.push(Span::styled(
+                        format!("{}{} commits", self.commit_count, more),
+                        self.theme.muted_style(),
+                    ));
+                    spans.push(Span::raw("  "));
+                }
+                StatusSegment::Clock => {
+                    spans.push(Span::styled(self.clock.clone(), self.theme.muted_style()));
+                    spans.push(Span::raw("  "));
+                }
+                StatusSegment::Hints => {
+                    self.push_activity(&mut spans, true, max_width, key_style, desc_style);
+                    activity_shown = true;
+                }
             }
         }
+        // Messages and dialog keys are shown even when the hints segment is left out
+        if !activity_shown {
+            self.push_activity(&mut spans, false, max_width, key_style, desc_style);
+        }
+
+        let line = Line::from(spans);
+        buf.set_line(area.x, area.y, &line, area.width - badge_width);
+
+        if let Some(text) = mode_text.filter(|_| badge_width > 0) {
+            let x = area.x + area.width - badge_width;
+            buf.set_string(x, area.y, text, mode_style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_to_string(status_bar: StatusBar, width: u16) -> String {
+        let area = Rect::new(0, 0, width, 1);
+        let mut buf = Buffer::empty(area);
+        status_bar.render(area, &mut buf);
+        (0..width)
+            .map(|x| buf[(x, 0)].symbol().to_string())
+            .collect()
+    }
+
+    fn status_bar<'a>(mode: &'a AppMode, head_name: Option<&'a str>) -> StatusBar<'a> {
+        StatusBar {
+            mode,
+            segments: &[
+                StatusSegment::Repo,
+                StatusSegment::Head,
+                StatusSegment::Hints,
+            ],
+            repo_path: "/tmp/repo",
+            separate_workdir: None,
+            head_name: head_name.map(str::to_string),
+            head_name_max_width: 32,
+            uncommitted_count: Some(2),
+            commit_count: 500,
+            more_commits: true,
+            clock: "14:05".to_string(),
+            view_indicators: Vec::new(),
+            update_version: None,
+            error_message: None,
+            message: None,
+            message_warning: false,
+            is_fetching: false,
+            startup_fetch: false,
+            search_info: None,
+            pending_key: None,
+            hints: vec![("j/k", "move"), ("?", "help"), ("q", "quit")],
+            theme: Theme::default(),
+        }
+    }
+
+    #[test]
+    fn test_truncate_middle_keeps_prefix_and_tail() {
+        let name = "feature/automated-dependency-update-for-some-package-TICKET-1234";
+        let clipped = truncate_middle(name, 20);
+        assert_eq!(clipped.width(), 20);
+        assert!(clipped.starts_with("feature/"));
+        assert!(clipped.ends_with("1234"));
+        assert!(clipped.contains(ELLIPSIS));
+    }
+
+    #[test]
+    fn test_truncate_middle_short_name_unchanged() {
+        assert_eq!(truncate_middle("main", 32), "main");
+    }
+
+    #[test]
+    fn test_truncate_middle_wide_chars() {
+        let clipped = truncate_middle("リリース/二〇二四年度版ブランチ", 9);
+        assert!(clipped.width() <= 9);
+        assert!(clipped.contains(ELLIPSIS));
+    }
+
+    #[test]
+    fn test_badge_intact_with_long_head_name() {
+        let mode = AppMode::Help;
+        let long_name = "bot/".to_string() + &"x".repeat(120);
+        for width in [20u16, 30, 40, 80] {
+            let rendered = render_to_string(status_bar(&mode, Some(&long_name)), width);
+            assert!(
+                rendered.ends_with(" HELP "),
+                "badge overwritten at width {}: {:?}",
+                width,
+                rendered
+            );
+        }
+    }
+
+    #[test]
+    fn test_head_name_clipped_in_status_bar() {
+        let mode = AppMode::Normal;
+        let long_name = "bot/".to_string() + &"x".repeat(120) + "-42";
+        let rendered = render_to_string(status_bar(&mode, Some(&long_name)), 200);
+        assert!(rendered.contains(ELLIPSIS));
+        assert!(rendered.contains("-42"));
+        // Key hints are still visible after the clipped name
+        assert!(rendered.contains("quit"));
+    }
+
+    #[test]
+    fn test_narrow_status_bar_keeps_leading_hints() {
+        let mode = AppMode::Normal;
+        let mut bar = status_bar(&mode, None);
+        bar.hints = vec![("c", "checkout"), ("d", "delete"), ("q", "quit")];
+        let rendered = render_to_string(bar, 30);
+        assert!(rendered.contains("checkout"));
+        assert!(!rendered.contains("quit"));
+        // A hint is either shown whole or dropped
+        assert!(!rendered.contains("qu"));
+    }
+
+    #[test]
+    fn test_segments_render_in_configured_order() {
+        let mode = AppMode::Normal;
+        let mut bar = status_bar(&mode, Some("main"));
+        bar.segments = &[
+            StatusSegment::Clock,
+            StatusSegment::Dirty,
+            StatusSegment::Commits,
+            StatusSegment::Head,
+        ];
+        let rendered = render_to_string(bar, 80);
+        assert!(
+            rendered.starts_with("14:05   ● 2 changed  500+ commits   main "),
+            "{:?}",
+            rendered
+        );
+        // Leaving out the repo and hints segments hides them
+        assert!(!rendered.contains("repo"));
+        assert!(!rendered.contains("quit"));
+    }
+
+    #[test]
+    fn test_messages_show_without_hints_segment() {
+        let mode = AppMode::Normal;
+        let mut bar = status_bar(&mode, None);
+        bar.segments = &[StatusSegment::Repo];
+        bar.message = Some("Fetched from origin");
+        assert!(render_to_string(bar, 80).contains("Fetched from origin"));
+    }
+
+    #[test]
+    fn test_pending_key_sequence_shows_as_badge() {
+        let mode = AppMode::Normal;
+        let mut bar = status_bar(&mode, None);
+        bar.pending_key = Some('g');
+        assert!(render_to_string(bar, 80).ends_with(" g… "));
     }
 }
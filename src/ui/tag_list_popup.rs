@@ -0,0 +1,114 @@
+//! Tag browser popup
+
+use chrono::{Local, TimeZone};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Widget},
+};
+
+use super::theme::Theme;
+use crate::git::tag::{TagInfo, TagSort};
+
+pub struct TagListPopup<'a> {
+    tags: &'a [TagInfo],
+    selected: usize,
+    sort: TagSort,
+    theme: Theme,
+}
+
+impl<'a> TagListPopup<'a> {
+    pub fn new(tags: &'a [TagInfo], selected: usize, sort: TagSort, theme: Theme) -> Self {
+        Self {
+            tags,
+            selected,
+            sort,
+            theme,
+        }
+    }
+
+    fn row(&self, tag: &TagInfo, name_width: usize) -> Line<'static> {
+        let date = Local
+            .timestamp_opt(tag.time, 0)
+            .single()
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        let mut spans = vec![
+            Span::styled(
+                format!(" {:<width$} ", tag.name, width = name_width),
+                self.theme
+                    .selectable_style(Style::default().fg(Color::Yellow)),
+            ),
+            Span::styled(
+                format!("{} ", &tag.target.to_string()[..7]),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::styled(format!("{} ", date), self.theme.muted_style()),
+        ];
+        // Annotated tags only
+        if let Some(tagger) = &tag.tagger {
+            spans.push(Span::styled(
+                format!("{} ", tagger),
+                Style::default().fg(Color::Green),
+            ));
+        }
+        if let Some(message) = &tag.message {
+            spans.push(Span::styled(
+                message.clone(),
+                Style::default().fg(Color::White),
+            ));
+        }
+        Line::from(spans)
+    }
+}
+
+impl Widget for TagListPopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .title(format!(
+                " Tags ({}, by {}) ",
+                self.tags.len(),
+                self.sort.label()
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let height = inner.height as usize;
+        if height == 0 {
+            return;
+        }
+        // Keep the selected row in view
+        let offset = self.selected.saturating_sub(height - 1);
+        let name_width = self
+            .tags
+            .iter()
+            .map(|t| t.name.len())
+            .max()
+            .unwrap_or(0)
+            .min(inner.width as usize / 3);
+
+        for (i, tag) in self.tags.iter().enumerate().skip(offset).take(height) {
+            let y = inner.y + (i - offset) as u16;
+            let mut line = self.row(tag, name_width);
+            if i == self.selected {
+                line = line.patch_style(self.theme.selection);
+                buf.set_style(
+                    Rect {
+                        y,
+                        height: 1,
+                        ..inner
+                    },
+                    self.theme.selection,
+                );
+            }
+            buf.set_line(inner.x, y, &line, inner.width);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Theme and motion settings consulted by widgets
+
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::{config::UiConfig, graph::glyphs::GraphGlyphs};
+
+/// Spinner frames for in-progress states
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Visual parameters shared by all widgets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Secondary text (dates, hints, placeholders)
+    pub muted: Color,
+    /// Panel borders
+    pub border: Color,
+    /// Selected row in the commit list
+    pub selection: Style,
+    /// Rows inside the visual mode span (weaker than `selection`)
+    pub visual: Style,
+    /// Symbol drawn before the selected row
+    pub selection_symbol: &'static str,
+    /// Modifier added to selectable content (commit messages, branch names)
+    pub selectable: Modifier,
+    /// Show static text instead of animations
+    pub reduced_motion: bool,
+    /// HEAD decoration before the branch labels
+    pub head_arrow: Color,
+    /// Characters the commit graph is drawn with
+    pub graph_glyphs: GraphGlyphs,
+}
+
+impl Theme {
+    pub fn new(high_contrast: bool, reduced_motion: bool) -> Self {
+        if high_contrast {
+            Self {
+                muted: Color::Gray,
+                border: Color::White,
+                selection: Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                visual: Style::default().add_modifier(Modifier::UNDERLINED),
+                selection_symbol: "▶",
+                selectable: Modifier::BOLD,
+                reduced_motion,
+                head_arrow: Color::Green,
+                graph_glyphs: GraphGlyphs::default(),
+            }
+        } else {
+            Self {
+                muted: Color::DarkGray,
+                border: Color::DarkGray,
+                selection: Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+                visual: Style::default().bg(Color::Indexed(236)),
+                selection_symbol: "",
+                selectable: Modifier::empty(),
+                reduced_motion,
+                head_arrow: Color::Green,
+                graph_glyphs: GraphGlyphs::default(),
+            }
+        }
+    }
+
+    pub fn from_config(ui: &UiConfig) -> Self {
+        Self {
+            head_arrow: ui.head_arrow_color,
+            graph_glyphs: ui.graph_glyphs,
+            ..Self::new(ui.high_contrast, ui.reduced_motion)
+        }
+    }
+
+    pub fn muted_style(&self) -> Style {
+        Style::default().fg(self.muted)
+    }
+
+    pub fn border_style(&self) -> Style {
+        Style::default().fg(self.border)
+    }
+
+    /// Style for selectable text with the given base style
+    pub fn selectable_style(&self, style: Style) -> Style {
+        style.add_modifier(self.selectable)
+    }
+
+    /// Map a content color to this theme (dimmed colors become `muted`)
+    pub fn color(&self, color: Color) -> Color {
+        if color == Color::DarkGray {
+            self.muted
+        } else {
+            color
+        }
+    }
+
+    /// Spinner frame for the given tick (None with reduced motion)
+    pub fn spinner(&self, tick: u128) -> Option<&'static str> {
+        if self.reduced_motion {
+            return None;
+        }
+        Some(SPINNER_FRAMES[(tick % SPINNER_FRAMES.len() as u128) as usize])
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(false, false)
+    }
+}
+
+/// Current spinner tick (advances every 100ms)
+pub fn spinner_tick() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() / 100)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduced_motion_disables_spinner() {
+        assert!(Theme::new(false, false).spinner(3).is_some());
+        assert_eq!(Theme::new(false, true).spinner(3), None);
+        assert_eq!(Theme::new(true, true).spinner(3), None);
+    }
+
+    #[test]
+    fn test_high_contrast_maps_dim_colors() {
+        let theme = Theme::new(true, false);
+        assert_ne!(theme.color(Color::DarkGray), Color::DarkGray);
+        assert_eq!(theme.color(Color::Red), Color::Red);
+    }
+}
@@ -0,0 +1,220 @@
+//! Opt-in update check against the GitHub releases API
+//!
+//! The HTTP request is only compiled with the `update-check` feature.
+//! Results are cached for 24 hours; any failure is silently ignored.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+
+use anyhow::Result;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::worker;
+
+/// How long a cached check result stays valid
+const CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+
+/// A newer release than the running version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateNotice {
+    /// Release version without the leading "v" (e.g. "0.4.0")
+    pub version: String,
+    /// Release page URL
+    pub url: String,
+}
+
+/// Cached result of the last check
+#[derive(Debug, Serialize, Deserialize)]
+struct UpdateCache {
+    /// Unix timestamp of the check
+    checked_at: i64,
+    latest_tag: String,
+    url: String,
+    /// Version whose notice the user dismissed; it is not announced again
+    #[serde(default)]
+    dismissed: Option<String>,
+}
+
+/// Return the release version if `latest_tag` is newer than `current`
+/// Tags may have a leading "v"; unparsable versions are never newer
+pub fn newer_version(current: &str, latest_tag: &str) -> Option<Version> {
+    let current = Version::parse(current).ok()?;
+    let latest = Version::parse(latest_tag.trim_start_matches('v')).ok()?;
+    (latest > current).then_some(latest)
+}
+
+/// Start the check in the background
+/// The receiver yields a notice only when a newer release exists
+pub fn spawn_check() -> Receiver<Result<Option<UpdateNotice>, String>> {
+    worker::spawn(|| check().ok().flatten())
+}
+
+/// Check for a newer release, using the cache when it is fresh
+fn check() -> Result<Option<UpdateNotice>> {
+    let now = chrono::Utc::now().timestamp();
+    let cache_path = cache_path();
+    let cached = cache_path.as_deref().and_then(read_cache);
+
+    let cache = match cached {
+        Some(cache) if is_fresh(&cache, now) => cache,
+        cached => {
+            let (latest_tag, url) = fetch_latest_release()?;
+            let cache = UpdateCache {
+                checked_at: now,
+                latest_tag,
+                url,
+                dismissed: cached.and_then(|cache| cache.dismissed),
+            };
+            if let Some(path) = &cache_path {
+                write_cache(path, &cache);
+            }
+            cache
+        }
+    };
+    Ok(notice(&cache))
+}
+
+/// The notice for a cached result, unless it is not newer or was dismissed
+fn notice(cache: &UpdateCache) -> Option<UpdateNotice> {
+    newer_version(env!("CARGO_PKG_VERSION"), &cache.latest_tag)
+        .map(|version| version.to_string())
+        .filter(|version| cache.dismissed.as_ref() != Some(version))
+        .map(|version| UpdateNotice {
+            version,
+            url: cache.url.clone(),
+        })
+}
+
+/// Stop announcing this release (newer ones are still announced)
+/// Failures are ignored like the check's: the notice then comes back next time
+pub fn dismiss(notice: &UpdateNotice) {
+    let Some(path) = cache_path() else {
+        return;
+    };
+    if let Some(mut cache) = read_cache(&path) {
+        cache.dismissed = Some(notice.version.clone());
+        write_cache(&path, &cache);
+    }
+}
+
+/// ~/.cache/keifu/update_check.toml
+fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|p| p.join("keifu").join("update_check.toml"))
+}
+
+fn read_cache(path: &Path) -> Option<UpdateCache> {
+    let content = fs::read_to_string(path).ok()?;
+    toml::from_str(&content).ok()
+}
+
+/// Whether the cached result is younger than the TTL
+fn is_fresh(cache: &UpdateCache, now: i64) -> bool {
+    now - cache.checked_at < CACHE_TTL_SECS
+}
+
+fn write_cache(path: &Path, cache: &UpdateCache) {
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(content) = toml::to_string(cache) {
+        let _ = fs::write(path, content);
+    }
+}
+
+/// Fetch the latest release tag and page URL
+#[cfg(feature = "update-check")]
+fn fetch_latest_release() -> Result<(String, String)> {
+    use std::time::Duration;
+
+    #[derive(Deserialize)]
+    struct Release {
+        tag_name: String,
+        html_url: String,
+    }
+
+    let repo = env!("CARGO_PKG_REPOSITORY").trim_start_matches("https://github.com/");
+    let endpoint = format!("https://api.github.com/repos/{}/releases/latest", repo);
+    let release: Release = ureq::get(&endpoint)
+        .set("User-Agent", concat!("keifu/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(3))
+        .call()?
+        .into_json()?;
+    Ok((release.tag_name, release.html_url))
+}
+
+#[cfg(not(feature = "update-check"))]
+fn fetch_latest_release() -> Result<(String, String)> {
+    anyhow::bail!("keifu was built without the update-check feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newer_version() {
+        assert_eq!(
+            newer_version("0.2.2", "v0.4.0"),
+            Some(Version::new(0, 4, 0))
+        );
+        assert_eq!(
+            newer_version("0.2.2", "0.10.0"),
+            Some(Version::new(0, 10, 0))
+        );
+        assert_eq!(newer_version("0.2.2", "v0.2.2"), None);
+        assert_eq!(newer_version("0.2.2", "v0.2.1"), None);
+        // Pre-releases sort below the release
+        assert_eq!(newer_version("0.3.0", "v0.3.0-rc.1"), None);
+        assert_eq!(newer_version("0.2.2", "nightly"), None);
+    }
+
+    #[test]
+    fn test_cache_expires_after_ttl() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("update_check.toml");
+        write_cache(
+            &path,
+            &UpdateCache {
+                checked_at: 1_000,
+                latest_tag: "v9.9.9".to_string(),
+                url: "https://example.com".to_string(),
+                dismissed: None,
+            },
+        );
+
+        let cache = read_cache(&path).unwrap();
+        assert!(is_fresh(&cache, 1_000 + CACHE_TTL_SECS - 1));
+        assert!(!is_fresh(&cache, 1_000 + CACHE_TTL_SECS));
+    }
+
+    #[test]
+    fn test_dismissed_version_is_not_announced() {
+        let mut cache = UpdateCache {
+            checked_at: 1_000,
+            latest_tag: "v9.9.9".to_string(),
+            url: "https://example.com".to_string(),
+            dismissed: None,
+        };
+        assert_eq!(notice(&cache).unwrap().version, "9.9.9");
+
+        cache.dismissed = Some("9.9.9".to_string());
+        assert_eq!(notice(&cache), None);
+        // A later release is announced again
+        cache.latest_tag = "v10.0.0".to_string();
+        assert_eq!(notice(&cache).unwrap().version, "10.0.0");
+    }
+
+    #[test]
+    fn test_caches_without_a_dismissed_version_still_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("update_check.toml");
+        fs::write(
+            &path,
+            "checked_at = 1000\nlatest_tag = \"v9.9.9\"\nurl = \"https://example.com\"\n",
+        )
+        .unwrap();
+        assert_eq!(read_cache(&path).unwrap().dismissed, None);
+    }
+}
@@ -0,0 +1,270 @@
+//! View choices remembered per repository (sort, filters, walk scope, diff options)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    git::{graph::GraphDensity, BranchSort, WalkScope},
+    graph::colors::ColorScheme,
+    search::{SearchCase, SubjectLayout},
+};
+
+/// View settings file name, stored under `<git dir>/keifu/`
+const VIEW_FILE: &str = "view.toml";
+
+/// How long a change waits before it is written, so a burst of toggles saves once
+pub const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// View choices that outlive a session
+/// `None` leaves the choice to the config file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ViewSettings {
+    pub hide_merges: bool,
+    pub walk_scope: WalkScope,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_sort: Option<BranchSort>,
+    pub search_case: SearchCase,
+    pub subject_layout: SubjectLayout,
+    pub raw_workdir_diff: bool,
+    pub follow_head: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph_density: Option<GraphDensity>,
+    pub lane_colors: ColorScheme,
+}
+
+impl ViewSettings {
+    /// Parse settings, keeping valid fields when others are invalid
+    /// Returns the settings, entries this version does not know, and the invalid keys
+    fn parse(content: &str) -> Result<(Self, toml::Table, Vec<String>)> {
+        let mut table: toml::Table = toml::from_str(content)?;
+        let mut invalid = Vec::new();
+        let settings = Self {
+            hide_merges: take(&mut table, "hide_merges", &mut invalid).unwrap_or_default(),
+            walk_scope: take(&mut table, "walk_scope", &mut invalid).unwrap_or_default(),
+            branch_sort: take(&mut table, "branch_sort", &mut invalid),
+            search_case: take(&mut table, "search_case", &mut invalid).unwrap_or_default(),
+            subject_layout: take(&mut table, "subject_layout", &mut invalid).unwrap_or_default(),
+            raw_workdir_diff: take(&mut table, "raw_workdir_diff", &mut invalid)
+                .unwrap_or_default(),
+            follow_head: take(&mut table, "follow_head", &mut invalid).unwrap_or_default(),
+            graph_density: take(&mut table, "graph_density", &mut invalid),
+            lane_colors: take(&mut table, "lane_colors", &mut invalid).unwrap_or_default(),
+        };
+        Ok((settings, table, invalid))
+    }
+}
+
+/// Remove a field from the table and parse it (None, recording the key, when invalid)
+fn take<T: DeserializeOwned>(
+    table: &mut toml::Table,
+    key: &str,
+    invalid: &mut Vec<String>,
+) -> Option<T> {
+    let value = table.remove(key)?;
+    value
+        .try_into()
+        .map_err(|_| invalid.push(key.to_string()))
+        .ok()
+}
+
+/// View settings with their file and pending save
+#[derive(Debug, Default)]
+pub struct ViewStore {
+    /// File to save to (None disables persistence)
+    path: Option<PathBuf>,
+    settings: ViewSettings,
+    /// Entries written by newer versions, saved back untouched
+    unknown: toml::Table,
+    /// Keys whose values were ignored when loading
+    invalid: Vec<String>,
+    /// When the settings last changed without being saved
+    changed_at: Option<Instant>,
+}
+
+impl ViewStore {
+    /// Load the view settings for the repository whose git dir is `git_dir`
+    /// Missing or unreadable files yield the defaults
+    pub fn load(git_dir: &Path) -> Self {
+        let path = git_dir.join("keifu").join(VIEW_FILE);
+        let (settings, unknown, invalid) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| ViewSettings::parse(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            settings,
+            unknown,
+            invalid,
+            changed_at: None,
+        }
+    }
+
+    pub fn settings(&self) -> &ViewSettings {
+        &self.settings
+    }
+
+    /// Keys whose values were ignored when loading (reported once at startup)
+    pub fn invalid_keys(&self) -> &[String] {
+        &self.invalid
+    }
+
+    /// Record the current settings; a change is saved by `save_if_due`
+    pub fn update(&mut self, settings: ViewSettings, now: Instant) {
+        if settings != self.settings {
+            self.settings = settings;
+            self.changed_at = Some(now);
+        }
+    }
+
+    /// Save a change once it is `SAVE_DELAY` old
+    pub fn save_if_due(&mut self, now: Instant) -> Result<()> {
+        match self.changed_at {
+            Some(at) if now.duration_since(at) >= SAVE_DELAY => self.save(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Save a pending change now (on exit)
+    pub fn flush(&mut self) -> Result<()> {
+        if self.changed_at.is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Save the settings to disk, keeping unknown entries
+    pub fn save(&mut self) -> Result<()> {
+        self.changed_at = None;
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut table = toml::Table::try_from(&self.settings)?;
+        for (key, value) in &self.unknown {
+            table.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        let content = toml::to_string(&table)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create view settings directory")?;
+        }
+        fs::write(path, content).context("Failed to save view settings")?;
+        Ok(())
+    }
+
+    /// Go back to the defaults and remove the file
+    pub fn reset(&mut self) -> Result<()> {
+        self.settings = ViewSettings::default();
+        self.unknown.clear();
+        self.changed_at = None;
+        if let Some(path) = &self.path {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(e).context("Failed to remove view settings");
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed() -> ViewSettings {
+        ViewSettings {
+            hide_merges: true,
+            walk_scope: WalkScope::CurrentBranch,
+            branch_sort: Some(BranchSort::Date),
+            search_case: SearchCase::Insensitive,
+            subject_layout: SubjectLayout::TwoLine,
+            raw_workdir_diff: true,
+            follow_head: true,
+            graph_density: Some(GraphDensity::Compact),
+            lane_colors: ColorScheme::Rotation,
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut store = ViewStore::load(dir.path());
+        assert_eq!(store.settings(), &ViewSettings::default());
+
+        let now = Instant::now();
+        store.update(changed(), now);
+        // Not written until the change has settled
+        store.save_if_due(now).unwrap();
+        assert!(!dir.path().join("keifu").join(VIEW_FILE).exists());
+        store.save_if_due(now + SAVE_DELAY).unwrap();
+
+        let loaded = ViewStore::load(dir.path());
+        assert_eq!(loaded.settings(), &changed());
+        assert!(loaded.invalid_keys().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_fields_survive_a_load_save_cycle() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("keifu")).unwrap();
+        let path = dir.path().join("keifu").join(VIEW_FILE);
+        fs::write(
+            &path,
+            "hide_merges = true\ndate_format = \"relative\"\n\n[diff]\nignore_whitespace = true\n",
+        )
+        .unwrap();
+
+        let mut store = ViewStore::load(dir.path());
+        assert!(store.settings().hide_merges);
+        store.update(
+            ViewSettings {
+                follow_head: true,
+                ..store.settings().clone()
+            },
+            Instant::now(),
+        );
+        store.flush().unwrap();
+
+        let saved: toml::Table = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["date_format"].as_str(), Some("relative"));
+        assert_eq!(saved["diff"]["ignore_whitespace"].as_bool(), Some(true));
+        assert_eq!(saved["follow_head"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn test_invalid_values_fall_back_field_by_field() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("keifu")).unwrap();
+        fs::write(
+            dir.path().join("keifu").join(VIEW_FILE),
+            "walk_scope = \"sideways\"\nbranch_sort = \"date\"\nhide_merges = 3\n",
+        )
+        .unwrap();
+
+        let store = ViewStore::load(dir.path());
+        assert_eq!(store.settings().walk_scope, WalkScope::AllBranches);
+        assert_eq!(store.settings().branch_sort, Some(BranchSort::Date));
+        assert!(!store.settings().hide_merges);
+        assert_eq!(store.invalid_keys(), ["hide_merges", "walk_scope"]);
+    }
+
+    #[test]
+    fn test_reset_removes_the_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut store = ViewStore::load(dir.path());
+        store.update(changed(), Instant::now());
+        store.flush().unwrap();
+
+        store.reset().unwrap();
+        assert_eq!(store.settings(), &ViewSettings::default());
+        assert!(!dir.path().join("keifu").join(VIEW_FILE).exists());
+        // Resetting without a file is fine too
+        store.reset().unwrap();
+    }
+}
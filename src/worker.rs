@@ -0,0 +1,65 @@
+//! Background worker threads with panic capture
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Name given to worker threads (the panic hook leaves the terminal alone for them)
+pub const WORKER_THREAD_NAME: &str = "keifu-worker";
+
+/// Run `job` on a background thread and return a receiver for its result
+/// A panic inside the job is sent as `Err(payload)`, so the receiver never waits forever
+pub fn spawn<T, F>(job: F) -> Receiver<Result<T, String>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    let spawned = thread::Builder::new()
+        .name(WORKER_THREAD_NAME.to_string())
+        .spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(job))
+                .map_err(|payload| panic_message(payload.as_ref()));
+            let _ = tx.send(result);
+        });
+    // If the thread could not be spawned, the sender is dropped and the receiver disconnects
+    drop(spawned);
+    rx
+}
+
+/// Whether the current thread is a worker thread
+pub fn is_worker_thread() -> bool {
+    thread::current().name() == Some(WORKER_THREAD_NAME)
+}
+
+/// Extract a readable message from a panic payload
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_is_sent_as_error_and_later_jobs_still_run() {
+        let rx = spawn(|| -> u32 { panic!("malformed object") });
+        assert_eq!(rx.recv().unwrap(), Err("malformed object".to_string()));
+
+        let rx = spawn(|| 42);
+        assert_eq!(rx.recv().unwrap(), Ok(42));
+    }
+
+    #[test]
+    fn test_formatted_panic_message() {
+        let rx: Receiver<Result<(), String>> = spawn(|| panic!("bad oid {}", 7));
+        assert_eq!(rx.recv().unwrap(), Err("bad oid 7".to_string()));
+    }
+}
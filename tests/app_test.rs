@@ -0,0 +1,2169 @@
+//! App flows driven through Actions against real Git repositories
+
+mod common;
+
+use std::sync::atomic::AtomicBool;
+
+use common::{
+    harness::{buffer_lines, render, untracked_conflict_harness, AppHarness},
+    repo_with_slow_origin, stacked_branches_repo, TestRepo,
+};
+use git2::{BranchType, Oid};
+use keifu::{
+    action::Action,
+    app::{AppMode, ConflictOp, COMMIT_PAGE_SIZE},
+    config::{Config, QKey},
+    git::operations::{fetch_origin, merge_branch},
+    git::{BranchGrouping, BranchSort},
+    graph::colors::LANE_COLORS,
+    gutter::GutterMark,
+    search::{SearchCase, SubjectLayout},
+};
+
+/// HEAD branch with one commit on top of `base`, where `feature` points
+fn two_branch_repo() -> (TestRepo, Oid, Oid) {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    test_repo.branch("feature", base);
+    let tip = test_repo.commit_file("b.txt", "b", "main work");
+    (test_repo, base, tip)
+}
+
+fn branch_exists(harness: &AppHarness, name: &str) -> bool {
+    harness
+        .repo
+        .repo
+        .find_branch(name, BranchType::Local)
+        .is_ok()
+}
+
+#[test]
+fn test_help_toggle() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::ToggleHelp);
+    assert!(matches!(harness.current_mode(), AppMode::Help));
+    assert!(harness.buffer_contains("Jump to HEAD"));
+
+    harness.act(Action::ToggleHelp);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(!harness.buffer_contains("Jump to HEAD"));
+}
+
+#[test]
+fn test_checkout_selected_branch() {
+    let (test_repo, base, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(base);
+    assert_eq!(harness.app.selected_branch_name(), Some("feature"));
+    harness.act(Action::Checkout);
+
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.app.head_name.as_deref(), Some("feature"));
+    assert_eq!(harness.repo.head_branch(), "feature");
+}
+
+#[test]
+fn test_checkout_while_index_locked_offers_retry() {
+    let (test_repo, base, _) = two_branch_repo();
+    let lock = test_repo.path().join(".git/index.lock");
+    std::fs::write(&lock, "").unwrap();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(base);
+    harness.act(Action::Checkout);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+    assert!(harness.buffer_contains("locked by another git process"));
+    assert_ne!(harness.repo.head_branch(), "feature");
+
+    // Still locked: the dialog stays up
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+
+    std::fs::remove_file(&lock).unwrap();
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.repo.head_branch(), "feature");
+}
+
+#[test]
+fn test_create_branch_at_selected_commit() {
+    let (test_repo, base, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(base);
+    harness.act(Action::CreateBranch);
+    assert!(matches!(harness.current_mode(), AppMode::Input { .. }));
+    harness.type_text("topic");
+    harness.act(Action::Confirm);
+
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    let topic_target = harness
+        .repo
+        .repo
+        .find_branch("topic", BranchType::Local)
+        .unwrap()
+        .get()
+        .target();
+    assert_eq!(topic_target, Some(base));
+    assert!(harness.buffer_contains("topic"));
+}
+
+#[test]
+fn test_delete_branch_asks_for_confirmation() {
+    let (test_repo, base, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    // Cancelling keeps the branch
+    harness.select_commit(base);
+    harness.act(Action::DeleteBranch);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+    assert!(harness.buffer_contains("Delete branch 'feature'?"));
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(branch_exists(&harness, "feature"));
+
+    // Confirming deletes it
+    harness.act(Action::DeleteBranch);
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(!branch_exists(&harness, "feature"));
+}
+
+fn error_message(harness: &AppHarness) -> String {
+    match harness.current_mode() {
+        AppMode::Error { message } => message.clone(),
+        mode => panic!("expected an error dialog, got {:?}", mode),
+    }
+}
+
+#[test]
+fn test_delete_refused_when_branch_moved_after_dialog() {
+    let (test_repo, base, tip) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(base);
+    harness.act(Action::DeleteBranch);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+    // Another process moves the branch while the dialog is open
+    harness
+        .repo
+        .repo
+        .reference("refs/heads/feature", tip, true, "moved elsewhere")
+        .unwrap();
+    harness.act(Action::Confirm);
+
+    assert!(error_message(&harness).contains("refresh and retry"));
+    assert!(branch_exists(&harness, "feature"));
+
+    // After a refresh the dialog reflects the new state and goes through
+    harness.act(Action::Cancel);
+    harness.act(Action::Refresh);
+    harness.select_commit(tip);
+    harness.act(Action::BranchRight);
+    assert_eq!(harness.app.selected_branch_name(), Some("feature"));
+    harness.act(Action::DeleteBranch);
+    harness.act(Action::Confirm);
+    assert!(!branch_exists(&harness, "feature"));
+}
+
+#[test]
+fn test_cherry_pick_refused_when_head_moved_after_dialog() {
+    let (mut harness, picks) = visual_repo();
+    let head_before = harness.repo.repo.head().unwrap().target().unwrap();
+
+    harness.select_commit(picks[0]);
+    harness.act(Action::ToggleVisual);
+    harness.act(Action::CherryPick);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+    let moved = harness.repo.commit_file("z.txt", "z", "from a shell");
+    assert_ne!(moved, head_before);
+    harness.act(Action::Confirm);
+
+    assert!(error_message(&harness).contains("Repository changed"));
+    assert_eq!(harness.repo.repo.head().unwrap().target(), Some(moved));
+}
+
+#[test]
+fn test_failed_operation_shows_error_dialog() {
+    let (test_repo, base, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(base);
+    harness.act(Action::CreateBranch);
+    harness.type_text("feature");
+    harness.act(Action::Confirm);
+
+    assert!(matches!(harness.current_mode(), AppMode::Error { .. }));
+    assert!(harness.buffer_contains("Branch 'feature' already exists"));
+
+    // Closing the error returns to the input, so the name can be corrected
+    harness.act(Action::Cancel);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::Input { input, .. } if input == "feature"
+    ));
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+}
+
+#[test]
+fn test_selecting_branch_label_marks_its_upstream_until_next_key() {
+    let (test_repo, base, _) = two_branch_repo();
+    let head = test_repo.head_branch();
+    test_repo
+        .repo
+        .find_branch(&head, BranchType::Local)
+        .unwrap()
+        .set_upstream(Some("feature"))
+        .unwrap();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::NextBranch);
+    assert_eq!(harness.app.selected_branch_name(), Some("feature"));
+    assert_eq!(harness.app.gutter.get(base), None);
+
+    harness.act(Action::PrevBranch);
+    assert_eq!(harness.app.selected_branch_name(), Some(head.as_str()));
+    assert_eq!(
+        harness.app.get_message(),
+        Some("feature is 1 commit behind (row 2)")
+    );
+    assert_eq!(harness.app.gutter.get(base), Some(GutterMark::Upstream));
+    assert!(harness.buffer_contains("⇡"));
+
+    harness.act(Action::MoveDown);
+    assert_eq!(harness.app.gutter.get(base), None);
+}
+
+#[test]
+fn test_long_commit_message_is_truncated_in_detail_pane() {
+    let test_repo = TestRepo::new();
+    let body: Vec<String> = (1..=50).map(|i| format!("changelog line {}", i)).collect();
+    test_repo.commit_file("a.txt", "a", &format!("release\n\n{}", body.join("\n")));
+    let mut config = Config::default();
+    config.ui.max_message_lines = 4;
+    let mut harness = AppHarness::with_config(test_repo, config);
+
+    // Tall enough for the whole message
+    let text = buffer_lines(&render(&mut harness.app, 100, 80)).join("\n");
+    assert!(text.contains("changelog line 2"));
+    assert!(!text.contains("changelog line 3"));
+    assert!(text.contains("message truncated, press o"));
+
+    // The raw commit popup has the full message
+    harness.act(Action::ShowRawCommit);
+    let AppMode::RawCommit { content, .. } = harness.current_mode() else {
+        panic!("expected the raw commit popup");
+    };
+    assert!(content.contains("changelog line 50"));
+}
+
+#[test]
+fn test_command_palette_completes_and_runs_commands() {
+    let (test_repo, base, tip) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::CommandPalette);
+    harness.type_text("go fea");
+    assert_eq!(harness.app.command_completions(), ["goto feature"]);
+    harness.act(Action::CompleteCommand);
+    assert!(harness.buffer_contains(":goto feature_"));
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+    assert_eq!(harness.app.selected_branch_name(), Some("feature"));
+
+    harness.act(Action::CommandPalette);
+    harness.type_text("limit 1");
+    harness.act(Action::Confirm);
+    assert_eq!(harness.app.get_message(), Some("Loaded 1 commit"));
+    assert_eq!(harness.selected_commit_oid(), Some(tip));
+
+    harness.act(Action::CommandPalette);
+    harness.type_text("checkout feature");
+    harness.act(Action::Confirm);
+    assert_eq!(harness.repo.head_branch(), "feature");
+}
+
+#[test]
+fn test_command_palette_errors_return_to_the_palette() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::CommandPalette);
+    harness.type_text("goto nowhere");
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Error { .. }));
+
+    harness.act(Action::Cancel);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::Input { input, .. } if input == "goto nowhere"
+    ));
+}
+
+#[test]
+fn test_open_extracts_the_file_at_the_selected_commit() {
+    let test_repo = TestRepo::new();
+    let first = test_repo.commit_file("src/lib.rs", "first", "add lib");
+    test_repo.commit_file("src/lib.rs", "second", "change lib");
+    let mut index = test_repo.repo.index().unwrap();
+    index
+        .remove_path(std::path::Path::new("src/lib.rs"))
+        .unwrap();
+    index.write().unwrap();
+    let deleted = test_repo.commit_index("remove lib");
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(first);
+    harness.act(Action::CommandPalette);
+    harness.type_text("open src/lib.rs");
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    let file = harness.app.take_editor_file().unwrap();
+    assert_eq!(std::fs::read_to_string(file.path()).unwrap(), "first");
+    assert!(std::fs::metadata(file.path())
+        .unwrap()
+        .permissions()
+        .readonly());
+    let path = file.path().to_path_buf();
+    drop(file);
+    assert!(!path.exists());
+
+    harness.select_commit(deleted);
+    harness.act(Action::CommandPalette);
+    harness.type_text("open src/lib.rs");
+    harness.act(Action::Confirm);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::Error { message } if message.starts_with("'src/lib.rs' was deleted in")
+    ));
+    assert!(harness.app.take_editor_file().is_none());
+}
+
+#[test]
+fn test_search_jumps_to_branch() {
+    let (test_repo, base, tip) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+    assert_eq!(harness.selected_commit_oid(), Some(tip));
+
+    harness.act(Action::Search);
+    harness.type_text("feat");
+    harness.act(Action::Confirm);
+
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+    assert_eq!(harness.app.selected_branch_name(), Some("feature"));
+}
+
+#[test]
+fn test_branch_left_right_cycle_labels_on_one_commit() {
+    let (test_repo, base, tip) = two_branch_repo();
+    test_repo.branch("alpha", base);
+    test_repo.branch("zeta", base);
+    let mut harness = AppHarness::new(test_repo);
+    harness.select_commit(base);
+    assert_eq!(harness.app.selected_branch_name(), Some("alpha"));
+
+    harness.act(Action::BranchRight);
+    assert_eq!(harness.app.selected_branch_name(), Some("feature"));
+    harness.act_all([Action::BranchRight, Action::BranchRight]);
+    assert_eq!(harness.app.selected_branch_name(), Some("alpha"));
+    harness.act(Action::BranchLeft);
+    assert_eq!(harness.app.selected_branch_name(), Some("zeta"));
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+
+    // The highlighted label is the one deleted
+    harness.act_all([Action::DeleteBranch, Action::Confirm]);
+    assert!(!branch_exists(&harness, "zeta"));
+    assert!(branch_exists(&harness, "alpha"));
+
+    // Moving to another commit starts from its first label again
+    harness.select_commit(tip);
+    harness.select_commit(base);
+    assert_eq!(harness.app.selected_branch_name(), Some("alpha"));
+}
+
+#[test]
+fn test_search_selects_searched_label_on_shared_commit() {
+    let (test_repo, base, _) = two_branch_repo();
+    test_repo.branch("zeta", base);
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::Search);
+    harness.type_text("zeta");
+    harness.act(Action::Confirm);
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+    assert_eq!(harness.app.selected_branch_name(), Some("zeta"));
+
+    harness.act(Action::Checkout);
+    assert_eq!(harness.repo.head_branch(), "zeta");
+}
+
+#[test]
+fn test_search_case_toggle_reruns_query() {
+    let (test_repo, base, _) = two_branch_repo();
+    test_repo.branch("Feature-x", base);
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::Search);
+    harness.type_text("feature");
+    assert_eq!(harness.app.search_case(), SearchCase::Smart);
+    assert_eq!(harness.app.search_results().len(), 2);
+    assert!(harness.buffer_contains("smart case"));
+
+    harness.act(Action::CycleSearchCase);
+    assert_eq!(harness.app.search_case(), SearchCase::Sensitive);
+    assert_eq!(harness.app.search_results().len(), 1);
+    assert!(harness.buffer_contains("case-sensitive"));
+
+    harness.type_text("-X");
+    assert!(harness.app.search_results().is_empty());
+    harness.act(Action::CycleSearchCase);
+    assert_eq!(harness.app.search_case(), SearchCase::Insensitive);
+    assert_eq!(harness.app.search_results().len(), 1);
+}
+
+#[test]
+fn test_search_enter_without_matches_keeps_search_open() {
+    let (test_repo, _, tip) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::Search);
+    harness.type_text("zzzz");
+    harness.act(Action::Confirm);
+
+    assert!(matches!(harness.current_mode(), AppMode::Input { .. }));
+    assert_eq!(harness.app.get_message(), Some("No matches"));
+    assert!(harness.buffer_contains("No matches"));
+    assert_eq!(harness.selected_commit_oid(), Some(tip));
+}
+
+#[test]
+fn test_search_enter_jumps_to_top_result() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    // The empty query lists every branch; Enter takes the first one
+    harness.act(Action::Search);
+    let top = harness.app.search_results()[0].branch_idx;
+    let (node, name) = harness.app.branch_positions[top].clone();
+    harness.act(Action::Confirm);
+
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.app.graph_list_state.selected(), Some(node));
+    assert_eq!(harness.app.selected_branch_name(), Some(name.as_str()));
+}
+
+#[test]
+fn test_search_diffs_two_marked_branches() {
+    let (test_repo, _, _) = two_branch_repo();
+    let head = test_repo.head_branch();
+    let mut harness = AppHarness::new(test_repo);
+
+    // The HEAD branch is listed first, then feature
+    harness.act(Action::Search);
+    harness.act(Action::MarkBranchDiff);
+    assert_eq!(harness.app.search_diff_mark(), Some(head.as_str()));
+    assert!(harness.buffer_contains(&format!("diff from {}", head)));
+
+    harness.act(Action::SearchSelectDown);
+    harness.act(Action::MarkBranchDiff);
+    let AppMode::BranchDiff { comparison, .. } = harness.current_mode() else {
+        panic!("expected the branch diff popup");
+    };
+    assert_eq!(comparison.ahead_behind, Some((0, 1)));
+    assert_eq!(comparison.diff.files[0].path.to_str(), Some("b.txt"));
+    assert!(harness.buffer_contains(&format!("{} → feature", head)));
+    assert!(harness.buffer_contains(" D b.txt"));
+
+    // Closing returns to the search with the mark cleared
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Input { .. }));
+    assert_eq!(harness.app.search_diff_mark(), None);
+}
+
+#[test]
+fn test_branch_list_shows_commits_ahead_of_main_when_enabled() {
+    let (test_repo, base, _) = two_branch_repo();
+    let first = test_repo.commit_at("topic 1", &[base], 1_000);
+    let second = test_repo.commit_at("topic 2", &[first], 2_000);
+    test_repo.branch("topic", second);
+    let head = test_repo.head_branch();
+    let mut config = Config::default();
+    config.ui.branch_counts = true;
+    let mut harness = AppHarness::with_config(test_repo, config);
+
+    harness.act(Action::Search);
+    assert!(harness.buffer_contains("topic (2)"));
+    assert!(harness.buffer_contains(&format!("{} (0)", head)));
+    // Filtered results keep their counts
+    harness.type_text("top");
+    assert!(harness.buffer_contains("topic (2)"));
+}
+
+#[test]
+fn test_branch_list_hides_counts_by_default() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::Search);
+    assert!(harness.buffer_contains("feature"));
+    assert!(!harness.buffer_contains("feature ("));
+}
+
+#[test]
+fn test_search_cancel_restores_selection() {
+    let (test_repo, base, tip) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+    let original_branch = harness.app.selected_branch_name().map(str::to_string);
+
+    harness.act(Action::Search);
+    harness.type_text("feat");
+    // Live preview moves the selection while typing
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+    harness.act(Action::Cancel);
+
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.selected_commit_oid(), Some(tip));
+    assert_eq!(
+        harness.app.selected_branch_name().map(str::to_string),
+        original_branch
+    );
+}
+
+#[test]
+fn test_repeat_checkout() {
+    let (test_repo, base, _) = two_branch_repo();
+    let main_branch = test_repo.head_branch();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::RepeatLastOperation);
+    assert!(harness.buffer_contains("No operation to repeat"));
+
+    harness.select_commit(base);
+    harness.act(Action::Checkout);
+    harness.repo.checkout(&main_branch);
+    harness.act(Action::Refresh);
+    assert_eq!(harness.app.head_name.as_deref(), Some(main_branch.as_str()));
+
+    harness.act(Action::RepeatLastOperation);
+    assert_eq!(harness.app.head_name.as_deref(), Some("feature"));
+}
+
+#[test]
+fn test_delete_branch_confirms_by_default() {
+    let (test_repo, base, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(base);
+    harness.act(Action::DeleteBranch);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+    assert!(branch_exists(&harness, "feature"));
+
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(branch_exists(&harness, "feature"));
+}
+
+#[test]
+fn test_disabled_confirmation_runs_the_operation_at_once() {
+    let (test_repo, base, _) = two_branch_repo();
+    let mut config = Config::default();
+    config.confirm.delete_branch = false;
+    let mut harness = AppHarness::with_config(test_repo, config);
+
+    harness.select_commit(base);
+    harness.act(Action::DeleteBranch);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(!branch_exists(&harness, "feature"));
+}
+
+#[test]
+fn test_repeat_destructive_operation_always_confirms() {
+    let (test_repo, base, _) = two_branch_repo();
+    let mut config = keifu::config::Config::default();
+    config.confirm.delete_branch = false;
+    let mut harness = AppHarness::with_config(test_repo, config);
+
+    harness.select_commit(base);
+    harness.act(Action::DeleteBranch);
+    assert!(!branch_exists(&harness, "feature"));
+
+    harness.repo.branch("feature", base);
+    harness.act(Action::RepeatLastOperation);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+    assert!(branch_exists(&harness, "feature"));
+
+    harness.act(Action::Confirm);
+    assert!(!branch_exists(&harness, "feature"));
+}
+
+fn notes(harness: &AppHarness) -> String {
+    std::fs::read_to_string(harness.repo.path().join("notes.txt")).unwrap()
+}
+
+#[test]
+fn test_checkout_over_untracked_files_can_be_cancelled() {
+    let mut harness = untracked_conflict_harness();
+    let main = harness.repo.head_branch();
+
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::CheckoutBlocked {
+            local_changes: false,
+            ..
+        }
+    ));
+    assert!(harness.buffer_contains("Untracked files would be overwritten"));
+    assert!(harness.buffer_contains("notes.txt"));
+
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.repo.head_branch(), main);
+    assert_eq!(notes(&harness), "local notes");
+}
+
+#[test]
+fn test_checkout_over_untracked_files_can_overwrite_them() {
+    let mut harness = untracked_conflict_harness();
+
+    harness.act(Action::OverwriteUntracked);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.repo.head_branch(), "feature");
+    assert_eq!(notes(&harness), "from feature");
+}
+
+#[test]
+fn test_checkout_over_untracked_files_can_stash_and_retry() {
+    let mut harness = untracked_conflict_harness();
+
+    harness.act(Action::StashAndRetry);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.repo.head_branch(), "feature");
+    assert_eq!(notes(&harness), "from feature");
+    assert!(harness.repo.repo.revparse_single("refs/stash").is_ok());
+}
+
+#[test]
+fn test_checkout_over_uncommitted_changes_offers_only_stash() {
+    let (test_repo, base, _) = two_branch_repo();
+    test_repo.commit_file("a.txt", "changed on main", "main edit");
+    test_repo.write_file("a.txt", "uncommitted");
+    let main = test_repo.head_branch();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(base);
+    harness.act(Action::Checkout);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::CheckoutBlocked {
+            local_changes: true,
+            ..
+        }
+    ));
+    assert!(harness.buffer_contains("Uncommitted changes would be overwritten"));
+    assert!(harness.buffer_contains("a.txt"));
+
+    // Overwriting would lose the edit, so it is not available
+    harness.act(Action::OverwriteUntracked);
+    assert_eq!(harness.repo.head_branch(), main);
+    let content = std::fs::read_to_string(harness.repo.path().join("a.txt")).unwrap();
+    assert_eq!(content, "uncommitted");
+
+    harness.act(Action::StashAndRetry);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.repo.head_branch(), "feature");
+    assert!(harness.repo.repo.revparse_single("refs/stash").is_ok());
+}
+
+#[test]
+fn test_view_settings_are_restored_per_repository_until_reset() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+    harness.act(Action::ToggleHideMerges);
+    harness.act(Action::ToggleWalkScope);
+    harness.app.flush_view_settings().unwrap();
+
+    let mut harness = AppHarness::new(harness.repo);
+    assert_eq!(
+        harness.app.view_indicators(),
+        ["[no-merges]", "[this-branch]"]
+    );
+
+    harness.act(Action::CommandPalette);
+    harness.type_text("reset-view");
+    harness.act(Action::Confirm);
+    assert_eq!(
+        harness.app.get_message(),
+        Some("View settings reset to defaults")
+    );
+    assert!(harness.app.view_indicators().is_empty());
+    harness.app.flush_view_settings().unwrap();
+
+    let harness = AppHarness::new(harness.repo);
+    assert!(harness.app.view_indicators().is_empty());
+}
+
+#[test]
+fn test_compact_graph_toggle_drops_connector_rows() {
+    let (test_repo, base, _) = two_branch_repo();
+    // Two branches fork from a merge commit, whose own merge line keeps the fork
+    // connector on a row of its own at the default density
+    let side = test_repo.commit_at("side", &[base], 1_000);
+    let merge = test_repo.commit_at("merge", &[base, side], 2_000);
+    test_repo.branch("left", test_repo.commit_at("left", &[merge], 3_000));
+    test_repo.branch("right", test_repo.commit_at("right", &[merge], 4_000));
+    let mut harness = AppHarness::new(test_repo);
+    let connector_rows = |harness: &AppHarness| {
+        harness
+            .app
+            .graph_layout
+            .nodes
+            .iter()
+            .filter(|n| n.is_connector())
+            .count()
+    };
+    assert_eq!(connector_rows(&harness), 1);
+
+    harness.select_commit(merge);
+    harness.act(Action::ToggleCompactGraph);
+    assert_eq!(connector_rows(&harness), 0);
+    assert_eq!(harness.app.graph_layout.nodes.len(), 6);
+    assert_eq!(harness.selected_commit_oid(), Some(merge));
+    assert_eq!(
+        harness.app.get_message(),
+        Some("Compact graph: one row per commit")
+    );
+
+    harness.act(Action::ToggleCompactGraph);
+    assert_eq!(connector_rows(&harness), 1);
+    assert_eq!(harness.selected_commit_oid(), Some(merge));
+}
+
+#[test]
+fn test_lane_colors_toggle_between_schemes() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+    let colors = |harness: &AppHarness| {
+        harness
+            .app
+            .graph_layout
+            .nodes
+            .iter()
+            .filter(|n| n.commit.is_some())
+            .map(|n| (n.lane, n.color_index))
+            .collect::<Vec<_>>()
+    };
+    let balanced = colors(&harness);
+
+    harness.act(Action::ToggleLaneColors);
+    assert_eq!(
+        harness.app.get_message(),
+        Some("Lane colors: one per lane position")
+    );
+    assert!(colors(&harness)
+        .iter()
+        .all(|&(lane, color)| color == lane % LANE_COLORS.len()));
+    // Remembered with the other view settings
+    harness.app.flush_view_settings().unwrap();
+    let mut harness = AppHarness::new(harness.repo);
+    assert_ne!(colors(&harness), balanced);
+
+    harness.act(Action::ToggleLaneColors);
+    assert_eq!(harness.app.get_message(), Some("Lane colors: balanced"));
+    assert_eq!(colors(&harness), balanced);
+}
+
+#[test]
+fn test_jump_to_head_selects_detached_head_commit() {
+    let test_repo = TestRepo::new();
+    let mut commits = vec![test_repo.commit_file("a.txt", "0", "commit 0")];
+    for i in 1..8 {
+        commits.push(test_repo.commit_file("a.txt", &i.to_string(), &format!("commit {}", i)));
+    }
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.app.graph_list_state.select(Some(5));
+    harness.act(Action::JumpToHead);
+    assert_eq!(harness.selected_commit_oid(), Some(commits[7]));
+
+    // Detached HEAD has no branch label, so the commit itself is selected
+    harness.repo.repo.set_head_detached(commits[3]).unwrap();
+    harness.act(Action::Refresh);
+    harness.app.graph_list_state.select(Some(0));
+    harness.act(Action::JumpToHead);
+    assert_eq!(harness.selected_commit_oid(), Some(commits[3]));
+}
+
+#[test]
+fn test_jump_to_head_reports_head_outside_the_view() {
+    let (test_repo, base, tip) = two_branch_repo();
+    let merge = test_repo.commit_at("merge feature", &[tip, base], 1_900_000_000);
+    test_repo.repo.set_head_detached(merge).unwrap();
+    let mut harness = AppHarness::new(test_repo);
+
+    // Hiding merges hides the detached HEAD commit
+    harness.act(Action::ToggleHideMerges);
+    harness.select_commit(base);
+    harness.act(Action::JumpToHead);
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+    assert_eq!(harness.app.get_message(), Some("HEAD not in view"));
+}
+
+#[test]
+fn test_follow_head_returns_selection_to_head_after_operations() {
+    let (test_repo, base, tip) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    // Without follow, the selection stays where the operation ran
+    harness.select_commit(base);
+    harness.act(Action::CreateBranch);
+    harness.type_text("topic");
+    harness.act(Action::Confirm);
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+
+    harness.act(Action::ToggleFollowHead);
+    assert_eq!(harness.selected_commit_oid(), Some(tip));
+    assert!(harness.buffer_contains("[follow]"));
+
+    harness.select_commit(base);
+    harness.act(Action::CreateBranch);
+    harness.type_text("other");
+    harness.act(Action::Confirm);
+    assert_eq!(harness.selected_commit_oid(), Some(tip));
+
+    // Checking out moves HEAD, and the selection with it
+    harness.select_commit(base);
+    harness.act(Action::Checkout);
+    assert_eq!(harness.repo.head_branch(), "feature");
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+}
+
+#[test]
+fn test_start_at_rev_selects_and_marks_the_commit() {
+    let (test_repo, base, tip) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.app.start_at_rev("HEAD~1").unwrap();
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+    assert_eq!(harness.app.gutter.get(base), Some(GutterMark::StartRev));
+
+    harness.select_commit(tip);
+    harness.act(Action::JumpToStartRev);
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+
+    assert!(harness.app.start_at_rev("nope").is_err());
+}
+
+#[test]
+fn test_tag_browser_jumps_to_and_checks_out_tags() {
+    let (test_repo, base, tip) = two_branch_repo();
+    {
+        let repo = &test_repo.repo;
+        let sig = repo.signature().unwrap();
+        let base_object = repo.find_object(base, None).unwrap();
+        repo.tag(
+            "v1.9.0",
+            &base_object,
+            &sig,
+            "First release\n\nDetails",
+            false,
+        )
+        .unwrap();
+        let tip_object = repo.find_object(tip, None).unwrap();
+        repo.tag_lightweight("v1.10.0", &tip_object, false).unwrap();
+    }
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::ShowTags);
+    assert!(matches!(harness.current_mode(), AppMode::Tags { .. }));
+    assert!(harness.buffer_contains("Tags (2, by version)"));
+    assert!(harness.buffer_contains("First release"));
+    assert!(!harness.buffer_contains("Details"));
+
+    // Version order puts v1.10.0 first; the annotated tag is second
+    harness.act(Action::MoveDown);
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+
+    harness.act(Action::ShowTags);
+    harness.act(Action::Checkout);
+    assert!(harness.repo.repo.head_detached().unwrap());
+    assert_eq!(harness.repo.repo.head().unwrap().target(), Some(tip));
+}
+
+/// `feature` has three commits on top of `base`; HEAD is on the other branch
+fn visual_repo() -> (AppHarness, Vec<Oid>) {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let main = test_repo.head_branch();
+    test_repo.branch("feature", base);
+    test_repo.checkout("feature");
+    let picks = vec![
+        test_repo.commit_file("one.txt", "1", "one"),
+        test_repo.commit_file("two.txt", "2\n2", "two"),
+        test_repo.commit_file("three.txt", "3", "three"),
+    ];
+    test_repo.checkout(&main);
+    // Newer than the feature commits, so it is listed above them instead of inside the span
+    let later = test_repo
+        .repo
+        .find_commit(picks[2])
+        .unwrap()
+        .time()
+        .seconds()
+        + 60;
+    let main_work = test_repo.commit_at("main work", &[base], later);
+    test_repo
+        .repo
+        .reference(
+            &format!("refs/heads/{}", main),
+            main_work,
+            true,
+            "main work",
+        )
+        .unwrap();
+    (AppHarness::new(test_repo), picks)
+}
+
+#[test]
+fn test_visual_mode_cherry_picks_span_in_order() {
+    let (mut harness, picks) = visual_repo();
+
+    harness.select_commit(picks[2]);
+    harness.act(Action::ToggleVisual);
+    assert!(harness.buffer_contains("VISUAL"));
+    harness.select_commit(picks[0]);
+
+    harness.act(Action::ShowSeriesStats);
+    assert_eq!(
+        harness.app.get_message(),
+        Some("3 commits by 1 author, 3 files changed, +4 -0")
+    );
+
+    harness.act(Action::CherryPick);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+
+    // Picked oldest first on top of HEAD
+    let mut revwalk = harness.repo.repo.revwalk().unwrap();
+    revwalk.push_head().unwrap();
+    let summaries: Vec<String> = revwalk
+        .take(3)
+        .map(|oid| {
+            let commit = harness.repo.repo.find_commit(oid.unwrap()).unwrap();
+            commit.summary().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(summaries, ["three", "two", "one"]);
+}
+
+#[test]
+fn test_cherry_pick_of_the_selected_commit() {
+    let (mut harness, picks) = visual_repo();
+    let short = picks[1].to_string()[..7].to_string();
+
+    harness.select_commit(picks[1]);
+    harness.act(Action::CherryPick);
+    assert_eq!(
+        confirm_message(&harness),
+        format!("Cherry-pick {} onto HEAD?", short)
+    );
+    harness.act(Action::Confirm);
+    assert_eq!(
+        harness.app.get_message(),
+        Some(format!("Cherry-picked {}", short).as_str())
+    );
+    let summary = |harness: &AppHarness| {
+        let head = harness.repo.repo.head().unwrap().peel_to_commit().unwrap();
+        head.summary().unwrap().to_string()
+    };
+    assert_eq!(summary(&harness), "two");
+
+    // Picking it again conflicts with the copy; HEAD stays where it is
+    harness.repo.commit_file("two.txt", "changed", "change two");
+    harness.act(Action::Refresh);
+    let head = harness.repo.repo.head().unwrap().target();
+    harness.select_commit(picks[1]);
+    harness.act_all([Action::CherryPick, Action::Confirm]);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::Conflict { files, .. } if files == &["two.txt"]
+    ));
+    assert_eq!(harness.repo.repo.head().unwrap().target(), head);
+    assert_eq!(summary(&harness), "change two");
+
+    harness.act_all([Action::AbortMerge, Action::Confirm]);
+    harness.act(Action::JumpToHead);
+    harness.act(Action::CherryPick);
+    assert_eq!(
+        harness.app.get_message(),
+        Some("Cannot cherry-pick HEAD onto itself")
+    );
+}
+
+#[test]
+fn test_cherry_picked_commits_link_back_to_their_originals() {
+    let (mut harness, picks) = visual_repo();
+
+    harness.select_commit(picks[1]);
+    harness.act(Action::ToggleVisual);
+    harness.act_all([Action::CherryPick, Action::Confirm]);
+    let head = harness.repo.repo.head().unwrap().target().unwrap();
+    assert_eq!(harness.app.provenance.origin_of(head), Some(picks[1]));
+
+    harness.select_commit(head);
+    let short = &picks[1].to_string()[..7];
+    assert!(harness.buffer_contains(&format!("(from {})", short)));
+    assert!(harness.buffer_contains("↻"));
+
+    // % jumps to the original and back
+    harness.act(Action::JumpToProvenance);
+    assert_eq!(harness.selected_commit_oid(), Some(picks[1]));
+    harness.act(Action::JumpToProvenance);
+    assert_eq!(harness.selected_commit_oid(), Some(head));
+
+    harness.select_commit(picks[0]);
+    harness.act(Action::JumpToProvenance);
+    assert_eq!(harness.selected_commit_oid(), Some(picks[0]));
+    assert_eq!(
+        harness.app.get_message(),
+        Some("Not cherry-picked or rebased in this session")
+    );
+}
+
+#[test]
+fn test_visual_mode_exports_patches() {
+    let (mut harness, picks) = visual_repo();
+
+    harness.select_commit(picks[1]);
+    harness.act(Action::ToggleVisual);
+    harness.select_commit(picks[0]);
+    harness.act(Action::ExportPatches);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+
+    let dir = harness.repo.path().join("patches");
+    let first = std::fs::read_to_string(dir.join("0001-one.patch")).unwrap();
+    assert!(first.contains("Subject: [PATCH 1/2] one"), "{}", first);
+    assert!(dir.join("0002-two.patch").exists());
+
+    harness.act(Action::ToggleVisual);
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+}
+
+#[test]
+fn test_refresh_failure_keeps_the_previous_view() {
+    let (test_repo, _, tip) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+    let commits = harness.app.commits.len();
+
+    // A ref to a commit that does not exist fails every read, not just one
+    let broken = harness.repo.repo.path().join("refs/heads/broken");
+    std::fs::write(&broken, format!("{}\n", "1".repeat(40))).unwrap();
+
+    assert!(harness.app.refresh(true).is_err());
+    assert_eq!(harness.app.commits.len(), commits);
+    assert_eq!(harness.selected_commit_oid(), Some(tip));
+
+    std::fs::remove_file(&broken).unwrap();
+    harness.app.refresh(true).unwrap();
+    assert_eq!(harness.app.commits.len(), commits);
+}
+
+#[test]
+fn test_unborn_head_shown_in_status_bar() {
+    let test_repo = TestRepo::new();
+    test_repo.repo.set_head("refs/heads/trunk").unwrap();
+    let mut harness = AppHarness::new(test_repo);
+
+    assert!(harness.buffer_contains("(unborn) trunk"));
+    harness.act(Action::Refresh);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(harness.buffer_contains("(unborn) trunk"));
+}
+
+#[test]
+fn test_available_actions_follow_selected_row() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    test_repo.branch("feature", base);
+    let remote = test_repo.commit_file("b.txt", "b", "remote work");
+    test_repo
+        .repo
+        .reference("refs/remotes/origin/topic", remote, true, "test")
+        .unwrap();
+    let plain = test_repo.commit_file("c.txt", "c", "unlabelled");
+    test_repo.commit_file("d.txt", "d", "main work");
+    let mut harness = AppHarness::new(test_repo);
+    let keys = |harness: &AppHarness| harness.app.available_actions();
+
+    harness.select_commit(base);
+    let actions = keys(&harness);
+    assert_eq!(actions[..2], [("c", "checkout"), ("d", "delete")]);
+    assert!(harness.buffer_contains("delete"));
+
+    harness.select_commit(remote);
+    let actions = keys(&harness);
+    assert_eq!(actions[0], ("c", "checkout (creates local)"));
+    assert!(!actions.contains(&("d", "delete")));
+
+    harness.select_commit(plain);
+    let actions = keys(&harness);
+    assert_eq!(
+        actions[..2],
+        [("c", "checkout (detached)"), ("b", "branch")]
+    );
+
+    // Deleting is refused for the HEAD branch, so it is not offered there
+    harness.act(Action::GoToTop);
+    assert!(!keys(&harness).contains(&("d", "delete")));
+}
+
+#[test]
+fn test_visual_mode_squashes_span_ending_at_head() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let one = test_repo.commit_file("b.txt", "b", "one");
+    let two = test_repo.commit_file("c.txt", "c", "two");
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(two);
+    harness.act(Action::ToggleVisual);
+    harness.select_commit(one);
+    harness.act(Action::Squash);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::Input { input, .. } if input == "one"
+    ));
+    harness.act(Action::InputBackspace);
+    harness.act(Action::InputBackspace);
+    harness.act(Action::InputBackspace);
+    harness.type_text("both");
+    harness.act(Action::Confirm);
+
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    let head = harness.repo.repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.message(), Some("both\n\none\n\ntwo\n"));
+    assert_eq!(head.parent_id(0).unwrap(), base);
+}
+
+#[test]
+fn test_commit_menu_creates_fixup_for_selected_commit() {
+    let test_repo = TestRepo::new();
+    let target = test_repo.commit_file("a.txt", "a", "Add a");
+    let head = test_repo.commit_file("b.txt", "b", "Add b");
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(target);
+    harness.act(Action::ShowCommitMenu);
+    assert!(matches!(harness.current_mode(), AppMode::CommitMenu { .. }));
+    // Nothing staged yet
+    harness.act(Action::CommitFixup);
+    assert!(matches!(harness.current_mode(), AppMode::Error { .. }));
+    harness.act(Action::Cancel);
+
+    harness.repo.stage_file("a.txt", "fixed");
+    harness.act_all([Action::ShowCommitMenu, Action::CommitFixup]);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    let commit = harness.repo.repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(commit.message(), Some("fixup! Add a"));
+    assert_eq!(commit.parent_id(0).unwrap(), head);
+}
+
+#[test]
+fn test_date_range_filter_shows_commits_in_range() {
+    let test_repo = TestRepo::new();
+    let day = 24 * 60 * 60;
+    let start = 1_700_000_000;
+    let old = test_repo.commit_at("old", &[], start);
+    let middle = test_repo.commit_at("middle", &[old], start + 10 * day);
+    let new = test_repo.commit_at("new", &[middle], start + 20 * day);
+    test_repo
+        .repo
+        .reference("refs/heads/main", new, true, "new")
+        .unwrap();
+    test_repo.repo.set_head("refs/heads/main").unwrap();
+    let mut harness = AppHarness::new(test_repo);
+    let date = |time: i64| {
+        chrono::DateTime::from_timestamp(time, 0)
+            .unwrap()
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d")
+            .to_string()
+    };
+
+    harness.act(Action::FilterDates);
+    let range = format!("{}..{}", date(start + 5 * day), date(start + 15 * day));
+    harness.type_text(&range);
+    harness.act(Action::Confirm);
+
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(harness.buffer_contains(&format!("[dates {}", range)));
+    assert!(harness.buffer_contains("middle"));
+    assert!(!harness.buffer_contains(" old"));
+    assert!(!harness.buffer_contains(" new"));
+
+    // Unparsable input is reported and keeps the current filter
+    harness.act(Action::FilterDates);
+    harness.type_text("x");
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Error { .. }));
+    harness.act_all([Action::Cancel, Action::Cancel]);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(harness.buffer_contains("[dates"));
+
+    harness.act(Action::ClearDateRange);
+    assert!(!harness.buffer_contains("[dates"));
+    assert!(harness.buffer_contains(" old"));
+}
+
+/// Repository whose origin does not exist, opened with `fetch_on_start`
+fn offline_harness() -> AppHarness {
+    let test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "a", "base");
+    let missing = test_repo.path().join("missing-remote");
+    test_repo
+        .repo
+        .remote("origin", missing.to_str().unwrap())
+        .unwrap();
+    let mut config = Config::default();
+    config.refresh.fetch_on_start = true;
+    AppHarness::with_config(test_repo, config)
+}
+
+fn wait_for_fetch(harness: &mut AppHarness) {
+    for _ in 0..500 {
+        harness.app.update_fetch_status();
+        if !harness.app.is_fetching() {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    panic!("fetch did not finish");
+}
+
+#[test]
+fn test_failed_startup_fetch_leaves_only_a_warning() {
+    let mut harness = offline_harness();
+    harness.app.start_startup_fetch();
+    assert!(harness.app.is_startup_fetch());
+    assert!(harness.buffer_contains("fetching"));
+
+    wait_for_fetch(&mut harness);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(harness.app.message_is_warning());
+    assert!(harness
+        .app
+        .get_message()
+        .is_some_and(|m| m.starts_with("Showing local state")));
+}
+
+#[test]
+fn test_manual_fetch_joins_running_startup_fetch() {
+    let mut harness = offline_harness();
+    harness.app.start_startup_fetch();
+    harness.act(Action::Fetch);
+    assert!(!harness.app.is_startup_fetch());
+    assert_eq!(harness.app.get_message(), Some("Fetching from origin..."));
+
+    // The joined fetch is reported like a manual one
+    wait_for_fetch(&mut harness);
+    assert!(matches!(harness.current_mode(), AppMode::Error { .. }));
+}
+
+#[test]
+fn test_manual_fetch_shows_new_remote_commits() {
+    let (origin, local) = repo_with_slow_origin(0);
+    local.commit_file("b.txt", "b", "local work");
+    let remote_tip = origin.commit_file("c.txt", "c", "remote work");
+    let remote_branch = format!("origin/{}", origin.head_branch());
+    let mut harness = AppHarness::new(local);
+    assert!(harness.app.graph_layout.row_of(remote_tip).is_none());
+
+    harness.act(Action::Fetch);
+    assert!(harness.buffer_contains("Fetching from origin..."));
+    wait_for_fetch(&mut harness);
+
+    assert_eq!(harness.app.get_message(), Some("Fetched from origin"));
+    assert!(harness.app.graph_layout.row_of(remote_tip).is_some());
+    assert!(harness
+        .app
+        .branch_positions
+        .iter()
+        .any(|(_, name)| *name == remote_branch));
+}
+
+/// Local repository whose HEAD branch tracks the same branch of an origin without delay
+fn tracking_clone() -> (TestRepo, TestRepo) {
+    let (origin, local) = repo_with_slow_origin(0);
+    let name = origin.head_branch();
+    fetch_origin(
+        local.path().to_str().unwrap(),
+        std::time::Duration::from_secs(30),
+        &AtomicBool::new(false),
+    )
+    .unwrap();
+    let tip = origin.repo.head().unwrap().target().unwrap();
+    local.branch(&name, tip);
+    local.checkout(&name);
+    local
+        .repo
+        .find_branch(&name, BranchType::Local)
+        .unwrap()
+        .set_upstream(Some(&format!("origin/{}", name)))
+        .unwrap();
+    (origin, local)
+}
+
+#[test]
+fn test_pull_fast_forwards_to_the_fetched_upstream() {
+    let (origin, local) = tracking_clone();
+    let remote_tip = origin.commit_file("b.txt", "b", "remote work");
+    let mut harness = AppHarness::new(local);
+
+    harness.act(Action::Pull);
+    assert_eq!(harness.app.get_message(), Some("Pulling from origin..."));
+    wait_for_fetch(&mut harness);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.app.head_oid, Some(remote_tip));
+    assert!(harness.repo.path().join("b.txt").exists());
+
+    harness.act(Action::Pull);
+    wait_for_fetch(&mut harness);
+    assert_eq!(harness.app.get_message(), Some("Already up to date"));
+}
+
+#[test]
+fn test_pull_offers_a_merge_when_diverged() {
+    let (origin, local) = tracking_clone();
+    origin.commit_file("b.txt", "b", "remote work");
+    let local_tip = local.commit_file("c.txt", "c", "local work");
+    let mut harness = AppHarness::new(local);
+
+    harness.act(Action::Pull);
+    wait_for_fetch(&mut harness);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::Confirm { message, .. } if message.contains("diverged")
+    ));
+    assert_eq!(harness.app.head_oid, Some(local_tip));
+
+    harness.act(Action::Confirm);
+    let head = harness.repo.repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.parent_count(), 2);
+    assert_eq!(head.parent_id(0).unwrap(), local_tip);
+}
+
+#[test]
+fn test_pull_needs_an_upstream() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+    harness.act(Action::Pull);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::Error { message } if message.ends_with("has no upstream")
+    ));
+    assert!(!harness.app.is_fetching());
+}
+
+#[test]
+fn test_scrolling_to_the_bottom_loads_the_next_page() {
+    let mut harness = AppHarness::new(long_history_repo(COMMIT_PAGE_SIZE + 20));
+    assert_eq!(harness.app.commits.len(), COMMIT_PAGE_SIZE);
+
+    // Jumping to the bottom shows what is loaded; scrolling there loads more
+    harness.act(Action::GoToBottom);
+    assert_eq!(harness.app.commits.len(), COMMIT_PAGE_SIZE);
+    harness.act(Action::MoveUp);
+    let row = harness.app.graph_list_state.selected();
+    harness.act(Action::MoveDown);
+    assert_eq!(harness.app.commits.len(), COMMIT_PAGE_SIZE + 21);
+    assert_eq!(harness.app.graph_list_state.selected(), row.map(|r| r + 1));
+
+    // The whole history is loaded, so the bottom stays put
+    harness.act(Action::GoToBottom);
+    harness.act(Action::MoveDown);
+    assert_eq!(harness.app.commits.len(), COMMIT_PAGE_SIZE + 21);
+}
+
+/// Repository whose HEAD branch has `count` commits after the root, in a single line
+fn long_history_repo(count: usize) -> TestRepo {
+    let test_repo = TestRepo::new();
+    let mut tip = test_repo.commit_file("a.txt", "a", "root");
+    for i in 0..count {
+        tip = test_repo.commit_at(&format!("commit {}", i), &[tip], 1_700_000_000 + i as i64);
+    }
+    test_repo
+        .repo
+        .reference(
+            &format!("refs/heads/{}", test_repo.head_branch()),
+            tip,
+            true,
+            "history",
+        )
+        .unwrap();
+    test_repo
+}
+
+/// App loading `page_size` commits at a time, without the page load debounce
+fn paged_harness(test_repo: TestRepo, page_size: usize) -> AppHarness {
+    let mut config = Config::default();
+    config.ui.commit_page_size = page_size;
+    let mut harness = AppHarness::with_config(test_repo, config);
+    harness
+        .app
+        .set_load_more_debounce(std::time::Duration::ZERO);
+    harness
+}
+
+#[test]
+fn test_successive_page_loads_only_append_rows() {
+    let mut harness = paged_harness(long_history_repo(2 * 20 + 20), 20);
+    let rows = |harness: &AppHarness| -> Vec<Oid> {
+        harness
+            .app
+            .graph_layout
+            .nodes
+            .iter()
+            .filter_map(|n| n.commit.as_ref().map(|c| c.oid))
+            .collect()
+    };
+
+    let mut previous = rows(&harness);
+    assert_eq!(previous.len(), 20);
+    for _ in 0..2 {
+        harness.act(Action::GoToBottom);
+        harness.act(Action::MoveUp);
+        harness.act(Action::MoveDown);
+        let current = rows(&harness);
+        assert!(current.len() > previous.len());
+        assert_eq!(current[..previous.len()], previous[..]);
+        let unique: std::collections::HashSet<&Oid> = current.iter().collect();
+        assert_eq!(unique.len(), current.len());
+        previous = current;
+    }
+    assert_eq!(previous.len(), 3 * 20);
+}
+
+#[test]
+fn test_refresh_goes_back_to_the_first_page() {
+    let mut harness = paged_harness(long_history_repo(50), 20);
+    harness.act(Action::GoToBottom);
+    harness.act(Action::MoveDown);
+    assert_eq!(harness.app.commits.len(), 40);
+
+    harness.act(Action::Refresh);
+    assert_eq!(harness.app.commits.len(), 20);
+    assert!(harness.app.has_more_commits());
+    let selected = harness.app.graph_list_state.selected().unwrap();
+    assert!(selected < harness.app.graph_layout.nodes.len());
+}
+
+/// App on a repository whose fetch takes `delay_secs`, with the fetch already running
+/// Also returns the origin, which must outlive the fetch
+fn harness_fetching(delay_secs: u32) -> (TestRepo, AppHarness) {
+    let (origin, local) = repo_with_slow_origin(delay_secs);
+    let mut harness = AppHarness::new(local);
+    harness.act(Action::Fetch);
+    assert!(harness.app.is_fetching());
+    (origin, harness)
+}
+
+/// Run the main loop's fetch polling until the app quits
+fn wait_for_quit(harness: &mut AppHarness) {
+    for _ in 0..500 {
+        harness.app.update_fetch_status();
+        if harness.app.should_quit {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    panic!("app did not quit");
+}
+
+#[test]
+fn test_quit_during_fetch_waits_for_it() {
+    let (_origin, mut harness) = harness_fetching(1);
+    harness.act(Action::Quit);
+    assert!(!harness.app.should_quit);
+    assert!(harness.buffer_contains("A fetch from origin is still running"));
+
+    harness.act(Action::WaitAndQuit);
+    assert!(harness.buffer_contains("Waiting for it to finish"));
+    wait_for_quit(&mut harness);
+    assert_eq!(
+        harness.app.exit_message.as_deref(),
+        Some("Fetched from origin")
+    );
+}
+
+#[test]
+fn test_quit_during_fetch_can_abort_it() {
+    let (_origin, mut harness) = harness_fetching(30);
+    harness.act(Action::Quit);
+    harness.act(Action::AbortAndQuit);
+    // Cancellation is noticed within the fetch's polling interval, not after the 30s sleep
+    wait_for_quit(&mut harness);
+    assert!(!harness.app.is_fetching());
+}
+
+#[test]
+fn test_quit_during_fetch_can_be_cancelled() {
+    let (_origin, mut harness) = harness_fetching(30);
+    harness.act(Action::Quit);
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(!harness.app.should_quit);
+    assert!(harness.app.is_fetching());
+    // Abort the fetch so the test does not leave it running
+    harness.act(Action::Quit);
+    harness.act(Action::AbortAndQuit);
+    wait_for_quit(&mut harness);
+}
+
+#[test]
+fn test_back_closes_one_level_then_quits() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act_all([Action::ToggleVisual, Action::ToggleHelp]);
+    harness.act(Action::Back);
+    assert!(matches!(harness.current_mode(), AppMode::Visual { .. }));
+    harness.act(Action::Back);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(!harness.app.should_quit);
+    harness.act(Action::Back);
+    assert!(harness.app.should_quit);
+}
+
+#[test]
+fn test_back_can_ask_before_quitting() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut config = Config::default();
+    config.keys.q = QKey::ConfirmQuit;
+    let mut harness = AppHarness::with_config(test_repo, config);
+
+    harness.act(Action::Back);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+    // q in the dialog backs out of it
+    harness.act(Action::Back);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(!harness.app.should_quit);
+
+    harness.act_all([Action::Back, Action::Confirm]);
+    assert!(harness.app.should_quit);
+}
+
+#[test]
+fn test_file_annotations_show_previous_change_when_toggled() {
+    let test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "1", "add a");
+    test_repo.commit_file("a.txt", "2", "change a");
+    let mut harness = AppHarness::new(test_repo);
+    let path = std::path::Path::new("a.txt");
+
+    // Off by default: nothing is computed
+    harness.render();
+    assert!(!harness.app.file_annotations());
+
+    harness.act(Action::ToggleFileAnnotations);
+    let mut annotated = false;
+    for _ in 0..500 {
+        harness.render();
+        if let Some(touch) = harness.app.last_touch(path) {
+            assert_eq!(touch.map(|t| t.author.as_str()), Some("test"));
+            annotated = true;
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(annotated, "annotation did not arrive");
+    assert!(harness.buffer_contains("last touched by test"));
+}
+
+#[test]
+fn test_error_over_confirm_returns_to_the_pending_dialog() {
+    let (test_repo, base, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+    harness.select_commit(base);
+    harness.act(Action::DeleteBranch);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+
+    // e.g. a manual fetch failing while the dialog is open
+    harness.app.show_error("Fetch failed".to_string());
+    assert!(matches!(harness.current_mode(), AppMode::Error { .. }));
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+
+    harness.act(Action::Confirm);
+    assert!(!branch_exists(&harness, "feature"));
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+}
+
+#[test]
+fn test_help_over_search_keeps_the_query() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+    harness.act(Action::Search);
+    harness.type_text("feat");
+
+    harness.act(Action::ToggleHelp);
+    assert!(matches!(harness.current_mode(), AppMode::Help));
+    // The search stays beneath the help (and is drawn under it)
+    assert!(matches!(harness.app.mode_stack(), [AppMode::Input { .. }]));
+
+    harness.act(Action::Cancel);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::Input { input, .. } if input == "feat"
+    ));
+    harness.act(Action::Confirm);
+    assert_eq!(harness.app.selected_branch_name(), Some("feature"));
+}
+
+#[test]
+fn test_second_error_closes_back_to_the_first() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+    harness.app.show_error("first".to_string());
+    harness.app.show_error("second".to_string());
+
+    harness.act(Action::Cancel);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::Error { message } if message == "first"
+    ));
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+}
+
+#[test]
+fn test_dialogs_opened_from_visual_mode_return_to_it_when_cancelled() {
+    let (test_repo, _, _) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+    harness.act_all([Action::ToggleVisual, Action::MoveDown, Action::CherryPick]);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Visual { .. }));
+}
+
+/// `main` one commit past a base and `feature` `len` commits past it, checked out on `main`
+/// Feature commits are the newest, so a short history window ends before the base
+fn long_branch_repo(len: i64) -> TestRepo {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_at("base", &[], 1_000);
+    let main = test_repo.commit_at("main work", &[base], 2_000);
+    let mut tip = base;
+    for i in 0..len {
+        tip = test_repo.commit_at(&format!("feature {}", i), &[tip], 3_000 + i);
+    }
+    test_repo.branch("main", main);
+    test_repo.branch("feature", tip);
+    test_repo.checkout("main");
+    test_repo
+}
+
+fn confirm_message(harness: &AppHarness) -> String {
+    match harness.current_mode() {
+        AppMode::Confirm { message, .. } => message.clone(),
+        _ => panic!("expected a confirm dialog"),
+    }
+}
+
+#[test]
+fn test_merge_confirm_loads_history_down_to_the_merge_base() {
+    let test_repo = long_branch_repo(5);
+    let tip = test_repo.repo.revparse_single("feature").unwrap().id();
+    let mut harness = AppHarness::new(test_repo);
+    harness.act(Action::CommandPalette);
+    harness.type_text("limit 3");
+    harness.act(Action::Confirm);
+    harness.select_commit(tip);
+
+    harness.act(Action::Merge);
+    assert_eq!(
+        confirm_message(&harness),
+        "Merge 'feature' (5 commits) into current branch?"
+    );
+    // The base is on screen now, below the dialog
+    harness.act(Action::Cancel);
+    assert!(harness.buffer_contains("base"));
+    assert_eq!(harness.selected_commit_oid(), Some(tip));
+}
+
+#[test]
+fn test_merge_confirm_warns_when_the_merge_base_stays_out_of_view() {
+    let test_repo = long_branch_repo(15);
+    let tip = test_repo.repo.revparse_single("feature").unwrap().id();
+    let mut harness = paged_harness(test_repo, 3);
+    harness.select_commit(tip);
+
+    harness.act(Action::Merge);
+    assert_eq!(
+        confirm_message(&harness),
+        "Merge 'feature' (15 commits) into current branch?\n\
+         merge base is below the loaded history, so the graph does not show it"
+    );
+    assert!(harness.buffer_contains("merge base is below"));
+    let commit_rows = |harness: &AppHarness| {
+        harness
+            .app
+            .graph_layout
+            .nodes
+            .iter()
+            .filter(|n| n.commit.is_some())
+            .count()
+    };
+    // The window stops at four pages
+    assert_eq!(commit_rows(&harness), 12);
+
+    // Later previews do not grow it any further
+    harness.act(Action::Cancel);
+    harness.act(Action::Rebase);
+    assert_eq!(
+        confirm_message(&harness),
+        "Rebase current branch (1 commit) onto 'feature'?\n\
+         merge base is below the loaded history, so the graph does not show it"
+    );
+    assert_eq!(commit_rows(&harness), 12);
+}
+
+#[test]
+fn test_branch_ops_relabel_rows_without_reloading_history() {
+    let (test_repo, base, tip) = two_branch_repo();
+    let mut harness = AppHarness::new(test_repo);
+    // Not loaded until history is walked again
+    let unseen = harness.repo.commit_at("unseen work", &[tip], 1_000);
+    harness.repo.branch("other", unseen);
+
+    harness.select_commit(base);
+    harness.act(Action::CommandPalette);
+    harness.type_text("branch topic");
+    harness.act(Action::Confirm);
+    assert!(harness.buffer_contains("topic"));
+    assert!(!harness.buffer_contains("unseen work"));
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+
+    harness.act_all([Action::BranchRight, Action::DeleteBranch, Action::Confirm]);
+    assert_eq!(
+        [
+            branch_exists(&harness, "feature"),
+            branch_exists(&harness, "topic")
+        ],
+        [true, false]
+    );
+    assert!(!harness.buffer_contains("unseen work"));
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+
+    harness.act(Action::Refresh);
+    assert!(harness.buffer_contains("unseen work"));
+}
+
+#[test]
+fn test_deleting_the_only_branch_to_a_commit_reloads_history() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let side = test_repo.commit_at("side only", &[base], 1_000);
+    test_repo.branch("side", side);
+    let mut harness = AppHarness::new(test_repo);
+    assert!(harness.buffer_contains("side only"));
+
+    harness.select_commit(side);
+    harness.act_all([Action::DeleteBranch, Action::Confirm]);
+    assert!(!branch_exists(&harness, "side"));
+    assert!(!harness.buffer_contains("side only"));
+}
+
+#[test]
+fn test_diff_view_shows_and_scrolls_the_selected_commit_patch() {
+    let test_repo = TestRepo::new();
+    let lines: String = (1..=40).map(|i| format!("line {}\n", i)).collect();
+    let oid = test_repo.commit_file("a.txt", &lines, "add lines");
+    let mut harness = AppHarness::new(test_repo);
+    harness.select_commit(oid);
+
+    harness.act(Action::OpenDiff);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::DiffView {
+            scroll_offset: 0,
+            ..
+        }
+    ));
+    assert!(harness.buffer_contains("+line 1"));
+    assert!(!harness.buffer_contains("Changed Files"));
+
+    harness.act_all(std::iter::repeat_n(Action::DiffScrollDown, 10));
+    assert!(!harness.buffer_contains("+line 1 "));
+    assert!(harness.buffer_contains("+line 10"));
+    // Stops at the last line
+    harness.act_all(std::iter::repeat_n(Action::DiffScrollDown, 100));
+    let AppMode::DiffView { scroll_offset, .. } = harness.current_mode() else {
+        panic!("expected the diff view");
+    };
+    assert_eq!(*scroll_offset, 45);
+    harness.act(Action::DiffScrollUp);
+    assert!(harness.buffer_contains("+line 40"));
+
+    harness.act(Action::OpenDiff);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert!(harness.buffer_contains("Changed Files"));
+}
+
+#[test]
+fn test_merge_and_rebase_report_their_result() {
+    let (test_repo, _, _) = two_branch_repo();
+    let head = test_repo.head_branch();
+    test_repo.checkout("feature");
+    let feature = test_repo.commit_file("c.txt", "c", "feature work");
+    test_repo.checkout(&head);
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(feature);
+    harness.act_all([Action::Rebase, Action::Confirm]);
+    assert_eq!(
+        harness.app.get_message(),
+        Some("Rebased 1 commit onto 'feature'")
+    );
+
+    harness.select_commit(feature);
+    harness.act_all([Action::Merge, Action::Confirm]);
+    assert_eq!(
+        harness.app.get_message(),
+        Some("Already up to date with 'feature'")
+    );
+}
+
+#[test]
+fn test_merge_conflict_opens_the_conflict_view() {
+    let (test_repo, _, _) = two_branch_repo();
+    let head = test_repo.head_branch();
+    test_repo.checkout("feature");
+    let feature = test_repo.commit_file("a.txt", "feature", "change a on feature");
+    test_repo.checkout(&head);
+    test_repo.commit_file("a.txt", "main", "change a on main");
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(feature);
+    harness.act_all([Action::Merge, Action::Confirm]);
+    let AppMode::Conflict { files, .. } = harness.current_mode() else {
+        panic!("expected the conflict view");
+    };
+    assert_eq!(files, &["a.txt"]);
+    assert!(harness.buffer_contains(" CONFLICT "));
+    assert!(harness.buffer_contains("a.txt"));
+
+    // Closing the view leaves the merge to resolve; a refresh lists the files again
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.repo.repo.state(), git2::RepositoryState::Merge);
+    harness.act(Action::Refresh);
+    assert!(matches!(harness.current_mode(), AppMode::Conflict { .. }));
+
+    // Aborting resets to HEAD, so it is confirmed first
+    harness.act(Action::AbortMerge);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+    harness.act(Action::Cancel);
+    assert!(matches!(harness.current_mode(), AppMode::Conflict { .. }));
+    assert_eq!(harness.repo.repo.state(), git2::RepositoryState::Merge);
+
+    harness.act_all([Action::AbortMerge, Action::Confirm]);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.app.get_message(), Some("Merge aborted"));
+    assert_eq!(harness.repo.repo.state(), git2::RepositoryState::Clean);
+}
+
+#[test]
+fn test_conflicts_left_by_an_earlier_session_open_the_conflict_view() {
+    let (test_repo, _, _) = two_branch_repo();
+    let head = test_repo.head_branch();
+    test_repo.checkout("feature");
+    test_repo.commit_file("a.txt", "feature", "change a on feature");
+    test_repo.checkout(&head);
+    test_repo.commit_file("a.txt", "main", "change a on main");
+    assert!(merge_branch(&test_repo.repo, "feature").is_err());
+
+    let harness = AppHarness::new(test_repo);
+    let AppMode::Conflict { op, files, .. } = harness.current_mode() else {
+        panic!("expected the conflict view");
+    };
+    assert_eq!(*op, ConflictOp::Merge);
+    assert_eq!(files, &["a.txt"]);
+}
+
+#[test]
+fn test_cherry_pick_conflict_opens_the_conflict_view() {
+    let (test_repo, _, _) = two_branch_repo();
+    let head = test_repo.head_branch();
+    test_repo.checkout("feature");
+    let feature = test_repo.commit_file("a.txt", "feature", "change a on feature");
+    test_repo.checkout(&head);
+    test_repo.commit_file("a.txt", "main", "change a on main");
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(feature);
+    harness.act_all([Action::CherryPick, Action::Confirm]);
+    let AppMode::Conflict { op, files, .. } = harness.current_mode() else {
+        panic!("expected the conflict view");
+    };
+    assert_eq!(*op, ConflictOp::CherryPick);
+    assert_eq!(files, &["a.txt"]);
+    assert!(harness.buffer_contains("Cherry-pick stopped with conflicts"));
+    assert_eq!(harness.repo.repo.state(), git2::RepositoryState::CherryPick);
+
+    harness.act_all([Action::AbortMerge, Action::Confirm]);
+    assert_eq!(harness.app.get_message(), Some("Cherry-pick aborted"));
+    assert_eq!(harness.repo.repo.state(), git2::RepositoryState::Clean);
+}
+
+#[test]
+fn test_selection_passes_over_elision_rows() {
+    // Hiding merges elides `merge` between `tip` and `base`
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_at("base", &[], 1_000);
+    let side = test_repo.commit_at("side", &[base], 2_000);
+    let merge = test_repo.commit_at("merge", &[base, side], 3_000);
+    let tip = test_repo.commit_at("tip", &[merge], 4_000);
+    test_repo.branch("main", tip);
+    test_repo.checkout("main");
+    let mut harness = AppHarness::new(test_repo);
+    harness.act(Action::ToggleHideMerges);
+    let elision = harness
+        .app
+        .graph_layout
+        .nodes
+        .iter()
+        .position(|n| n.elided.is_some())
+        .expect("an elision row");
+
+    let selected = |harness: &AppHarness| harness.app.graph_list_state.selected().unwrap();
+    harness.select_commit(tip);
+    while selected(&harness) + 1 < elision {
+        harness.act(Action::MoveDown);
+    }
+    harness.act(Action::MoveDown);
+    assert_eq!(selected(&harness), elision + 1);
+    harness.act(Action::MoveUp);
+    assert_eq!(selected(&harness), elision - 1);
+}
+
+#[test]
+fn test_branch_list_shows_tip_subjects() {
+    let (test_repo, base, _) = two_branch_repo();
+    let topic = test_repo.commit_at("tidy the parser", &[base], 1_000);
+    test_repo.branch("topic", topic);
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::Search);
+    assert_eq!(harness.app.subject_layout(), SubjectLayout::Inline);
+    assert!(harness.buffer_contains("topic  tidy the parser"));
+    assert!(harness.buffer_contains("feature  base"));
+
+    // The two-line layout puts the subject on its own, indented row
+    harness.act(Action::ToggleSubjectLayout);
+    assert_eq!(harness.app.subject_layout(), SubjectLayout::TwoLine);
+    assert!(!harness.buffer_contains("topic  tidy the parser"));
+    assert!(harness.buffer_contains("│    tidy the parser"));
+    // and is remembered with the other view settings
+    harness.app.flush_view_settings().unwrap();
+    let harness = AppHarness::new(harness.repo);
+    assert_eq!(harness.app.subject_layout(), SubjectLayout::TwoLine);
+}
+
+#[test]
+fn test_stash_list_applies_and_drops_stashes() {
+    let (test_repo, _, _) = two_branch_repo();
+    test_repo.write_file("a.txt", "dirty");
+    let mut harness = AppHarness::new(test_repo);
+    assert_eq!(harness.app.uncommitted_count(), Some(1));
+
+    harness.act(Action::Stash);
+    assert_eq!(
+        harness.app.get_message(),
+        Some("Stashed local changes as stash@{0}")
+    );
+    assert_eq!(harness.app.uncommitted_count(), None);
+    harness.act(Action::Stash);
+    assert!(
+        matches!(harness.current_mode(), AppMode::Error { message } if message == "No local changes to stash")
+    );
+    harness.act(Action::Cancel);
+
+    harness.act(Action::ShowStashes);
+    assert!(matches!(harness.current_mode(), AppMode::StashList { .. }));
+    assert!(harness.buffer_contains("Stashes (1)"));
+    assert!(harness.buffer_contains("stash@{0}"));
+    assert!(harness.buffer_contains("WIP on"));
+
+    // Applying keeps the stash and brings the changes back into the graph
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.app.get_message(), Some("Applied stash@{0}"));
+    assert_eq!(harness.app.uncommitted_count(), Some(1));
+
+    harness.act(Action::ShowStashes);
+    harness.act(Action::DropStash);
+    assert!(matches!(harness.current_mode(), AppMode::Confirm { .. }));
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.app.get_message(), Some("Dropped stash@{0}"));
+    harness.act(Action::ShowStashes);
+    assert_eq!(harness.app.get_message(), Some("No stashes"));
+}
+
+#[test]
+fn test_conflicting_stash_pop_shows_an_error_and_keeps_the_stash() {
+    let (test_repo, _, _) = two_branch_repo();
+    test_repo.write_file("a.txt", "stashed");
+    let mut harness = AppHarness::new(test_repo);
+    harness.act(Action::Stash);
+    harness.repo.commit_file("a.txt", "committed", "change a");
+    harness.act(Action::Refresh);
+
+    harness.act(Action::ShowStashes);
+    harness.act(Action::StashPop);
+    let AppMode::Error { message } = harness.current_mode() else {
+        panic!("expected the error popup");
+    };
+    assert!(message.contains("left conflicts"), "{}", message);
+    // The conflict is left in the working tree, and the stash is still listed
+    let a = std::fs::read_to_string(harness.repo.path().join("a.txt")).unwrap();
+    assert!(a.contains("<<<<<<<"), "{}", a);
+    harness.act(Action::Cancel);
+    harness.act(Action::ShowStashes);
+    assert!(harness.buffer_contains("Stashes (1)"));
+}
+
+#[test]
+fn test_rebase_offers_to_move_the_branches_of_the_stack() {
+    let (test_repo, main) = stacked_branches_repo();
+    let tip = |repo: &git2::Repository, name: &str| repo.revparse_single(name).unwrap().id();
+    let main_tip = tip(&test_repo.repo, &main);
+    let old_bottom = tip(&test_repo.repo, "bottom");
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.select_commit(main_tip);
+    harness.act_all([Action::Rebase, Action::Confirm]);
+    assert!(matches!(
+        harness.current_mode(),
+        AppMode::RetargetBranches { .. }
+    ));
+    assert!(harness.buffer_contains("Move branches onto the rebased commits?"));
+    assert!(harness.buffer_contains("[x] bottom"));
+    assert!(harness.buffer_contains("[x] middle"));
+
+    // Leave bottom where it is
+    harness.act(Action::ToggleChecked);
+    assert!(harness.buffer_contains("[ ] bottom"));
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(
+        harness.app.get_message(),
+        Some("Moved 'middle' onto its rewritten commit")
+    );
+    let repo = &harness.repo.repo;
+    let new_top = repo.find_commit(tip(repo, "top")).unwrap();
+    assert_eq!(tip(repo, "middle"), new_top.parent_id(0).unwrap());
+    assert_eq!(tip(repo, "bottom"), old_bottom);
+}
+
+#[test]
+fn test_jump_to_child_picks_among_several_children() {
+    let (test_repo, base, main_tip) = two_branch_repo();
+    test_repo.checkout("feature");
+    let feature_tip = test_repo.commit_file("c.txt", "c", "feature work");
+    let mut harness = AppHarness::new(test_repo);
+
+    // A single child is jumped to directly
+    harness.select_commit(feature_tip);
+    harness.act(Action::JumpToParent);
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+
+    harness.act(Action::JumpToChild);
+    assert!(matches!(harness.current_mode(), AppMode::PickChild { .. }));
+    assert!(harness.buffer_contains(" CHILD "));
+    // Children are listed newest first and the nearest one, the last, is offered first
+    let AppMode::PickChild { children, selected } = harness.current_mode() else {
+        unreachable!();
+    };
+    assert_eq!(children.len(), 2);
+    assert_eq!(*selected, 1);
+    let (above, nearest) = (children[0], children[1]);
+    assert_eq!(
+        harness.app.graph_layout.row_of(nearest).unwrap(),
+        harness.app.graph_layout.row_of(base).unwrap() - 1
+    );
+
+    harness.act(Action::MoveUp);
+    harness.act(Action::Confirm);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.selected_commit_oid(), Some(above));
+
+    harness.act_all([Action::JumpToParent, Action::JumpToChild, Action::Confirm]);
+    assert_eq!(harness.selected_commit_oid(), Some(nearest));
+
+    harness.act_all([Action::JumpToParent, Action::JumpToChild, Action::Cancel]);
+    assert!(matches!(harness.current_mode(), AppMode::Normal));
+    assert_eq!(harness.selected_commit_oid(), Some(base));
+
+    harness.select_commit(main_tip);
+    harness.act(Action::JumpToChild);
+    assert_eq!(harness.app.get_message(), Some("No children loaded"));
+}
+
+#[test]
+fn test_branch_list_sort_keeps_selected_branch() {
+    let (test_repo, base, _) = two_branch_repo();
+    test_repo.branch("zeta", base);
+    test_repo.branch("alpha", base);
+    let mut harness = AppHarness::new(test_repo);
+    let names = |harness: &AppHarness| -> Vec<String> {
+        let app = &harness.app;
+        app.search_results()
+            .iter()
+            .map(|r| app.branch_positions[r.branch_idx].1.clone())
+            .collect()
+    };
+
+    harness.act(Action::Search);
+    let head = harness.repo.head_branch();
+    assert_eq!(names(&harness), [head.as_str(), "alpha", "feature", "zeta"]);
+
+    harness.act_all([Action::SearchSelectDown, Action::CycleBranchSort]);
+    assert_eq!(harness.app.branch_sort(), BranchSort::Date);
+    let app = &harness.app;
+    let selected = &app.search_results()[app.search_selection().unwrap()];
+    assert_eq!(app.branch_positions[selected.branch_idx].1, "alpha");
+}
+
+#[test]
+fn test_branch_list_sections_follow_grouping() {
+    let test_repo = TestRepo::new();
+    let oid = test_repo.commit_file("a.txt", "a", "initial");
+    test_repo.branch("alpha", oid);
+    test_repo
+        .repo
+        .reference("refs/remotes/origin/topic", oid, true, "test")
+        .unwrap();
+    let mut harness = AppHarness::new(test_repo);
+
+    harness.act(Action::Search);
+    assert_eq!(harness.app.search_sections(), ["local", "local", "origin"]);
+    // Typing a query ranks by score, so the headers go away
+    harness.act(Action::InputChar('a'));
+    assert!(harness.app.search_sections().is_empty());
+    harness.act(Action::Cancel);
+
+    harness.app.config.ui.branch_grouping = BranchGrouping::Interleaved;
+    harness.act(Action::Search);
+    assert!(harness.app.search_sections().is_empty());
+    assert_eq!(harness.app.search_results().len(), 3);
+}
@@ -0,0 +1,137 @@
+//! Tests for the bisect session against real Git repositories
+
+mod common;
+
+use common::TestRepo;
+use git2::Oid;
+use keifu::git::bisect::{BisectSession, BisectStep};
+
+/// Create a linear history of `n` commits, returning OIDs oldest first
+fn linear_history(test_repo: &TestRepo, n: usize) -> Vec<Oid> {
+    (0..n)
+        .map(|i| test_repo.commit_file("a.txt", &i.to_string(), &format!("c{}", i)))
+        .collect()
+}
+
+#[test]
+fn test_bisect_finds_first_bad_commit() {
+    let test_repo = TestRepo::new();
+    let oids = linear_history(&test_repo, 10);
+    let first_bad = oids[6];
+
+    let mut session = BisectSession::start(&test_repo.repo).unwrap();
+    assert_eq!(session.next_step(), BisectStep::NeedBad);
+    session.mark_bad(&test_repo.repo, oids[9]).unwrap();
+    assert_eq!(session.next_step(), BisectStep::NeedGood);
+    session.mark_good(&test_repo.repo, oids[0]).unwrap();
+    assert_eq!(session.remaining(), Some(9));
+
+    let mut steps = 0;
+    let found = loop {
+        match session.next_step() {
+            BisectStep::Test(oid) => {
+                let idx = oids.iter().position(|o| *o == oid).unwrap();
+                if idx >= 6 {
+                    session.mark_bad(&test_repo.repo, oid).unwrap();
+                } else {
+                    session.mark_good(&test_repo.repo, oid).unwrap();
+                }
+                steps += 1;
+                assert!(steps <= 10, "bisect did not converge");
+            }
+            BisectStep::Found(oid) => break oid,
+            step => panic!("unexpected step {:?}", step),
+        }
+    };
+
+    assert_eq!(found, first_bad);
+    // log2(9) rounded up
+    assert!(steps <= 4, "took {} steps", steps);
+}
+
+#[test]
+fn test_bisect_rejects_bad_ancestor_of_good() {
+    let test_repo = TestRepo::new();
+    let oids = linear_history(&test_repo, 3);
+
+    let mut session = BisectSession::start(&test_repo.repo).unwrap();
+    session.mark_good(&test_repo.repo, oids[2]).unwrap();
+    assert!(session.mark_bad(&test_repo.repo, oids[0]).is_err());
+    assert_eq!(session.next_step(), BisectStep::NeedBad);
+}
+
+#[test]
+fn test_bisect_refuses_repository_already_bisecting() {
+    let test_repo = TestRepo::new();
+    linear_history(&test_repo, 1);
+    std::fs::write(test_repo.repo.path().join("BISECT_LOG"), "").unwrap();
+
+    assert!(BisectSession::start(&test_repo.repo).is_err());
+}
+
+#[test]
+fn test_bisect_reset_restores_branch() {
+    let test_repo = TestRepo::new();
+    let oids = linear_history(&test_repo, 3);
+    let branch = test_repo.head_branch();
+
+    let session = BisectSession::start(&test_repo.repo).unwrap();
+    keifu::git::operations::checkout_commit(&test_repo.repo, oids[0]).unwrap();
+    assert!(test_repo.repo.head_detached().unwrap());
+
+    session.reset(&test_repo.repo).unwrap();
+    assert_eq!(test_repo.head_branch(), branch);
+}
+
+#[test]
+fn test_bisect_midpoint_halves_history_with_merges() {
+    let test_repo = TestRepo::new();
+    let good = test_repo.commit_file("a.txt", "0", "good");
+    let chain = |name: &str, count: usize| -> Vec<Oid> {
+        let mut tip = good;
+        (0..count)
+            .map(|i| {
+                tip = test_repo.commit_at(&format!("{}{}", name, i + 1), &[tip], 1_000 + i as i64);
+                tip
+            })
+            .collect()
+    };
+    let short = chain("a", 3);
+    let long = chain("b", 10);
+    let merge = test_repo.commit_at("merge", &[short[2], long[9]], 2_000);
+
+    let mut session = BisectSession::start(&test_repo.repo).unwrap();
+    session.mark_bad(&test_repo.repo, merge).unwrap();
+    session.mark_good(&test_repo.repo, good).unwrap();
+    assert_eq!(session.remaining(), Some(14));
+    // b7 has 7 of the 14 candidates as ancestors (itself included), splitting them in half
+    assert_eq!(session.next_step(), BisectStep::Test(long[6]));
+}
+
+#[test]
+fn test_bisect_midpoint_counts_shared_ancestors_of_merges_once() {
+    // Five diamonds in a row: each merge joins two sides of the previous merge
+    let test_repo = TestRepo::new();
+    let good = test_repo.commit_file("a.txt", "0", "good");
+    let mut base = good;
+    let mut diamonds = Vec::new();
+    for i in 0..5 {
+        let time = 1_000 + 10 * i;
+        let left = test_repo.commit_at(&format!("left{}", i), &[base], time);
+        let right = test_repo.commit_at(&format!("right{}", i), &[base], time + 1);
+        base = test_repo.commit_at(&format!("merge{}", i), &[left, right], time + 2);
+        diamonds.push((left, right));
+    }
+
+    let mut session = BisectSession::start(&test_repo.repo).unwrap();
+    session.mark_bad(&test_repo.repo, base).unwrap();
+    session.mark_good(&test_repo.repo, good).unwrap();
+    assert_eq!(session.remaining(), Some(15));
+    // Either side of the third diamond has 7 of the 15 candidates as ancestors;
+    // counting the shared ones twice would pick a later merge
+    let (left, right) = diamonds[2];
+    let BisectStep::Test(midpoint) = session.next_step() else {
+        panic!("expected a commit to test");
+    };
+    assert!(midpoint == left || midpoint == right);
+}
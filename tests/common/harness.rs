@@ -0,0 +1,114 @@
+//! Drive an App with Actions and inspect what it renders
+
+use git2::Oid;
+use keifu::{
+    action::Action,
+    app::{App, AppMode},
+    config::Config,
+    git::GitRepository,
+    ui,
+};
+use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+
+use super::{untracked_conflict_repo, TestRepo};
+
+/// Terminal size used by `AppHarness::render`
+pub const WIDTH: u16 = 100;
+pub const HEIGHT: u16 = 30;
+
+/// Render the whole UI into a buffer
+pub fn render(app: &mut App, width: u16, height: u16) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|frame| ui::draw(frame, app)).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// Text of each buffer row
+pub fn buffer_lines(buf: &Buffer) -> Vec<String> {
+    let width = buf.area.width as usize;
+    buf.content()
+        .chunks(width)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+        .collect()
+}
+
+/// An App opened on a test repository
+pub struct AppHarness {
+    pub repo: TestRepo,
+    pub app: App,
+}
+
+impl AppHarness {
+    pub fn new(repo: TestRepo) -> Self {
+        Self::with_config(repo, Config::default())
+    }
+
+    pub fn with_config(repo: TestRepo, config: Config) -> Self {
+        let app = App::with_repo(GitRepository::open(repo.path()).unwrap(), config).unwrap();
+        Self { repo, app }
+    }
+
+    /// Handle an action the way the main loop does (errors open the error dialog)
+    pub fn act(&mut self, action: Action) {
+        if let Err(e) = self.app.handle_action(action) {
+            self.app.show_error(format!("{}", e));
+        }
+    }
+
+    pub fn act_all(&mut self, actions: impl IntoIterator<Item = Action>) {
+        for action in actions {
+            self.act(action);
+        }
+    }
+
+    /// Type text into the open input dialog
+    pub fn type_text(&mut self, text: &str) {
+        self.act_all(text.chars().map(Action::InputChar));
+    }
+
+    /// Move the selection down until the given commit is selected
+    pub fn select_commit(&mut self, oid: Oid) {
+        self.act(Action::GoToTop);
+        for _ in 0..self.app.graph_layout.nodes.len() {
+            if self.selected_commit_oid() == Some(oid) {
+                return;
+            }
+            self.act(Action::MoveDown);
+        }
+        panic!("commit {} is not in the graph", oid);
+    }
+
+    pub fn render(&mut self) -> Buffer {
+        render(&mut self.app, WIDTH, HEIGHT)
+    }
+
+    /// Whether any rendered row contains the text
+    pub fn buffer_contains(&mut self, text: &str) -> bool {
+        buffer_lines(&self.render())
+            .iter()
+            .any(|line| line.contains(text))
+    }
+
+    pub fn selected_commit_oid(&self) -> Option<Oid> {
+        self.app
+            .graph_list_state
+            .selected()
+            .and_then(|i| self.app.graph_layout.nodes.get(i))
+            .and_then(|n| n.commit.as_ref())
+            .map(|c| c.oid)
+    }
+
+    pub fn current_mode(&self) -> &AppMode {
+        &self.app.mode
+    }
+}
+
+/// `untracked_conflict_repo` with feature's commit checked out, so the app is in the
+/// checkout-blocked dialog
+pub fn untracked_conflict_harness() -> AppHarness {
+    let (test_repo, feature) = untracked_conflict_repo();
+    let mut harness = AppHarness::new(test_repo);
+    harness.select_commit(feature);
+    harness.act(Action::Checkout);
+    harness
+}
@@ -0,0 +1,67 @@
+//! Shared helpers for tests that need a real Git repository
+
+#![allow(dead_code)]
+
+pub mod harness;
+mod repo;
+
+use git2::Oid;
+
+pub use repo::TestRepo;
+
+/// main ← bottom ← middle ← top, each one commit ahead of the one below; main has moved
+/// on since, and HEAD is on top
+pub fn stacked_branches_repo() -> (TestRepo, String) {
+    let test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "a", "base");
+    let main = test_repo.head_branch();
+    let mut below = main.clone();
+    for name in ["bottom", "middle", "top"] {
+        let tip = test_repo.repo.revparse_single(&below).unwrap().id();
+        test_repo.branch(name, tip);
+        test_repo.checkout(name);
+        test_repo.commit_file(&format!("{}.txt", name), name, &format!("{} work", name));
+        below = name.to_string();
+    }
+    test_repo.checkout(&main);
+    test_repo.commit_file("main.txt", "main", "main moves on");
+    test_repo.checkout("top");
+    (test_repo, main)
+}
+
+/// `feature` adds notes.txt; on the checked-out branch the same path exists as an
+/// untracked file. Returns the repository and feature's commit
+pub fn untracked_conflict_repo() -> (TestRepo, Oid) {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let main = test_repo.head_branch();
+    test_repo.branch("feature", base);
+    test_repo.checkout("feature");
+    let feature = test_repo.commit_file("notes.txt", "from feature", "add notes");
+    test_repo.checkout(&main);
+    test_repo.write_file("notes.txt", "local notes");
+    (test_repo, feature)
+}
+
+/// A repository whose origin waits `delay_secs` before serving a fetch
+/// Returns (origin, local); keep the origin alive for the duration of the test
+pub fn repo_with_slow_origin(delay_secs: u32) -> (TestRepo, TestRepo) {
+    let origin = TestRepo::new();
+    origin.commit_file("a.txt", "a", "first");
+
+    let local = TestRepo::new();
+    local
+        .repo
+        .remote("origin", origin.path().to_str().unwrap())
+        .unwrap();
+    local
+        .repo
+        .config()
+        .unwrap()
+        .set_str(
+            "remote.origin.uploadpack",
+            &format!("sleep {}; git-upload-pack", delay_secs),
+        )
+        .unwrap();
+    (origin, local)
+}
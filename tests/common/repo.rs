@@ -0,0 +1,105 @@
+//! The temporary repository shared by integration and unit tests
+//! (`src/app.rs` includes this file for tests that need App internals)
+
+use std::fs;
+use std::path::Path;
+
+use git2::{Oid, Repository, Signature};
+use tempfile::TempDir;
+
+/// A temporary Git repository for tests
+pub struct TestRepo {
+    pub dir: TempDir,
+    pub repo: Repository,
+}
+
+impl TestRepo {
+    /// Create an empty repository with a test identity configured
+    pub fn new() -> Self {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        {
+            let mut config = repo.config().unwrap();
+            config.set_str("user.name", "test").unwrap();
+            config.set_str("user.email", "test@example.com").unwrap();
+        }
+        Self { dir, repo }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Write a file into the working tree
+    pub fn write_file(&self, name: &str, content: &str) {
+        let path = self.path().join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(path, content).unwrap();
+    }
+
+    /// Write a file and stage it
+    pub fn stage_file(&self, name: &str, content: &str) {
+        self.write_file(name, content);
+        let mut index = self.repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+    }
+
+    /// Write a file, stage it and commit on HEAD
+    pub fn commit_file(&self, name: &str, content: &str, message: &str) -> Oid {
+        self.stage_file(name, content);
+        self.commit_index(message)
+    }
+
+    /// Commit the current index on HEAD
+    pub fn commit_index(&self, message: &str) -> Oid {
+        let mut index = self.repo.index().unwrap();
+        let tree_oid = index.write_tree().unwrap();
+        let tree = self.repo.find_tree(tree_oid).unwrap();
+        let sig = Signature::now("test", "test@example.com").unwrap();
+        let parent = self.repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        self.repo
+            .commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    /// Commit the current index with explicit parents and timestamp, without moving HEAD
+    pub fn commit_at(&self, message: &str, parents: &[Oid], time: i64) -> Oid {
+        let mut index = self.repo.index().unwrap();
+        let tree_oid = index.write_tree().unwrap();
+        let tree = self.repo.find_tree(tree_oid).unwrap();
+        let sig = Signature::new("test", "test@example.com", &git2::Time::new(time, 0)).unwrap();
+        let parents: Vec<git2::Commit> = parents
+            .iter()
+            .map(|oid| self.repo.find_commit(*oid).unwrap())
+            .collect();
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+        self.repo
+            .commit(None, &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    /// Create a branch at the given commit
+    pub fn branch(&self, name: &str, oid: Oid) {
+        let commit = self.repo.find_commit(oid).unwrap();
+        self.repo.branch(name, &commit, false).unwrap();
+    }
+
+    /// Checkout an existing local branch (updating the working tree)
+    pub fn checkout(&self, name: &str) {
+        let refname = format!("refs/heads/{}", name);
+        let obj = self.repo.revparse_single(&refname).unwrap();
+        self.repo
+            .checkout_tree(&obj, Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+        self.repo.set_head(&refname).unwrap();
+    }
+
+    /// Name of the initial branch created by `git init`
+    pub fn head_branch(&self) -> String {
+        self.repo.head().unwrap().shorthand().unwrap().to_string()
+    }
+}
@@ -0,0 +1,189 @@
+//! Tests for working tree and commit diffs against real Git repositories
+
+mod common;
+
+use std::path::Path;
+
+use common::TestRepo;
+use keifu::git::{commit_patch, BranchComparison, CommitDiffInfo, FileChangeKind};
+
+#[test]
+fn test_autocrlf_line_ending_only_change_counts_no_lines() {
+    let test_repo = TestRepo::new();
+    test_repo
+        .repo
+        .config()
+        .unwrap()
+        .set_bool("core.autocrlf", true)
+        .unwrap();
+    test_repo.commit_file("a.txt", "a\nb\n", "first");
+
+    // Touched with Windows line endings, content unchanged
+    test_repo.write_file("a.txt", "a\r\nb\r\n");
+
+    let filtered = CommitDiffInfo::from_working_tree(&test_repo.repo, false).unwrap();
+    assert_eq!(filtered.total_insertions, 0);
+    assert_eq!(filtered.total_deletions, 0);
+
+    let raw = CommitDiffInfo::from_working_tree(&test_repo.repo, true).unwrap();
+    assert_eq!(raw.files.len(), 1);
+    assert_eq!((raw.total_insertions, raw.total_deletions), (2, 2));
+}
+
+#[test]
+fn test_eol_attribute_is_respected() {
+    let test_repo = TestRepo::new();
+    test_repo.commit_file(".gitattributes", "*.txt text eol=crlf\n", "attributes");
+    test_repo.commit_file("a.txt", "a\nb\n", "first");
+
+    test_repo.write_file("a.txt", "a\r\nb\r\nc\r\n");
+
+    let filtered = CommitDiffInfo::from_working_tree(&test_repo.repo, false).unwrap();
+    assert_eq!(
+        (filtered.total_insertions, filtered.total_deletions),
+        (1, 0)
+    );
+}
+
+#[test]
+fn test_raw_diff_reports_real_changes_and_deletions() {
+    let test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "a\n", "first");
+    test_repo.commit_file("b.txt", "b\n", "second");
+
+    test_repo.write_file("a.txt", "a\nmore\n");
+    std::fs::remove_file(test_repo.path().join("b.txt")).unwrap();
+
+    let raw = CommitDiffInfo::from_working_tree(&test_repo.repo, true).unwrap();
+    let filtered = CommitDiffInfo::from_working_tree(&test_repo.repo, false).unwrap();
+    assert_eq!(raw.total_files, 2);
+    assert_eq!(
+        (raw.total_insertions, raw.total_deletions),
+        (filtered.total_insertions, filtered.total_deletions)
+    );
+    assert_eq!((raw.total_insertions, raw.total_deletions), (1, 1));
+}
+
+#[test]
+fn test_from_commits_compares_trees_of_two_commits() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a\n", "base");
+    test_repo.commit_file("a.txt", "a\nb\n", "grow");
+    let tip = test_repo.commit_file("c.txt", "c\n", "add");
+
+    let diff = CommitDiffInfo::from_commits(&test_repo.repo, base, tip).unwrap();
+    assert_eq!(diff.total_files, 2);
+    assert_eq!((diff.total_insertions, diff.total_deletions), (2, 0));
+
+    // The other way round undoes the same changes
+    let back = CommitDiffInfo::from_commits(&test_repo.repo, tip, base).unwrap();
+    let c = back.files.iter().find(|f| f.path.to_str() == Some("c.txt"));
+    assert_eq!(c.map(|f| f.kind), Some(FileChangeKind::Deleted));
+}
+
+#[test]
+fn test_branches_without_shared_history_are_still_compared() {
+    let test_repo = TestRepo::new();
+    let main = test_repo.commit_file("a.txt", "a\n", "main");
+    // A root commit unrelated to main, with a different file
+    test_repo.stage_file("b.txt", "b\n");
+    let mut index = test_repo.repo.index().unwrap();
+    index.remove_path(std::path::Path::new("a.txt")).unwrap();
+    index.write().unwrap();
+    let orphan = test_repo.commit_at("orphan", &[], 1_000);
+
+    let comparison =
+        BranchComparison::new(&test_repo.repo, ("main", main), ("orphan", orphan)).unwrap();
+    assert_eq!(comparison.ahead_behind, None);
+    assert_eq!(comparison.diff.total_files, 2);
+}
+
+#[test]
+fn test_commit_patch_is_a_unified_diff_against_the_parent() {
+    let test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "one\ntwo\n", "first");
+    test_repo.stage_file("a.txt", "one\nthree\n");
+    test_repo.stage_file("image.bin", "\0\x01\x02");
+    let oid = test_repo.commit_index("second");
+
+    let patch = commit_patch(&test_repo.repo, oid).unwrap();
+    let lines: Vec<&str> = patch.lines().collect();
+    assert!(lines.contains(&"diff --git a/a.txt b/a.txt"));
+    assert!(lines.contains(&"@@ -1,2 +1,2 @@"));
+    assert!(lines.contains(&" one"));
+    assert!(lines.contains(&"-two"));
+    assert!(lines.contains(&"+three"));
+    assert!(lines.contains(&"[binary file]"));
+}
+
+#[test]
+fn test_raw_diff_skips_submodules() {
+    let test_repo = TestRepo::new();
+    let head = test_repo.commit_file("a.txt", "a\n", "first");
+    // A gitlink entry, as `git submodule add` leaves it (its directory is not checked out)
+    let mut index = test_repo.repo.index().unwrap();
+    let mut entry = index.get_path(Path::new("a.txt"), 0).unwrap();
+    entry.path = b"vendor/lib".to_vec();
+    entry.mode = 0o160000;
+    entry.id = head;
+    index.add(&entry).unwrap();
+    index.write().unwrap();
+    test_repo.commit_index("add submodule");
+
+    let raw = CommitDiffInfo::from_working_tree(&test_repo.repo, true).unwrap();
+    assert_eq!(raw.total_files, 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_raw_diff_compares_symlink_targets() {
+    let test_repo = TestRepo::new();
+    test_repo.write_file("a.txt", "a\n");
+    test_repo.write_file("b.txt", "b\n");
+    std::os::unix::fs::symlink("a.txt", test_repo.path().join("link")).unwrap();
+    let mut index = test_repo.repo.index().unwrap();
+    for path in ["a.txt", "b.txt", "link"] {
+        index.add_path(Path::new(path)).unwrap();
+    }
+    index.write().unwrap();
+    test_repo.commit_index("add link");
+
+    // The link itself is unchanged, whatever the file it points to contains
+    let raw = CommitDiffInfo::from_working_tree(&test_repo.repo, true).unwrap();
+    assert_eq!(raw.total_files, 0);
+
+    std::fs::remove_file(test_repo.path().join("link")).unwrap();
+    std::os::unix::fs::symlink("b.txt", test_repo.path().join("link")).unwrap();
+    let raw = CommitDiffInfo::from_working_tree(&test_repo.repo, true).unwrap();
+    assert_eq!(raw.total_files, 1);
+    assert_eq!(raw.files[0].path, Path::new("link"));
+    assert_eq!(raw.files[0].kind, FileChangeKind::Modified);
+}
+
+#[test]
+fn test_mode_only_change_is_reported() {
+    let test_repo = TestRepo::new();
+    test_repo.commit_file("run.sh", "echo hi\n", "add script");
+    let mut index = test_repo.repo.index().unwrap();
+    let mut entry = index.get_path(Path::new("run.sh"), 0).unwrap();
+    entry.mode = 0o100755;
+    index.add(&entry).unwrap();
+    index.write().unwrap();
+    let oid = test_repo.commit_index("make script executable");
+
+    let diff = CommitDiffInfo::from_commit(&test_repo.repo, oid).unwrap();
+    let file = &diff.files[0];
+    assert_eq!(file.kind, FileChangeKind::Modified);
+    assert_eq!((file.insertions, file.deletions), (0, 0));
+    assert_eq!(file.mode_change, Some((0o100644, 0o100755)));
+
+    // New files have no mode to change from
+    let first = test_repo
+        .repo
+        .find_commit(oid)
+        .unwrap()
+        .parent_id(0)
+        .unwrap();
+    let diff = CommitDiffInfo::from_commit(&test_repo.repo, first).unwrap();
+    assert_eq!(diff.files[0].mode_change, None);
+}
@@ -0,0 +1,42 @@
+//! Repository discovery from GIT_DIR and GIT_WORK_TREE
+//!
+//! A test binary of its own: the variables are set for the whole process, where they
+//! would reach the repositories and git commands of tests running alongside
+
+mod common;
+
+use common::TestRepo;
+use keifu::git::{GitRepository, RepoLocation};
+
+#[test]
+fn test_discover_honors_git_dir_and_work_tree() {
+    let test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "a", "first");
+    let worktree = tempfile::TempDir::new().unwrap();
+
+    // The test runs inside keifu's own checkout, which must not be picked up
+    std::env::set_var("GIT_DIR", test_repo.path().join(".git"));
+    std::env::set_var("GIT_WORK_TREE", worktree.path());
+    let result = GitRepository::discover();
+    std::env::remove_var("GIT_DIR");
+    std::env::remove_var("GIT_WORK_TREE");
+
+    let repo = result.unwrap();
+    let location = RepoLocation::of(&repo.repo);
+    assert!(same_file(&location.git_dir, &test_repo.path().join(".git")));
+    assert!(same_file(
+        location.separate_workdir().unwrap(),
+        worktree.path()
+    ));
+
+    // Reopening on a worker keeps the working tree
+    let reopened = location.open().unwrap();
+    assert!(same_file(reopened.workdir().unwrap(), worktree.path()));
+
+    let plain = GitRepository::open(test_repo.path()).unwrap();
+    assert_eq!(RepoLocation::of(&plain.repo).separate_workdir(), None);
+}
+
+fn same_file(a: &std::path::Path, b: &std::path::Path) -> bool {
+    a.canonicalize().unwrap() == b.canonicalize().unwrap()
+}
@@ -0,0 +1,132 @@
+//! Tests for fetch against remotes that hang or are missing
+
+mod common;
+
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use common::{repo_with_slow_origin, TestRepo};
+use keifu::{git::operations::fetch_origin, Error};
+
+/// A repository whose origin hangs before serving the fetch
+fn repo_with_hanging_origin() -> (TestRepo, TestRepo) {
+    repo_with_slow_origin(30)
+}
+
+#[test]
+fn test_fetch_times_out() {
+    let (_origin, local) = repo_with_hanging_origin();
+
+    let started = Instant::now();
+    let result = fetch_origin(
+        local.path().to_str().unwrap(),
+        Duration::from_millis(300),
+        &AtomicBool::new(false),
+    );
+
+    assert!(
+        matches!(result, Err(Error::FetchTimeout(_))),
+        "{:?}",
+        result
+    );
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn test_fetch_can_be_cancelled() {
+    let (_origin, local) = repo_with_hanging_origin();
+
+    let result = fetch_origin(
+        local.path().to_str().unwrap(),
+        Duration::from_secs(60),
+        &AtomicBool::new(true),
+    );
+
+    assert!(matches!(result, Err(Error::FetchCancelled)), "{:?}", result);
+}
+
+#[test]
+fn test_fetch_without_origin_reports_git_error() {
+    let local = TestRepo::new();
+    local.commit_file("a.txt", "a", "first");
+
+    let result = fetch_origin(
+        local.path().to_str().unwrap(),
+        Duration::from_secs(60),
+        &AtomicBool::new(false),
+    );
+
+    match result {
+        Err(Error::FetchFailed(message)) => assert!(message.contains("origin"), "{}", message),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_fetch_timeout_kills_the_transport_processes() {
+    let (_origin, local) = repo_with_slow_origin(30);
+    // The upload-pack shell runs under git; record its pid
+    let pid_file = local.path().join("upload-pack.pid");
+    local
+        .repo
+        .config()
+        .unwrap()
+        .set_str(
+            "remote.origin.uploadpack",
+            &format!(
+                "echo $$ > {}; sleep 30; git-upload-pack",
+                pid_file.display()
+            ),
+        )
+        .unwrap();
+
+    let result = fetch_origin(
+        local.path().to_str().unwrap(),
+        Duration::from_millis(500),
+        &AtomicBool::new(false),
+    );
+    assert!(
+        matches!(result, Err(Error::FetchTimeout(_))),
+        "{:?}",
+        result
+    );
+
+    let pid = std::fs::read_to_string(&pid_file).unwrap();
+    let proc_dir = std::path::Path::new("/proc").join(pid.trim());
+    let started = Instant::now();
+    while proc_dir.exists() && started.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert!(
+        !proc_dir.exists(),
+        "upload-pack {} still running",
+        pid.trim()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_fetch_reads_long_error_output_while_waiting() {
+    let (_origin, local) = repo_with_slow_origin(0);
+    // More than a pipe buffer of output before failing
+    local
+        .repo
+        .config()
+        .unwrap()
+        .set_str(
+            "remote.origin.uploadpack",
+            "yes 'progress line' | head -c 500000 >&2; exit 1",
+        )
+        .unwrap();
+
+    let result = fetch_origin(
+        local.path().to_str().unwrap(),
+        Duration::from_secs(20),
+        &AtomicBool::new(false),
+    );
+    match result {
+        Err(Error::FetchFailed(message)) => assert!(message.contains("progress line")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -2,7 +2,15 @@
 
 use chrono::Local;
 use git2::Oid;
-use keifu::git::{build_graph, graph::CellType, BranchInfo, CommitInfo};
+use keifu::{
+    git::{
+        build_graph, build_graph_with_options,
+        filter::CommitFilter,
+        graph::{CellType, ElidedSpan, GraphDensity, HiddenLinks, LayoutOptions},
+        BranchInfo, CommitInfo,
+    },
+    graph::colors::{ColorScheme, LANE_COLORS, MAIN_BRANCH_COLOR},
+};
 
 fn make_oid(id: &str) -> Oid {
     // Convert id into a 40-char hex hash
@@ -21,6 +29,7 @@ fn make_commit(id: &str, parents: Vec<&str>) -> CommitInfo {
         author_name: "test".to_string(),
         author_email: "test@example.com".to_string(),
         timestamp: Local::now(),
+        author_time: 0,
         message: format!("Commit {}", id),
         full_message: format!("Commit {}", id),
         parent_oids: parents.into_iter().map(make_oid).collect(),
@@ -34,6 +43,7 @@ fn make_branch(name: &str, tip: &str, is_head: bool) -> BranchInfo {
         is_head,
         is_remote: false,
         upstream: None,
+        tip_time: 0,
     }
 }
 
@@ -53,6 +63,7 @@ fn render_cells(cells: &[CellType]) -> String {
             CellType::TeeRight(_) => '├',
             CellType::TeeLeft(_) => '┤',
             CellType::TeeUp(_) => '┴',
+            CellType::TeeDown(_) => '┬',
         })
         .collect()
 }
@@ -502,3 +513,1207 @@ fn test_hotfix_merged_into_multiple_branches() {
         "Expected continuous Pipe line from main-merge to hotfix"
     );
 }
+
+#[test]
+fn test_hide_merge_commits_rewires_first_parent() {
+    // M (merge) -> A, F
+    // A -> B
+    // F -> B
+    // B (root)
+    // X -> M (child of the merge)
+    let commits = vec![
+        make_commit("X", vec!["M"]),
+        make_commit("M", vec!["A", "F"]),
+        make_commit("A", vec!["B"]),
+        make_commit("F", vec!["B"]),
+        make_commit("B", vec![]),
+    ];
+    let branches = vec![
+        make_branch("main", "M", true),
+        make_branch("feature", "F", false),
+    ];
+
+    let filter = CommitFilter::merges(&commits);
+    let filtered = filter.apply(&commits);
+    let filtered_branches = filter.apply_branches(&branches);
+
+    // The merge is removed and X now continues on the first-parent line
+    assert_eq!(filtered.len(), 4);
+    assert!(filtered.iter().all(|c| c.parent_oids.len() < 2));
+    assert_eq!(filtered[0].parent_oids.as_slice(), [make_oid("A")]);
+    // Branch label on the merge moves to its first parent
+    assert_eq!(filtered_branches[0].tip_oid, make_oid("A"));
+
+    let layout = build_graph(&filtered, &filtered_branches, None, None);
+
+    println!("\nHidden merges:");
+    for node in &layout.nodes {
+        println!(
+            "  {} lane={} -> '{}'",
+            get_short_id(node),
+            node.lane,
+            render_cells(&node.cells)
+        );
+    }
+
+    // X and A share the first-parent lane without a gap
+    let x = layout
+        .nodes
+        .iter()
+        .find(|n| get_short_id(n) == "X")
+        .unwrap();
+    let a = layout
+        .nodes
+        .iter()
+        .find(|n| get_short_id(n) == "A")
+        .unwrap();
+    assert_eq!(x.lane, a.lane);
+    assert!(a.branch_names.contains(&"main".to_string()));
+}
+
+/// Assert every connector row points at the commit row it belongs to
+/// Layout that keeps every connector row, for tests about the connector rows themselves
+fn build_spacious_graph(
+    commits: &[CommitInfo],
+    branches: &[BranchInfo],
+    uncommitted_count: Option<usize>,
+    head_commit_oid: Option<Oid>,
+) -> keifu::git::graph::GraphLayout {
+    let options = LayoutOptions {
+        density: GraphDensity::Spacious,
+        ..Default::default()
+    };
+    build_graph_with_options(
+        commits,
+        branches,
+        uncommitted_count,
+        head_commit_oid,
+        &options,
+    )
+}
+
+fn assert_connector_owners(layout: &keifu::git::graph::GraphLayout) {
+    for (idx, node) in layout.nodes.iter().enumerate() {
+        if node.is_connector() {
+            let owner = node
+                .owner_row
+                .unwrap_or_else(|| panic!("connector row {} has no owner", idx));
+            assert!(
+                layout.nodes[owner].commit.is_some(),
+                "connector row {} owner {} is not a commit row",
+                idx,
+                owner
+            );
+        } else {
+            assert_eq!(node.owner_row, None, "non-connector row {} has owner", idx);
+        }
+    }
+}
+
+#[test]
+fn test_fork_connector_owner_row() {
+    // A and B both branch from R (fork point) -> fork connector before R
+    let commits = vec![
+        make_commit("A", vec!["R"]),
+        make_commit("B", vec!["R"]),
+        make_commit("R", vec![]),
+    ];
+    let branches = vec![make_branch("main", "A", true), make_branch("b", "B", false)];
+
+    let layout = build_spacious_graph(&commits, &branches, None, None);
+    assert_connector_owners(&layout);
+
+    let connector_idx = layout
+        .nodes
+        .iter()
+        .position(|n| n.is_connector())
+        .expect("fork connector expected");
+    let owner = layout.nodes[connector_idx].owner_row.unwrap();
+    assert_eq!(owner, connector_idx + 1);
+    assert_eq!(get_short_id(&layout.nodes[owner]), "R");
+}
+
+#[test]
+fn test_connector_owner_row_with_uncommitted_node() {
+    let commits = vec![
+        make_commit("A", vec!["R"]),
+        make_commit("B", vec!["R"]),
+        make_commit("R", vec![]),
+    ];
+    let branches = vec![make_branch("main", "A", true), make_branch("b", "B", false)];
+
+    // The uncommitted row at index 0 shifts all owners by one
+    let layout = build_spacious_graph(&commits, &branches, Some(2), Some(make_oid("A")));
+    assert!(layout.nodes[0].is_uncommitted);
+    assert_connector_owners(&layout);
+}
+
+#[test]
+fn test_connector_owner_rows_in_complex_histories() {
+    let commits = vec![
+        make_commit("release-merge", vec!["version-bump", "hotfix"]),
+        make_commit("main-merge", vec!["base", "hotfix"]),
+        make_commit("version-bump", vec!["base"]),
+        make_commit("hotfix", vec!["base"]),
+        make_commit("base", vec![]),
+    ];
+    let layout = build_spacious_graph(&commits, &[], None, None);
+    assert_connector_owners(&layout);
+}
+
+#[test]
+fn test_permanently_diverged_heads_keep_both_lanes() {
+    // main and release share history up to B, then diverge and never merge
+    // M3 -> M2 -> M1 -> B
+    // R3 -> R2 -> R1 -> B
+    // B -> A (root)
+    let commits = vec![
+        make_commit("M3", vec!["M2"]),
+        make_commit("R3", vec!["R2"]),
+        make_commit("M2", vec!["M1"]),
+        make_commit("R2", vec!["R1"]),
+        make_commit("M1", vec!["B"]),
+        make_commit("R1", vec!["B"]),
+        make_commit("B", vec!["A"]),
+        make_commit("A", vec![]),
+    ];
+    let branches = vec![
+        make_branch("main", "M3", true),
+        make_branch("release/1.x", "R3", false),
+    ];
+
+    let layout = build_graph(&commits, &branches, None, None);
+
+    let node_of = |id: &str| {
+        layout
+            .nodes
+            .iter()
+            .position(|n| n.commit.as_ref().is_some_and(|c| c.short_id == id))
+            .unwrap()
+    };
+    let main_ids = ["M3", "M2", "M1"];
+    let release_ids = ["R3", "R2", "R1"];
+
+    // Each line stays in one lane with one color
+    let main_node = &layout.nodes[node_of("M3")];
+    let release_node = &layout.nodes[node_of("R3")];
+    for id in main_ids {
+        let node = &layout.nodes[node_of(id)];
+        assert_eq!(node.lane, main_node.lane, "{} left the main lane", id);
+        assert_eq!(
+            node.color_index, main_node.color_index,
+            "{} changed color",
+            id
+        );
+    }
+    for id in release_ids {
+        let node = &layout.nodes[node_of(id)];
+        assert_eq!(node.lane, release_node.lane, "{} left the release lane", id);
+        assert_eq!(
+            node.color_index, release_node.color_index,
+            "{} changed color",
+            id
+        );
+    }
+    assert_ne!(main_node.lane, release_node.lane);
+    assert_ne!(main_node.color_index, release_node.color_index);
+
+    // Between each tip and the fork point, the other lane is never dropped
+    let fork_row = node_of("B");
+    for (lane, color, tip) in [
+        (main_node.lane, main_node.color_index, node_of("M3")),
+        (release_node.lane, release_node.color_index, node_of("R3")),
+    ] {
+        for (row, node) in layout.nodes.iter().enumerate().take(fork_row).skip(tip) {
+            let cell = &node.cells[lane * 2];
+            let ok = match cell {
+                CellType::Commit(c) | CellType::Pipe(c) => *c == color,
+                // Fork connector row joining the lanes into the fork point
+                _ => node.is_connector(),
+            };
+            assert!(
+                ok,
+                "lane {} broken at row {} ('{}')",
+                lane,
+                row,
+                render_cells(&node.cells)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_diverged_release_lane_survives_feature_merges_on_main() {
+    // A feature branch is merged into main while release/1.x stays diverged
+    // M3 (merge) -> M2, F1
+    // F1 -> M1
+    // M2 -> M1
+    // R2 -> R1 -> B, M1 -> B
+    let commits = vec![
+        make_commit("M3", vec!["M2", "F1"]),
+        make_commit("R2", vec!["R1"]),
+        make_commit("F1", vec!["M1"]),
+        make_commit("M2", vec!["M1"]),
+        make_commit("R1", vec!["B"]),
+        make_commit("M1", vec!["B"]),
+        make_commit("B", vec![]),
+    ];
+    let branches = vec![
+        make_branch("main", "M3", true),
+        make_branch("release/1.x", "R2", false),
+    ];
+
+    let layout = build_graph(&commits, &branches, None, None);
+    for node in &layout.nodes {
+        println!(
+            "  {:<3} '{}'",
+            get_short_id(node),
+            render_cells(&node.cells)
+        );
+    }
+
+    let node_of = |id: &str| {
+        layout
+            .nodes
+            .iter()
+            .position(|n| n.commit.as_ref().is_some_and(|c| c.short_id == id))
+            .unwrap()
+    };
+    let r2 = &layout.nodes[node_of("R2")];
+    let r1 = &layout.nodes[node_of("R1")];
+    assert_eq!(r1.lane, r2.lane);
+    assert_eq!(r1.color_index, r2.color_index);
+
+    // The release lane is drawn on every row between its tip and R1
+    for node in &layout.nodes[node_of("R2") + 1..node_of("R1")] {
+        assert!(
+            matches!(node.cells[r2.lane * 2], CellType::Pipe(c) if c == r2.color_index),
+            "release lane broken: '{}'",
+            render_cells(&node.cells)
+        );
+    }
+}
+
+#[test]
+fn test_branch_tip_outside_loaded_commits_is_reported() {
+    // c2 -> c1 loaded; "stale" points at c0, which is beyond the commit window
+    let commits = vec![make_commit("c2", vec!["c1"]), make_commit("c1", vec!["c0"])];
+    let branches = vec![
+        make_branch("main", "c2", true),
+        make_branch("stale", "c0", false),
+    ];
+
+    let layout = build_graph(&commits, &branches, None, None);
+
+    assert_eq!(layout.offscreen_branches, vec!["stale".to_string()]);
+    assert!(layout
+        .nodes
+        .iter()
+        .all(|n| !n.branch_names.contains(&"stale".to_string())));
+}
+
+#[test]
+fn test_children_of_loaded_commits() {
+    // M merges F into B; B and F both fork from A, whose parent is outside the window
+    let commits = vec![
+        make_commit("M", vec!["B", "F"]),
+        make_commit("F", vec!["A"]),
+        make_commit("B", vec!["A"]),
+        make_commit("A", vec!["Z"]),
+    ];
+    let branches = vec![make_branch("main", "M", true)];
+
+    let layout = build_graph(&commits, &branches, None, None);
+
+    assert_eq!(
+        layout.children_of(make_oid("A")),
+        [make_oid("F"), make_oid("B")]
+    );
+    assert_eq!(layout.children_of(make_oid("F")), [make_oid("M")]);
+    assert!(layout.children_of(make_oid("M")).is_empty());
+    assert!(layout.children_of(make_oid("Z")).is_empty());
+}
+
+#[test]
+fn test_hidden_commits_render_as_elision_row() {
+    // A -> (hidden B, C) -> D
+    let commits = vec![make_commit("A", vec!["D"]), make_commit("D", vec![])];
+    let branches = vec![make_branch("main", "A", true)];
+    let options = LayoutOptions {
+        density: GraphDensity::Spacious,
+        hidden: HiddenLinks {
+            filter: "merge",
+            counts: [((make_oid("A"), make_oid("D")), 2)].into_iter().collect(),
+        },
+        ..Default::default()
+    };
+
+    let layout = build_graph_with_options(&commits, &branches, None, None, &options);
+
+    assert_eq!(layout.nodes.len(), 3);
+    assert_eq!(get_short_id(&layout.nodes[0]), "A");
+    assert_eq!(get_short_id(&layout.nodes[2]), "D");
+
+    let elision = &layout.nodes[1];
+    assert!(elision.commit.is_none() && !elision.is_connector());
+    assert_eq!(
+        elision.elided,
+        Some(ElidedSpan {
+            count: 2,
+            filter: "merge"
+        })
+    );
+
+    // The lane keeps A's color through the elision row down to D
+    let color = layout.nodes[0].color_index;
+    assert_eq!(elision.cells[0], CellType::Pipe(color));
+    assert_eq!(layout.nodes[2].cells[0], CellType::Commit(color));
+}
+
+#[test]
+fn test_merge_filter_counts_hidden_merges_per_link() {
+    // X -> M2 -> M1 -> A (M1, M2 are merges of side commits S1, S2)
+    let commits = vec![
+        make_commit("X", vec!["M2"]),
+        make_commit("M2", vec!["M1", "S2"]),
+        make_commit("S2", vec!["A"]),
+        make_commit("M1", vec!["A", "S1"]),
+        make_commit("S1", vec!["A"]),
+        make_commit("A", vec![]),
+    ];
+
+    let hidden = CommitFilter::merges(&commits).hidden_links(&commits);
+
+    assert_eq!(hidden.counts.len(), 1);
+    assert_eq!(hidden.counts[&(make_oid("X"), make_oid("A"))], 2);
+}
+
+fn layout_with_density(
+    commits: &[CommitInfo],
+    density: GraphDensity,
+) -> keifu::git::graph::GraphLayout {
+    let options = LayoutOptions {
+        density,
+        ..Default::default()
+    };
+    build_graph_with_options(commits, &[], None, None, &options)
+}
+
+fn rendered_rows(layout: &keifu::git::graph::GraphLayout) -> Vec<String> {
+    layout
+        .nodes
+        .iter()
+        .map(|n| format!("{} {}", render_cells(&n.cells).trim_end(), get_short_id(n)))
+        .collect()
+}
+
+#[test]
+fn test_rotation_colors_follow_the_lane_and_keep_the_layout() {
+    let commits = vec![
+        make_commit("M", vec!["A", "B"]),
+        make_commit("A", vec!["R"]),
+        make_commit("B", vec!["R"]),
+        make_commit("C", vec!["R"]),
+        make_commit("R", vec![]),
+    ];
+    let layout_with = |colors| {
+        let options = LayoutOptions {
+            density: GraphDensity::Spacious,
+            colors,
+            ..Default::default()
+        };
+        build_graph_with_options(&commits, &[], None, None, &options)
+    };
+    let balanced = layout_with(ColorScheme::Balanced);
+    let rotation = layout_with(ColorScheme::Rotation);
+
+    // Only the colors differ
+    assert_eq!(rendered_rows(&balanced), rendered_rows(&rotation));
+    assert_eq!(balanced.nodes[0].color_index, MAIN_BRANCH_COLOR);
+    for node in rotation.nodes.iter().filter(|n| n.commit.is_some()) {
+        assert_eq!(node.color_index, node.lane % LANE_COLORS.len());
+    }
+    assert!(balanced.max_lane >= 2);
+    assert_ne!(
+        balanced
+            .nodes
+            .iter()
+            .map(|n| n.color_index)
+            .collect::<Vec<_>>(),
+        rotation
+            .nodes
+            .iter()
+            .map(|n| n.color_index)
+            .collect::<Vec<_>>()
+    );
+}
+
+/// Fork point that is also a merge commit: its own merge line overlaps the connector
+fn fork_point_merge_commits() -> Vec<CommitInfo> {
+    vec![
+        make_commit("X", vec!["R"]),
+        make_commit("Y", vec!["R"]),
+        make_commit("R", vec!["P", "Q"]),
+        make_commit("Q", vec!["P"]),
+        make_commit("P", vec![]),
+    ]
+}
+
+#[test]
+fn test_spacious_density_keeps_every_connector_row() {
+    let commits = fork_point_merge_commits();
+    let layout = layout_with_density(&commits, GraphDensity::Spacious);
+    assert_connector_owners(&layout);
+    assert_eq!(
+        rendered_rows(&layout),
+        [
+            "○ X",
+            "│ ○ Y",
+            "├─╯ (connector)",
+            "○─╮ R",
+            "│ ○ Q",
+            "├─╯ (connector)",
+            "○ P",
+        ]
+    );
+}
+
+#[test]
+fn test_normal_density_keeps_connector_rows_above_merges_only() {
+    let commits = fork_point_merge_commits();
+    let layout = layout_with_density(&commits, GraphDensity::Normal);
+    assert_connector_owners(&layout);
+    // R is a merge, so its connector keeps a row; P's folds into P's own row
+    assert_eq!(
+        rendered_rows(&layout),
+        ["○ X", "│ ○ Y", "├─╯ (connector)", "○─╮ R", "│ ○ Q", "○─╯ P",]
+    );
+}
+
+#[test]
+fn test_compact_density_has_one_row_per_commit() {
+    let commits = fork_point_merge_commits();
+    let layout = layout_with_density(&commits, GraphDensity::Compact);
+    assert_eq!(
+        rendered_rows(&layout),
+        ["○ X", "│ ○ Y", "○─┤ R", "│ ○ Q", "○─╯ P"]
+    );
+
+    let hotfix = vec![
+        make_commit("release-merge", vec!["version-bump", "hotfix"]),
+        make_commit("main-merge", vec!["base", "hotfix"]),
+        make_commit("version-bump", vec!["base"]),
+        make_commit("hotfix", vec!["base"]),
+        make_commit("base", vec![]),
+    ];
+    let layout = layout_with_density(&hotfix, GraphDensity::Compact);
+    assert_eq!(layout.nodes.len(), hotfix.len());
+    assert_eq!(rendered_rows(&layout)[4], "○─┴─╯ base");
+}
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+/// Directions a line glyph reaches out to (None for commit nodes, which join anything)
+fn cell_arms(cell: CellType) -> Option<u8> {
+    Some(match cell {
+        CellType::Commit(_) => return None,
+        CellType::Empty => 0,
+        CellType::Pipe(_) => UP | DOWN,
+        CellType::BranchRight(_) => DOWN | RIGHT,
+        CellType::BranchLeft(_) => DOWN | LEFT,
+        CellType::MergeRight(_) => UP | RIGHT,
+        CellType::MergeLeft(_) => UP | LEFT,
+        CellType::Horizontal(_) => LEFT | RIGHT,
+        CellType::HorizontalPipe(..) => UP | DOWN | LEFT | RIGHT,
+        CellType::TeeRight(_) => UP | DOWN | RIGHT,
+        CellType::TeeLeft(_) => UP | DOWN | LEFT,
+        CellType::TeeUp(_) => UP | LEFT | RIGHT,
+        CellType::TeeDown(_) => DOWN | LEFT | RIGHT,
+    })
+}
+
+/// Assert every line end meets a line (or a commit node) in the neighbouring cell
+fn assert_lines_connect(layout: &keifu::git::graph::GraphLayout) {
+    let rows = rendered_rows(layout);
+    let arms_at = |row: usize, col: usize| -> Option<u8> {
+        cell_arms(
+            layout
+                .nodes
+                .get(row)
+                .and_then(|n| n.cells.get(col).copied())
+                .unwrap_or(CellType::Empty),
+        )
+    };
+    for (row, node) in layout.nodes.iter().enumerate() {
+        for col in 0..node.cells.len() {
+            let Some(arms) = arms_at(row, col) else {
+                continue;
+            };
+            let joins = |neighbour: Option<u8>, arm: u8| neighbour.is_none_or(|n| n & arm != 0);
+            if arms & RIGHT != 0 {
+                assert!(joins(arms_at(row, col + 1), LEFT), "{:?}", rows);
+            }
+            if arms & LEFT != 0 {
+                assert!(col > 0 && joins(arms_at(row, col - 1), RIGHT), "{:?}", rows);
+            }
+            if arms & DOWN != 0 {
+                assert!(joins(arms_at(row + 1, col), UP), "{:?}", rows);
+            }
+            if arms & UP != 0 {
+                assert!(row > 0 && joins(arms_at(row - 1, col), DOWN), "{:?}", rows);
+            }
+        }
+    }
+}
+
+/// Histories from the tests above, for checking every density against the same shapes
+fn topologies() -> Vec<Vec<CommitInfo>> {
+    vec![
+        vec![
+            make_commit("c4", vec!["c3", "c2"]),
+            make_commit("c3", vec!["c1"]),
+            make_commit("c2", vec!["c1"]),
+            make_commit("c1", vec![]),
+        ],
+        vec![
+            make_commit("c7", vec!["c6", "c5"]),
+            make_commit("c6", vec!["c4"]),
+            make_commit("c5", vec!["c4"]),
+            make_commit("c4", vec!["c3", "c2"]),
+            make_commit("c3", vec!["c1"]),
+            make_commit("c2", vec!["c1"]),
+            make_commit("c1", vec![]),
+        ],
+        vec![
+            make_commit("M", vec!["A", "B", "C"]),
+            make_commit("A", vec!["R"]),
+            make_commit("B", vec!["R"]),
+            make_commit("C", vec!["R"]),
+            make_commit("R", vec![]),
+        ],
+        vec![
+            make_commit("M2", vec!["A2", "B2"]),
+            make_commit("A2", vec!["A1"]),
+            make_commit("B2", vec!["B1"]),
+            make_commit("A1", vec!["M1"]),
+            make_commit("B1", vec!["M1"]),
+            make_commit("M1", vec!["R", "X"]),
+            make_commit("X", vec!["R"]),
+            make_commit("R", vec![]),
+        ],
+        vec![
+            make_commit("main-merge", vec!["feature-merge", "release"]),
+            make_commit("develop-merge", vec!["develop", "release"]),
+            make_commit("release", vec!["root"]),
+            make_commit("develop", vec!["root"]),
+            make_commit("feature-merge", vec!["root", "hotfix"]),
+            make_commit("hotfix", vec!["root"]),
+            make_commit("root", vec![]),
+        ],
+        vec![
+            make_commit("release-merge", vec!["version-bump", "hotfix"]),
+            make_commit("main-merge", vec!["base", "hotfix"]),
+            make_commit("version-bump", vec!["base"]),
+            make_commit("hotfix", vec!["base"]),
+            make_commit("base", vec![]),
+        ],
+        vec![
+            make_commit("M3", vec!["M2"]),
+            make_commit("R3", vec!["R2"]),
+            make_commit("M2", vec!["M1"]),
+            make_commit("R2", vec!["R1"]),
+            make_commit("M1", vec!["B"]),
+            make_commit("R1", vec!["B"]),
+            make_commit("B", vec!["A"]),
+            make_commit("A", vec![]),
+        ],
+        fork_point_merge_commits(),
+        fork_point_merge_below_freed_lane(),
+    ]
+}
+
+/// Fork point merge whose new lane opens in a freed lane the connector passes over
+fn fork_point_merge_below_freed_lane() -> Vec<CommitInfo> {
+    vec![
+        make_commit("X", vec!["R"]),
+        make_commit("W", vec!["V"]),
+        make_commit("Y", vec!["R"]),
+        make_commit("V", vec![]),
+        make_commit("R", vec!["P", "Q"]),
+        make_commit("Q", vec!["P"]),
+        make_commit("P", vec![]),
+    ]
+}
+
+#[test]
+fn test_compact_density_has_one_row_per_commit_in_every_topology() {
+    for commits in topologies() {
+        let layout = layout_with_density(&commits, GraphDensity::Compact);
+        assert_eq!(
+            layout.nodes.len(),
+            commits.len(),
+            "{:?}",
+            rendered_rows(&layout)
+        );
+        assert!(layout.nodes.iter().all(|n| !n.is_connector()));
+        assert_lines_connect(&layout);
+    }
+}
+
+const DENSITIES: [GraphDensity; 3] = [
+    GraphDensity::Spacious,
+    GraphDensity::Normal,
+    GraphDensity::Compact,
+];
+
+#[test]
+fn test_every_density_draws_connected_lines() {
+    for density in DENSITIES {
+        for commits in topologies() {
+            let layout = layout_with_density(&commits, density);
+            assert_connector_owners(&layout);
+            assert_lines_connect(&layout);
+        }
+    }
+}
+
+#[test]
+fn test_each_density_renders_every_topology() {
+    // Expected rows per topology, in DENSITIES order: spacious keeps every
+    // connector row, normal only the ones above merge commits, compact none
+    let expected: [[&[&str]; 3]; 9] = [
+        [
+            &["○─╮ c4", "○ │ c3", "│ ○ c2", "├─╯ (connector)", "○ c1"],
+            &["○─╮ c4", "○ │ c3", "│ ○ c2", "○─╯ c1"],
+            &["○─╮ c4", "○ │ c3", "│ ○ c2", "○─╯ c1"],
+        ],
+        [
+            &[
+                "○─╮ c7",
+                "○ │ c6",
+                "│ ○ c5",
+                "├─╯ (connector)",
+                "○─╮ c4",
+                "○ │ c3",
+                "│ ○ c2",
+                "├─╯ (connector)",
+                "○ c1",
+            ],
+            &[
+                "○─╮ c7",
+                "○ │ c6",
+                "│ ○ c5",
+                "├─╯ (connector)",
+                "○─╮ c4",
+                "○ │ c3",
+                "│ ○ c2",
+                "○─╯ c1",
+            ],
+            &[
+                "○─╮ c7",
+                "○ │ c6",
+                "│ ○ c5",
+                "○─┤ c4",
+                "○ │ c3",
+                "│ ○ c2",
+                "○─╯ c1",
+            ],
+        ],
+        [
+            &[
+                "○─┬─╮ M",
+                "○ │ │ A",
+                "│ ○ │ B",
+                "│ │ ○ C",
+                "├─┴─╯ (connector)",
+                "○ R",
+            ],
+            &["○─┬─╮ M", "○ │ │ A", "│ ○ │ B", "│ │ ○ C", "○─┴─╯ R"],
+            &["○─┬─╮ M", "○ │ │ A", "│ ○ │ B", "│ │ ○ C", "○─┴─╯ R"],
+        ],
+        [
+            &[
+                "○─╮ M2",
+                "○ │ A2",
+                "│ ○ B2",
+                "○ │ A1",
+                "│ ○ B1",
+                "├─╯ (connector)",
+                "○─╮ M1",
+                "│ ○ X",
+                "├─╯ (connector)",
+                "○ R",
+            ],
+            &[
+                "○─╮ M2",
+                "○ │ A2",
+                "│ ○ B2",
+                "○ │ A1",
+                "│ ○ B1",
+                "├─╯ (connector)",
+                "○─╮ M1",
+                "│ ○ X",
+                "○─╯ R",
+            ],
+            &[
+                "○─╮ M2",
+                "○ │ A2",
+                "│ ○ B2",
+                "○ │ A1",
+                "│ ○ B1",
+                "○─┤ M1",
+                "│ ○ X",
+                "○─╯ R",
+            ],
+        ],
+        [
+            &[
+                "○─╮ main-merge",
+                "│ ├─○ develop-merge",
+                "│ ○ │ release",
+                "│ │ ○ develop",
+                "○─┼─┼─╮ feature-merge",
+                "│ │ │ ○ hotfix",
+                "├─┴─┴─╯ (connector)",
+                "○ root",
+            ],
+            &[
+                "○─╮ main-merge",
+                "│ ├─○ develop-merge",
+                "│ ○ │ release",
+                "│ │ ○ develop",
+                "○─┼─┼─╮ feature-merge",
+                "│ │ │ ○ hotfix",
+                "○─┴─┴─╯ root",
+            ],
+            &[
+                "○─╮ main-merge",
+                "│ ├─○ develop-merge",
+                "│ ○ │ release",
+                "│ │ ○ develop",
+                "○─┼─┼─╮ feature-merge",
+                "│ │ │ ○ hotfix",
+                "○─┴─┴─╯ root",
+            ],
+        ],
+        [
+            &[
+                "○─╮ release-merge",
+                "│ ├─○ main-merge",
+                "○ │ │ version-bump",
+                "│ ○ │ hotfix",
+                "├─┴─╯ (connector)",
+                "○ base",
+            ],
+            &[
+                "○─╮ release-merge",
+                "│ ├─○ main-merge",
+                "○ │ │ version-bump",
+                "│ ○ │ hotfix",
+                "○─┴─╯ base",
+            ],
+            &[
+                "○─╮ release-merge",
+                "│ ├─○ main-merge",
+                "○ │ │ version-bump",
+                "│ ○ │ hotfix",
+                "○─┴─╯ base",
+            ],
+        ],
+        [
+            &[
+                "○ M3",
+                "│ ○ R3",
+                "○ │ M2",
+                "│ ○ R2",
+                "○ │ M1",
+                "│ ○ R1",
+                "├─╯ (connector)",
+                "○ B",
+                "○ A",
+            ],
+            &[
+                "○ M3",
+                "│ ○ R3",
+                "○ │ M2",
+                "│ ○ R2",
+                "○ │ M1",
+                "│ ○ R1",
+                "○─╯ B",
+                "○ A",
+            ],
+            &[
+                "○ M3",
+                "│ ○ R3",
+                "○ │ M2",
+                "│ ○ R2",
+                "○ │ M1",
+                "│ ○ R1",
+                "○─╯ B",
+                "○ A",
+            ],
+        ],
+        [
+            &[
+                "○ X",
+                "│ ○ Y",
+                "├─╯ (connector)",
+                "○─╮ R",
+                "│ ○ Q",
+                "├─╯ (connector)",
+                "○ P",
+            ],
+            &["○ X", "│ ○ Y", "├─╯ (connector)", "○─╮ R", "│ ○ Q", "○─╯ P"],
+            &["○ X", "│ ○ Y", "○─┤ R", "│ ○ Q", "○─╯ P"],
+        ],
+        [
+            &[
+                "○ X",
+                "│ ○ W",
+                "│ │ ○ Y",
+                "│ ○ │ V",
+                "├───╯ (connector)",
+                "○─╮ R",
+                "│ ○ Q",
+                "├─╯ (connector)",
+                "○ P",
+            ],
+            &[
+                "○ X",
+                "│ ○ W",
+                "│ │ ○ Y",
+                "│ ○ │ V",
+                "├───╯ (connector)",
+                "○─╮ R",
+                "│ ○ Q",
+                "○─╯ P",
+            ],
+            &[
+                "○ X",
+                "│ ○ W",
+                "│ │ ○ Y",
+                "│ ○ │ V",
+                "○─┬─╯ R",
+                "│ ○ Q",
+                "○─╯ P",
+            ],
+        ],
+    ];
+    for (commits, levels) in topologies().iter().zip(expected) {
+        for (density, rows) in DENSITIES.into_iter().zip(levels) {
+            let layout = layout_with_density(commits, density);
+            assert_eq!(rendered_rows(&layout), rows, "{:?}", density);
+        }
+    }
+}
+
+#[test]
+fn test_half_rows_move_connector_rows_into_the_fork_point() {
+    for commits in topologies() {
+        let options = LayoutOptions {
+            density: GraphDensity::Spacious,
+            half_rows: true,
+            ..Default::default()
+        };
+        let half = build_graph_with_options(&commits, &[], None, None, &options);
+        let full = layout_with_density(&commits, GraphDensity::Spacious);
+        assert_eq!(
+            half.nodes.len(),
+            commits.len(),
+            "{:?}",
+            rendered_rows(&half)
+        );
+
+        // Each connector row now sits in the upper half of the row it belonged to
+        let mut full_rows = full.nodes.iter();
+        for node in &half.nodes {
+            let mut row = full_rows.next().unwrap();
+            let mut upper = None;
+            if row.is_connector() {
+                upper = Some(row.cells.clone());
+                row = full_rows.next().unwrap();
+            }
+            assert_eq!(node.upper_cells, upper);
+            assert_eq!(node.cells, row.cells);
+        }
+    }
+}
+
+#[test]
+fn test_compact_connector_over_a_new_lane_draws_a_down_tee() {
+    let commits = fork_point_merge_below_freed_lane();
+    let layout = layout_with_density(&commits, GraphDensity::Compact);
+    // The connector from Y passes over the lane R opens for Q
+    assert_eq!(
+        rendered_rows(&layout),
+        [
+            "○ X",
+            "│ ○ W",
+            "│ │ ○ Y",
+            "│ ○ │ V",
+            "○─┬─╯ R",
+            "│ ○ Q",
+            "○─╯ P",
+        ]
+    );
+}
+
+#[test]
+fn test_octopus_merge_line_continues_past_nearer_parents() {
+    let commits = &topologies()[2];
+    let layout = layout_with_density(commits, GraphDensity::Compact);
+    assert_eq!(rendered_rows(&layout)[0], "○─┬─╮ M");
+}
+
+#[test]
+fn test_identical_timestamps_resolve_lanes_by_topology() {
+    // Imported history: every commit has the same timestamp, so the walk order only
+    // respects parent/child topology and interleaves the two lines of work
+    // M (merge) -> A3, B3
+    // A3 -> A2 -> A1 -> R
+    // B3 -> B2 -> B1 -> R
+    let timestamp = Local::now();
+    let commits: Vec<CommitInfo> = [
+        ("M", vec!["A3", "B3"]),
+        ("B3", vec!["B2"]),
+        ("A3", vec!["A2"]),
+        ("B2", vec!["B1"]),
+        ("A2", vec!["A1"]),
+        ("B1", vec!["R"]),
+        ("A1", vec!["R"]),
+        ("R", vec![]),
+    ]
+    .into_iter()
+    .map(|(id, parents)| CommitInfo {
+        timestamp,
+        ..make_commit(id, parents)
+    })
+    .collect();
+    let branches = vec![make_branch("main", "M", true)];
+
+    let layout = build_graph(&commits, &branches, None, None);
+
+    let lane_of = |id: &str| {
+        layout
+            .nodes
+            .iter()
+            .find(|n| n.commit.as_ref().is_some_and(|c| c.short_id == id))
+            .map(|n| n.lane)
+            .unwrap()
+    };
+    // Each line of work stays on one lane regardless of the interleaving
+    for id in ["M", "A3", "A2", "A1", "R"] {
+        assert_eq!(lane_of(id), 0, "{} should be on the first-parent lane", id);
+    }
+    for id in ["B3", "B2", "B1"] {
+        assert_eq!(lane_of(id), 1, "{} should be on the merged branch lane", id);
+    }
+    assert_eq!(layout.max_lane, 1);
+}
+
+#[test]
+fn test_out_of_order_commits_are_laid_out_topologically() {
+    // M merges F into A; both descend from B
+    let ordered = vec![
+        make_commit("M", vec!["A", "F"]),
+        make_commit("A", vec!["B"]),
+        make_commit("F", vec!["B"]),
+        make_commit("B", vec![]),
+    ];
+    // Parents listed before their children
+    let shuffled = vec![
+        ordered[3].clone(),
+        ordered[1].clone(),
+        ordered[0].clone(),
+        ordered[2].clone(),
+    ];
+    let branches = vec![make_branch("main", "M", true)];
+
+    let expected = build_graph(&ordered, &branches, None, None);
+    let layout = build_graph(&shuffled, &branches, None, None);
+
+    let ids: Vec<String> = layout.nodes.iter().map(get_short_id).collect();
+    assert_eq!(
+        ids,
+        expected.nodes.iter().map(get_short_id).collect::<Vec<_>>()
+    );
+    for (node, expected) in layout.nodes.iter().zip(&expected.nodes) {
+        assert_eq!(node.cells, expected.cells);
+    }
+    assert_eq!(ids.first().map(String::as_str), Some("M"));
+    assert_eq!(ids.last().map(String::as_str), Some("B"));
+}
+
+#[test]
+fn test_merge_second_parent_in_freed_left_lane_starts_with_corner() {
+    // X ends lane 0 before merge M (lane 1) is reached, so M's second parent
+    // P2 reuses lane 0 to the left of M and must continue downward from a ╭
+    let commits = vec![
+        make_commit("A", vec!["X"]),
+        make_commit("B", vec!["M"]),
+        make_commit("X", vec![]),
+        make_commit("M", vec!["P1", "P2"]),
+        make_commit("P1", vec![]),
+        make_commit("P2", vec![]),
+    ];
+    let branches = vec![make_branch("main", "A", true), make_branch("b", "B", false)];
+
+    let layout = build_graph(&commits, &branches, None, None);
+
+    let row = layout
+        .nodes
+        .iter()
+        .position(|n| get_short_id(n) == "M")
+        .unwrap();
+    let merge = &layout.nodes[row];
+    assert_eq!(merge.lane, 1);
+    assert!(
+        matches!(merge.cells[0], CellType::BranchRight(_)),
+        "{}",
+        render_cells(&merge.cells)
+    );
+    assert!(matches!(merge.cells[1], CellType::Horizontal(_)));
+    // The new lane continues down to P2
+    let p2 = layout
+        .nodes
+        .iter()
+        .position(|n| get_short_id(n) == "P2")
+        .unwrap();
+    assert_eq!(layout.nodes[p2].lane, 0);
+    for node in &layout.nodes[row + 1..p2] {
+        assert!(
+            matches!(node.cells[0], CellType::Pipe(_)),
+            "{}",
+            render_cells(&node.cells)
+        );
+    }
+}
+
+#[test]
+fn test_fork_connector_crosses_unrelated_lane_between_fork_lanes() {
+    // A, C and D fork from R while U's lane (1) stays active past R, so the
+    // connector crosses it on the way to the middle (2) and rightmost (3) fork lanes
+    let commits = vec![
+        make_commit("A", vec!["R"]),
+        make_commit("U", vec!["U0"]),
+        make_commit("C", vec!["R"]),
+        make_commit("D", vec!["R"]),
+        make_commit("R", vec![]),
+        make_commit("U0", vec![]),
+    ];
+    let branches = vec![
+        make_branch("main", "A", true),
+        make_branch("u", "U", false),
+        make_branch("c", "C", false),
+        make_branch("d", "D", false),
+    ];
+
+    let layout = build_spacious_graph(&commits, &branches, None, None);
+    assert_connector_owners(&layout);
+    let connector = layout.nodes.iter().find(|n| n.is_connector()).unwrap();
+    assert_eq!(render_cells(&connector.cells).trim_end(), "├─┼─┴─╯");
+    assert!(matches!(connector.cells[0], CellType::TeeRight(_)));
+    // Each segment takes the color of the fork lane it leads to; the crossed
+    // lane keeps its own color in the ┼
+    assert_eq!(
+        connector.cells[1..],
+        [
+            CellType::Horizontal(2),
+            CellType::HorizontalPipe(2, 1),
+            CellType::Horizontal(2),
+            CellType::TeeUp(2),
+            CellType::Horizontal(3),
+            CellType::MergeLeft(3),
+            CellType::Empty,
+        ]
+    );
+    assert_eq!(
+        rendered_rows(&layout),
+        [
+            "○ A",
+            "│ ○ U",
+            "│ │ ○ C",
+            "│ │ │ ○ D",
+            "├─┼─┴─╯ (connector)",
+            "○ │ R",
+            "  ○ U0",
+        ]
+    );
+}
+
+/// A history of `n` commits whose parents are picked by a fixed pseudo-random sequence
+/// Parents reach far down, so dozens of lanes are open at once and get freed and reused
+fn pseudo_random_history(seed: u64, n: usize) -> Vec<CommitInfo> {
+    let mut state = seed;
+    let mut next = |bound: usize| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as usize % bound
+    };
+    let ids: Vec<String> = (0..n).map(|i| format!("c{}", i)).collect();
+    (0..n)
+        .map(|i| {
+            let mut parents: Vec<&str> = Vec::new();
+            let older = n - i - 1;
+            if older > 0 {
+                let first = i + 1 + next(older.min(60));
+                parents.push(&ids[first]);
+                if next(2) == 0 {
+                    let second = i + 1 + next(older.min(80));
+                    if second != first {
+                        parents.push(&ids[second]);
+                    }
+                }
+            }
+            make_commit(&ids[i], parents)
+        })
+        .collect()
+}
+
+/// Color of the vertical line in each lane of a row (None where no line runs down)
+fn lane_line_colors(cells: &[CellType]) -> Vec<Option<usize>> {
+    cells
+        .iter()
+        .step_by(2)
+        .map(|cell| match *cell {
+            CellType::Pipe(color) | CellType::Commit(color) => Some(color),
+            CellType::HorizontalPipe(_, color) => Some(color),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_adjacent_lanes_never_share_a_color() {
+    // Each of these histories used to give a reused lane the color of its neighbor
+    for seed in [87, 300, 443, 533] {
+        let commits = pseudo_random_history(seed, 120);
+        let branches = vec![make_branch("main", "c0", true)];
+        let layout = build_graph(&commits, &branches, None, None);
+        assert!(
+            layout.max_lane >= 12,
+            "seed {}: only {} lanes",
+            seed,
+            layout.max_lane + 1
+        );
+
+        for node in &layout.nodes {
+            let colors = lane_line_colors(&node.cells);
+            for (lane, pair) in colors.windows(2).enumerate() {
+                assert!(
+                    pair[0].is_none() || pair[0] != pair[1],
+                    "seed {}: lanes {} and {} share color {:?} in '{}'",
+                    seed,
+                    lane,
+                    lane + 1,
+                    pair[0],
+                    render_cells(&node.cells)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Commit identity lookup without the user's own git config
+//!
+//! A test binary of its own: it points libgit2's global and system config search
+//! paths at an empty directory, which holds for the whole process
+
+mod common;
+
+use common::TestRepo;
+use git2::ConfigLevel;
+use keifu::{git::operations::commit_signature, Error};
+
+#[test]
+fn test_missing_identity() {
+    let empty = tempfile::TempDir::new().unwrap();
+    for level in [
+        ConfigLevel::ProgramData,
+        ConfigLevel::System,
+        ConfigLevel::XDG,
+        ConfigLevel::Global,
+    ] {
+        // SAFETY: the only test in this binary, so nothing reads the paths concurrently
+        unsafe { git2::opts::set_search_path(level, empty.path()).unwrap() };
+    }
+
+    let test_repo = TestRepo::new();
+    assert!(commit_signature(&test_repo.repo).is_ok());
+    test_repo
+        .repo
+        .config()
+        .unwrap()
+        .remove("user.email")
+        .unwrap();
+    assert!(matches!(
+        commit_signature(&test_repo.repo),
+        Err(Error::MissingIdentity("user.email"))
+    ));
+}
@@ -0,0 +1,716 @@
+//! Tests for git operations against real Git repositories
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::{stacked_branches_repo, untracked_conflict_repo, TestRepo};
+use git2::{BranchType, Oid};
+use keifu::{
+    git::operations::{
+        abort_merge, checkout_branch, cherry_pick, commit_autosquash, commit_signature,
+        create_branch, delete_branch, fast_forward_to_upstream, merge_branch, rebase_branch,
+        remove_untracked_files, rename_branch, retarget_branches, squash_commits, stale_branches,
+        AutosquashKind, BranchRetarget, MergeOutcome, PullOutcome,
+    },
+    git::stash::{stash_apply, stash_drop, stash_list, stash_pop, stash_save},
+    Error,
+};
+
+#[test]
+fn test_only_locks_held_by_another_process_are_transient() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+
+    // Another git process updating the ref holds its lock file
+    let lock = test_repo.repo.path().join("refs/heads/topic.lock");
+    fs::write(&lock, "").unwrap();
+    let err = create_branch(&test_repo.repo, "topic", base).unwrap_err();
+    assert!(err.is_transient(), "{:?}", err);
+    fs::remove_file(&lock).unwrap();
+    create_branch(&test_repo.repo, "topic", base).unwrap();
+
+    // A ref to a missing commit stays broken however often it is read
+    let broken = test_repo.repo.path().join("refs/heads/broken");
+    fs::write(&broken, format!("{}\n", "1".repeat(40))).unwrap();
+    let repo = keifu::git::GitRepository::open(test_repo.path()).unwrap();
+    let err = repo.snapshot(500).unwrap_err();
+    assert!(!err.is_transient(), "{:?}", err);
+}
+
+#[test]
+fn test_commit_signature_uses_conditional_include() {
+    let test_repo = TestRepo::new();
+    let includes = tempfile::TempDir::new().unwrap();
+    let work = includes.path().join("work.gitconfig");
+    let personal = includes.path().join("personal.gitconfig");
+    std::fs::write(&work, "[user]\n\tname = Work\n\temail = work@example.com\n").unwrap();
+    std::fs::write(
+        &personal,
+        "[user]\n\tname = Personal\n\temail = me@example.com\n",
+    )
+    .unwrap();
+
+    // Only the include whose gitdir matches this repository applies
+    let gitdir = test_repo.path().canonicalize().unwrap();
+    {
+        let mut config = test_repo.repo.config().unwrap();
+        config.remove("user.name").unwrap();
+        config.remove("user.email").unwrap();
+        config
+            .set_str(
+                &format!("includeIf.gitdir:{}/.path", gitdir.display()),
+                work.to_str().unwrap(),
+            )
+            .unwrap();
+        config
+            .set_str(
+                "includeIf.gitdir:/nonexistent/.path",
+                personal.to_str().unwrap(),
+            )
+            .unwrap();
+    }
+
+    let signature = commit_signature(&test_repo.repo).unwrap();
+    assert_eq!(signature.name(), Some("Work"));
+    assert_eq!(signature.email(), Some("work@example.com"));
+}
+
+#[test]
+fn test_branch_errors_have_specific_variants() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    test_repo.branch("feature", base);
+    let repo = &test_repo.repo;
+
+    assert!(matches!(
+        checkout_branch(repo, "missing"),
+        Err(Error::BranchNotFound(name)) if name == "missing"
+    ));
+    assert!(matches!(
+        create_branch(repo, "feature", base),
+        Err(Error::BranchExists(name)) if name == "feature"
+    ));
+    assert!(matches!(
+        create_branch(repo, "bad..name", base),
+        Err(Error::InvalidBranchName(_))
+    ));
+    assert!(matches!(
+        rename_branch(repo, "feature", &test_repo.head_branch()),
+        Err(Error::BranchExists(_))
+    ));
+    assert!(matches!(
+        delete_branch(repo, &test_repo.head_branch()),
+        Err(Error::CurrentBranch)
+    ));
+    assert!(matches!(
+        create_branch(repo, "topic", Oid::from_bytes(&[7; 20]).unwrap()),
+        Err(Error::CommitNotFound(id)) if id == "0707070"
+    ));
+}
+
+#[test]
+fn test_checkout_over_local_changes_lists_them() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    test_repo.branch("feature", base);
+    test_repo.commit_file("a.txt", "changed on main", "main work");
+    test_repo.write_file("a.txt", "uncommitted");
+
+    let result = checkout_branch(&test_repo.repo, "feature");
+    assert!(
+        matches!(&result, Err(Error::LocalChangesOverwritten(paths)) if paths == &[PathBuf::from("a.txt")]),
+        "{:?}",
+        result
+    );
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "You have uncommitted changes to 1 file(s) that checkout would overwrite; commit or stash them first"
+    );
+    assert_eq!(
+        fs::read_to_string(test_repo.path().join("a.txt")).unwrap(),
+        "uncommitted"
+    );
+}
+
+#[test]
+fn test_only_dirty_worktree_errors_become_dirty_worktree() {
+    let error = |code, class| Error::from(git2::Error::new(code, class, "test"));
+    assert!(matches!(
+        error(git2::ErrorCode::Conflict, git2::ErrorClass::Checkout),
+        Error::DirtyWorktree
+    ));
+    assert!(matches!(
+        error(git2::ErrorCode::Uncommitted, git2::ErrorClass::Rebase),
+        Error::DirtyWorktree
+    ));
+    // A patch that does not apply is not about local changes
+    assert!(matches!(
+        error(git2::ErrorCode::Conflict, git2::ErrorClass::Patch),
+        Error::Git(_)
+    ));
+}
+
+#[test]
+fn test_lookups_report_errors_other_than_not_found() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    // An unreadable loose ref is an error, not a missing branch
+    fs::write(test_repo.repo.path().join("refs/heads/broken"), "garbage\n").unwrap();
+    let result = delete_branch(&test_repo.repo, "broken");
+    assert!(matches!(&result, Err(Error::Git(_))), "{:?}", result);
+    assert!(matches!(
+        delete_branch(&test_repo.repo, "missing"),
+        Err(Error::BranchNotFound(name)) if name == "missing"
+    ));
+    create_branch(&test_repo.repo, "topic", base).unwrap();
+}
+
+#[test]
+fn test_checkout_over_untracked_files_lists_them() {
+    let test_repo = untracked_conflict_repo().0;
+    let main = test_repo.head_branch();
+
+    let result = checkout_branch(&test_repo.repo, "feature");
+    let Err(Error::UntrackedFilesOverwritten(paths)) = result else {
+        panic!("unexpected result: {:?}", result);
+    };
+    assert_eq!(paths, vec![PathBuf::from("notes.txt")]);
+    // Nothing was touched
+    assert_eq!(test_repo.head_branch(), main);
+    let notes = fs::read_to_string(test_repo.path().join("notes.txt")).unwrap();
+    assert_eq!(notes, "local notes");
+}
+
+#[test]
+fn test_overwrite_untracked_files_then_checkout() {
+    let test_repo = untracked_conflict_repo().0;
+    let paths = vec![PathBuf::from("notes.txt")];
+
+    remove_untracked_files(&test_repo.repo, &paths).unwrap();
+    checkout_branch(&test_repo.repo, "feature").unwrap();
+
+    assert_eq!(test_repo.head_branch(), "feature");
+    let notes = fs::read_to_string(test_repo.path().join("notes.txt")).unwrap();
+    assert_eq!(notes, "from feature");
+}
+
+#[test]
+fn test_overwrite_untracked_directories_then_checkout() {
+    // feature's notes.txt file would replace an untracked directory of the same name
+    let test_repo = untracked_conflict_repo().0;
+    fs::remove_file(test_repo.path().join("notes.txt")).unwrap();
+    test_repo.write_file("notes.txt/draft.txt", "draft");
+
+    let result = checkout_branch(&test_repo.repo, "feature");
+    let Err(Error::UntrackedFilesOverwritten(paths)) = result else {
+        panic!("unexpected result: {:?}", result);
+    };
+    remove_untracked_files(&test_repo.repo, &paths).unwrap();
+    checkout_branch(&test_repo.repo, "feature").unwrap();
+
+    let notes = fs::read_to_string(test_repo.path().join("notes.txt")).unwrap();
+    assert_eq!(notes, "from feature");
+}
+
+#[test]
+fn test_stash_untracked_files_then_checkout() {
+    let mut test_repo = untracked_conflict_repo().0;
+
+    stash_save(&mut test_repo.repo, Some("before checkout")).unwrap();
+    checkout_branch(&test_repo.repo, "feature").unwrap();
+
+    assert_eq!(test_repo.head_branch(), "feature");
+    let stashes = stash_list(&mut test_repo.repo).unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert!(
+        stashes[0].message.contains("before checkout"),
+        "{}",
+        stashes[0].message
+    );
+}
+
+#[test]
+fn test_cherry_pick_keeps_author_and_leaves_conflicts_in_progress() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let main = test_repo.head_branch();
+    test_repo.branch("feature", base);
+    test_repo.checkout("feature");
+    let clean = test_repo.commit_file("b.txt", "b", "add b");
+    let conflicting = test_repo.commit_file("a.txt", "feature", "change a");
+    test_repo.checkout(&main);
+    test_repo.commit_file("a.txt", "main", "change a on main");
+
+    let picked = cherry_pick(&test_repo.repo, clean).unwrap();
+    let commit = test_repo.repo.find_commit(picked).unwrap();
+    assert_eq!(commit.summary(), Some("add b"));
+    assert_eq!(test_repo.repo.head().unwrap().target(), Some(picked));
+    assert!(test_repo.path().join("b.txt").exists());
+
+    let result = cherry_pick(&test_repo.repo, conflicting);
+    let Err(Error::CherryPickConflict { commit, files }) = &result else {
+        panic!("expected a cherry-pick conflict: {:?}", result);
+    };
+    assert!(conflicting.to_string().starts_with(commit.as_str()));
+    assert_eq!(files, &["a.txt"]);
+    // Left mid-pick with the conflict markers written out; HEAD stays put
+    assert_eq!(test_repo.repo.state(), git2::RepositoryState::CherryPick);
+    assert_eq!(test_repo.repo.head().unwrap().target(), Some(picked));
+    let a = fs::read_to_string(test_repo.path().join("a.txt")).unwrap();
+    assert!(a.contains("<<<<<<<"), "{}", a);
+
+    abort_merge(&test_repo.repo).unwrap();
+    assert_eq!(test_repo.repo.state(), git2::RepositoryState::Clean);
+    let a = fs::read_to_string(test_repo.path().join("a.txt")).unwrap();
+    assert_eq!(a, "main");
+}
+
+#[test]
+fn test_squash_commits_ending_at_head() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let one = test_repo.commit_file("b.txt", "b", "one");
+    let two = test_repo.commit_file("c.txt", "c", "two");
+    let three = test_repo.commit_file("d.txt", "d", "three");
+
+    // Guards: the range must end at HEAD and the tree must be clean
+    let result = squash_commits(&test_repo.repo, &[one, two], "x");
+    assert!(
+        matches!(result, Err(Error::SquashRefused(_))),
+        "{:?}",
+        result
+    );
+    test_repo.write_file("a.txt", "changed");
+    let result = squash_commits(&test_repo.repo, &[two, three], "x");
+    assert!(matches!(result, Err(Error::DirtyWorktree)), "{:?}", result);
+    test_repo.write_file("a.txt", "a");
+
+    let squashed = squash_commits(&test_repo.repo, &[one, two, three], "squashed").unwrap();
+    let commit = test_repo.repo.find_commit(squashed).unwrap();
+    assert_eq!(commit.summary(), Some("squashed"));
+    assert_eq!(commit.parent_ids().collect::<Vec<_>>(), [base]);
+    assert_eq!(
+        commit.tree_id(),
+        test_repo.repo.find_commit(three).unwrap().tree_id()
+    );
+    assert_eq!(test_repo.repo.head().unwrap().target(), Some(squashed));
+}
+
+#[test]
+fn test_commit_autosquash_uses_target_subject() {
+    let test_repo = TestRepo::new();
+    let target = test_repo.commit_file("a.txt", "a", "Add a\n\nLong description");
+    let head = test_repo.commit_file("b.txt", "b", "Add b");
+
+    let result = commit_autosquash(&test_repo.repo, target, AutosquashKind::Fixup);
+    assert!(matches!(result, Err(Error::NothingStaged)), "{:?}", result);
+
+    // Only staged changes are committed
+    test_repo.stage_file("a.txt", "fixed");
+    test_repo.write_file("b.txt", "unstaged");
+
+    let fixup = commit_autosquash(&test_repo.repo, target, AutosquashKind::Fixup).unwrap();
+    let commit = test_repo.repo.find_commit(fixup).unwrap();
+    assert_eq!(commit.message(), Some("fixup! Add a"));
+    assert_eq!(commit.parent_ids().collect::<Vec<_>>(), [head]);
+    let tree = commit.tree().unwrap();
+    let blob = |name: &str| {
+        let entry = tree.get_name(name).unwrap();
+        test_repo
+            .repo
+            .find_blob(entry.id())
+            .unwrap()
+            .content()
+            .to_vec()
+    };
+    assert_eq!(blob("a.txt"), b"fixed");
+    assert_eq!(blob("b.txt"), b"b");
+
+    test_repo.stage_file("c.txt", "c");
+    let squash = commit_autosquash(&test_repo.repo, head, AutosquashKind::Squash).unwrap();
+    assert_eq!(
+        test_repo.repo.find_commit(squash).unwrap().message(),
+        Some("squash! Add b")
+    );
+}
+
+#[test]
+fn test_commit_autosquash_refuses_commits_outside_head() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let side = test_repo.commit_at("side", &[base], 1_700_000_000);
+    test_repo.stage_file("a.txt", "changed");
+
+    let result = commit_autosquash(&test_repo.repo, side, AutosquashKind::Fixup);
+    assert!(
+        matches!(result, Err(Error::NotInHeadHistory(_))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn test_squash_refuses_merges_in_range() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let side = test_repo.commit_at("side", &[base], 1_700_000_000);
+    let merge = test_repo.commit_at("merge", &[base, side], 1_700_000_100);
+    test_repo
+        .repo
+        .reference(
+            &format!("refs/heads/{}", test_repo.head_branch()),
+            merge,
+            true,
+            "merge",
+        )
+        .unwrap();
+
+    let result = squash_commits(&test_repo.repo, &[base, merge], "x");
+    assert!(
+        matches!(
+            result,
+            Err(Error::SquashRefused("merge commits cannot be squashed"))
+        ),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn test_fast_forward_to_upstream_never_merges() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let head = test_repo.head_branch();
+    assert!(matches!(
+        fast_forward_to_upstream(&test_repo.repo),
+        Err(Error::NoUpstream(name)) if name == head
+    ));
+
+    test_repo.branch("upstream", base);
+    test_repo
+        .repo
+        .find_branch(&head, git2::BranchType::Local)
+        .unwrap()
+        .set_upstream(Some("upstream"))
+        .unwrap();
+    assert_eq!(
+        fast_forward_to_upstream(&test_repo.repo).unwrap(),
+        PullOutcome::UpToDate
+    );
+
+    // Upstream ahead: HEAD and the working tree move to it
+    test_repo.checkout("upstream");
+    let ahead = test_repo.commit_file("b.txt", "b", "upstream work");
+    test_repo.checkout(&head);
+    assert_eq!(
+        fast_forward_to_upstream(&test_repo.repo).unwrap(),
+        PullOutcome::FastForwarded
+    );
+    assert_eq!(test_repo.repo.head().unwrap().target(), Some(ahead));
+    assert!(test_repo.path().join("b.txt").exists());
+
+    // Both ahead: nothing changes
+    let local = test_repo.commit_file("c.txt", "c", "local work");
+    test_repo.checkout("upstream");
+    test_repo.commit_file("d.txt", "d", "more upstream work");
+    test_repo.checkout(&head);
+    assert_eq!(
+        fast_forward_to_upstream(&test_repo.repo).unwrap(),
+        PullOutcome::Diverged
+    );
+    assert_eq!(test_repo.repo.head().unwrap().target(), Some(local));
+}
+
+#[test]
+fn test_merge_branch_reports_what_it_did() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let head = test_repo.head_branch();
+    test_repo.branch("feature", base);
+    assert_eq!(
+        merge_branch(&test_repo.repo, "feature").unwrap(),
+        MergeOutcome::UpToDate
+    );
+
+    test_repo.checkout("feature");
+    let feature = test_repo.commit_file("b.txt", "b", "feature work");
+    test_repo.checkout(&head);
+    assert_eq!(
+        merge_branch(&test_repo.repo, "feature").unwrap(),
+        MergeOutcome::FastForwarded
+    );
+    assert_eq!(test_repo.repo.head().unwrap().target(), Some(feature));
+
+    test_repo.checkout("feature");
+    test_repo.commit_file("c.txt", "c", "more feature work");
+    test_repo.checkout(&head);
+    let local = test_repo.commit_file("d.txt", "d", "local work");
+    let MergeOutcome::Merged(merge) = merge_branch(&test_repo.repo, "feature").unwrap() else {
+        panic!("expected a merge commit");
+    };
+    let commit = test_repo.repo.find_commit(merge).unwrap();
+    assert_eq!(commit.parent_id(0).unwrap(), local);
+    assert_eq!(test_repo.repo.head().unwrap().target(), Some(merge));
+}
+
+#[test]
+fn test_merge_conflict_lists_files_and_abort_restores_head() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let head = test_repo.head_branch();
+    test_repo.branch("feature", base);
+    test_repo.checkout("feature");
+    test_repo.commit_file("a.txt", "feature", "change a on feature");
+    test_repo.checkout(&head);
+    let local = test_repo.commit_file("a.txt", "main", "change a on main");
+
+    let Err(Error::MergeConflict(files)) = merge_branch(&test_repo.repo, "feature") else {
+        panic!("expected a merge conflict");
+    };
+    assert_eq!(files, ["a.txt"]);
+    // Left mid-merge, like git
+    assert_eq!(test_repo.repo.state(), git2::RepositoryState::Merge);
+
+    abort_merge(&test_repo.repo).unwrap();
+    assert_eq!(test_repo.repo.state(), git2::RepositoryState::Clean);
+    assert!(!test_repo.repo.index().unwrap().has_conflicts());
+    assert_eq!(test_repo.repo.head().unwrap().target(), Some(local));
+    assert_eq!(
+        fs::read_to_string(test_repo.path().join("a.txt")).unwrap(),
+        "main"
+    );
+}
+
+#[test]
+fn test_stash_apply_keeps_the_stash_and_pop_drops_it() {
+    let mut test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "a", "base");
+    test_repo.write_file("a.txt", "changed");
+    test_repo.write_file("new.txt", "untracked");
+
+    stash_save(&mut test_repo.repo, None).unwrap();
+    let workdir = test_repo.path().to_path_buf();
+    let read = |name: &str| fs::read_to_string(workdir.join(name)).ok();
+    assert_eq!(read("a.txt").as_deref(), Some("a"));
+    assert_eq!(read("new.txt"), None);
+    let stashes = stash_list(&mut test_repo.repo).unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert_eq!(stashes[0].index, 0);
+    assert!(
+        stashes[0].message.starts_with("WIP on"),
+        "{}",
+        stashes[0].message
+    );
+
+    stash_apply(&mut test_repo.repo, &stashes[0]).unwrap();
+    assert_eq!(read("a.txt").as_deref(), Some("changed"));
+    assert_eq!(read("new.txt").as_deref(), Some("untracked"));
+    assert_eq!(stash_list(&mut test_repo.repo).unwrap().len(), 1);
+
+    // Stash again, then pop the older of the two
+    stash_save(&mut test_repo.repo, Some("second")).unwrap();
+    let stashes = stash_list(&mut test_repo.repo).unwrap();
+    assert!(stashes[0].message.contains("second"));
+    stash_pop(&mut test_repo.repo, &stashes[1]).unwrap();
+    assert_eq!(read("a.txt").as_deref(), Some("changed"));
+    let remaining = stash_list(&mut test_repo.repo).unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].oid, stashes[0].oid);
+
+    stash_drop(&mut test_repo.repo, &remaining[0]).unwrap();
+    assert!(stash_list(&mut test_repo.repo).unwrap().is_empty());
+    // The stash is gone, so it cannot be dropped twice
+    assert!(matches!(
+        stash_drop(&mut test_repo.repo, &remaining[0]),
+        Err(Error::RepositoryChanged)
+    ));
+}
+
+#[test]
+fn test_stash_without_changes_is_refused() {
+    let mut test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "a", "base");
+
+    assert!(matches!(
+        stash_save(&mut test_repo.repo, None),
+        Err(Error::NothingToStash)
+    ));
+}
+
+#[test]
+fn test_conflicting_stash_pop_keeps_the_stash() {
+    let mut test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "a", "base");
+    test_repo.write_file("a.txt", "stashed");
+    stash_save(&mut test_repo.repo, None).unwrap();
+    test_repo.commit_file("a.txt", "committed", "change a");
+
+    let stashes = stash_list(&mut test_repo.repo).unwrap();
+    let result = stash_pop(&mut test_repo.repo, &stashes[0]);
+    assert!(
+        matches!(result, Err(Error::StashConflict(0))),
+        "{:?}",
+        result
+    );
+    // The conflict is left to resolve, and the stash is not lost
+    let a = fs::read_to_string(test_repo.path().join("a.txt")).unwrap();
+    assert!(a.contains("<<<<<<<"), "{}", a);
+    assert!(test_repo.repo.index().unwrap().has_conflicts());
+    assert_eq!(stash_list(&mut test_repo.repo).unwrap().len(), 1);
+}
+
+#[test]
+fn test_merge_into_unborn_head_is_an_error() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    test_repo.branch("feature", base);
+    test_repo.repo.set_head("refs/heads/orphan").unwrap();
+
+    let result = merge_branch(&test_repo.repo, "feature");
+    assert!(matches!(result, Err(Error::NoHead)), "{:?}", result);
+    assert!(test_repo.repo.head().is_err());
+}
+
+#[test]
+fn test_conflicting_rebase_is_aborted() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let main = test_repo.head_branch();
+    test_repo.branch("feature", base);
+    test_repo.commit_file("a.txt", "main", "change a on main");
+    test_repo.checkout("feature");
+    test_repo.commit_file("b.txt", "b", "add b");
+    let conflicting = test_repo.commit_file("a.txt", "feature", "change a on feature");
+
+    let result = rebase_branch(&test_repo.repo, &main);
+    assert!(
+        matches!(&result, Err(Error::RebaseConflict(id)) if conflicting.to_string().starts_with(id.as_str())),
+        "{:?}",
+        result
+    );
+    // Back where it started: on feature, no rebase in progress, no conflict markers
+    assert_eq!(test_repo.repo.state(), git2::RepositoryState::Clean);
+    assert_eq!(test_repo.head_branch(), "feature");
+    assert_eq!(branch_tip(&test_repo, "feature"), conflicting);
+    assert!(!test_repo.repo.index().unwrap().has_conflicts());
+    assert_eq!(
+        fs::read_to_string(test_repo.path().join("a.txt")).unwrap(),
+        "feature"
+    );
+}
+
+#[test]
+fn test_rebase_skips_commits_already_on_the_new_base() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let main = test_repo.head_branch();
+    test_repo.branch("feature", base);
+    test_repo.commit_file("b.txt", "b", "add b on main");
+    test_repo.checkout("feature");
+    test_repo.commit_file("b.txt", "b", "add b on feature");
+    let own = test_repo.commit_file("c.txt", "c", "add c");
+
+    let replayed = rebase_branch(&test_repo.repo, &main).unwrap();
+    assert_eq!(replayed.len(), 1);
+    assert_eq!(replayed[0].0, own);
+    assert_eq!(test_repo.repo.state(), git2::RepositoryState::Clean);
+    let tip = test_repo
+        .repo
+        .find_commit(branch_tip(&test_repo, "feature"))
+        .unwrap();
+    assert_eq!(tip.parent_id(0).unwrap(), branch_tip(&test_repo, &main));
+}
+
+fn branch_tip(test_repo: &TestRepo, name: &str) -> Oid {
+    test_repo
+        .repo
+        .find_branch(name, BranchType::Local)
+        .unwrap()
+        .get()
+        .target()
+        .unwrap()
+}
+
+#[test]
+fn test_branches_below_a_rebased_stack_move_onto_the_rewrites() {
+    let (test_repo, main) = stacked_branches_repo();
+    let old_bottom = branch_tip(&test_repo, "bottom");
+    let old_middle = branch_tip(&test_repo, "middle");
+
+    let replayed = rebase_branch(&test_repo.repo, &main).unwrap();
+    assert_eq!(replayed.len(), 3);
+    let rewrite_of = |oid: Oid| replayed.iter().find(|(o, _)| *o == oid).unwrap().1;
+
+    let stale = stale_branches(&test_repo.repo, &replayed, Some("top")).unwrap();
+    assert_eq!(
+        stale,
+        [
+            BranchRetarget {
+                name: "bottom".to_string(),
+                from: old_bottom,
+                to: rewrite_of(old_bottom),
+            },
+            BranchRetarget {
+                name: "middle".to_string(),
+                from: old_middle,
+                to: rewrite_of(old_middle),
+            },
+        ]
+    );
+
+    retarget_branches(&test_repo.repo, &stale).unwrap();
+    let new_bottom = branch_tip(&test_repo, "bottom");
+    let new_middle = branch_tip(&test_repo, "middle");
+    let new_top = branch_tip(&test_repo, "top");
+    assert_eq!(new_bottom, rewrite_of(old_bottom));
+    assert_eq!(new_middle, rewrite_of(old_middle));
+    // The stack is whole again on top of the new main
+    let parent = |oid: Oid| {
+        test_repo
+            .repo
+            .find_commit(oid)
+            .unwrap()
+            .parent_id(0)
+            .unwrap()
+    };
+    assert_eq!(parent(new_top), new_middle);
+    assert_eq!(parent(new_middle), new_bottom);
+    assert_eq!(parent(new_bottom), branch_tip(&test_repo, &main));
+}
+
+#[test]
+fn test_retarget_moves_nothing_when_one_branch_cannot_move() {
+    let (test_repo, main) = stacked_branches_repo();
+    let old_bottom = branch_tip(&test_repo, "bottom");
+    let replayed = rebase_branch(&test_repo.repo, &main).unwrap();
+    let stale = stale_branches(&test_repo.repo, &replayed, Some("top")).unwrap();
+
+    // Another git process moves middle after the list was made
+    let commit = test_repo.repo.find_commit(old_bottom).unwrap();
+    test_repo.repo.branch("middle", &commit, true).unwrap();
+
+    let result = retarget_branches(&test_repo.repo, &stale);
+    assert!(
+        matches!(result, Err(Error::RepositoryChanged)),
+        "{:?}",
+        result
+    );
+    // bottom was moved first, then put back
+    assert_eq!(branch_tip(&test_repo, "bottom"), old_bottom);
+    assert_eq!(branch_tip(&test_repo, "middle"), old_bottom);
+}
+
+#[test]
+fn test_retarget_rollback_failures_name_the_branches() {
+    let error = Error::RetargetRollbackFailed {
+        cause: Box::new(Error::RepositoryChanged),
+        branches: vec!["bottom".to_string(), "middle".to_string()],
+    };
+    assert_eq!(
+        error.to_string(),
+        "Repository changed since the dialog was opened — refresh and retry; \
+         these branches were moved and could not be put back: bottom, middle"
+    );
+}
@@ -0,0 +1,474 @@
+//! Tests for repository loading against real Git repositories
+
+mod common;
+
+use common::TestRepo;
+use keifu::git::{
+    commit::raw_commit_object,
+    ignore::add_pattern,
+    last_touch::LastTouch,
+    repo_info::{RepoInfo, RepoStats},
+    signature::SignatureInfo,
+    tag::TagDistance,
+    BranchReport, GitRepository, WalkScope,
+};
+
+#[test]
+fn test_snapshot_branch_tips_are_loaded_commits() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    test_repo.branch("feature", base);
+    test_repo.commit_file("b.txt", "b", "second");
+
+    let repo = GitRepository::open(test_repo.path()).unwrap();
+    let snapshot = repo.snapshot(500).unwrap();
+
+    assert_eq!(snapshot.commits.len(), 2);
+    for branch in &snapshot.branches {
+        assert!(
+            snapshot.commits.iter().any(|c| c.oid == branch.tip_oid),
+            "branch {} points outside the snapshot",
+            branch.name
+        );
+    }
+    assert_eq!(snapshot.head_oid, Some(snapshot.commits[0].oid));
+    assert_eq!(snapshot.head_name, Some(test_repo.head_branch()));
+    assert_eq!(snapshot.branches.iter().filter(|b| b.is_head).count(), 1);
+}
+
+#[test]
+fn test_snapshot_cannot_mix_reads_across_a_concurrent_commit() {
+    let test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "a", "base");
+    let repo = GitRepository::open(test_repo.path()).unwrap();
+
+    // Separate calls: a commit landing between them yields a branch tip
+    // that is not in the commit list (the inconsistency snapshots prevent)
+    let commits = repo.get_commits(500).unwrap();
+    let new_tip = test_repo.commit_file("b.txt", "b", "concurrent");
+    let branches = repo.get_branches().unwrap();
+    assert!(branches.iter().any(|b| b.tip_oid == new_tip));
+    assert!(!commits.iter().any(|c| c.oid == new_tip));
+
+    // A snapshot reads refs once and walks from exactly those tips
+    let snapshot = repo.snapshot(500).unwrap();
+    for branch in &snapshot.branches {
+        assert!(snapshot.commits.iter().any(|c| c.oid == branch.tip_oid));
+    }
+    let head_branch = snapshot.branches.iter().find(|b| b.is_head).unwrap();
+    assert_eq!(Some(head_branch.tip_oid), snapshot.head_oid);
+}
+
+#[test]
+fn test_current_branch_scope_follows_first_parent_of_head() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    test_repo.branch("other", base);
+    let main_tip = test_repo.commit_file("b.txt", "b", "main work");
+    test_repo.checkout("other");
+    test_repo.commit_file("c.txt", "c", "other work");
+
+    let repo = GitRepository::open(test_repo.path()).unwrap();
+    let all = repo.snapshot_scoped(500, WalkScope::AllBranches).unwrap();
+    assert_eq!(all.commits.len(), 3);
+
+    let scoped = repo.snapshot_scoped(500, WalkScope::CurrentBranch).unwrap();
+    assert_eq!(scoped.commits.len(), 2);
+    assert!(scoped.commits.iter().all(|c| c.oid != main_tip));
+    assert!(scoped.commits.iter().all(|c| c.parent_oids.len() <= 1));
+    // Branches pointing outside the first-parent line are dropped
+    let names: Vec<&str> = scoped.branches.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, vec!["other"]);
+}
+
+#[test]
+fn test_upstream_fork_point_only_when_diverged() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let head = test_repo.head_branch();
+    test_repo.branch("upstream", base);
+    test_repo
+        .repo
+        .find_branch(&head, git2::BranchType::Local)
+        .unwrap()
+        .set_upstream(Some("upstream"))
+        .unwrap();
+
+    // Only ahead: no fork point
+    test_repo.commit_file("b.txt", "b", "local");
+    let repo = GitRepository::open(test_repo.path()).unwrap();
+    assert_eq!(repo.snapshot(500).unwrap().upstream_fork_point, None);
+
+    // Ahead and behind: the merge base is marked
+    test_repo.checkout("upstream");
+    test_repo.commit_file("c.txt", "c", "remote");
+    test_repo.checkout(&head);
+    let repo = GitRepository::open(test_repo.path()).unwrap();
+    assert_eq!(repo.snapshot(500).unwrap().upstream_fork_point, Some(base));
+}
+
+#[test]
+fn test_branch_report_counts_divergence_from_upstream() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let head = test_repo.head_branch();
+    test_repo.branch("upstream", base);
+    test_repo
+        .repo
+        .find_branch(&head, git2::BranchType::Local)
+        .unwrap()
+        .set_upstream(Some("upstream"))
+        .unwrap();
+    let local = test_repo.commit_file("b.txt", "b", "local");
+    test_repo.commit_file("c.txt", "c", "local again");
+    test_repo.checkout("upstream");
+    test_repo.commit_file("d.txt", "d", "remote");
+    test_repo.checkout(&head);
+
+    let report = BranchReport::list(&test_repo.repo).unwrap();
+    let current = report.iter().find(|b| b.branch.name == head).unwrap();
+    assert_eq!((current.ahead, current.behind), (Some(2), Some(1)));
+    let upstream = report.iter().find(|b| b.branch.name == "upstream").unwrap();
+    assert_eq!((upstream.ahead, upstream.behind), (None, None));
+
+    let json = serde_json::to_value(current).unwrap();
+    assert_eq!(json["upstream"], "upstream");
+    assert_eq!(json["ahead"], 2);
+    assert_eq!(
+        json["tip_oid"],
+        test_repo.repo.head().unwrap().target().unwrap().to_string()
+    );
+    assert_ne!(json["tip_oid"], local.to_string());
+}
+
+#[test]
+fn test_raw_commit_object_matches_cat_file_layout() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    let oid = test_repo.commit_file("b.txt", "b", "second\n\nbody");
+
+    let raw = raw_commit_object(&test_repo.repo, oid).unwrap();
+    let tree = test_repo.repo.find_commit(oid).unwrap().tree_id();
+    let mut lines = raw.lines();
+
+    assert_eq!(lines.next(), Some(format!("tree {}", tree).as_str()));
+    assert_eq!(lines.next(), Some(format!("parent {}", base).as_str()));
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("author test <test@example.com> "));
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("committer test <test@example.com> "));
+    assert_eq!(lines.collect::<Vec<_>>(), ["", "second", "", "body"]);
+}
+
+#[test]
+fn test_repo_info_collects_remotes_and_stats() {
+    let test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "a", "first");
+    test_repo.commit_file("b.txt", "b", "second");
+    test_repo
+        .repo
+        .remote("origin", "https://example.com/repo.git")
+        .unwrap();
+
+    let info = RepoInfo::collect(&test_repo.repo);
+    assert_eq!(info.head, Some(test_repo.head_branch()));
+    assert_eq!(info.upstream, None);
+    assert_eq!(
+        info.remotes,
+        vec![(
+            "origin".to_string(),
+            Some("https://example.com/repo.git".to_string())
+        )]
+    );
+    assert!(!info.is_bare);
+    assert!(!info.is_shallow);
+    assert_eq!(info.has_submodules, Some(false));
+
+    let stats = RepoStats::compute(test_repo.path().to_str().unwrap());
+    assert_eq!(stats.total_commits, Some(2));
+    assert!(stats.git_dir_size.unwrap() > 0);
+}
+
+#[test]
+fn test_open_outside_repository_is_repository_not_found() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let result = GitRepository::open(dir.path());
+    assert!(matches!(
+        result,
+        Err(keifu::Error::RepositoryNotFound(path)) if path == dir.path()
+    ));
+}
+
+#[test]
+fn test_open_reports_a_broken_repository_as_it_is() {
+    let test_repo = TestRepo::new();
+    std::fs::write(test_repo.repo.path().join("config"), "[core\n").unwrap();
+    let result = GitRepository::open(test_repo.path());
+    assert!(
+        matches!(&result, Err(keifu::Error::Git(e)) if e.class() == git2::ErrorClass::Config),
+        "{:?}",
+        result.err()
+    );
+}
+
+#[test]
+fn test_identical_timestamps_load_children_before_parents() {
+    let test_repo = TestRepo::new();
+    const TIME: i64 = 1_700_000_000;
+    let root = test_repo.commit_at("root", &[], TIME);
+    let (mut a, mut b) = (root, root);
+    for i in 0..5 {
+        a = test_repo.commit_at(&format!("a{}", i), &[a], TIME);
+        b = test_repo.commit_at(&format!("b{}", i), &[b], TIME);
+    }
+    let merge = test_repo.commit_at("merge", &[a, b], TIME);
+    test_repo.branch("imported", merge);
+    test_repo.branch("side", b);
+
+    let repo = GitRepository::open(test_repo.path()).unwrap();
+    let commits = repo.get_commits(500).unwrap();
+    assert_eq!(commits.len(), 12);
+
+    let row_of = |oid| commits.iter().position(|c| c.oid == oid).unwrap();
+    for (row, commit) in commits.iter().enumerate() {
+        for parent in &commit.parent_oids {
+            assert!(
+                row_of(*parent) > row,
+                "{} is listed before its child {}",
+                parent,
+                commit.short_id
+            );
+        }
+    }
+}
+
+#[test]
+fn test_pinned_snapshot_extends_past_the_limit() {
+    let test_repo = TestRepo::new();
+    let oldest = test_repo.commit_file("a.txt", "0", "commit 0");
+    for i in 1..10 {
+        test_repo.commit_file("a.txt", &i.to_string(), &format!("commit {}", i));
+    }
+    let repo = GitRepository::open(test_repo.path()).unwrap();
+
+    let snapshot = repo.snapshot(5).unwrap();
+    assert_eq!(snapshot.commits.len(), 5);
+
+    let snapshot = repo
+        .snapshot_pinned(5, WalkScope::AllBranches, Some(oldest))
+        .unwrap();
+    assert_eq!(snapshot.commits.len(), 10);
+    assert_eq!(snapshot.commits.last().map(|c| c.oid), Some(oldest));
+}
+
+#[test]
+fn test_pinned_snapshot_loads_commits_off_every_branch() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    // Reachable from a tag only
+    let tagged = test_repo.commit_at("tagged only", &[base], 1_700_000_000);
+    let object = test_repo.repo.find_object(tagged, None).unwrap();
+    test_repo
+        .repo
+        .tag_lightweight("v1", &object, false)
+        .unwrap();
+
+    let repo = GitRepository::open(test_repo.path()).unwrap();
+    let oid = repo.resolve_commit("v1").unwrap();
+    assert_eq!(oid, tagged);
+    let snapshot = repo
+        .snapshot_pinned(500, WalkScope::AllBranches, Some(oid))
+        .unwrap();
+    assert!(snapshot.commits.iter().any(|c| c.oid == tagged));
+}
+
+#[test]
+fn test_resolve_commit_reports_bad_revspecs() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    test_repo.commit_file("a.txt", "b", "second");
+    let repo = GitRepository::open(test_repo.path()).unwrap();
+
+    assert_eq!(repo.resolve_commit("HEAD~1").unwrap(), base);
+    let err = repo.resolve_commit("no-such-ref").unwrap_err();
+    assert!(
+        matches!(&err, keifu::Error::InvalidRevision { spec, .. } if spec == "no-such-ref"),
+        "{:?}",
+        err
+    );
+    assert!(err
+        .to_string()
+        .starts_with("Cannot resolve revision 'no-such-ref': "));
+}
+
+#[test]
+fn test_merge_base_of_unrelated_histories_is_none_but_errors_are_reported() {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_at("base", &[], 1_000);
+    let tip = test_repo.commit_at("tip", &[base], 2_000);
+    let orphan = test_repo.commit_at("orphan", &[], 3_000);
+    let repo = GitRepository::open(test_repo.path()).unwrap();
+
+    assert_eq!(repo.merge_base(tip, base).unwrap(), Some(base));
+    assert_eq!(repo.merge_base(tip, orphan).unwrap(), None);
+    let missing = git2::Oid::from_bytes(&[7; 20]).unwrap();
+    assert!(repo.merge_base(tip, missing).is_err());
+}
+
+#[test]
+fn test_unborn_head_reports_its_branch() {
+    let test_repo = TestRepo::new();
+    test_repo.repo.set_head("refs/heads/trunk").unwrap();
+    let repo = GitRepository::open(test_repo.path()).unwrap();
+
+    let snapshot = repo.snapshot(500).unwrap();
+    assert!(snapshot.commits.is_empty());
+    assert_eq!(snapshot.head_oid, None);
+    assert_eq!(snapshot.head_name, None);
+    assert_eq!(snapshot.unborn_branch.as_deref(), Some("trunk"));
+    assert_eq!(
+        RepoInfo::collect(&repo.repo).head.as_deref(),
+        Some("(unborn) trunk")
+    );
+
+    // The first commit makes the branch real
+    test_repo.commit_file("a.txt", "a", "first");
+    let snapshot = repo.snapshot(500).unwrap();
+    assert_eq!(snapshot.head_name.as_deref(), Some("trunk"));
+    assert_eq!(snapshot.unborn_branch, None);
+}
+
+#[test]
+fn test_tag_distance_counts_commits_since_latest_tag() {
+    let test_repo = TestRepo::new();
+    let root = test_repo.commit_file("a.txt", "a", "root");
+    let release = test_repo.commit_file("b.txt", "b", "release");
+    test_repo.commit_file("c.txt", "c", "fix");
+    let tip = test_repo.commit_file("d.txt", "d", "feature");
+    let repo = &test_repo.repo;
+    let sig = repo.signature().unwrap();
+    let release_object = repo.find_object(release, None).unwrap();
+    repo.tag("v1.2.0-rc-1", &release_object, &sig, "rc", false)
+        .unwrap();
+
+    let distance = |oid| TagDistance::of(repo, oid).unwrap();
+    assert_eq!(distance(root), None);
+    assert_eq!(distance(release).unwrap().describe(), "at v1.2.0-rc-1");
+    assert_eq!(
+        distance(tip).unwrap().describe(),
+        "2 commits since v1.2.0-rc-1"
+    );
+
+    // Lightweight tags count as well, and the nearest one wins
+    let tip_object = repo.find_object(tip, None).unwrap();
+    repo.tag_lightweight("v1.2.0", &tip_object, false).unwrap();
+    assert_eq!(
+        distance(tip),
+        Some(TagDistance {
+            tag: "v1.2.0".to_string(),
+            commits: 0
+        })
+    );
+}
+
+#[test]
+fn test_last_touch_is_the_previous_change_to_the_path() {
+    let test_repo = TestRepo::new();
+    test_repo.stage_file("a.txt", "1");
+    let root = test_repo.commit_at("add a", &[], 1_000);
+    test_repo.stage_file("b.txt", "1");
+    let other = test_repo.commit_at("add b", &[root], 2_000);
+    test_repo.stage_file("a.txt", "2");
+    test_repo.stage_file("c.txt", "1");
+    let change = test_repo.commit_at("change a, add c", &[other], 1_000 + 12 * 86_400);
+
+    let touch = |path: &str| {
+        LastTouch::before(&test_repo.repo, change, std::path::Path::new(path)).unwrap()
+    };
+    // "add b" in between did not touch a.txt
+    assert_eq!(
+        touch("a.txt").unwrap().describe(),
+        "last touched by test, 12d before"
+    );
+    // A new file has no earlier change
+    assert_eq!(touch("c.txt"), None);
+    // Nor does anything in a root commit
+    assert_eq!(
+        LastTouch::before(&test_repo.repo, root, std::path::Path::new("a.txt")).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_add_ignore_pattern_hides_untracked_file() {
+    let test_repo = TestRepo::new();
+    test_repo.commit_file("a.txt", "a", "first");
+    test_repo.write_file("build.log", "noise");
+    test_repo.write_file(".gitignore", "*.tmp");
+    assert!(!test_repo
+        .repo
+        .status_should_ignore("build.log".as_ref())
+        .unwrap());
+
+    assert!(add_pattern(&test_repo.repo, "build.log").unwrap());
+    assert!(!add_pattern(&test_repo.repo, "build.log").unwrap());
+    assert_eq!(
+        std::fs::read_to_string(test_repo.path().join(".gitignore")).unwrap(),
+        "*.tmp\nbuild.log\n"
+    );
+    assert!(test_repo
+        .repo
+        .status_should_ignore("build.log".as_ref())
+        .unwrap());
+}
+
+#[test]
+fn test_signature_of_ssh_signed_commit_names_allowed_signer() {
+    let test_repo = TestRepo::new();
+    let unsigned = test_repo.commit_file("a.txt", "a", "unsigned");
+    assert_eq!(SignatureInfo::of(&test_repo.repo, unsigned).unwrap(), None);
+
+    // The signature does not have to verify; only its public key is read
+    let block = "-----BEGIN SSH SIGNATURE-----
+U1NIU0lHAAAAAQAAADMAAAALc3NoLWVkMjU1MTkAAAAgkreU5VAO6GcjG5AOqXAhzZ3zaq
+xzeSTKl66TiP2TuRoAAAADZ2l0AAAAAAAAAAZzaGE1MTIAAABTAAAAC3NzaC1lZDI1NTE5
+AAAAQIF/bJXwgsx016knOHAdR6PaFYdHEn30U31cfbfSHz06c6++qnRHkNDcOw2QqUBCNt
+JWmDeoWn4ipqYlidbW0Ag=
+-----END SSH SIGNATURE-----";
+    let repo = &test_repo.repo;
+    let parent = repo.find_commit(unsigned).unwrap();
+    let signature = repo.signature().unwrap();
+    let content = repo
+        .commit_create_buffer(
+            &signature,
+            &signature,
+            "signed",
+            &parent.tree().unwrap(),
+            &[&parent],
+        )
+        .unwrap();
+    let signed = repo
+        .commit_signed(content.as_str().unwrap(), block, None)
+        .unwrap();
+
+    test_repo.write_file(
+        "allowed_signers",
+        "dev@example.com ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIJK3lOVQDuhnIxuQDqlwIc2d82qsc3kkypeuk4j9k7ka\n",
+    );
+    repo.config()
+        .unwrap()
+        .set_str(
+            "gpg.ssh.allowedSignersFile",
+            test_repo.path().join("allowed_signers").to_str().unwrap(),
+        )
+        .unwrap();
+    let info = SignatureInfo::of(repo, signed).unwrap().unwrap();
+    assert_eq!(
+        info.describe(),
+        "ssh-ed25519 key, principal dev@example.com"
+    );
+}
@@ -0,0 +1,49 @@
+//! Full-screen render tests against real Git repositories
+
+mod common;
+
+use common::{harness::render, TestRepo};
+use keifu::{app::App, config::Config, git::GitRepository};
+use ratatui::style::Color;
+
+/// Repository with branches, a merge and uncommitted changes (exercises most styles)
+fn sample_repo() -> TestRepo {
+    let test_repo = TestRepo::new();
+    let base = test_repo.commit_file("a.txt", "a", "base");
+    test_repo.branch("feature", base);
+    test_repo.commit_file("b.txt", "b", "main work");
+    test_repo.checkout("feature");
+    test_repo.commit_file("c.txt", "c", "feature work");
+    test_repo.write_file("a.txt", "changed");
+    test_repo
+}
+
+fn app_with_config(test_repo: &TestRepo, config: Config) -> App {
+    App::with_repo(GitRepository::open(test_repo.path()).unwrap(), config).unwrap()
+}
+
+#[test]
+fn test_high_contrast_never_uses_dark_gray() {
+    let test_repo = sample_repo();
+    let mut config = Config::default();
+    config.ui.high_contrast = true;
+    let mut app = app_with_config(&test_repo, config);
+
+    // Uncommitted row, then a commit row
+    for _ in 0..2 {
+        let buf = render(&mut app, 100, 30);
+        for cell in buf.content() {
+            assert_ne!(cell.fg, Color::DarkGray, "DarkGray text: {:?}", cell);
+        }
+        app.handle_action(keifu::action::Action::MoveDown).unwrap();
+    }
+}
+
+#[test]
+fn test_default_theme_uses_dark_gray_borders() {
+    let test_repo = sample_repo();
+    let mut app = app_with_config(&test_repo, Config::default());
+
+    let buf = render(&mut app, 100, 30);
+    assert!(buf.content().iter().any(|c| c.fg == Color::DarkGray));
+}